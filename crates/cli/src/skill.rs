@@ -577,11 +577,15 @@ pub(crate) fn run_skill(cmd: SkillCmd) -> Result<()> {
                 }
             };
 
-            let executor = lib::tools::GenericToolExecutor::from_descriptors(
+            let mut executor = lib::tools::GenericToolExecutor::from_descriptors(
                 &descriptors,
                 &skill_dirs,
                 sandbox,
             );
+            // Profile `exec` settings (e.g. `exec.env`) shape the previewed command.
+            if let Ok((config, _)) = lib::config::load_config(profile.as_deref()) {
+                executor = executor.with_exec_config(config.exec);
+            }
 
             if !executor.has_tool(&tool) {
                 // List available tools to help the user.
@@ -14,7 +14,7 @@ mod state;
 mod types;
 mod ui;
 
-pub use types::{AgentReply, AgentSkillsRuntime, ChannelBinding, ChatMessage, GatewayStatusDetails, ProviderStatusInfo, SessionEvent, SessionHistory, SessionSummary};
+pub use types::{AgentReply, AgentSkillsRuntime, ChannelBinding, ChatMessage, DelegationTranscript, GatewayStatusDetails, ProviderStatusInfo, SessionEvent, SessionHistory, SessionSummary};
 
 #[derive(Clone, Copy, PartialEq, Eq, Default)]
 enum Screen {
@@ -106,6 +106,8 @@ struct GatewayState {
     stop_receiver: Option<mpsc::Receiver<Result<bool, String>>>,
     /// True after the user requests a stop, until the chat turn actually completes.
     chat_stopping: bool,
+    /// When Some, a `delegation.cancel` request is in flight for this profile's gateway.
+    delegation_cancel_receiver: Option<mpsc::Receiver<Result<bool, String>>>,
     /// User message we sent for the in-flight turn (used when reply creates a new session).
     pending_user_message: Option<String>,
     /// True when the in-flight turn was started for a new (previously unbound) session.
@@ -124,6 +126,11 @@ struct GatewayState {
     sessions_history_receiver: Option<(String, mpsc::Receiver<Result<SessionHistory, String>>)>,
     /// Session id whose history is currently loading for this profile.
     loading_session_id: Option<String>,
+    /// Worker transcripts fetched via `sessions.delegation`, keyed by delegation id. Errors are
+    /// cached too so a missing transcript is not re-requested every frame.
+    delegation_transcripts: BTreeMap<String, Result<DelegationTranscript, String>>,
+    /// When Some, a `sessions.delegation` fetch is in flight for this profile.
+    delegation_transcript_receiver: Option<(String, mpsc::Receiver<Result<DelegationTranscript, String>>)>,
     /// When Some, a `sessions.delete` fetch is in flight for this profile.
     sessions_delete_receiver: Option<(String, mpsc::Receiver<Result<bool, String>>)>,
     /// When Some, a `sessions.delete_all` fetch is in flight for this profile.
@@ -182,6 +189,7 @@ impl Default for GatewayState {
             chat_turn_receiver: None,
             stop_receiver: None,
             chat_stopping: false,
+            delegation_cancel_receiver: None,
             pending_user_message: None,
             chat_turn_is_new_session: false,
             session_messages: BTreeMap::new(),
@@ -191,6 +199,8 @@ impl Default for GatewayState {
             sessions_list_fetched: false,
             sessions_history_receiver: None,
             loading_session_id: None,
+            delegation_transcripts: BTreeMap::new(),
+            delegation_transcript_receiver: None,
             sessions_delete_receiver: None,
             sessions_delete_all_receiver: None,
             show_clear_all_confirm: false,
@@ -583,6 +593,49 @@ impl ChaiApp {
         self.gw_ref().map(|gw| &gw.agent_detail_cache)
     }
 
+    /// Active profile's worker transcripts fetched via `sessions.delegation`.
+    pub fn delegation_transcripts(&self) -> Option<&BTreeMap<String, Result<DelegationTranscript, String>>> {
+        self.gw_ref().map(|gw| &gw.delegation_transcripts)
+    }
+
+    /// Start a `sessions.delegation` fetch for `delegation_id` unless it is cached or a fetch is
+    /// already in flight.
+    pub fn request_delegation_transcript(&mut self, session_id: &str, delegation_id: &str) {
+        let profile_override = Some(self.profile_active.clone());
+        let gw = self.gw();
+        if !gw.responds
+            || gw.delegation_transcript_receiver.is_some()
+            || gw.delegation_transcripts.contains_key(delegation_id)
+        {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let session_id = session_id.to_string();
+        let id = delegation_id.to_string();
+        std::thread::spawn(move || {
+            let result = state::gateway::fetch_sessions_delegation(profile_override.as_deref(), &session_id, &id);
+            let _ = tx.send(result);
+        });
+        gw.delegation_transcript_receiver = Some((delegation_id.to_string(), rx));
+    }
+
+    /// Ask the gateway to cancel a running delegation. The worker result becomes a
+    /// "cancelled by operator" tool result and the orchestrator turn continues.
+    pub fn cancel_delegation(&mut self, delegation_id: &str) {
+        let profile_override = Some(self.profile_active.clone());
+        let gw = self.gw();
+        if !gw.responds || gw.delegation_cancel_receiver.is_some() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let id = delegation_id.to_string();
+        std::thread::spawn(move || {
+            let result = state::gateway::send_delegation_cancel(profile_override.as_deref(), &id);
+            let _ = tx.send(result);
+        });
+        gw.delegation_cancel_receiver = Some(rx);
+    }
+
     /// Active profile's agent detail fetch error.
     pub fn agent_detail_fetch_error(&self) -> Option<&(String, String)> {
         self.gw_ref().and_then(|gw| gw.agent_detail_fetch_error.as_ref())
@@ -967,6 +1020,8 @@ impl ChaiApp {
             gw.sessions_delete_all_receiver = None;
             gw.sessions_history_receiver = None;
             gw.loading_session_id = None;
+            gw.delegation_transcripts.clear();
+            gw.delegation_transcript_receiver = None;
             gw.chat_session_id = None;
             gw.chat_messages.clear();
             gw.chat_turn_receiver = None;
@@ -974,6 +1029,7 @@ impl ChaiApp {
             gw.pending_user_message = None;
             gw.chat_turn_is_new_session = false;
             gw.stop_receiver = None;
+            gw.delegation_cancel_receiver = None;
             gw.session_messages.clear();
             gw.session_summaries.clear();
             gw.session_order.clear();
@@ -1006,6 +1062,7 @@ impl ChaiApp {
         // previous server session (which would make the next send continue that history).
         gw.chat_turn_receiver = None;
         gw.stop_receiver = None;
+        gw.delegation_cancel_receiver = None;
         gw.chat_stopping = false;
         gw.pending_user_message = None;
         gw.chat_turn_is_new_session = false;
@@ -1081,6 +1138,7 @@ impl ChaiApp {
         gw.chat_messages.clear();
         gw.chat_turn_receiver = None;
         gw.stop_receiver = None;
+        gw.delegation_cancel_receiver = None;
         gw.chat_stopping = false;
         gw.pending_user_message = None;
         gw.chat_turn_is_new_session = false;
@@ -1096,6 +1154,8 @@ impl ChaiApp {
         gw.sessions_list_receiver = None;
         gw.sessions_history_receiver = None;
         gw.loading_session_id = None;
+        gw.delegation_transcripts.clear();
+        gw.delegation_transcript_receiver = None;
         gw.sessions_delete_receiver = None;
         gw.sessions_delete_all_receiver = None;
         gw.show_clear_all_confirm = false;
@@ -1292,7 +1352,8 @@ impl ChaiApp {
                         // When the tool loop iteration limit was reached, add a banner
                         // message so the user knows what happened.
                         if reply.loop_limit_reached {
-                            let already_has_banner = entry.iter().any(|m| m.role == "tool_loop_limit");
+                            let turn_start = state::chat::current_turn_start(entry);
+                            let already_has_banner = entry[turn_start..].iter().any(|m| m.role == "tool_loop_limit");
                             if !already_has_banner {
                                 entry.push(ChatMessage::tool_loop_limit(
                                     "tool loop iteration limit reached",
@@ -1507,7 +1568,7 @@ impl ChaiApp {
 
     /// Append the same text as the **`/help`** command to the active chat session.
     pub(crate) fn show_chat_help(&mut self) {
-        const TEXT: &str = "Available commands:\n\n/new - start a new session (clear conversation history)\n/help - show this help message";
+        const TEXT: &str = "Available commands:\n\n/new - start a new session (clear conversation history)\n/continue - run the tool calls left pending by the tool loop limit\n/help - show this help message";
         let msg = ChatMessage::system(TEXT.to_string());
         let sid = self.gw_ref().and_then(|gw| gw.chat_session_id.clone());
         if let Some(sid) = sid {
@@ -1575,6 +1636,12 @@ impl ChaiApp {
             return;
         }
 
+        if message.eq_ignore_ascii_case("/continue") {
+            self.gw().pending_user_message = None;
+            self.continue_chat_turn();
+            return;
+        }
+
         // Send to the current conversation session (chat_session_id), not the merely selected one.
         // None = new session; reply will set chat_session_id.
         let session_id = chat_session_id;
@@ -1623,7 +1690,60 @@ impl ChaiApp {
         let profile_override = Some(self.profile_active.clone());
         let (tx, rx) = mpsc::channel();
         std::thread::spawn(move || {
-            let result = state::gateway::run_agent_turn(profile_override.as_deref(), session_id, message, provider, model, orchestrator_id);
+            let result = state::gateway::run_agent_turn(profile_override.as_deref(), session_id, Some(message), provider, model, orchestrator_id);
+            let _ = tx.send(result);
+        });
+        self.gw().chat_turn_receiver = Some(rx);
+    }
+
+    /// Resume the active session's last turn after it hit the tool loop limit: the gateway runs
+    /// the pending tool calls and continues the tool loop without a new user message.
+    fn continue_chat_turn(&mut self) {
+        let (session_id, active_orchestrator_id, current_provider, current_model, status, busy) = {
+            let gw = self.gw();
+            (
+                gw.chat_session_id.clone(),
+                gw.active_orchestrator_id.clone(),
+                gw.current_provider.clone(),
+                gw.current_model.clone(),
+                gw.status.clone(),
+                gw.chat_turn_receiver.is_some(),
+            )
+        };
+        let Some(session_id) = session_id else { return };
+        if busy {
+            return;
+        }
+        {
+            let gw = self.gw();
+            gw.chat_turn_is_new_session = false;
+            let marker = ChatMessage::system(state::chat::CONTINUE_MARKER);
+            gw.session_messages
+                .entry(session_id.clone())
+                .or_default()
+                .push(marker.clone());
+            if gw.selected_session_id.as_deref() == Some(session_id.as_str()) {
+                gw.chat_messages.push(marker);
+            }
+        }
+        self.move_session_to_front(&session_id);
+        let active_orch = active_orchestrator_id.as_deref();
+        let provider = current_provider.or_else(|| {
+            status
+                .as_ref()
+                .and_then(|s| s.default_provider_for(active_orch).map(String::from))
+        });
+        let profile_override = Some(self.profile_active.clone());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = state::gateway::run_agent_turn(
+                profile_override.as_deref(),
+                Some(session_id),
+                None,
+                provider,
+                current_model,
+                active_orchestrator_id,
+            );
             let _ = tx.send(result);
         });
         self.gw().chat_turn_receiver = Some(rx);
@@ -1662,6 +1782,14 @@ impl ChaiApp {
                 gw.stop_receiver = None;
             }
         }
+        if let Some(rx) = &gw.delegation_cancel_receiver {
+            if let Ok(result) = rx.try_recv() {
+                if let Err(e) = result {
+                    log::warn!("delegation.cancel failed: {}", e);
+                }
+                gw.delegation_cancel_receiver = None;
+            }
+        }
     }
 
     /// Poll for `sessions.list` fetch result and trigger a fetch on gateway connect.
@@ -1755,6 +1883,20 @@ impl ChaiApp {
         }
     }
 
+    /// Poll for `sessions.delegation` fetch result. Call each frame.
+    fn poll_delegation_transcript(&mut self) {
+        let gw = self.gw();
+        if let Some((ref id, ref rx)) = gw.delegation_transcript_receiver {
+            if let Ok(result) = rx.try_recv() {
+                if let Err(ref e) = result {
+                    log::warn!("sessions.delegation fetch failed for {}: {}", id, e);
+                }
+                gw.delegation_transcripts.insert(id.clone(), result);
+                gw.delegation_transcript_receiver = None;
+            }
+        }
+    }
+
     /// Poll for `sessions.delete` fetch result. Call each frame.
     fn poll_sessions_delete(&mut self) {
         let (result, sid_opt) = {
@@ -1930,6 +2072,7 @@ impl eframe::App for ChaiApp {
         self.poll_session_events();
         self.poll_sessions_list();
         self.poll_sessions_history();
+        self.poll_delegation_transcript();
         self.poll_sessions_delete();
         self.poll_sessions_delete_all();
         self.poll_chat_turn();
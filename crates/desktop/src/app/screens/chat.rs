@@ -1,8 +1,11 @@
 use eframe::egui;
 
-use crate::app::{ChaiApp, ChatMessage};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::app::{ChaiApp, ChatMessage, DelegationTranscript};
 use lib::orchestration::{
-    EVENT_DELEGATE_COMPLETE, EVENT_DELEGATE_ERROR, EVENT_DELEGATE_REJECTED, EVENT_DELEGATE_START,
+    EVENT_DELEGATE_COMPLETE, EVENT_DELEGATE_ERROR, EVENT_DELEGATE_REJECTED, EVENT_DELEGATE_RETRY,
+    EVENT_DELEGATE_START, EVENT_WORKFLOW_COMPLETE, EVENT_WORKFLOW_ERROR, EVENT_WORKFLOW_START,
 };
 
 const CHAT_INPUT_HEIGHT: f32 = 148.0; // 8 rows
@@ -46,6 +49,13 @@ pub fn ui_chat_screen(app: &mut ChaiApp, ui: &mut egui::Ui, running: bool) {
         } else {
             app.chat_messages().cloned().unwrap_or_default()
         };
+        let mut transcript_wanted: Option<String> = None;
+        let mut cancel_wanted: Option<String> = None;
+        let running_delegations = if app.chat_turn_receiver().is_some() {
+            running_delegation_ids(&messages_to_show)
+        } else {
+            HashSet::new()
+        };
         egui::ScrollArea::vertical()
             .stick_to_bottom(true)
             .show(&mut messages_ui, |ui| {
@@ -57,8 +67,25 @@ pub fn ui_chat_screen(app: &mut ChaiApp, ui: &mut egui::Ui, running: bool) {
                     .gateway_status()
                     .and_then(|s| s.orchestrator_id())
                     .unwrap_or("orchestrator");
+                let empty = BTreeMap::new();
+                let transcripts = app.delegation_transcripts().unwrap_or(&empty);
                 for (idx, m) in messages_to_show.iter().enumerate() {
-                    render_chat_message(ui, idx, m, &user_display, orchestrator_id);
+                    if let Some(id) = render_chat_message(ui, idx, m, &user_display, orchestrator_id, transcripts) {
+                        transcript_wanted = Some(id);
+                    }
+                    if let Some(id) = m.delegation_id.as_deref().filter(|id| {
+                        m.delegation_event.as_deref() == Some(EVENT_DELEGATE_START)
+                            && running_delegations.contains(id)
+                    }) {
+                        ui.add_space(4.0);
+                        if ui
+                            .small_button("Cancel delegation")
+                            .on_hover_text("Stop this worker; the orchestrator continues without its result")
+                            .clicked()
+                        {
+                            cancel_wanted = Some(id.to_string());
+                        }
+                    }
                     ui.add_space(8.0);
                 }
                 // Show loading indicator when session history is being fetched.
@@ -67,6 +94,15 @@ pub fn ui_chat_screen(app: &mut ChaiApp, ui: &mut egui::Ui, running: bool) {
                     ui.label(egui::RichText::new("Loading session history…").weak());
                 }
             });
+        if let Some(delegation_id) = cancel_wanted {
+            app.cancel_delegation(&delegation_id);
+        }
+        if let Some(delegation_id) = transcript_wanted {
+            let session_id = app.selected_session_id().or(app.chat_session_id()).cloned();
+            if let Some(session_id) = session_id {
+                app.request_delegation_transcript(&session_id, &delegation_id);
+            }
+        }
 
         ui.add_space(8.0);
 
@@ -187,6 +223,18 @@ pub fn ui_chat_screen(app: &mut ChaiApp, ui: &mut egui::Ui, running: bool) {
                 {
                     app.stop_chat_turn();
                 }
+                // Continue button: resumes a turn that stopped at the tool loop limit.
+                let can_continue = can_send && crate::app::state::chat::can_continue_turn(&messages_to_show);
+                if can_continue {
+                    ui.add_space(4.0);
+                    if ui
+                        .button("Continue")
+                        .on_hover_text("run the pending tool calls and resume the turn")
+                        .clicked()
+                    {
+                        app.continue_chat_turn();
+                    }
+                }
                 if !model_options.is_empty() {
                     ui.add_space(8.0);
                     let current_label = app
@@ -320,14 +368,32 @@ fn is_worker_tool(m: &ChatMessage) -> bool {
         .is_some_and(|s| s != "orchestrator")
 }
 
+/// Delegation ids that have a start row but no finished (complete or error) row yet.
+fn running_delegation_ids(messages: &[ChatMessage]) -> HashSet<&str> {
+    let mut running = HashSet::new();
+    for m in messages {
+        let (Some(event), Some(id)) = (m.delegation_event.as_deref(), m.delegation_id.as_deref()) else {
+            continue;
+        };
+        if event == EVENT_DELEGATE_START {
+            running.insert(id);
+        } else if event == EVENT_DELEGATE_COMPLETE || event == EVENT_DELEGATE_ERROR {
+            running.remove(id);
+        }
+    }
+    running
+}
+
 /// Renders a single chat message in the same style as the chat screen (frame, role-based fill, content, tool calls).
+/// Returns the delegation id of a worker transcript that was expanded but is not loaded yet.
 fn render_chat_message(
     ui: &mut egui::Ui,
     index: usize,
     m: &ChatMessage,
     user_display: &str,
     orchestrator_id: &str,
-) {
+    transcripts: &BTreeMap<String, Result<DelegationTranscript, String>>,
+) -> Option<String> {
     let is_user = m.role == "user";
     let is_assistant = m.role == "assistant";
     let is_assistant_progress = m.role == "assistant_progress";
@@ -345,8 +411,9 @@ fn render_chat_message(
     // useful information (e.g. when the tool loop limit is reached and the model
     // only produced tool calls without text).
     if (is_assistant || is_assistant_progress) && m.content.trim().is_empty() {
-        return;
+        return None;
     }
+    let mut transcript_wanted = None;
 
     let frame = egui::Frame::none()
         .fill(if is_user {
@@ -387,7 +454,11 @@ fn render_chat_message(
                 Some(s) if s == EVENT_DELEGATE_START => egui::Color32::from_rgb(70, 110, 180),
                 Some(s) if s == EVENT_DELEGATE_COMPLETE => egui::Color32::from_rgb(60, 140, 90),
                 Some(s) if s == EVENT_DELEGATE_REJECTED => egui::Color32::from_rgb(180, 120, 40),
+                Some(s) if s == EVENT_DELEGATE_RETRY => egui::Color32::from_rgb(180, 120, 40),
                 Some(s) if s == EVENT_DELEGATE_ERROR => egui::Color32::from_rgb(180, 60, 60),
+                Some(s) if s == EVENT_WORKFLOW_START => egui::Color32::from_rgb(70, 110, 180),
+                Some(s) if s == EVENT_WORKFLOW_COMPLETE => egui::Color32::from_rgb(60, 140, 90),
+                Some(s) if s == EVENT_WORKFLOW_ERROR => egui::Color32::from_rgb(180, 60, 60),
                 _ => ui.style().visuals.weak_text_color(),
             };
             ui.label(
@@ -395,6 +466,26 @@ fn render_chat_message(
                     .italics()
                     .color(accent),
             );
+            let finished = matches!(
+                m.delegation_event.as_deref(),
+                Some(s) if s == EVENT_DELEGATE_COMPLETE || s == EVENT_DELEGATE_ERROR
+            );
+            if let (true, Some(delegation_id)) = (finished, m.delegation_id.as_deref()) {
+                ui.add_space(4.0);
+                egui::CollapsingHeader::new(egui::RichText::new("Worker transcript").small())
+                    .id_source(format!("delegation_transcript_{}_{}", index, delegation_id))
+                    .default_open(false)
+                    .show(ui, |ui| match transcripts.get(delegation_id) {
+                        Some(Ok(t)) => render_delegation_transcript(ui, t),
+                        Some(Err(e)) => {
+                            ui.label(egui::RichText::new(e).small().color(egui::Color32::RED));
+                        }
+                        None => {
+                            transcript_wanted = Some(delegation_id.to_string());
+                            ui.label(egui::RichText::new("Loading transcript…").small().weak());
+                        }
+                    });
+            }
         } else if is_worker_reply {
             // Worker reply — rendered as a first-class chat line showing the
             // worker's response text directly, not buried inside a tool result.
@@ -490,8 +581,8 @@ fn render_chat_message(
             ui.add_space(4.0);
             ui.label(
                 egui::RichText::new(
-                    "The agent's last tool call was not executed. Send another message \
-                     to continue the session and ask the agent to retry.",
+                    "The agent's last tool calls were not executed. Click Continue (or send \
+                     /continue) to run them and resume the turn.",
                 )
                 .small(),
             );
@@ -550,4 +641,47 @@ fn render_chat_message(
             ui.label(egui::RichText::new(&m.content));
         }
     });
+    transcript_wanted
+}
+
+/// Renders a worker transcript from `sessions.delegation` inside a delegation row.
+fn render_delegation_transcript(ui: &mut egui::Ui, t: &DelegationTranscript) {
+    ui.label(
+        egui::RichText::new(format!("{} · {}/{} · {}", t.worker_id, t.provider, t.model, t.delegation_id))
+            .small()
+            .weak(),
+    );
+    if t.stopped {
+        ui.label(egui::RichText::new("stopped before the worker finished").small().weak());
+    }
+    if let Some(ref e) = t.error {
+        ui.label(egui::RichText::new(e).small().color(egui::Color32::RED));
+    }
+    for msg in &t.messages {
+        ui.add_space(6.0);
+        let label = match msg.tool_name.as_deref() {
+            Some(name) => format!("{} ({})", msg.role, name),
+            None => msg.role.clone(),
+        };
+        ui.label(egui::RichText::new(label).small().weak());
+        if !msg.content.trim().is_empty() {
+            let shown = if msg.role == "tool" {
+                format_tool_content_display(&msg.content)
+            } else {
+                msg.content.clone()
+            };
+            ui.label(egui::RichText::new(shown).small().monospace());
+        }
+        for name in &msg.tool_calls {
+            ui.label(egui::RichText::new(format!("  🔧 {}", name)).small().monospace());
+        }
+    }
+    if !t.nested.is_empty() {
+        ui.add_space(6.0);
+        ui.label(
+            egui::RichText::new(format!("Nested delegations: {}", t.nested.join(", ")))
+                .small()
+                .weak(),
+        );
+    }
 }
@@ -8,7 +8,9 @@ use futures_util::{SinkExt, StreamExt};
 use tokio_tungstenite::tungstenite::Message;
 
 use lib::orchestration::{
-    EVENT_DELEGATE_COMPLETE, EVENT_DELEGATE_ERROR, EVENT_DELEGATE_REJECTED, EVENT_DELEGATE_START,
+    EVENT_DELEGATE_COMPLETE, EVENT_DELEGATE_ERROR, EVENT_DELEGATE_REJECTED, EVENT_DELEGATE_RETRY,
+    EVENT_DELEGATE_START, EVENT_MODEL_ROUTE, EVENT_WORKFLOW_COMPLETE, EVENT_WORKFLOW_ERROR,
+    EVENT_WORKFLOW_START, EVENT_WORKFLOW_STEP,
 };
 
 use super::super::{ChannelBinding, ChaiApp, ChatMessage, SessionEvent, SessionSummary};
@@ -19,6 +21,25 @@ pub(crate) fn last_non_delegation(messages: &[ChatMessage]) -> Option<&ChatMessa
     messages.iter().rev().find(|m| !matches!(m.role.as_str(), "delegation" | "tool_call" | "tool_result" | "assistant_progress" | "tool_loop_limit" | "turn_stopped"))
 }
 
+/// Timeline row added when the user resumes a turn that stopped at the tool loop limit.
+pub(crate) const CONTINUE_MARKER: &str = "Continuing: running pending tool calls.";
+
+/// Index where the current turn's rows start: just after the last user message or continue marker.
+pub(crate) fn current_turn_start(messages: &[ChatMessage]) -> usize {
+    messages
+        .iter()
+        .rposition(|m| m.role == "user" || (m.role == "system" && m.content == CONTINUE_MARKER))
+        .map_or(0, |i| i + 1)
+}
+
+/// Whether the current turn ended at the tool loop limit with tool calls still pending
+/// (enables the chat screen's Continue button).
+pub(crate) fn can_continue_turn(messages: &[ChatMessage]) -> bool {
+    messages[current_turn_start(messages)..].iter().any(|m| {
+        m.role == "tool_loop_limit" && m.pending_tool_calls.as_ref().is_some_and(|p| !p.is_empty())
+    })
+}
+
 /// Same assistant turn as already shown (same content), ignoring delegation rows in between.
 pub(crate) fn is_duplicate_assistant_row(
     prev: &ChatMessage,
@@ -31,8 +52,61 @@ pub(crate) fn is_duplicate_assistant_row(
         && prev.content == content
 }
 
+/// Human-readable line for a gateway `orchestration.workflow.*` payload.
+fn format_workflow_line(event_name: &str, data: &serde_json::Value) -> String {
+    let workflow = data.get("workflowId").and_then(|v| v.as_str()).unwrap_or("?");
+    let err = data
+        .get("error")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown error");
+    if event_name == EVENT_WORKFLOW_STEP {
+        let step = data.get("stepId").and_then(|v| v.as_str()).unwrap_or("?");
+        let status = data.get("status").and_then(|v| v.as_str()).unwrap_or("");
+        let mut s = format!("Workflow `{}` · step `{}` {}", workflow, step, status);
+        if let Some(w) = data.get("workerId").and_then(|v| v.as_str()) {
+            s.push_str(&format!(" · worker `{}`", w));
+        }
+        if status == "failed" {
+            s.push_str(&format!(": {}", err));
+        }
+        return s;
+    }
+    if event_name == EVENT_WORKFLOW_START {
+        let steps = data.get("steps").and_then(|v| v.as_u64()).unwrap_or(0);
+        return format!("Workflow `{}` starting · {} step(s)", workflow, steps);
+    }
+    if event_name == EVENT_WORKFLOW_COMPLETE {
+        return format!("Workflow `{}` finished", workflow);
+    }
+    format!("Workflow `{}` failed: {}", workflow, err)
+}
+
+/// Human-readable line for a gateway `orchestration.model.route` payload.
+fn format_route_line(data: &serde_json::Value) -> String {
+    let field = |k: &str| data.get(k).and_then(|v| v.as_str()).unwrap_or("?");
+    let mut s = format!(
+        "Model routing · `{}` · tier {} · {} / {}",
+        field("agentId"),
+        field("tier"),
+        field("provider"),
+        field("model")
+    );
+    match field("reason") {
+        "validation" => s.push_str(" · escalated after failed output validation"),
+        "loop_limit" => s.push_str(" · escalated at the tool loop limit"),
+        _ => {}
+    }
+    s
+}
+
 /// Human-readable line for a gateway `orchestration.delegate.*` payload.
 fn format_delegation_line(event_name: &str, data: &serde_json::Value) -> String {
+    if event_name.starts_with("orchestration.workflow.") {
+        return format_workflow_line(event_name, data);
+    }
+    if event_name == EVENT_MODEL_ROUTE {
+        return format_route_line(data);
+    }
     let worker = data
         .get("workerId")
         .and_then(|v| v.as_str())
@@ -75,6 +149,22 @@ fn format_delegation_line(event_name: &str, data: &serde_json::Value) -> String
         s.push_str(&format!(" · {} tool call(s)", n_calls));
         return s;
     }
+    if event_name == EVENT_DELEGATE_RETRY {
+        let attempt = data.get("attempt").and_then(|v| v.as_u64()).unwrap_or(0);
+        let err = data
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown error");
+        let mut s = format!("Delegation retrying · attempt {}", attempt);
+        if let Some(w) = worker {
+            s.push_str(&format!(" · worker `{}`", w));
+        }
+        if !pm.is_empty() {
+            s.push_str(&format!(" · {}", pm));
+        }
+        s.push_str(&format!(" (previous attempt: {})", err));
+        return s;
+    }
     if event_name == EVENT_DELEGATE_ERROR {
         let err = data
             .get("error")
@@ -296,6 +386,7 @@ impl ChaiApp {
                         tool_index: ev.tool_index,
                         source: ev.source.clone(),
                         pending_tool_calls: ev.pending_tool_calls.clone(),
+                        delegation_id: ev.delegation_id.clone(),
                     });
                 }
                 self.update_session_channel_meta(&session_id, ev.channel_id.clone(), ev.conversation_id.clone());
@@ -330,6 +421,7 @@ impl ChaiApp {
                     tool_index: ev.tool_index,
                     source: ev.source.clone(),
                     pending_tool_calls: ev.pending_tool_calls.clone(),
+                    delegation_id: ev.delegation_id.clone(),
                 });
                 self.update_session_channel_meta(&session_id, ev.channel_id.clone(), ev.conversation_id.clone());
                 self.move_session_to_front(&session_id);
@@ -423,6 +515,7 @@ impl ChaiApp {
                 tool_index: ev.tool_index,
                 source: ev.source.clone(),
                 pending_tool_calls: ev.pending_tool_calls.clone(),
+                delegation_id: ev.delegation_id.clone(),
             };
             if ev.role == "assistant" && has_streamed_tools_this_turn(entry) {
                 ev_msg.tool_calls = None;
@@ -645,6 +738,7 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                                 tool_index: None,
                                 source: None,
                                 pending_tool_calls: None,
+                                delegation_id: None,
                                 orchestrator_id: event_orchestrator_id,
                             };
                             let _ = tx.send(ev);
@@ -655,7 +749,13 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                         EVENT_DELEGATE_START
                             | EVENT_DELEGATE_COMPLETE
                             | EVENT_DELEGATE_ERROR
+                            | EVENT_DELEGATE_RETRY
                             | EVENT_DELEGATE_REJECTED
+                            | EVENT_WORKFLOW_START
+                            | EVENT_WORKFLOW_STEP
+                            | EVENT_WORKFLOW_COMPLETE
+                            | EVENT_WORKFLOW_ERROR
+                            | EVENT_MODEL_ROUTE
                     ) {
                         if let Some(payload) = val.get("payload") {
                             let data = payload.get("data").unwrap_or(payload);
@@ -672,6 +772,10 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                                 .and_then(|v| v.as_str())
                                 .map(|s| s.to_string());
                             let content = format_delegation_line(event_name, data);
+                            let delegation_id = data
+                                .get("delegationId")
+                                .and_then(|v| v.as_str())
+                                .map(|s| s.to_string());
 
                             if event_name == EVENT_DELEGATE_COMPLETE {
                                 if let Some(reply) = data
@@ -701,6 +805,7 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                                         tool_index: None,
                                         source: Some(worker_id.to_string()),
                                         pending_tool_calls: None,
+                                        delegation_id: delegation_id.clone(),
                                         orchestrator_id: event_orchestrator_id.clone(),
                                     };
                                     let _ = tx.send(worker_ev);
@@ -722,6 +827,7 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                                 tool_index: None,
                                 source: None,
                                 pending_tool_calls: None,
+                                delegation_id,
                                 orchestrator_id: event_orchestrator_id,
                             };
                             let _ = tx.send(ev);
@@ -779,6 +885,7 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                                 tool_index,
                                 source,
                                 pending_tool_calls: None,
+                                delegation_id: None,
                                 orchestrator_id: event_orchestrator_id,
                             };
                             let _ = tx.send(ev);
@@ -826,6 +933,7 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                                 tool_index: None,
                                 source,
                                 pending_tool_calls: None,
+                                delegation_id: None,
                                 orchestrator_id: event_orchestrator_id,
                             };
                             let _ = tx.send(ev);
@@ -865,6 +973,7 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                                 tool_index: None,
                                 source: None,
                                 pending_tool_calls,
+                                delegation_id: None,
                                 orchestrator_id: event_orchestrator_id,
                             };
                             let _ = tx.send(ev);
@@ -900,6 +1009,7 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                                 tool_index: None,
                                 source: None,
                                 pending_tool_calls: None,
+                                delegation_id: None,
                                 orchestrator_id: event_orchestrator_id,
                             };
                             let _ = tx.send(ev);
@@ -934,11 +1044,45 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                                 tool_index: None,
                                 source: None,
                                 pending_tool_calls: None,
+                                delegation_id: None,
                                 orchestrator_id: event_orchestrator_id,
                             };
                             let _ = tx.send(ev);
                             ctx.request_repaint();
                         }
+                    } else if event_name == "session.transferred" {
+                        // A moved session leaves the source orchestrator's list; a copy leaves
+                        // it in place.
+                        let data = val.get("payload").cloned().unwrap_or_default();
+                        let field = |k: &str| {
+                            data.get(k)
+                                .and_then(|v| v.as_str())
+                                .map(|s| s.trim())
+                                .filter(|s| !s.is_empty())
+                                .map(|s| s.to_string())
+                        };
+                        if field("mode").as_deref() == Some("move") {
+                            let ev = SessionEvent {
+                                session_id: field("sessionId").unwrap_or_default(),
+                                role: "session_deleted".to_string(),
+                                content: String::new(),
+                                channel_id: None,
+                                conversation_id: None,
+                                tool_calls: None,
+                                tool_results: None,
+                                delegation_event: None,
+                                tool_name: None,
+                                tool_args: None,
+                                tool_result: None,
+                                tool_index: None,
+                                source: None,
+                                pending_tool_calls: None,
+                                delegation_id: None,
+                                orchestrator_id: field("fromOrchestratorId"),
+                            };
+                            let _ = tx.send(ev);
+                            ctx.request_repaint();
+                        }
                     } else if event_name == "sessions.cleared" {
                         let cleared_orchestrator_id = val
                             .get("payload")
@@ -960,11 +1104,15 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                             tool_index: None,
                             source: None,
                             pending_tool_calls: None,
+                            delegation_id: None,
                             orchestrator_id: cleared_orchestrator_id,
                         };
                         let _ = tx.send(ev);
                         ctx.request_repaint();
-                    } else if event_name == "gateway.config.changed" {
+                    } else if event_name == "gateway.config.changed"
+                        || event_name == "sandbox.roots.changed"
+                    {
+                        // Both change what the status payload reports; refetch it.
                         let ev = SessionEvent {
                             session_id: String::new(),
                             role: "config_changed".to_string(),
@@ -980,6 +1128,7 @@ fn run_session_events_loop(tx: mpsc::Sender<SessionEvent>, ctx: egui::Context, p
                             tool_index: None,
                             source: None,
                             pending_tool_calls: None,
+                            delegation_id: None,
                             orchestrator_id: None,
                         };
                         let _ = tx.send(ev);
@@ -1156,10 +1156,12 @@ pub(crate) fn resolve_chai_binary() -> Option<PathBuf> {
 }
 
 /// Run one agent turn against the gateway: connect, send message, return reply and session id.
+/// When `message` is `None`, sends `continue` instead to run the tool calls left pending when the
+/// session's last turn hit the tool loop limit.
 pub(crate) fn run_agent_turn(
     profile_override: Option<&str>,
     session_id: Option<String>,
-    message: String,
+    message: Option<String>,
     provider: Option<String>,
     model: Option<String>,
     orchestrator_id: Option<String>,
@@ -1235,10 +1237,12 @@ pub(crate) fn run_agent_turn(
             }
         }
 
-        // Build agent turn request
-        let mut agent_params = serde_json::json!({
-            "message": message,
-        });
+        // Build agent turn request ("continue" when there is no message)
+        let method = if message.is_some() { "agent" } else { "continue" };
+        let mut agent_params = match message {
+            Some(message) => serde_json::json!({ "message": message }),
+            None => serde_json::json!({}),
+        };
         if let Some(sid) = &session_id {
             agent_params["sessionId"] = serde_json::Value::String(sid.clone());
         }
@@ -1255,7 +1259,7 @@ pub(crate) fn run_agent_turn(
         let agent_req = serde_json::json!({
             "type": "req",
             "id": "2",
-            "method": "agent",
+            "method": method,
             "params": agent_params
         });
         ws.send(Message::Text(agent_req.to_string().into()))
@@ -1335,6 +1339,28 @@ pub(crate) fn run_agent_turn(
 pub(crate) fn send_stop(
     profile_override: Option<&str>,
     session_id: &str,
+) -> Result<bool, String> {
+    send_control_request(profile_override, "stop", serde_json::json!({ "sessionId": session_id }))
+}
+
+/// Send a `delegation.cancel` request for a running delegation. Returns `Ok(false)` when the
+/// gateway reports that the delegation is no longer running.
+pub(crate) fn send_delegation_cancel(
+    profile_override: Option<&str>,
+    delegation_id: &str,
+) -> Result<bool, String> {
+    send_control_request(
+        profile_override,
+        "delegation.cancel",
+        serde_json::json!({ "delegationId": delegation_id }),
+    )
+}
+
+/// Send a single request whose response only matters as ok / not ok.
+fn send_control_request(
+    profile_override: Option<&str>,
+    method: &'static str,
+    params: serde_json::Value,
 ) -> Result<bool, String> {
     let info = resolve_ws_connect_info(profile_override)?;
     let ws_url = info.ws_url;
@@ -1407,13 +1433,13 @@ pub(crate) fn send_stop(
             }
         }
 
-        let stop_req = serde_json::json!({
+        let req = serde_json::json!({
             "type": "req",
             "id": "2",
-            "method": "stop",
-            "params": { "sessionId": session_id }
+            "method": method,
+            "params": params
         });
-        ws.send(Message::Text(stop_req.to_string().into()))
+        ws.send(Message::Text(req.to_string().into()))
             .await
             .map_err(|e| e.to_string())?;
 
@@ -1429,7 +1455,7 @@ pub(crate) fn send_stop(
                 return Ok(ok);
             }
         }
-        Err("no stop response".to_string())
+        Err(format!("no {} response", method))
     })
 }
 
@@ -1709,8 +1735,34 @@ pub(crate) fn fetch_sessions_history(
                     .get("messages")
                     .and_then(|v| v.as_array())
                     .ok_or("missing messages array")?;
+                // Top-level delegations with a persisted worker transcript, keyed by the index of
+                // the session message they precede (nested ones are reached from their parent).
+                let mut transcript_links: Vec<(usize, String, String)> = payload
+                    .get("delegationTranscripts")
+                    .and_then(|v| v.as_array())
+                    .map(|links| {
+                        links
+                            .iter()
+                            .filter(|l| l.get("parentDelegationId").is_none())
+                            .filter_map(|l| {
+                                Some((
+                                    l.get("messageIndex")?.as_u64()? as usize,
+                                    l.get("delegationId")?.as_str()?.to_string(),
+                                    l.get("workerId")?.as_str()?.to_string(),
+                                ))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                transcript_links.sort_by_key(|(index, _, _)| *index);
+                let mut next_link = 0;
                 let mut messages = Vec::new();
-                for entry in messages_arr {
+                for (msg_index, entry) in messages_arr.iter().enumerate() {
+                    while next_link < transcript_links.len() && transcript_links[next_link].0 <= msg_index {
+                        let (_, ref delegation_id, ref worker_id) = transcript_links[next_link];
+                        messages.push(delegation_transcript_row(delegation_id, worker_id));
+                        next_link += 1;
+                    }
                     let role = entry
                         .get("role")
                         .and_then(|v| v.as_str())
@@ -1750,6 +1802,7 @@ pub(crate) fn fetch_sessions_history(
                                 tool_index: None,
                                 source: None,
                                 pending_tool_calls: None,
+                                delegation_id: None,
                             });
                         }
                         if has_tool_calls {
@@ -1776,6 +1829,7 @@ pub(crate) fn fetch_sessions_history(
                                         tool_index: Some(idx),
                                         source: None,
                                         pending_tool_calls: None,
+                                        delegation_id: None,
                                     });
                                 }
                             }
@@ -1801,6 +1855,7 @@ pub(crate) fn fetch_sessions_history(
                             tool_index: None, // resolved below
                             source: None,
                             pending_tool_calls: None,
+                            delegation_id: None,
                         });
                     } else {
                         // User, system, or other roles — pass through as-is.
@@ -1816,10 +1871,15 @@ pub(crate) fn fetch_sessions_history(
                             tool_index: None,
                             source: None,
                             pending_tool_calls: None,
+                            delegation_id: None,
                         });
                     }
                 }
 
+                for (_, delegation_id, worker_id) in &transcript_links[next_link..] {
+                    messages.push(delegation_transcript_row(delegation_id, worker_id));
+                }
+
                 // Merge tool results into their corresponding tool_call entries.
                 // Walk the message list: for each tool_result, find the most
                 // recent unmatched tool_call (same tool_name, no result yet) in
@@ -1838,6 +1898,168 @@ pub(crate) fn fetch_sessions_history(
     })
 }
 
+/// Fetch the worker transcript of one delegation via the `sessions.delegation` WS method.
+pub(crate) fn fetch_sessions_delegation(
+    profile_override: Option<&str>,
+    session_id: &str,
+    delegation_id: &str,
+) -> Result<crate::app::types::DelegationTranscript, String> {
+    let info = resolve_ws_connect_info(profile_override)?;
+    let ws_url = info.ws_url;
+    let token = info.gateway_token;
+    let paths = info.paths;
+
+    let rt = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    rt.block_on(async move {
+        let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let first = ws
+            .next()
+            .await
+            .ok_or("no first frame")?
+            .map_err(|e| e.to_string())?;
+        let Message::Text(challenge_text) = first else {
+            return Err("expected text challenge frame".to_string());
+        };
+        let challenge: serde_json::Value =
+            serde_json::from_str(&challenge_text).map_err(|e| e.to_string())?;
+        let nonce = challenge
+            .get("payload")
+            .and_then(|p| p.get("nonce").and_then(|n| n.as_str()))
+            .ok_or("expected connect.challenge event with nonce")?
+            .to_string();
+
+        let connect_params = build_connect_params(&paths, token.as_deref(), &nonce)?;
+        let connect_req = serde_json::json!({
+            "type": "req",
+            "id": "1",
+            "method": "connect",
+            "params": connect_params
+        });
+        ws.send(Message::Text(connect_req.to_string().into()))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        // Wait for connect response.
+        loop {
+            let msg = ws
+                .next()
+                .await
+                .ok_or("no connect response")?
+                .map_err(|e| e.to_string())?;
+            let Message::Text(text) = msg else { continue };
+            let res: serde_json::Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+            if res.get("type").and_then(|v| v.as_str()) != Some("res") {
+                continue;
+            }
+            if res.get("id").and_then(|v| v.as_str()) == Some("1") {
+                if !res.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+                    let err = res
+                        .get("error")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("connect failed");
+                    if err == "invalid device token" {
+                        let _ = std::fs::remove_file(paths.device_token_path());
+                    }
+                    return Err(err.to_string());
+                }
+                if let Some(auth) = res.get("payload").and_then(|p| p.get("auth")) {
+                    if let Some(dt) = auth.get("deviceToken").and_then(|v| v.as_str()) {
+                        let _ = lib::device::save_device_token_to(&paths.device_token_path(), dt);
+                    }
+                }
+                break;
+            }
+        }
+
+        let req = serde_json::json!({
+            "type": "req",
+            "id": "2",
+            "method": "sessions.delegation",
+            "params": { "sessionId": session_id, "delegationId": delegation_id }
+        });
+        ws.send(Message::Text(req.to_string().into()))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        while let Some(msg) = ws.next().await {
+            let msg = msg.map_err(|e| e.to_string())?;
+            let Message::Text(text) = msg else { continue };
+            let res: serde_json::Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+            if res.get("type").and_then(|v| v.as_str()) != Some("res") {
+                continue;
+            }
+            if res.get("id").and_then(|v| v.as_str()) == Some("2") {
+                if !res.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+                    let err = res
+                        .get("error")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("sessions.delegation failed");
+                    return Err(err.to_string());
+                }
+                let payload = res.get("payload").ok_or("missing payload")?;
+                let str_field = |key: &str| {
+                    payload
+                        .get(key)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string()
+                };
+                let messages = payload
+                    .get("messages")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| {
+                        arr.iter()
+                            .map(|m| crate::app::types::DelegationTranscriptMessage {
+                                role: m.get("role").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                                content: m.get("content").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                                tool_name: m.get("toolName").and_then(|v| v.as_str()).map(String::from),
+                                tool_calls: m
+                                    .get("toolCalls")
+                                    .and_then(|v| v.as_array())
+                                    .map(|calls| {
+                                        calls
+                                            .iter()
+                                            .filter_map(|c| {
+                                                c.get("function")
+                                                    .and_then(|f| f.get("name"))
+                                                    .and_then(|n| n.as_str())
+                                                    .map(String::from)
+                                            })
+                                            .collect()
+                                    })
+                                    .unwrap_or_default(),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let nested = payload
+                    .get("nested")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|l| l.get("delegationId").and_then(|v| v.as_str()).map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                return Ok(crate::app::types::DelegationTranscript {
+                    delegation_id: str_field("delegationId"),
+                    worker_id: str_field("workerId"),
+                    provider: str_field("provider"),
+                    model: str_field("model"),
+                    stopped: payload.get("stopped").and_then(|v| v.as_bool()).unwrap_or(false),
+                    error: payload.get("error").and_then(|v| v.as_str()).map(String::from),
+                    messages,
+                    nested,
+                });
+            }
+        }
+        Err("no sessions.delegation response".to_string())
+    })
+}
+
 /// Delete a session via `sessions.delete` WS method.
 pub(crate) fn fetch_sessions_delete(
     profile_override: Option<&str>,
@@ -2056,6 +2278,24 @@ pub(crate) fn fetch_sessions_delete_all(
     })
 }
 
+/// Delegation row for a reloaded session so the worker transcript stays reachable from history.
+fn delegation_transcript_row(delegation_id: &str, worker_id: &str) -> super::super::ChatMessage {
+    super::super::ChatMessage {
+        role: "delegation".to_string(),
+        content: format!("Delegation · worker `{}`", worker_id),
+        tool_calls: None,
+        tool_results: None,
+        delegation_event: Some(lib::orchestration::EVENT_DELEGATE_COMPLETE.to_string()),
+        tool_name: None,
+        tool_args: None,
+        tool_result: None,
+        tool_index: None,
+        source: None,
+        pending_tool_calls: None,
+        delegation_id: Some(delegation_id.to_string()),
+    }
+}
+
 /// Merge tool results into their corresponding tool_call entries for historical
 /// session display. The server stores tool calls and results as separate messages
 /// (assistant with toolCalls, then tool with toolName + content). After
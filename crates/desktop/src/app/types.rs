@@ -25,6 +25,9 @@ pub struct ChatMessage {
     /// Tool calls that were generated but not executed because the loop limit was reached.
     /// Set on `tool_loop_limit` role messages.
     pub(crate) pending_tool_calls: Option<Vec<serde_json::Value>>,
+    /// Delegation id (`dlg-<uuid>`) of the `delegate_task` call this row reports on. When set,
+    /// the row offers the worker transcript (`sessions.delegation`).
+    pub(crate) delegation_id: Option<String>,
 }
 
 impl ChatMessage {
@@ -41,6 +44,7 @@ impl ChatMessage {
             tool_index: None,
             source: None,
             pending_tool_calls: None,
+            delegation_id: None,
         }
     }
 
@@ -57,6 +61,7 @@ impl ChatMessage {
             tool_index: None,
             source: None,
             pending_tool_calls: None,
+            delegation_id: None,
         }
     }
 
@@ -77,6 +82,7 @@ impl ChatMessage {
             tool_index: None,
             source: None,
             pending_tool_calls: None,
+            delegation_id: None,
         }
     }
 
@@ -93,6 +99,7 @@ impl ChatMessage {
             tool_index: None,
             source: None,
             pending_tool_calls: None,
+            delegation_id: None,
         }
     }
 
@@ -117,6 +124,7 @@ impl ChatMessage {
             } else {
                 Some(pending_tool_calls)
             },
+            delegation_id: None,
         }
     }
 
@@ -135,6 +143,7 @@ impl ChatMessage {
             tool_index: None,
             source: None,
             pending_tool_calls: None,
+            delegation_id: None,
         }
     }
 }
@@ -181,6 +190,8 @@ pub struct SessionEvent {
     /// Tool calls that were generated but not executed because the loop limit was reached.
     /// Set on `session.tool_loop_limit` events.
     pub(crate) pending_tool_calls: Option<Vec<serde_json::Value>>,
+    /// Delegation id from `orchestration.delegate.*` payloads.
+    pub(crate) delegation_id: Option<String>,
     /// Orchestrator id from `sessions.cleared` events. When set, only sessions for this
     /// orchestrator were deleted. When None, all orchestrators' sessions were cleared.
     pub(crate) orchestrator_id: Option<String>,
@@ -231,6 +242,34 @@ pub struct SessionHistory {
     pub(crate) updated_at: String,
 }
 
+/// Worker transcript of one `delegate_task` call as returned by `sessions.delegation`.
+#[derive(Clone, Default)]
+pub struct DelegationTranscript {
+    pub(crate) delegation_id: String,
+    pub(crate) worker_id: String,
+    pub(crate) provider: String,
+    pub(crate) model: String,
+    /// True when the worker turn was stopped before it finished.
+    pub(crate) stopped: bool,
+    /// Error that ended the delegation (provider failure, `outputSchema` mismatch).
+    pub(crate) error: Option<String>,
+    /// Full worker conversation: system prompt, task, tool calls and results, final reply.
+    pub(crate) messages: Vec<DelegationTranscriptMessage>,
+    /// Delegation ids started by this worker (nested delegations).
+    pub(crate) nested: Vec<String>,
+}
+
+/// One message of a [`DelegationTranscript`].
+#[derive(Clone, Default)]
+pub struct DelegationTranscriptMessage {
+    pub(crate) role: String,
+    pub(crate) content: String,
+    /// Tool name on `tool` role messages.
+    pub(crate) tool_name: Option<String>,
+    /// Names of the tools called by an `assistant` message.
+    pub(crate) tool_calls: Vec<String>,
+}
+
 /// One orchestrator row derived from gateway **`status`** `payload.agents` (**`role`** **`orchestrator`**).
 #[derive(Clone, Default)]
 pub struct StatusOrchestratorRow {
@@ -5,14 +5,26 @@
 //! Gateway dispatch uses [`run_turn_dyn`] with [`crate::orchestration::ProviderClients::as_dyn`].
 //! When the gateway passes [`crate::orchestration::DelegateContext`], the built-in tool **`delegate_task`** runs a worker
 //! via [`crate::orchestration::execute_delegate_task`] on another enabled provider: per-worker system context and tools
-//! when **`workerId`** is set, otherwise the orchestrator’s skill bundle; nested **`delegate_task`** is disabled (see epic).
-
+//! when **`workerId`** is set, otherwise the orchestrator’s skill bundle; nested **`delegate_task`** runs only within **`maxDelegationDepth`**.
+//! **`delegate_many`** fans one instruction out over a list of items via [`crate::orchestration::execute_delegate_many`].
+//! Orchestrator **`workflow_<id>`** tools run a configured workflow via [`crate::orchestration::run_workflow_call`].
+//! With **`asyncDelegation`**, `delegate_task` runs in the background ([`crate::orchestration::BackgroundDelegations`])
+//! and finished results are added to the conversation before the next model call.
+//! A **`handoff`** call is only checked here; the gateway moves the session after the turn.
+//! When an [`ApprovalScope`] is set (orchestrator via [`DelegateContext::approval`], worker via
+//! [`WorkerTurn::approval`]), gated tool calls wait for an operator decision before they run.
+
+use crate::approval::ApprovalScope;
 use crate::orchestration::{
-    execute_delegate_task, DelegateContext, DelegateObservability, DelegateTaskResult,
-    DELEGATE_TASK_TOOL_NAME,
+    delegate_many_count, execute_delegate_many, execute_delegate_task, is_delegation_handle_tool,
+    is_workflow_tool, run_workflow_call, BackgroundDelegations, DelegateContext,
+    DelegateObservability, DelegateTaskResult, DelegationBatch, HandoffRequest, AWAIT_DELEGATION_TOOL_NAME,
+    DELEGATE_MANY_TOOL_NAME, DELEGATE_TASK_TOOL_NAME, HANDOFF_TOOL_NAME,
 };
-use crate::providers::{ChatMessage, Provider, ProviderError, ToolCall, ToolDefinition};
+use crate::providers::{ChatMessage, ChatResponse, Provider, ProviderError, ToolCall, ToolDefinition};
 use crate::session::SessionStore;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -43,9 +55,31 @@ pub struct AgentTurnResult {
     pub stopped: bool,
 }
 
+/// Per-call inputs of [`ToolExecutor::execute`] besides the tool name and arguments.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToolCallContext<'a> {
+    /// Session the call belongs to; `None` when the turn is not saved to a session.
+    pub session_id: Option<&'a str>,
+    /// The result goes to a provider in the profile's `redaction` scope: apply the redaction
+    /// rules to the output.
+    pub redact_output: bool,
+}
+
 /// Executes a tool by name and JSON arguments. Returns output or error string.
 pub trait ToolExecutor: Send + Sync {
-    fn execute(&self, name: &str, args: &serde_json::Value, session_id: Option<&str>) -> Result<String, String>;
+    fn execute(&self, name: &str, args: &serde_json::Value, ctx: ToolCallContext<'_>) -> Result<String, String>;
+
+    /// Working directory the call would run in, validated against the sandbox the same way as
+    /// the call itself, for processes run around it (profile hooks). `None` = the gateway's own.
+    fn working_dir(&self, _name: &str, _args: &serde_json::Value) -> Result<Option<std::path::PathBuf>, String> {
+        Ok(None)
+    }
+
+    /// Kernel policy the executor's own commands run under (`sandbox.kernel`), for processes run
+    /// around its calls (profile hooks). `None` = not confined.
+    fn kernel_policy(&self) -> Option<crate::exec::KernelPolicy> {
+        None
+    }
 }
 
 /// Run one agent turn: load session messages, call the given provider (streaming when on_chunk is Some); if tools are provided and the model returns tool_calls, execute them and re-call until no more tool_calls or max iterations.
@@ -58,41 +92,98 @@ pub async fn run_turn<B: Provider>(
     system_context: Option<&str>,
     max_tool_loops_per_turn: Option<u32>,
     tools: Option<Vec<ToolDefinition>>,
-    tool_executor: Option<&dyn ToolExecutor>,
-    delegate: Option<DelegateContext<'_>>,
+    tool_executor: Option<Arc<dyn ToolExecutor>>,
+    delegate: Option<DelegateContext>,
     on_chunk: Option<&mut (dyn FnMut(&str) + Send)>,
     stop_flag: Option<Arc<AtomicBool>>,
 ) -> Result<AgentTurnResult, ProviderError> {
     run_turn_dyn(
-        store,
-        session_id,
-        provider as &dyn Provider,
-        model,
-        system_context,
-        max_tool_loops_per_turn,
-        tools,
-        tool_executor,
-        delegate,
+        SessionTurn {
+            store,
+            session_id,
+            provider: provider as &dyn Provider,
+            model,
+            system_context,
+            max_tool_loops_per_turn,
+            tools,
+            tool_executor,
+            delegate,
+            stop_flag,
+            redact_tool_output: false,
+        },
         on_chunk,
-        stop_flag,
     )
     .await
 }
 
 /// Same as [`run_turn`] but accepts a [`Provider`] trait object (e.g. from [`crate::orchestration::ProviderClients::as_dyn`]).
 pub async fn run_turn_dyn(
+    turn: SessionTurn<'_>,
+    mut on_chunk: Option<&mut (dyn FnMut(&str) + Send)>,
+) -> Result<AgentTurnResult, ProviderError> {
+    let mut messages = load_turn_messages(turn.store, turn.session_id, turn.system_context).await?;
+    execute_turn_main(turn, &mut messages, &mut on_chunk, None).await
+}
+
+/// Session-backed turn inputs for [`run_turn_dyn`] and [`continue_turn_dyn`].
+pub struct SessionTurn<'a> {
+    pub store: &'a SessionStore,
+    pub session_id: &'a str,
+    pub provider: &'a dyn Provider,
+    pub model: &'a str,
+    pub system_context: Option<&'a str>,
+    pub max_tool_loops_per_turn: Option<u32>,
+    pub tools: Option<Vec<ToolDefinition>>,
+    pub tool_executor: Option<Arc<dyn ToolExecutor>>,
+    pub delegate: Option<DelegateContext>,
+    pub stop_flag: Option<Arc<AtomicBool>>,
+    /// `provider` is in the profile's `redaction` scope: skill tool output is redacted before it
+    /// reaches the model (see [`crate::config::RedactionConfig::applies_to`]).
+    pub redact_tool_output: bool,
+}
+
+/// Resume a turn that stopped at `maxToolLoopsPerTurn` with tool calls still pending.
+///
+/// The session must end with the assistant message whose `tool_calls` were not executed (what
+/// [`AgentTurnResult::pending_tool_calls`] reports). Those calls run first, their results are
+/// appended, and the tool loop continues with a fresh iteration budget that starts after them. No
+/// user message is added, so the model sees an uninterrupted transcript. Errors when there is
+/// nothing to continue.
+pub async fn continue_turn_dyn(
+    turn: SessionTurn<'_>,
+    mut on_chunk: Option<&mut (dyn FnMut(&str) + Send)>,
+) -> Result<AgentTurnResult, ProviderError> {
+    let mut messages = load_turn_messages(turn.store, turn.session_id, turn.system_context).await?;
+    let pending = match messages.last() {
+        Some(m) if m.role == "assistant" && m.tool_calls.as_ref().is_some_and(|c| !c.is_empty()) => {
+            messages.pop()
+        }
+        _ => None,
+    };
+    let pending = pending.ok_or_else(|| {
+        ProviderError::Session("nothing to continue: the last turn has no pending tool calls".to_string())
+    })?;
+    log::info!(
+        "agent: continuing turn with {} pending tool call(s)",
+        pending.tool_calls.as_ref().map_or(0, |c| c.len())
+    );
+    let resume = ChatResponse {
+        message: Some(pending),
+        done: true,
+        finish_reason: None,
+        eval_count: None,
+        prompt_eval_count: None,
+        usage: None,
+    };
+    execute_turn_main(turn, &mut messages, &mut on_chunk, Some(resume)).await
+}
+
+/// Session history as chat messages, with `system_context` (when non-empty) prepended.
+async fn load_turn_messages(
     store: &SessionStore,
     session_id: &str,
-    provider: &dyn Provider,
-    model: &str,
     system_context: Option<&str>,
-    max_tool_loops_per_turn: Option<u32>,
-    tools: Option<Vec<ToolDefinition>>,
-    tool_executor: Option<&dyn ToolExecutor>,
-    delegate: Option<DelegateContext<'_>>,
-    mut on_chunk: Option<&mut (dyn FnMut(&str) + Send)>,
-    stop_flag: Option<Arc<AtomicBool>>,
-) -> Result<AgentTurnResult, ProviderError> {
+) -> Result<Vec<ChatMessage>, ProviderError> {
     let session = store
         .get(session_id)
         .await
@@ -122,20 +213,7 @@ pub async fn run_turn_dyn(
             );
         }
     }
-
-    execute_turn_main(
-        provider,
-        model,
-        &mut messages,
-        tools,
-        tool_executor,
-        &mut on_chunk,
-        Some((store, session_id)),
-        delegate,
-        max_tool_loops_per_turn,
-        stop_flag,
-    )
-    .await
+    Ok(messages)
 }
 
 /// Run a single turn with an explicit message list. **Does not** read or write [`SessionStore`].
@@ -150,7 +228,7 @@ pub async fn run_turn_with_messages<B: Provider>(
     model: &str,
     messages: Vec<ChatMessage>,
     tools: Option<Vec<ToolDefinition>>,
-    tool_executor: Option<&dyn ToolExecutor>,
+    tool_executor: Option<Arc<dyn ToolExecutor>>,
     max_tool_loops_per_turn: Option<u32>,
     stop_flag: Option<Arc<AtomicBool>>,
 ) -> Result<AgentTurnResult, ProviderError> {
@@ -158,55 +236,94 @@ pub async fn run_turn_with_messages<B: Provider>(
         provider as &dyn Provider,
         model,
         messages,
-        tools,
-        tool_executor,
-        max_tool_loops_per_turn,
-        None,
-        stop_flag,
+        WorkerTurn {
+            tools,
+            tool_executor,
+            max_tool_loops_per_turn,
+            stop_flag,
+            ..Default::default()
+        },
     )
     .await
 }
 
+/// Tools, limits and orchestrator hooks of a worker turn ([`run_turn_with_messages_dyn`],
+/// [`run_turn_with_transcript`]). `Default` = no tools, no limit, nothing attached.
+#[derive(Clone, Default)]
+pub struct WorkerTurn<'a> {
+    pub tools: Option<Vec<ToolDefinition>>,
+    pub tool_executor: Option<Arc<dyn ToolExecutor>>,
+    pub max_tool_loops_per_turn: Option<u32>,
+    /// Emits the worker's assistant messages and tool events.
+    pub observability: Option<&'a DelegateObservability>,
+    /// Gated tool calls wait for an operator decision before they run.
+    pub approval: Option<&'a ApprovalScope>,
+    /// Enables nested `delegate_task` (within `maxDelegationDepth`).
+    pub delegate: Option<&'a DelegateContext>,
+    pub stop_flag: Option<Arc<AtomicBool>>,
+    /// The turn's provider is in the profile's `redaction` scope (or the worker's reply goes to
+    /// a delegating turn that is): skill tool output is redacted before it reaches the model.
+    pub redact_tool_output: bool,
+    /// Registers the turn's tool calls while they run (see [`ToolCallsInFlight`]).
+    pub in_flight: Option<ToolCallsInFlight>,
+}
+
+/// Tool calls of one delegation attempt still running on the blocking pool. A timed-out or
+/// cancelled attempt drops its worker turn, but a call already handed to the pool runs to the end;
+/// [`ToolCallsInFlight::wait`] lets the next attempt start only after it has.
+#[derive(Clone, Default)]
+pub struct ToolCallsInFlight(Arc<tokio::sync::RwLock<()>>);
+
+impl ToolCallsInFlight {
+    /// Wait until no registered tool call is running.
+    pub async fn wait(&self) {
+        let _idle = self.0.write().await;
+    }
+}
+
 /// Same as [`run_turn_with_messages`] but accepts a [`Provider`] trait object.
 pub async fn run_turn_with_messages_dyn(
     provider: &dyn Provider,
     model: &str,
     mut messages: Vec<ChatMessage>,
-    tools: Option<Vec<ToolDefinition>>,
-    tool_executor: Option<&dyn ToolExecutor>,
-    max_tool_loops_per_turn: Option<u32>,
-    observability: Option<&DelegateObservability>,
-    stop_flag: Option<Arc<AtomicBool>>,
+    turn: WorkerTurn<'_>,
+) -> Result<AgentTurnResult, ProviderError> {
+    run_turn_with_transcript(provider, model, &mut messages, turn).await
+}
+
+/// Same as [`run_turn_with_messages_dyn`] but leaves the whole conversation in `messages`:
+/// assistant replies (including the final one) and tool results are appended in order.
+pub async fn run_turn_with_transcript(
+    provider: &dyn Provider,
+    model: &str,
+    messages: &mut Vec<ChatMessage>,
+    turn: WorkerTurn<'_>,
 ) -> Result<AgentTurnResult, ProviderError> {
     let mut on_chunk: Option<&mut (dyn FnMut(&str) + Send)> = None;
-    execute_turn_worker(
-        provider,
-        model,
-        &mut messages,
-        tools,
-        tool_executor,
-        &mut on_chunk,
-        None,
-        max_tool_loops_per_turn,
-        observability,
-        stop_flag,
-    )
-    .await
+    execute_turn_worker(provider, model, messages, &mut on_chunk, None, turn).await
 }
 
-/// In-memory tool loop without `delegate_task` handling (nested delegation). Used by [`run_turn_with_messages_dyn`].
+/// In-memory tool loop for worker turns. Used by [`run_turn_with_messages_dyn`]. `delegate_task`
+/// runs only when `turn.delegate` is set (nested delegation); the calls run one at a time.
 async fn execute_turn_worker(
     provider: &dyn Provider,
     model: &str,
     messages: &mut Vec<ChatMessage>,
-    tools: Option<Vec<ToolDefinition>>,
-    tool_executor: Option<&dyn ToolExecutor>,
     on_chunk: &mut Option<&mut (dyn FnMut(&str) + Send)>,
     persist: Option<(&SessionStore, &str)>,
-    max_tool_loops_per_turn: Option<u32>,
-    observability: Option<&DelegateObservability>,
-    stop_flag: Option<Arc<AtomicBool>>,
+    turn: WorkerTurn<'_>,
 ) -> Result<AgentTurnResult, ProviderError> {
+    let WorkerTurn {
+        tools,
+        tool_executor,
+        max_tool_loops_per_turn,
+        observability,
+        approval,
+        delegate,
+        stop_flag,
+        redact_tool_output,
+        in_flight,
+    } = turn;
     let model_name = model.trim();
     let model_name = if model_name.is_empty() {
         log::warn!("agent: configured model was empty, using fallback");
@@ -345,6 +462,7 @@ async fn execute_turn_worker(
                 }
                 continue;
             }
+            messages.push(assistant_msg);
             break;
         }
 
@@ -354,6 +472,7 @@ async fn execute_turn_worker(
                 log::warn!("agent: max tool loop iterations reached ({}), some tool calls were not executed", loop_count);
                 loop_limit_reached = true;
                 pending_tool_calls = last_tool_calls.clone();
+                messages.push(assistant_msg);
                 break;
             }
         }
@@ -363,6 +482,7 @@ async fn execute_turn_worker(
             .any(|c| c.function.name != DELEGATE_TASK_TOOL_NAME);
         if needs_executor && tool_executor.is_none() {
             log::debug!("agent: tool_calls returned but no executor");
+            messages.push(assistant_msg);
             break;
         }
 
@@ -370,43 +490,50 @@ async fn execute_turn_worker(
         for (idx, call) in last_tool_calls.iter().enumerate() {
             let name = call.function.name.as_str();
             let args = &call.function.arguments;
+            // A nested delegate_task call reports its tool events under the child delegation id.
+            let call_ctx = delegate
+                .filter(|_| name == DELEGATE_TASK_TOOL_NAME)
+                .map(DelegateContext::for_call);
+            let obs = match call_ctx {
+                Some(ref ctx) => ctx.observability.as_ref(),
+                None => observability,
+            };
 
             // Emit session.tool_call event before execution so the desktop can
             // render worker tool calls as separate timeline entries as they happen.
-            if let Some(ref obs) = observability {
+            if let Some(obs) = obs {
                 obs.emit_tool_call(name, args, executed_tool_calls.len() + idx);
             }
 
-            let result = if name == DELEGATE_TASK_TOOL_NAME {
-                log::debug!("agent: delegate_task not available in worker turn");
-                "error: delegate_task is not available in this context".to_string()
-            } else {
-                match tool_executor {
-                    Some(executor) => match executor.execute(name, args, persist.map(|(_, sid)| sid)) {
-                        Ok(out) => out.clone(),
-                        Err(e) => {
-                            // Log the short status at warn level; log the
-                            // full detail (expected/actual diffs, etc.) at
-                            // debug level so it doesn't overwhelm the chat
-                            // log view.
-                            let (base, detail) = e.split_once(": ").unwrap_or((&e, ""));
-                            log::warn!("agent: tool {} failed: {}", name, base);
-                            if !detail.is_empty() {
-                                log::debug!("agent: tool {} failed: {}: {}", name, base, detail);
-                            }
-                            format!("error: {}", e)
-                        }
-                    },
+            let denial = match approval {
+                Some(scope) => scope.check(name, args, stop_flag.as_ref()).await,
+                None => None,
+            };
+            let result = if let Some(denial) = denial {
+                denial
+            } else if name == DELEGATE_TASK_TOOL_NAME {
+                match call_ctx {
+                    Some(ref ctx) => {
+                        let (output, worker_stopped) = run_nested_delegate_call(ctx, args).await;
+                        stopped |= worker_stopped;
+                        output
+                    }
                     None => {
-                        log::debug!("agent: missing executor for tool");
-                        format!("error: no executor for tool {}", name)
+                        log::debug!("agent: delegate_task not available in worker turn");
+                        "error: delegate_task is not available in this context".to_string()
                     }
                 }
+            } else {
+                let ctx = ToolCallContext {
+                    session_id: persist.map(|(_, sid)| sid),
+                    redact_output: redact_tool_output,
+                };
+                execute_tool(tool_executor.as_ref(), name, args, ctx, in_flight.as_ref()).await
             };
             executed_tool_results.push(result.clone());
 
             // Emit session.tool_result event after execution completes.
-            if let Some(ref obs) = observability {
+            if let Some(obs) = obs {
                 obs.emit_tool_result(name, &result, executed_tool_calls.len() + idx);
             }
 
@@ -483,17 +610,25 @@ async fn execute_turn_worker(
 }
 /// Session-backed tool loop with `delegate_task` (nested worker turns use [`execute_turn_worker`] only).
 async fn execute_turn_main(
-    provider: &dyn Provider,
-    model: &str,
+    turn: SessionTurn<'_>,
     messages: &mut Vec<ChatMessage>,
-    tools: Option<Vec<ToolDefinition>>,
-    tool_executor: Option<&dyn ToolExecutor>,
     on_chunk: &mut Option<&mut (dyn FnMut(&str) + Send)>,
-    persist: Option<(&SessionStore, &str)>,
-    mut delegate: Option<DelegateContext<'_>>,
-    max_tool_loops_per_turn: Option<u32>,
-    stop_flag: Option<Arc<AtomicBool>>,
+    mut resume: Option<ChatResponse>,
 ) -> Result<AgentTurnResult, ProviderError> {
+    let SessionTurn {
+        store,
+        session_id,
+        provider,
+        model,
+        system_context: _,
+        max_tool_loops_per_turn,
+        tools,
+        tool_executor,
+        mut delegate,
+        stop_flag,
+        redact_tool_output,
+    } = turn;
+    let persist = Some((store, session_id));
     let model_name = model.trim();
     let model_name = if model_name.is_empty() {
         log::warn!("agent: configured model was empty, using fallback");
@@ -509,13 +644,25 @@ async fn execute_turn_main(
     let mut last_content = String::new();
     let mut last_tool_calls: Vec<ToolCall>;
     let mut truncated = false;
-    let max_delegations_per_turn = delegate
-        .as_ref()
-        .and_then(|d| d.agents.default_orchestrator().max_delegations_per_turn);
+    // Workers' replies come back to this turn's provider, so they redact whenever it does.
+    if let Some(d) = delegate.as_mut() {
+        d.redact_tool_output = redact_tool_output;
+    }
+    let orchestrator = delegate.as_ref().and_then(|d| d.orchestrator());
+    let max_delegations_per_turn = orchestrator.and_then(|o| o.max_delegations_per_turn);
+    let max_parallel_workflows = orchestrator.map_or(1, |o| o.max_parallel_workflows());
+    // With `asyncDelegation`, `delegate_task` returns a handle and the worker runs in the background.
+    let mut background = orchestrator
+        .filter(|o| o.async_delegation())
+        .map(|_| BackgroundDelegations::new(max_parallel_workflows));
     let mut delegate_calls_this_turn: usize = 0;
     let mut loop_limit_reached = false;
     let mut pending_tool_calls: Vec<ToolCall> = Vec::new();
     let mut stopped = false;
+    let approval = delegate.as_ref().and_then(|d| d.approval.clone());
+
+    // Session truncation is refused while this guard is held.
+    let _active_turn = persist.map(|(store, session_id)| store.begin_turn(session_id));
 
     // Clear any stale stop flag from a previous turn before starting.
     if let Some(ref flag) = stop_flag {
@@ -523,6 +670,15 @@ async fn execute_turn_main(
     }
 
     loop {
+        // Add background delegation results that finished since the last model call. The last
+        // call the loop limit allows waits for all of them, so none is left out of the turn.
+        if let Some(ref mut bg) = background {
+            if max_tool_loops_per_turn.is_some_and(|max| loop_count + 1 >= max) {
+                bg.wait_all().await;
+            }
+            stopped |= append_background_results(messages, persist, bg).await?;
+        }
+
         // Check stop flag before each iteration. If set, break out of the loop
         // gracefully — the current tool call or model request has already completed.
         if let Some(ref flag) = stop_flag {
@@ -539,8 +695,14 @@ async fn execute_turn_main(
             }
         }
 
-        let use_stream = on_chunk.is_some() && loop_count == 0;
-        let res = if use_stream {
+        // When continuing a turn, the first iteration replays the persisted assistant message
+        // (already in the session) instead of calling the model; the stream goes to the next call.
+        let resumed = resume.take();
+        let is_resumed = resumed.is_some();
+        let use_stream = on_chunk.is_some() && loop_count == 0 && !is_resumed;
+        let res = if let Some(res) = resumed {
+            res
+        } else if use_stream {
             let cb = on_chunk.as_mut().unwrap();
             let mut delta_cb = |s: &str| cb(s);
             provider
@@ -583,7 +745,7 @@ async fn execute_turn_main(
         // Emit intermediate assistant message content so the user can see the
         // model's output between tool calls. Without this, only the final
         // iteration's content is visible — all intermediate messages are lost.
-        if !last_tool_calls.is_empty() && !last_content.trim().is_empty() {
+        if !is_resumed && !last_tool_calls.is_empty() && !last_content.trim().is_empty() {
             if let Some(ref d) = delegate {
                 if let Some(ref obs) = d.observability {
                     obs.emit_assistant_message(&last_content, loop_count);
@@ -602,7 +764,7 @@ async fn execute_turn_main(
             tool_name: None,
         };
 
-        if let Some((store, session_id)) = persist {
+        if let Some((store, session_id)) = persist.filter(|_| !is_resumed) {
             store
                 .append_message_full(
                     session_id,
@@ -647,154 +809,208 @@ async fn execute_turn_main(
                 }
                 continue;
             }
+            // The turn ends only once the model has seen every background result.
+            if let Some(bg) = background.as_mut().filter(|bg| bg.has_undelivered()) {
+                log::info!("agent: waiting for background delegations before ending the turn");
+                bg.wait_all().await;
+                messages.push(assistant_msg);
+                continue;
+            }
             break;
         }
 
-        loop_count += 1;
-        if let Some(max) = max_tool_loops_per_turn {
-            if loop_count >= (max as usize).try_into().unwrap() {
-                log::warn!("agent: max tool loop iterations reached ({}), some tool calls were not executed", loop_count);
-                loop_limit_reached = true;
-                pending_tool_calls = last_tool_calls.clone();
-                break;
+        // The replayed calls already counted against the previous budget; the fresh one starts after them.
+        if !is_resumed {
+            loop_count += 1;
+            if let Some(max) = max_tool_loops_per_turn {
+                if loop_count >= (max as usize).try_into().unwrap() {
+                    log::warn!("agent: max tool loop iterations reached ({}), some tool calls were not executed", loop_count);
+                    loop_limit_reached = true;
+                    pending_tool_calls = last_tool_calls.clone();
+                    break;
+                }
             }
         }
 
         let needs_executor = last_tool_calls
             .iter()
-            .any(|c| c.function.name != DELEGATE_TASK_TOOL_NAME);
+            .any(|c| {
+                let name = c.function.name.as_str();
+                name != DELEGATE_TASK_TOOL_NAME
+                    && name != DELEGATE_MANY_TOOL_NAME
+                    && !is_workflow_tool(name)
+                    && !(background.is_some() && is_delegation_handle_tool(name))
+                    && !(delegate.is_some() && name == HANDOFF_TOOL_NAME)
+            });
         if needs_executor && tool_executor.is_none() {
             log::debug!("agent: tool_calls returned but no executor");
             break;
         }
 
-
         messages.push(assistant_msg);
+
+        // With `maxParallelWorkflows` > 1 and several `delegate_task` calls in this response,
+        // consecutive delegations are queued and run concurrently. Any other call waits for the
+        // queued ones first, so calls still take effect in the order the model made them. All
+        // tool results are appended in call order so the transcript matches the request.
+        let delegate_call_count = last_tool_calls
+            .iter()
+            .filter(|c| c.function.name == DELEGATE_TASK_TOOL_NAME)
+            .count();
+        let batch = background.is_none() && delegate_call_count > 1 && max_parallel_workflows > 1;
+        let result_base = match persist {
+            Some((store, session_id)) if batch => store
+                .get(session_id)
+                .await
+                .map(|s| s.messages.len()),
+            _ => None,
+        };
+        let mut outcomes: Vec<Option<(String, bool)>> = Vec::with_capacity(last_tool_calls.len());
+        let mut queued: Vec<(usize, DelegateContext)> = Vec::new();
         for (idx, call) in last_tool_calls.iter().enumerate() {
             let name = call.function.name.as_str();
             let args = &call.function.arguments;
+            if name != DELEGATE_TASK_TOOL_NAME && !queued.is_empty() {
+                let base_index = executed_tool_calls.len();
+                run_queued_delegations(&mut queued, &last_tool_calls, &mut outcomes, base_index, max_parallel_workflows).await;
+            }
+            let call_ctx = delegate
+                .as_ref()
+                .filter(|_| name == DELEGATE_TASK_TOOL_NAME)
+                .map(DelegateContext::for_call);
+            let obs = call_ctx
+                .as_ref()
+                .or(delegate.as_ref())
+                .and_then(|d| d.observability.clone());
 
             // Emit session.tool_call event before execution so the desktop can
             // render tool calls as separate timeline entries as they happen.
-            if let Some(ref d) = delegate {
-                if let Some(ref obs) = d.observability {
-                    obs.emit_tool_call(name, args, executed_tool_calls.len() + idx);
-                }
+            if let Some(ref obs) = obs {
+                obs.emit_tool_call(name, args, executed_tool_calls.len() + idx);
             }
 
-            let (result, worker_stopped) = if name == DELEGATE_TASK_TOOL_NAME {
+            let denial = match approval {
+                Some(ref scope) => scope.check(name, args, stop_flag.as_ref()).await,
+                None => None,
+            };
+            let outcome = if let Some(denial) = denial {
+                Some((denial, false))
+            } else if name == DELEGATE_TASK_TOOL_NAME {
                 delegate_calls_this_turn += 1;
-                if let Some(max) = max_delegations_per_turn {
-                    if delegate_calls_this_turn > max {
+                match (call_ctx, max_delegations_per_turn) {
+                    (Some(ctx), Some(max)) if delegate_calls_this_turn > max => {
                         log::warn!(
                             "agent: delegate_task rejected (max delegations per turn: {})",
                             max
                         );
-                        if let Some(ref d) = delegate {
-                            if let Some(ref obs) = d.observability {
-                                obs.emit_rejected(args, "max_delegations_per_turn", Some(max));
-                            }
+                        if let Some(ref obs) = ctx.observability {
+                            obs.emit_rejected(args, "max_delegations_per_turn", Some(max));
                         }
-                        (
+                        Some((
                             format!(
                                 "error: max delegations per turn exceeded (maxDelegationsPerTurn={})",
                                 max
                             ),
                             false,
-                        )
-                    } else {
-                        match delegate {
-                            Some(ref mut ctx) => match execute_delegate_task(ctx, args).await {
-                                Ok(DelegateTaskResult { output, stopped, tool_call_count }) => {
-                                    // Accumulate the worker's tool call count into the offset
-                                    // so that subsequent delegations produce non-overlapping
-                                    // tool indices. Without this, successive delegations
-                                    // collide with earlier worker indices and the desktop
-                                    // silently drops them as duplicates.
-                                    ctx.tool_index_offset += tool_call_count;
-                                    (output, stopped)
-                                }
-                                Err(e) => {
-                                    log::warn!("agent: delegate_task failed: {}", e);
-                                    (format!("error: {}", e), false)
-                                }
-                            },
-                            None => {
-                                log::debug!("agent: delegate_task not available");
-                                ("error: delegate_task is not available in this context".to_string(), false)
-                            }
-                        }
+                        ))
                     }
-                } else {
-                    match delegate {
-                        Some(ref mut ctx) => match execute_delegate_task(ctx, args).await {
-                            Ok(DelegateTaskResult { output, stopped, tool_call_count }) => {
-                                ctx.tool_index_offset += tool_call_count;
-                                (output, stopped)
-                            }
-                            Err(e) => {
-                                log::warn!("agent: delegate_task failed: {}", e);
-                                (format!("error: {}", e), false)
-                            }
-                        },
-                        None => {
-                            log::debug!("agent: delegate_task not available");
-                            ("error: delegate_task is not available in this context".to_string(), false)
-                        }
+                    (Some(mut ctx), _) if background.is_some() => {
+                        // The delegation is recorded against the handle result, appended next.
+                        ctx.result_message_index = match persist {
+                            Some((store, session_id)) => store.get(session_id).await.map(|s| s.messages.len()),
+                            None => None,
+                        };
+                        background.as_mut().map(|bg| (bg.start(ctx, args.clone()), false))
+                    }
+                    (Some(mut ctx), _) if batch => {
+                        ctx.result_message_index = result_base.map(|base| base + idx);
+                        queued.push((idx, ctx));
+                        None
+                    }
+                    (Some(ctx), _) => Some(run_delegate_call(&ctx, args).await),
+                    (None, _) => {
+                        log::debug!("agent: delegate_task not available");
+                        Some(("error: delegate_task is not available in this context".to_string(), false))
                     }
                 }
-            } else {
-                match tool_executor {
-                    Some(executor) => match executor.execute(name, args, persist.map(|(_, sid)| sid)) {
-                        Ok(out) => (out.clone(), false),
-                        Err(e) => {
-                            // Log the short status at warn level; log the
-                            // full detail (expected/actual diffs, etc.) at
-                            // debug level so it doesn't overwhelm the chat
-                            // log view.
-                            let (base, detail) = e.split_once(": ").unwrap_or((&e, ""));
-                            log::warn!("agent: tool {} failed: {}", name, base);
-                            if !detail.is_empty() {
-                                log::debug!("agent: tool {} failed: {}: {}", name, base, detail);
-                            }
-                            (format!("error: {}", e), false)
+            } else if let Some(d) = delegate.as_ref().filter(|_| name == DELEGATE_MANY_TOOL_NAME) {
+                // Each item is a delegation, so the whole fan-out counts against the per-turn cap.
+                delegate_calls_this_turn += delegate_many_count(args);
+                match max_delegations_per_turn {
+                    Some(max) if delegate_calls_this_turn > max => {
+                        log::warn!(
+                            "agent: delegate_many rejected (max delegations per turn: {})",
+                            max
+                        );
+                        if let Some(ref obs) = d.observability {
+                            obs.emit_rejected(args, "max_delegations_per_turn", Some(max));
                         }
-                    },
-                    None => {
-                        log::debug!("agent: missing executor for tool");
-                        (format!("error: no executor for tool {}", name), false)
+                        Some((
+                            format!(
+                                "error: max delegations per turn exceeded (maxDelegationsPerTurn={})",
+                                max
+                            ),
+                            false,
+                        ))
+                    }
+                    _ => {
+                        let mut ctx = d.clone();
+                        if batch {
+                            ctx.result_message_index = result_base.map(|base| base + idx);
+                        }
+                        Some(run_delegate_many_call(&ctx, args).await)
                     }
                 }
+            } else if let Some(bg) = background.as_mut().filter(|_| is_delegation_handle_tool(name)) {
+                Some(bg.handle_call(args, name == AWAIT_DELEGATION_TOOL_NAME).await)
+            } else if let Some(d) = delegate.as_ref().filter(|_| name == HANDOFF_TOOL_NAME) {
+                // The gateway moves the session after the turn; here the call is only checked.
+                let result = HandoffRequest::from_args(&d.config.agents, d.orchestrator_id.as_deref(), args)
+                    .map_or_else(|e| format!("error: {}", e), |req| req.tool_result());
+                Some((result, false))
+            } else if let Some(d) = delegate.as_ref().filter(|_| is_workflow_tool(name)) {
+                // Workflow steps are delegations; in a batch they are recorded against this
+                // call's result message, which is appended after the queued delegations finish.
+                let mut ctx = d.clone();
+                if batch {
+                    ctx.result_message_index = result_base.map(|base| base + idx);
+                }
+                Some(run_workflow_call(&ctx, name, args).await)
+            } else {
+                let ctx = ToolCallContext {
+                    session_id: persist.map(|(_, sid)| sid),
+                    redact_output: redact_tool_output,
+                };
+                Some((execute_tool(tool_executor.as_ref(), name, args, ctx, None).await, false))
             };
-            // If the worker turn was stopped by the user, propagate the stop
-            // to the orchestrator loop. The orchestrator will emit the
-            // session.turn_stopped event and break at the top of the next
-            // iteration.
-            if worker_stopped {
-                stopped = true;
-            }
-            executed_tool_results.push(result.clone());
 
-            // Emit session.tool_result event after execution completes.
-            if let Some(ref d) = delegate {
-                if let Some(ref obs) = d.observability {
-                    obs.emit_tool_result(name, &result, executed_tool_calls.len() + idx);
-                }
+            // Emit session.tool_result event after execution completes. Queued delegations
+            // emit theirs as each one finishes.
+            if let (Some(ref obs), Some((result, _))) = (obs, outcome.as_ref()) {
+                obs.emit_tool_result(name, result, executed_tool_calls.len() + idx);
             }
-
-            messages.push(ChatMessage {
-                role: "tool".to_string(),
-                content: result.clone(),
-                tool_calls: None,
-                tool_name: Some(name.to_string()),
-            });
-            if let Some((store, session_id)) = persist {
-                store
-                    .append_message_full(session_id, "tool", &result, None, Some(name.to_string()))
-                    .await
-                    .map_err(|e| ProviderError::Session(e.to_string()))?;
+            outcomes.push(outcome);
+            if !batch {
+                let (result, worker_stopped) = outcomes.pop().flatten().unwrap_or_default();
+                // If the worker turn was stopped by the user, propagate the stop
+                // to the orchestrator loop. The orchestrator will emit the
+                // session.turn_stopped event and break at the top of the next
+                // iteration.
+                stopped |= worker_stopped;
+                append_tool_result(messages, persist, name, &result).await?;
+                executed_tool_results.push(result);
             }
+        }
 
+        if batch {
+            let base_index = executed_tool_calls.len();
+            run_queued_delegations(&mut queued, &last_tool_calls, &mut outcomes, base_index, max_parallel_workflows).await;
+            for (call, outcome) in last_tool_calls.iter().zip(outcomes) {
+                let (result, worker_stopped) = outcome.unwrap_or_default();
+                stopped |= worker_stopped;
+                append_tool_result(messages, persist, &call.function.name, &result).await?;
+                executed_tool_results.push(result);
+            }
         }
 
         executed_tool_calls.extend(last_tool_calls.clone());
@@ -832,6 +1048,12 @@ async fn execute_turn_main(
             continue;
         }
     }
+    // A stopped turn still records its background delegations; their workers share the stop
+    // flag, so they end promptly.
+    if let Some(ref mut bg) = background {
+        bg.wait_all().await;
+        stopped |= append_background_results(messages, persist, bg).await?;
+    }
     // Emit tool loop limit event so connected clients can inform the user.
     if loop_limit_reached {
         if let Some(ref d) = delegate {
@@ -851,6 +1073,183 @@ async fn execute_turn_main(
     })
 }
 
+/// Run a regular (non-`delegate_task`) tool, returning its output or an `error: …` result.
+/// Executors block (skill commands, hooks), so the call runs on the blocking pool and the
+/// turn's task stays free to observe timeouts, cancellation and concurrent delegations. The call
+/// is registered in `in_flight` until it returns.
+async fn execute_tool(
+    tool_executor: Option<&Arc<dyn ToolExecutor>>,
+    name: &str,
+    args: &serde_json::Value,
+    ctx: ToolCallContext<'_>,
+    in_flight: Option<&ToolCallsInFlight>,
+) -> String {
+    match tool_executor {
+        Some(executor) => match run_blocking_tool(executor.clone(), name, args, ctx, in_flight).await {
+            Ok(out) => out,
+            Err(e) => {
+                // Log the short status at warn level; log the
+                // full detail (expected/actual diffs, etc.) at
+                // debug level so it doesn't overwhelm the chat
+                // log view.
+                let (base, detail) = e.split_once(": ").unwrap_or((&e, ""));
+                log::warn!("agent: tool {} failed: {}", name, base);
+                if !detail.is_empty() {
+                    log::debug!("agent: tool {} failed: {}: {}", name, base, detail);
+                }
+                format!("error: {}", e)
+            }
+        },
+        None => {
+            log::debug!("agent: missing executor for tool");
+            format!("error: no executor for tool {}", name)
+        }
+    }
+}
+
+/// Call `executor` on the blocking pool with owned copies of the call's inputs. The blocking task
+/// holds its `in_flight` registration, so it is released when the call returns even if this
+/// future was dropped.
+async fn run_blocking_tool(
+    executor: Arc<dyn ToolExecutor>,
+    name: &str,
+    args: &serde_json::Value,
+    ctx: ToolCallContext<'_>,
+    in_flight: Option<&ToolCallsInFlight>,
+) -> Result<String, String> {
+    let name = name.to_string();
+    let args = args.clone();
+    let session_id = ctx.session_id.map(str::to_string);
+    let redact_output = ctx.redact_output;
+    let registration = match in_flight {
+        Some(in_flight) => Some(Arc::clone(&in_flight.0).read_owned().await),
+        None => None,
+    };
+    tokio::task::spawn_blocking(move || {
+        let _registration = registration;
+        let ctx = ToolCallContext {
+            session_id: session_id.as_deref(),
+            redact_output,
+        };
+        executor.execute(&name, &args, ctx)
+    })
+    .await
+    .unwrap_or_else(|e| Err(format!("tool task failed: {}", e)))
+}
+
+/// Run one `delegate_task` call, returning the tool result and whether the worker was stopped.
+async fn run_delegate_call(ctx: &DelegateContext, args: &serde_json::Value) -> (String, bool) {
+    match execute_delegate_task(ctx, args).await {
+        Ok(DelegateTaskResult { output, stopped }) => (output, stopped),
+        Err(e) => {
+            log::warn!("agent: delegate_task failed: {}", e);
+            (format!("error: {}", e), false)
+        }
+    }
+}
+
+/// Run the queued `delegate_task` calls of a batch as spawned tasks, at most `max_parallel` at a
+/// time, and store each outcome at its call index. `base_index` is the turn-wide index of the
+/// batch's first call.
+async fn run_queued_delegations(
+    queued: &mut Vec<(usize, DelegateContext)>,
+    calls: &[ToolCall],
+    outcomes: &mut [Option<(String, bool)>],
+    base_index: usize,
+    max_parallel: usize,
+) {
+    if queued.is_empty() {
+        return;
+    }
+    log::info!(
+        "agent: running {} delegations concurrently (maxParallelWorkflows={})",
+        queued.len(),
+        max_parallel
+    );
+    let mut batch = DelegationBatch::new(max_parallel);
+    let indices: Vec<usize> = queued.iter().map(|(idx, _)| *idx).collect();
+    for (idx, ctx) in queued.drain(..) {
+        let call = calls[idx].clone();
+        batch.spawn(async move {
+            let outcome = run_delegate_call(&ctx, &call.function.arguments).await;
+            if let Some(ref obs) = ctx.observability {
+                obs.emit_tool_result(&call.function.name, &outcome.0, base_index + idx);
+            }
+            outcome
+        });
+    }
+    for (idx, finished) in indices.into_iter().zip(batch.join().await) {
+        outcomes[idx] = Some(finished.unwrap_or_else(|e| (format!("error: {}", e), false)));
+    }
+}
+
+/// Run one `delegate_many` call, returning the tool result and whether a worker was stopped.
+async fn run_delegate_many_call(ctx: &DelegateContext, args: &serde_json::Value) -> (String, bool) {
+    match execute_delegate_many(ctx, args).await {
+        Ok(DelegateTaskResult { output, stopped }) => (output, stopped),
+        Err(e) => {
+            log::warn!("agent: delegate_many failed: {}", e);
+            (format!("error: {}", e), false)
+        }
+    }
+}
+
+/// [`run_delegate_call`] from inside a worker turn. Nested delegation makes the worker loop
+/// recursive, so the future is boxed behind a concrete `Send` type.
+fn run_nested_delegate_call<'a>(
+    ctx: &'a DelegateContext,
+    args: &'a serde_json::Value,
+) -> Pin<Box<dyn Future<Output = (String, bool)> + Send + 'a>> {
+    Box::pin(run_delegate_call(ctx, args))
+}
+
+/// Append finished background delegations as user messages (see [`BackgroundDelegations`]).
+/// Returns whether one of their workers was stopped.
+async fn append_background_results(
+    messages: &mut Vec<ChatMessage>,
+    persist: Option<(&SessionStore, &str)>,
+    background: &mut BackgroundDelegations,
+) -> Result<bool, ProviderError> {
+    let (finished, stopped) = background.take_finished().await;
+    for text in finished {
+        if let Some((store, session_id)) = persist {
+            store
+                .append_message_full(session_id, "user", &text, None, None)
+                .await
+                .map_err(|e| ProviderError::Session(e.to_string()))?;
+        }
+        messages.push(ChatMessage {
+            role: "user".to_string(),
+            content: text,
+            tool_calls: None,
+            tool_name: None,
+        });
+    }
+    Ok(stopped)
+}
+
+/// Append a tool result to the in-memory transcript and, when persisting, to the session.
+async fn append_tool_result(
+    messages: &mut Vec<ChatMessage>,
+    persist: Option<(&SessionStore, &str)>,
+    name: &str,
+    result: &str,
+) -> Result<(), ProviderError> {
+    messages.push(ChatMessage {
+        role: "tool".to_string(),
+        content: result.to_string(),
+        tool_calls: None,
+        tool_name: Some(name.to_string()),
+    });
+    if let Some((store, session_id)) = persist {
+        store
+            .append_message_full(session_id, "tool", result, None, Some(name.to_string()))
+            .await
+            .map_err(|e| ProviderError::Session(e.to_string()))?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -925,7 +1324,7 @@ mod tests {
     }
 
     impl ToolExecutor for MockToolExecutor {
-        fn execute(&self, name: &str, args: &serde_json::Value, _session_id: Option<&str>) -> Result<String, String> {
+        fn execute(&self, name: &str, args: &serde_json::Value, _ctx: ToolCallContext<'_>) -> Result<String, String> {
             let mut results = self.results.lock().unwrap();
             let result = format!("ok: {}({})", name, args);
             results.push(result.clone());
@@ -951,8 +1350,8 @@ mod tests {
     }
 
     impl<E: ToolExecutor, F: Fn() + Send + Sync> ToolExecutor for FlagSettingExecutor<E, F> {
-        fn execute(&self, name: &str, args: &serde_json::Value, session_id: Option<&str>) -> Result<String, String> {
-            let result = self.inner.execute(name, args, session_id);
+        fn execute(&self, name: &str, args: &serde_json::Value, ctx: ToolCallContext<'_>) -> Result<String, String> {
+            let result = self.inner.execute(name, args, ctx);
             (self.on_execute)();
             result
         }
@@ -1008,11 +1407,7 @@ mod tests {
             &provider as &dyn Provider,
             "test-model",
             messages,
-            None,
-            None,
-            None,
-            None,
-            None,
+            WorkerTurn::default(),
         )
         .await
         .unwrap();
@@ -1041,11 +1436,7 @@ mod tests {
             &provider as &dyn Provider,
             "test-model",
             messages,
-            None,
-            None,
-            None,
-            None,
-            None,
+            WorkerTurn::default(),
         )
         .await
         .unwrap();
@@ -1080,7 +1471,7 @@ mod tests {
                 Some(FinishReason::Stop),
             ),
         ]);
-        let executor = MockToolExecutor::new();
+        let executor = Arc::new(MockToolExecutor::new());
         let messages = vec![ChatMessage {
             role: "user".to_string(),
             content: "write a file".to_string(),
@@ -1091,11 +1482,10 @@ mod tests {
             &provider as &dyn Provider,
             "test-model",
             messages,
-            None,
-            Some(&executor as &dyn ToolExecutor),
-            None,
-            None,
-            None,
+            WorkerTurn {
+                tool_executor: Some(executor.clone() as Arc<dyn ToolExecutor>),
+                ..Default::default()
+            },
         )
         .await
         .unwrap();
@@ -1147,7 +1537,7 @@ mod tests {
                 Some(FinishReason::Stop),
             ),
         ]);
-        let executor = MockToolExecutor::new();
+        let executor = Arc::new(MockToolExecutor::new());
         let messages = vec![ChatMessage {
             role: "user".to_string(),
             content: "read a file".to_string(),
@@ -1158,11 +1548,10 @@ mod tests {
             &provider as &dyn Provider,
             "test-model",
             messages,
-            None,
-            Some(&executor as &dyn ToolExecutor),
-            None,
-            None,
-            None,
+            WorkerTurn {
+                tool_executor: Some(executor.clone() as Arc<dyn ToolExecutor>),
+                ..Default::default()
+            },
         )
         .await
         .unwrap();
@@ -1197,7 +1586,7 @@ mod tests {
                 Some(FinishReason::Stop),
             ),
         ]);
-        let executor = MockToolExecutor::new();
+        let executor = Arc::new(MockToolExecutor::new());
         let messages = vec![ChatMessage {
             role: "user".to_string(),
             content: "read".to_string(),
@@ -1208,11 +1597,10 @@ mod tests {
             &provider as &dyn Provider,
             "test-model",
             messages,
-            None,
-            Some(&executor as &dyn ToolExecutor),
-            None,
-            None,
-            None,
+            WorkerTurn {
+                tool_executor: Some(executor.clone() as Arc<dyn ToolExecutor>),
+                ..Default::default()
+            },
         )
         .await
         .unwrap();
@@ -1312,11 +1700,7 @@ mod tests {
             &provider as &dyn Provider,
             "test-model",
             messages,
-            None,
-            None,
-            None,
-            None,
-            None,
+            WorkerTurn::default(),
         )
         .await
         .unwrap();
@@ -1372,11 +1756,10 @@ mod tests {
             &provider as &dyn Provider,
             "test-model",
             messages,
-            None,
-            None,
-            Some(3),
-            None,
-            None,
+            WorkerTurn {
+                max_tool_loops_per_turn: Some(3),
+                ..Default::default()
+            },
         )
         .await
         .unwrap();
@@ -1416,18 +1799,18 @@ mod tests {
         // This is deterministic: the flag is guaranteed to be set before the
         // post-tool-execution check runs.
         let flag_setter = flag.clone();
-        let executor = FlagSettingExecutor::new(executor, move || {
+        let executor = Arc::new(FlagSettingExecutor::new(executor, move || {
             flag_setter.store(true, Ordering::SeqCst);
-        });
+        }));
         let result = run_turn_with_messages_dyn(
             &provider as &dyn Provider,
             "test-model",
             messages,
-            None,
-            Some(&executor as &dyn ToolExecutor),
-            None,
-            None,
-            Some(flag),
+            WorkerTurn {
+                tool_executor: Some(executor.clone() as Arc<dyn ToolExecutor>),
+                stop_flag: Some(flag),
+                ..Default::default()
+            },
         )
         .await
         .unwrap();
@@ -1438,4 +1821,1465 @@ mod tests {
         assert_eq!(executed.len(), 1, "one tool call should execute before stop");
         assert!(executed[0].starts_with("ok: read_file"));
     }
+
+    // --- Continue after loop limit (session-persisted main loop) ---
+
+    #[tokio::test]
+    async fn continue_runs_pending_calls_with_fresh_budget() {
+        let store = SessionStore::new();
+        let sid = store.create().await;
+        store.append_message(&sid, "user", "list then read").await.unwrap();
+        let tool_resp = |name: &str| {
+            make_chat_response(
+                Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: String::new(),
+                    tool_calls: Some(vec![make_tool_call(name, "{}")]),
+                    tool_name: None,
+                }),
+                true,
+                Some(FinishReason::Stop),
+            )
+        };
+        let provider = MockProvider::new(vec![tool_resp("list"), tool_resp("read")]);
+        let executor = Arc::new(MockToolExecutor::new());
+        let result = run_turn_dyn(
+            SessionTurn {
+                store: &store,
+                session_id: &sid,
+                provider: &provider as &dyn Provider,
+                model: "test-model",
+                system_context: None,
+                max_tool_loops_per_turn: Some(2),
+                tools: None,
+                tool_executor: Some(executor.clone() as Arc<dyn ToolExecutor>),
+                delegate: None,
+                stop_flag: None,
+                redact_tool_output: false,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(result.loop_limit_reached);
+        assert_eq!(result.pending_tool_calls.len(), 1);
+        assert_eq!(result.pending_tool_calls[0].function.name, "read");
+
+        // Continue: the pending `read` call runs without a new model call, then the
+        // model answers. The persisted assistant message is not duplicated, and the replay
+        // does not use up the fresh budget (a budget of one still gets its model call).
+        let provider = MockProvider::new(vec![make_chat_response(
+            Some(ChatMessage {
+                role: "assistant".to_string(),
+                content: "done".to_string(),
+                tool_calls: None,
+                tool_name: None,
+            }),
+            true,
+            Some(FinishReason::Stop),
+        )]);
+        let result = continue_turn_dyn(
+            SessionTurn {
+                store: &store,
+                session_id: &sid,
+                provider: &provider as &dyn Provider,
+                model: "test-model",
+                system_context: None,
+                max_tool_loops_per_turn: Some(1),
+                tools: None,
+                tool_executor: Some(executor.clone() as Arc<dyn ToolExecutor>),
+                delegate: None,
+                stop_flag: None,
+                redact_tool_output: false,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(!result.loop_limit_reached);
+        assert_eq!(result.content, "done");
+        assert_eq!(result.tool_calls.len(), 1);
+        assert_eq!(result.tool_calls[0].function.name, "read");
+
+        let roles: Vec<String> = store
+            .get(&sid)
+            .await
+            .unwrap()
+            .messages
+            .iter()
+            .map(|m| m.role.clone())
+            .collect();
+        assert_eq!(roles, ["user", "assistant", "tool", "assistant", "tool", "assistant"]);
+
+        // Nothing left to continue.
+        let err = continue_turn_dyn(
+            SessionTurn {
+                store: &store,
+                session_id: &sid,
+                provider: &provider as &dyn Provider,
+                model: "test-model",
+                system_context: None,
+                max_tool_loops_per_turn: None,
+                tools: None,
+                tool_executor: Some(executor.clone() as Arc<dyn ToolExecutor>),
+                delegate: None,
+                stop_flag: None,
+                redact_tool_output: false,
+            },
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("nothing to continue"));
+    }
+
+    // --- Parallel delegation (maxParallelWorkflows) ---
+
+    /// Worker provider that blocks until `barrier` is reached by every concurrent delegation,
+    /// then answers with the instruction it was given. Instruction "slow" finishes last.
+    struct BarrierProvider {
+        barrier: Arc<tokio::sync::Barrier>,
+    }
+
+    #[async_trait]
+    impl Provider for BarrierProvider {
+        async fn chat(
+            &self,
+            _model: &str,
+            messages: Vec<ChatMessage>,
+            _stream: bool,
+            _tools: Option<Vec<ToolDefinition>>,
+        ) -> Result<ChatResponse, ProviderError> {
+            let instruction = messages.last().map(|m| m.content.clone()).unwrap_or_default();
+            self.barrier.wait().await;
+            if instruction == "slow" {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+            Ok(make_chat_response(
+                Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: format!("done {}", instruction),
+                    tool_calls: None,
+                    tool_name: None,
+                }),
+                true,
+                Some(FinishReason::Stop),
+            ))
+        }
+
+        async fn chat_stream(
+            &self,
+            model: &str,
+            messages: Vec<ChatMessage>,
+            tools: Option<Vec<ToolDefinition>>,
+            _on_chunk: &mut (dyn for<'a> FnMut(&'a str) + Send),
+        ) -> Result<ChatResponse, ProviderError> {
+            self.chat(model, messages, true, tools).await
+        }
+    }
+
+    #[tokio::test]
+    async fn parallel_delegations_run_concurrently_and_append_in_call_order() {
+        use crate::config::Config;
+        use crate::orchestration::{DelegationTurn, ProviderClients};
+
+        let mut config = Config::default();
+        config.agents.orchestrators[0].max_parallel_workflows = Some(2);
+        let mut clients = ProviderClients::default();
+        clients.insert(
+            "ollama",
+            Arc::new(BarrierProvider {
+                barrier: Arc::new(tokio::sync::Barrier::new(2)),
+            }),
+        );
+        let store = Arc::new(SessionStore::new());
+        let sid = store.create().await;
+        store.append_message(&sid, "user", "do both").await.unwrap();
+        let delegate = DelegateContext {
+            clients,
+            config: Arc::new(config),
+            orchestrator_id: None,
+            orchestrator_system_context: None,
+            orchestrator_worker_tools: None,
+            orchestrator_tool_executor: None,
+            worker_runtimes: None,
+            observability: None,
+            session_store: Some(store.clone()),
+            session_id: Some(sid.clone()),
+            stop_flag: None,
+            turn: DelegationTurn::default(),
+            result_message_index: None,
+            depth: 0,
+            delegating_worker: None,
+            approval: None,
+            profile_dir: None,
+            cancels: None,
+            redact_tool_output: false,
+            tool_calls_in_flight: None,
+        };
+        let provider = MockProvider::new(vec![make_chat_response(
+            Some(ChatMessage {
+                role: "assistant".to_string(),
+                content: String::new(),
+                tool_calls: Some(vec![
+                    make_tool_call(DELEGATE_TASK_TOOL_NAME, r#"{"instruction":"slow"}"#),
+                    make_tool_call(DELEGATE_TASK_TOOL_NAME, r#"{"instruction":"fast"}"#),
+                ]),
+                tool_name: None,
+            }),
+            true,
+            Some(FinishReason::Stop),
+        )]);
+        // Both workers wait on the same barrier: run one at a time, the turn would never finish.
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            run_turn_dyn(
+                SessionTurn {
+                    store: &store,
+                    session_id: &sid,
+                    provider: &provider as &dyn Provider,
+                    model: "test-model",
+                    system_context: None,
+                    max_tool_loops_per_turn: None,
+                    tools: None,
+                    tool_executor: None,
+                    delegate: Some(delegate),
+                    stop_flag: None,
+                    redact_tool_output: false,
+                },
+                None,
+            ),
+        )
+        .await
+        .expect("delegations should run concurrently")
+        .unwrap();
+        assert_eq!(result.tool_results.len(), 2);
+        assert!(result.tool_results[0].contains("done slow"));
+        assert!(result.tool_results[1].contains("done fast"));
+
+        let session = store.get(&sid).await.unwrap();
+        assert!(session.messages[2].content.contains("done slow"));
+        assert!(session.messages[3].content.contains("done fast"));
+        let mut recorded: Vec<usize> = session
+            .delegation_log
+            .iter()
+            .map(|r| r.message_index)
+            .collect();
+        recorded.sort();
+        assert_eq!(recorded, [2, 3]);
+    }
+
+    /// Worker provider that records each instruction in `log` as its delegation finishes.
+    struct LoggingWorker {
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Provider for LoggingWorker {
+        async fn chat(
+            &self,
+            _model: &str,
+            messages: Vec<ChatMessage>,
+            _stream: bool,
+            _tools: Option<Vec<ToolDefinition>>,
+        ) -> Result<ChatResponse, ProviderError> {
+            let instruction = messages.last().map(|m| m.content.clone()).unwrap_or_default();
+            self.log.lock().unwrap().push(instruction.clone());
+            Ok(make_chat_response(
+                Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: format!("done {}", instruction),
+                    tool_calls: None,
+                    tool_name: None,
+                }),
+                true,
+                Some(FinishReason::Stop),
+            ))
+        }
+
+        async fn chat_stream(
+            &self,
+            model: &str,
+            messages: Vec<ChatMessage>,
+            tools: Option<Vec<ToolDefinition>>,
+            _on_chunk: &mut (dyn for<'a> FnMut(&'a str) + Send),
+        ) -> Result<ChatResponse, ProviderError> {
+            self.chat(model, messages, true, tools).await
+        }
+    }
+
+    /// Tool executor that records each call in the shared `log`.
+    struct LoggingExecutor {
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ToolExecutor for LoggingExecutor {
+        fn execute(&self, name: &str, _args: &serde_json::Value, _ctx: ToolCallContext<'_>) -> Result<String, String> {
+            self.log.lock().unwrap().push(name.to_string());
+            Ok(format!("ran {}", name))
+        }
+    }
+
+    #[tokio::test]
+    async fn batched_tools_wait_for_earlier_delegations() {
+        use crate::config::Config;
+        use crate::orchestration::{DelegationTurn, ProviderClients};
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut config = Config::default();
+        config.agents.orchestrators[0].max_parallel_workflows = Some(2);
+        let mut clients = ProviderClients::default();
+        clients.insert("ollama", Arc::new(LoggingWorker { log: log.clone() }));
+        let store = SessionStore::new();
+        let sid = store.create().await;
+        store.append_message(&sid, "user", "research, then write").await.unwrap();
+        let delegate = DelegateContext {
+            clients,
+            config: Arc::new(config),
+            orchestrator_id: None,
+            orchestrator_system_context: None,
+            orchestrator_worker_tools: None,
+            orchestrator_tool_executor: None,
+            worker_runtimes: None,
+            observability: None,
+            session_store: None,
+            session_id: None,
+            stop_flag: None,
+            turn: DelegationTurn::default(),
+            result_message_index: None,
+            depth: 0,
+            delegating_worker: None,
+            approval: None,
+            profile_dir: None,
+            cancels: None,
+            redact_tool_output: false,
+            tool_calls_in_flight: None,
+        };
+        let provider = MockProvider::new(vec![make_chat_response(
+            Some(ChatMessage {
+                role: "assistant".to_string(),
+                content: String::new(),
+                tool_calls: Some(vec![
+                    make_tool_call(DELEGATE_TASK_TOOL_NAME, r#"{"instruction":"a"}"#),
+                    make_tool_call(DELEGATE_TASK_TOOL_NAME, r#"{"instruction":"b"}"#),
+                    make_tool_call("write_file", "{}"),
+                    make_tool_call(DELEGATE_TASK_TOOL_NAME, r#"{"instruction":"c"}"#),
+                ]),
+                tool_name: None,
+            }),
+            true,
+            Some(FinishReason::Stop),
+        )]);
+        let executor = Arc::new(LoggingExecutor { log: log.clone() });
+        let result = run_turn_dyn(
+            SessionTurn {
+                store: &store,
+                session_id: &sid,
+                provider: &provider as &dyn Provider,
+                model: "test-model",
+                system_context: None,
+                max_tool_loops_per_turn: None,
+                tools: None,
+                tool_executor: Some(executor.clone() as Arc<dyn ToolExecutor>),
+                delegate: Some(delegate),
+                stop_flag: None,
+                redact_tool_output: false,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        let log = log.lock().unwrap().clone();
+        assert_eq!(log.len(), 4);
+        let mut before: Vec<&str> = log[..2].iter().map(String::as_str).collect();
+        before.sort();
+        assert_eq!(before, ["a", "b"]);
+        assert_eq!(log[2..], ["write_file", "c"]);
+        assert_eq!(result.tool_results.len(), 4);
+        assert!(result.tool_results[0].contains("done a"));
+        assert_eq!(result.tool_results[2], "ran write_file");
+        assert!(result.tool_results[3].contains("done c"));
+    }
+
+    /// Worker provider for nested delegation: `plan` delegates to `leaf`, `dig` answers directly,
+    /// and a turn that already has a tool result wraps it up.
+    struct NestedProvider;
+
+    #[async_trait]
+    impl Provider for NestedProvider {
+        async fn chat(
+            &self,
+            _model: &str,
+            messages: Vec<ChatMessage>,
+            _stream: bool,
+            tools: Option<Vec<ToolDefinition>>,
+        ) -> Result<ChatResponse, ProviderError> {
+            let last = messages.last().cloned().unwrap();
+            let can_delegate = tools
+                .iter()
+                .flatten()
+                .any(|t| t.function.name == DELEGATE_TASK_TOOL_NAME);
+            let (content, tool_calls) = if last.role == "tool" {
+                (format!("lead done: {}", last.content), None)
+            } else if last.content == "plan" && can_delegate {
+                (
+                    String::new(),
+                    Some(vec![make_tool_call(
+                        DELEGATE_TASK_TOOL_NAME,
+                        r#"{"instruction":"dig","workerId":"leaf"}"#,
+                    )]),
+                )
+            } else {
+                (format!("answered {}", last.content), None)
+            };
+            Ok(make_chat_response(
+                Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content,
+                    tool_calls,
+                    tool_name: None,
+                }),
+                true,
+                Some(FinishReason::Stop),
+            ))
+        }
+
+        async fn chat_stream(
+            &self,
+            model: &str,
+            messages: Vec<ChatMessage>,
+            tools: Option<Vec<ToolDefinition>>,
+            _on_chunk: &mut (dyn for<'a> FnMut(&'a str) + Send),
+        ) -> Result<ChatResponse, ProviderError> {
+            self.chat(model, messages, true, tools).await
+        }
+    }
+
+    async fn run_nested_delegation(
+        max_depth: usize,
+    ) -> (AgentTurnResult, crate::session::Session, Vec<serde_json::Value>) {
+        use crate::approval::ApprovalGate;
+        use crate::config::{ApprovalPolicy, Config, SkillContextMode};
+        use crate::orchestration::{DelegationTurn, ProviderClients, WorkerDelegateRuntime};
+        use std::collections::HashMap;
+
+        let config: Config = serde_json::from_str(&format!(
+            r#"{{"agents":[
+                {{"id":"orchestrator","role":"orchestrator","enabledWorkers":["lead"],"maxDelegationDepth":{}}},
+                {{"id":"lead","role":"worker","enabledWorkers":["leaf"]}},
+                {{"id":"leaf","role":"worker"}}
+            ]}}"#,
+            max_depth
+        ))
+        .unwrap();
+        let runtime = |workers_context: &str| WorkerDelegateRuntime {
+            system_context: String::new(),
+            workers_context: workers_context.to_string(),
+            skills: Arc::new(Vec::new()),
+            tools_list: None,
+            tool_executor: None,
+            context_mode: SkillContextMode::Full,
+            approval: ApprovalGate::new(ApprovalPolicy::Auto, Default::default()),
+            model_route: None,
+        };
+        let mut runtimes = HashMap::new();
+        runtimes.insert("lead".to_string(), runtime("## Workers"));
+        runtimes.insert("leaf".to_string(), runtime(""));
+        let mut clients = ProviderClients::default();
+        clients.insert("ollama", Arc::new(NestedProvider));
+        let (event_tx, mut event_rx) = tokio::sync::broadcast::channel::<String>(64);
+        let store = Arc::new(SessionStore::new());
+        let sid = store.create().await;
+        store.append_message(&sid, "user", "go").await.unwrap();
+        let delegate = DelegateContext {
+            clients,
+            config: Arc::new(config),
+            orchestrator_id: None,
+            orchestrator_system_context: None,
+            orchestrator_worker_tools: None,
+            orchestrator_tool_executor: None,
+            worker_runtimes: Some(Arc::new(runtimes)),
+            observability: Some(DelegateObservability {
+                event_tx,
+                session_id: Some(sid.clone()),
+                orchestrator_id: None,
+                source: Some("orchestrator".to_string()),
+                tool_index_offset: 0,
+                delegation_id: None,
+                parent_delegation_id: None,
+                tool_index_counter: None,
+                last_tool_index: std::sync::atomic::AtomicUsize::new(0),
+            }),
+            session_store: Some(store.clone()),
+            session_id: Some(sid.clone()),
+            stop_flag: None,
+            turn: DelegationTurn::default(),
+            result_message_index: None,
+            depth: 0,
+            delegating_worker: None,
+            approval: None,
+            profile_dir: None,
+            cancels: None,
+            redact_tool_output: false,
+            tool_calls_in_flight: None,
+        };
+        let provider = MockProvider::new(vec![make_chat_response(
+            Some(ChatMessage {
+                role: "assistant".to_string(),
+                content: String::new(),
+                tool_calls: Some(vec![make_tool_call(
+                    DELEGATE_TASK_TOOL_NAME,
+                    r#"{"instruction":"plan","workerId":"lead"}"#,
+                )]),
+                tool_name: None,
+            }),
+            true,
+            Some(FinishReason::Stop),
+        )]);
+        let result = run_turn_dyn(
+            SessionTurn {
+                store: &store,
+                session_id: &sid,
+                provider: &provider as &dyn Provider,
+                model: "test-model",
+                system_context: None,
+                max_tool_loops_per_turn: None,
+                tools: None,
+                tool_executor: None,
+                delegate: Some(delegate),
+                stop_flag: None,
+                redact_tool_output: false,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let mut events = Vec::new();
+        while let Ok(text) = event_rx.try_recv() {
+            events.push(serde_json::from_str::<serde_json::Value>(&text).unwrap());
+        }
+        (result, store.get(&sid).await.unwrap(), events)
+    }
+
+    #[tokio::test]
+    async fn nested_delegation_within_depth_limit() {
+        let (result, session, events) = run_nested_delegation(2).await;
+        assert!(result.tool_results[0].contains("lead done"));
+        assert!(result.tool_results[0].contains("answered dig"));
+        // Both levels count against the session caps and roll back with the root tool result.
+        let recorded: Vec<(&str, usize)> = session
+            .delegation_log
+            .iter()
+            .map(|r| (r.worker_id.as_str(), r.message_index))
+            .collect();
+        assert_eq!(recorded, [("leaf", 2), ("lead", 2)]);
+
+        let starts: Vec<&serde_json::Value> = events
+            .iter()
+            .filter(|e| e["event"] == crate::orchestration::EVENT_DELEGATE_START)
+            .map(|e| &e["payload"])
+            .collect();
+        assert_eq!(starts.len(), 2);
+        assert_eq!(starts[0]["workerId"], "lead");
+        assert!(starts[0].get("parentDelegationId").is_none());
+        assert_eq!(starts[1]["workerId"], "leaf");
+        assert_eq!(starts[1]["source"], "lead");
+        assert_eq!(starts[1]["parentDelegationId"], starts[0]["delegationId"]);
+    }
+
+    #[tokio::test]
+    async fn nested_delegation_not_offered_at_depth_limit() {
+        let (result, session, _) = run_nested_delegation(1).await;
+        assert!(result.tool_results[0].contains("answered plan"));
+        assert_eq!(session.delegation_log.len(), 1);
+    }
+
+    fn worker_reply(content: &str) -> ChatResponse {
+        make_chat_response(
+            Some(ChatMessage {
+                role: "assistant".to_string(),
+                content: content.to_string(),
+                tool_calls: None,
+                tool_name: None,
+            }),
+            true,
+            Some(FinishReason::Stop),
+        )
+    }
+
+    fn schema_delegate_context(replies: Vec<ChatResponse>) -> DelegateContext {
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert("ollama", Arc::new(MockProvider::new(replies)));
+        worker_delegate_context(
+            r#"{"agents":[
+                {"id":"orchestrator","role":"orchestrator","enabledWorkers":[]},
+                {"id":"reviewer","role":"worker","outputSchema":{
+                    "type":"object","required":["verdict"],
+                    "properties":{"verdict":{"enum":["approve","reject"]}}
+                }}
+            ]}"#,
+            clients,
+        )
+    }
+
+    /// Delegation context with a runtime for every worker in `config`.
+    fn worker_delegate_context(
+        config: &str,
+        clients: crate::orchestration::ProviderClients,
+    ) -> DelegateContext {
+        use crate::approval::ApprovalGate;
+        use crate::config::{ApprovalPolicy, Config, ModelRouting, SkillContextMode};
+        use crate::orchestration::{model_route, tier_candidates, DelegationTurn, WorkerDelegateRuntime};
+        use std::collections::HashMap;
+
+        let config: Config = serde_json::from_str(config).unwrap();
+        let candidates = tier_candidates(&config.providers, None);
+        let runtimes: HashMap<String, WorkerDelegateRuntime> = config
+            .agents
+            .workers
+            .iter()
+            .flatten()
+            .map(|w| {
+                let rt = WorkerDelegateRuntime {
+                    system_context: String::new(),
+                    workers_context: String::new(),
+                    skills: Arc::new(Vec::new()),
+                    tools_list: None,
+                    tool_executor: None,
+                    context_mode: SkillContextMode::Full,
+                    approval: ApprovalGate::new(ApprovalPolicy::Auto, Default::default()),
+                    model_route: (w.model_routing == Some(ModelRouting::Tier))
+                        .then(|| model_route(&candidates, &[]).unwrap()),
+                };
+                (w.id.clone(), rt)
+            })
+            .collect();
+        DelegateContext {
+            clients,
+            config: Arc::new(config),
+            orchestrator_id: None,
+            orchestrator_system_context: None,
+            orchestrator_worker_tools: None,
+            orchestrator_tool_executor: None,
+            worker_runtimes: Some(Arc::new(runtimes)),
+            observability: None,
+            session_store: None,
+            session_id: None,
+            stop_flag: None,
+            turn: DelegationTurn::default(),
+            result_message_index: None,
+            depth: 0,
+            delegating_worker: None,
+            approval: None,
+            profile_dir: None,
+            cancels: None,
+            redact_tool_output: false,
+            tool_calls_in_flight: None,
+        }
+    }
+
+    async fn run_schema_delegation(replies: Vec<ChatResponse>) -> String {
+        let ctx = schema_delegate_context(replies);
+        let args = serde_json::json!({"instruction": "review it", "workerId": "reviewer"});
+        crate::orchestration::execute_delegate_task(&ctx, &args).await.unwrap().output
+    }
+
+    #[tokio::test]
+    async fn delegation_transcript_is_persisted_and_linked() {
+        use crate::session::{read_delegation_transcript, SessionStore};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = Arc::new(SessionStore::with_data_dir(dir.path().join("orchestrator")));
+        let session_id = store.create().await;
+        store.append_message(&session_id, "user", "review the patch").await.unwrap();
+
+        let mut ctx = schema_delegate_context(vec![
+            worker_reply("Looks good to me."),
+            worker_reply(r#"{"verdict": "approve"}"#),
+        ]);
+        ctx.session_store = Some(store.clone());
+        ctx.session_id = Some(session_id.clone());
+        ctx.profile_dir = Some(dir.path().to_path_buf());
+        let args = serde_json::json!({"instruction": "review it", "workerId": "reviewer"});
+        crate::orchestration::execute_delegate_task(&ctx, &args).await.unwrap();
+
+        let session = store.get(&session_id).await.unwrap();
+        assert_eq!(session.delegation_transcripts.len(), 1);
+        let link = &session.delegation_transcripts[0];
+        assert_eq!(link.worker_id, "reviewer");
+        assert_eq!(link.message_index, 1);
+
+        let transcript = read_delegation_transcript(
+            &crate::config::sessions_dir(dir.path(), "reviewer"),
+            &link.delegation_id,
+        )
+        .expect("transcript file");
+        assert_eq!(transcript.parent_session_id.as_deref(), Some(session_id.as_str()));
+        assert_eq!(transcript.message_index, Some(1));
+        let roles: Vec<&str> = transcript.messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "assistant", "user", "assistant"]);
+        assert_eq!(transcript.messages[4].content, r#"{"verdict": "approve"}"#);
+    }
+
+    #[tokio::test]
+    async fn worker_output_schema_returns_validated_json_after_retry() {
+        let output = run_schema_delegation(vec![
+            worker_reply("Looks good to me."),
+            worker_reply("```json\n{\"verdict\": \"approve\"}\n```"),
+        ])
+        .await;
+        let v: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(v["output"], serde_json::json!({"verdict": "approve"}));
+        assert!(v.get("reply").is_none());
+    }
+
+    #[tokio::test]
+    async fn worker_output_schema_retry_continues_with_tools() {
+        let mut ctx = schema_delegate_context(vec![
+            make_chat_response(
+                Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: String::new(),
+                    tool_calls: Some(vec![make_tool_call("read_diff", "{}")]),
+                    tool_name: None,
+                }),
+                true,
+                Some(FinishReason::Stop),
+            ),
+            worker_reply("Looks good to me."),
+            // The repair turn can still use the worker's tools.
+            make_chat_response(
+                Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: String::new(),
+                    tool_calls: Some(vec![make_tool_call("read_diff", r#"{"file":"a.rs"}"#)]),
+                    tool_name: None,
+                }),
+                true,
+                Some(FinishReason::Stop),
+            ),
+            worker_reply(r#"{"verdict": "approve"}"#),
+        ]);
+        let executor = Arc::new(MockToolExecutor::new());
+        let runtimes = Arc::get_mut(ctx.worker_runtimes.as_mut().unwrap()).unwrap();
+        runtimes.get_mut("reviewer").unwrap().tool_executor = Some(executor.clone());
+        let args = serde_json::json!({"instruction": "review it", "workerId": "reviewer"});
+        let output = crate::orchestration::execute_delegate_task(&ctx, &args).await.unwrap().output;
+        let v: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(v["output"], serde_json::json!({"verdict": "approve"}));
+        assert_eq!(
+            *executor.results.lock().unwrap(),
+            [r#"ok: read_diff({})"#, r#"ok: read_diff({"file":"a.rs"})"#]
+        );
+    }
+
+    /// Tool executor that records whether each call asked for redacted output.
+    struct RedactionProbe {
+        calls: Mutex<Vec<bool>>,
+    }
+
+    impl ToolExecutor for RedactionProbe {
+        fn execute(&self, _name: &str, _args: &serde_json::Value, ctx: ToolCallContext<'_>) -> Result<String, String> {
+            self.calls.lock().unwrap().push(ctx.redact_output);
+            Ok("done".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_output_redaction_follows_each_turns_provider() {
+        let tool_then_reply = || {
+            Arc::new(MockProvider::new(vec![
+                make_chat_response(
+                    Some(ChatMessage {
+                        role: "assistant".to_string(),
+                        content: String::new(),
+                        tool_calls: Some(vec![make_tool_call("read_log", "{}")]),
+                        tool_name: None,
+                    }),
+                    true,
+                    Some(FinishReason::Stop),
+                ),
+                worker_reply("done"),
+            ]))
+        };
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert("ollama", tool_then_reply());
+        clients.insert("nim", tool_then_reply());
+        // One profile: `ollama` is local, `nim` is hosted and so in the default redaction scope.
+        let mut ctx = worker_delegate_context(
+            r#"{
+                "providers":[
+                    {"id":"ollama","endpointType":"ollama"},
+                    {"id":"nim","endpointType":"openai-compat","baseUrl":"https://integrate.api.nvidia.com/v1"}
+                ],
+                "redaction":{"rules":["email"]},
+                "agents":[
+                    {"id":"orchestrator","role":"orchestrator","enabledWorkers":[],"enabledProviders":["ollama","nim"]},
+                    {"id":"local","role":"worker","defaultProvider":"ollama","defaultModel":"m"},
+                    {"id":"hosted","role":"worker","defaultProvider":"nim","defaultModel":"m"}
+                ]
+            }"#,
+            clients,
+        );
+        let probe = Arc::new(RedactionProbe { calls: Mutex::new(Vec::new()) });
+        for rt in Arc::get_mut(ctx.worker_runtimes.as_mut().unwrap()).unwrap().values_mut() {
+            rt.tool_executor = Some(probe.clone());
+        }
+        for worker in ["local", "hosted"] {
+            let args = serde_json::json!({"instruction": "check the log", "workerId": worker});
+            crate::orchestration::execute_delegate_task(&ctx, &args).await.unwrap();
+        }
+        assert_eq!(*probe.calls.lock().unwrap(), [false, true]);
+
+        // A local worker of a redacting turn redacts too: its reply goes to that turn's provider.
+        ctx.clients.insert("ollama", tool_then_reply());
+        ctx.redact_tool_output = true;
+        let args = serde_json::json!({"instruction": "check the log", "workerId": "local"});
+        crate::orchestration::execute_delegate_task(&ctx, &args).await.unwrap();
+        assert_eq!(*probe.calls.lock().unwrap(), [false, true, true]);
+    }
+
+    /// Tool executor whose output holds an email address, redacted with the profile rules when the
+    /// call asks for it.
+    struct OwnerLookup {
+        redactor: crate::redaction::Redactor,
+    }
+
+    impl ToolExecutor for OwnerLookup {
+        fn execute(&self, _name: &str, _args: &serde_json::Value, ctx: ToolCallContext<'_>) -> Result<String, String> {
+            let output = "owner: bob@example.com";
+            Ok(if ctx.redact_output { self.redactor.redact(output) } else { output.to_string() })
+        }
+    }
+
+    /// Provider that records every message it is sent.
+    struct RecordingProvider {
+        inner: MockProvider,
+        seen: Mutex<Vec<ChatMessage>>,
+    }
+
+    #[async_trait]
+    impl Provider for RecordingProvider {
+        async fn chat(
+            &self,
+            model: &str,
+            messages: Vec<ChatMessage>,
+            stream: bool,
+            tools: Option<Vec<ToolDefinition>>,
+        ) -> Result<ChatResponse, ProviderError> {
+            self.seen.lock().unwrap().extend(messages.iter().cloned());
+            self.inner.chat(model, messages, stream, tools).await
+        }
+
+        async fn chat_stream(
+            &self,
+            model: &str,
+            messages: Vec<ChatMessage>,
+            tools: Option<Vec<ToolDefinition>>,
+            _on_chunk: &mut (dyn for<'a> FnMut(&'a str) + Send),
+        ) -> Result<ChatResponse, ProviderError> {
+            self.chat(model, messages, true, tools).await
+        }
+    }
+
+    #[tokio::test]
+    async fn escalation_to_a_hosted_tier_replays_redacted_tool_output() {
+        let config: crate::config::Config = serde_json::from_str(
+            r#"{
+                "providers":[
+                    {"id":"ollama","endpointType":"ollama","defaultModel":"small","tier":"minimal"},
+                    {"id":"cloud","endpointType":"openai-compat","baseUrl":"https://api.example.com/v1",
+                     "modelTiers":[{"model":"big","tier":"moderate"}]}
+                ],
+                "redaction":{"rules":["email"]}
+            }"#,
+        )
+        .unwrap();
+        let candidates = crate::orchestration::tier_candidates(&config.providers, None);
+        let route = crate::orchestration::model_route(&candidates, &[]).unwrap();
+        assert_eq!(route.steps[0].provider_id, "ollama");
+        assert!(!config.redaction.applies_to(&config.providers, "ollama"));
+        // The gateway runs every tier of the route with this flag.
+        let redact_tool_output = route.redacts_tool_output(&config.redaction, &config.providers);
+        assert!(redact_tool_output);
+
+        let executor: Arc<dyn ToolExecutor> = Arc::new(OwnerLookup {
+            redactor: crate::redaction::Redactor::compile(&config.redaction.rules).unwrap(),
+        });
+        let lookup = || orchestrator_calls(vec![make_tool_call("lookup_owner", "{}")]);
+        let store = SessionStore::new();
+        let sid = store.create().await;
+        store.append_message(&sid, "user", "who owns it?").await.unwrap();
+        let local = MockProvider::new(vec![lookup(), lookup()]);
+        let result = run_turn_dyn(
+            SessionTurn {
+                store: &store,
+                session_id: &sid,
+                provider: &local as &dyn Provider,
+                model: "small",
+                system_context: None,
+                max_tool_loops_per_turn: Some(2),
+                tools: None,
+                tool_executor: Some(executor.clone()),
+                delegate: None,
+                stop_flag: None,
+                redact_tool_output,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(result.loop_limit_reached);
+
+        let hosted = RecordingProvider {
+            inner: MockProvider::new(vec![worker_reply("ask the owner")]),
+            seen: Mutex::new(Vec::new()),
+        };
+        let result = continue_turn_dyn(
+            SessionTurn {
+                store: &store,
+                session_id: &sid,
+                provider: &hosted as &dyn Provider,
+                model: "big",
+                system_context: None,
+                max_tool_loops_per_turn: Some(2),
+                tools: None,
+                tool_executor: Some(executor),
+                delegate: None,
+                stop_flag: None,
+                redact_tool_output,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.content, "ask the owner");
+        let seen = hosted.seen.lock().unwrap();
+        let tool_results: Vec<&str> = seen.iter().filter(|m| m.role == "tool").map(|m| m.content.as_str()).collect();
+        assert_eq!(tool_results.len(), 2, "{:?}", *seen);
+        assert!(tool_results.iter().all(|c| !c.contains("bob@example.com")), "{:?}", tool_results);
+    }
+
+    #[tokio::test]
+    async fn worker_output_schema_fails_after_retries() {
+        let output = run_schema_delegation(vec![
+            worker_reply("Looks good to me."),
+            worker_reply(r#"{"verdict": "fine"}"#),
+            worker_reply(r#"{"verdict": "ok"}"#),
+            worker_reply(r#"{"verdict": "approve"}"#),
+        ])
+        .await;
+        assert!(output.starts_with("error: worker reply does not match outputSchema after 3 attempts"), "{output}");
+        assert!(output.contains("/verdict: must be one of"), "{output}");
+    }
+
+    /// Provider that fails its first `failures` requests, then answers `reply` after `delay`.
+    struct FlakyProvider {
+        failures: std::sync::atomic::AtomicUsize,
+        delay: std::time::Duration,
+        reply: &'static str,
+    }
+
+    impl FlakyProvider {
+        fn new(failures: usize, delay_ms: u64, reply: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                failures: std::sync::atomic::AtomicUsize::new(failures),
+                delay: std::time::Duration::from_millis(delay_ms),
+                reply,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Provider for FlakyProvider {
+        async fn chat(
+            &self,
+            _model: &str,
+            _messages: Vec<ChatMessage>,
+            _stream: bool,
+            _tools: Option<Vec<ToolDefinition>>,
+        ) -> Result<ChatResponse, ProviderError> {
+            use std::sync::atomic::Ordering;
+            if self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(ProviderError::Provider("connection refused".to_string()));
+            }
+            tokio::time::sleep(self.delay).await;
+            Ok(worker_reply(self.reply))
+        }
+
+        async fn chat_stream(
+            &self,
+            model: &str,
+            messages: Vec<ChatMessage>,
+            tools: Option<Vec<ToolDefinition>>,
+            _on_chunk: &mut (dyn for<'a> FnMut(&'a str) + Send),
+        ) -> Result<ChatResponse, ProviderError> {
+            self.chat(model, messages, false, tools).await
+        }
+    }
+
+    async fn run_review_workflow(replies: Vec<ChatResponse>) -> (String, bool) {
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert("ollama", Arc::new(MockProvider::new(replies)));
+        let ctx = worker_delegate_context(
+            r#"{"agents":[
+                {"id":"orchestrator","role":"orchestrator","enabledWorkers":[],"enabledWorkflows":[]},
+                {"id":"reviewer","role":"worker","outputSchema":{
+                    "type":"object","required":["verdict"],
+                    "properties":{"verdict":{"enum":["approve","reject"]}}
+                }},
+                {"id":"coder","role":"worker"}
+            ],"workflows":[{"id":"review-fix","inputs":[{"name":"patch"}],"steps":[
+                {"id":"review","workerId":"reviewer","instruction":"Review {{inputs.patch}}"},
+                {"id":"fix","workerId":"coder","instruction":"Address: {{steps.review.output}}",
+                 "when":{"ref":"steps.review.output.verdict","equals":"reject"}}
+            ],"output":"{{steps.review.output.verdict}}: {{steps.fix.output}}"}]}"#,
+            clients,
+        );
+        let args = serde_json::json!({"patch": "diff --git"});
+        crate::orchestration::run_workflow_call(&ctx, "workflow_review-fix", &args).await
+    }
+
+    #[tokio::test]
+    async fn workflow_runs_steps_in_order_and_skips_false_conditions() {
+        let (output, stopped) = run_review_workflow(vec![
+            worker_reply(r#"{"verdict": "reject"}"#),
+            worker_reply("patched"),
+        ])
+        .await;
+        assert!(!stopped);
+        let v: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(v["workflow"], "review-fix");
+        assert_eq!(v["output"], "reject: patched");
+
+        let (output, _) = run_review_workflow(vec![worker_reply(r#"{"verdict": "approve"}"#)]).await;
+        let v: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(v["output"], "approve: ");
+    }
+
+    #[tokio::test]
+    async fn workflow_tool_rejects_missing_inputs_and_failed_steps() {
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert("ollama", Arc::new(MockProvider::new(Vec::new())));
+        let ctx = worker_delegate_context(
+            r#"{"agents":[
+                {"id":"orchestrator","role":"orchestrator","enabledWorkers":["coder"],"enabledWorkflows":["broken"]},
+                {"id":"coder","role":"worker"},{"id":"other","role":"worker"}
+            ],"workflows":[{"id":"broken","inputs":[{"name":"task"}],"steps":[
+                {"id":"go","workerId":"other","instruction":"{{inputs.task}}"}
+            ]}]}"#,
+            clients,
+        );
+        let call = |name: &'static str, args: serde_json::Value| {
+            let ctx = &ctx;
+            async move { crate::orchestration::run_workflow_call(ctx, name, &args).await.0 }
+        };
+        let output = call("workflow_broken", serde_json::json!({})).await;
+        assert_eq!(output, "error: missing input: task");
+        let output = call("workflow_broken", serde_json::json!({"task": "x"})).await;
+        assert!(output.starts_with("error: workflow broken failed at step go: "), "{output}");
+        let output = call("workflow_other", serde_json::json!({})).await;
+        assert_eq!(output, "error: unknown workflow: other");
+    }
+
+    #[tokio::test]
+    async fn delegate_many_returns_indexed_results_and_reduces() {
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert(
+            "ollama",
+            Arc::new(MockProvider::new(vec![
+                worker_reply("short"),
+                worker_reply("long"),
+                worker_reply("1 short, 1 long"),
+            ])),
+        );
+        let ctx = worker_delegate_context(
+            r#"{"agents":[
+                {"id":"orchestrator","role":"orchestrator","enabledWorkers":["coder"],"maxParallelWorkflows":1},
+                {"id":"coder","role":"worker"}
+            ]}"#,
+            clients,
+        );
+        let args = serde_json::json!({
+            "instruction": "Summarize {{item}}",
+            "items": ["a.md", {"path": "b.md"}],
+            "reduce": "Count the summaries"
+        });
+        let (output, stopped) = run_delegate_many_call(&ctx, &args).await;
+        assert!(!stopped);
+        let v: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(v["completed"], 2);
+        assert_eq!(v["failed"], 0);
+        assert_eq!(v["results"][0]["index"], 0);
+        assert_eq!(v["results"][0]["reply"], "short");
+        assert_eq!(v["results"][1]["item"]["path"], "b.md");
+        assert_eq!(v["results"][1]["reply"], "long");
+        assert_eq!(v["reduced"]["reply"], "1 short, 1 long");
+
+        let (output, _) =
+            run_delegate_many_call(&ctx, &serde_json::json!({"instruction": "x", "items": []})).await;
+        assert_eq!(output, "error: items must be a non-empty array");
+    }
+
+    /// Worker provider that answers "done <instruction>" once `gate` is notified.
+    struct GatedProvider {
+        gate: Arc<tokio::sync::Notify>,
+    }
+
+    #[async_trait]
+    impl Provider for GatedProvider {
+        async fn chat(
+            &self,
+            _model: &str,
+            messages: Vec<ChatMessage>,
+            _stream: bool,
+            _tools: Option<Vec<ToolDefinition>>,
+        ) -> Result<ChatResponse, ProviderError> {
+            self.gate.notified().await;
+            let instruction = messages.last().map(|m| m.content.clone()).unwrap_or_default();
+            Ok(worker_reply(&format!("done {}", instruction)))
+        }
+
+        async fn chat_stream(
+            &self,
+            model: &str,
+            messages: Vec<ChatMessage>,
+            tools: Option<Vec<ToolDefinition>>,
+            _on_chunk: &mut (dyn for<'a> FnMut(&'a str) + Send),
+        ) -> Result<ChatResponse, ProviderError> {
+            self.chat(model, messages, true, tools).await
+        }
+    }
+
+    fn orchestrator_calls(calls: Vec<ToolCall>) -> ChatResponse {
+        make_chat_response(
+            Some(ChatMessage {
+                role: "assistant".to_string(),
+                content: String::new(),
+                tool_calls: Some(calls),
+                tool_name: None,
+            }),
+            true,
+            Some(FinishReason::Stop),
+        )
+    }
+
+    /// Run an orchestrator turn with `asyncDelegation` on a fresh session; returns the result and
+    /// the session's messages.
+    async fn run_async_delegation_turn(
+        worker: Arc<dyn Provider>,
+        replies: Vec<ChatResponse>,
+        executor: Arc<dyn ToolExecutor>,
+    ) -> (AgentTurnResult, crate::session::Session) {
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert("ollama", worker);
+        let mut ctx = worker_delegate_context(
+            r#"{"agents":[{"id":"orchestrator","role":"orchestrator","asyncDelegation":true}]}"#,
+            clients,
+        );
+        let store = Arc::new(SessionStore::new());
+        let sid = store.create().await;
+        store.append_message(&sid, "user", "dig and read").await.unwrap();
+        ctx.session_store = Some(store.clone());
+        ctx.session_id = Some(sid.clone());
+        let provider = MockProvider::new(replies);
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            run_turn_dyn(
+                SessionTurn {
+                    store: &store,
+                    session_id: &sid,
+                    provider: &provider as &dyn Provider,
+                    model: "test-model",
+                    system_context: None,
+                    max_tool_loops_per_turn: None,
+                    tools: None,
+                    tool_executor: Some(executor),
+                    delegate: Some(ctx),
+                    stop_flag: None,
+                    redact_tool_output: false,
+                },
+                None,
+            ),
+        )
+        .await
+        .expect("turn should finish")
+        .unwrap();
+        (result, store.get(&sid).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn async_delegation_lets_orchestrator_continue_and_injects_result() {
+        let gate = Arc::new(tokio::sync::Notify::new());
+        let release = gate.clone();
+        // The worker only answers after the orchestrator ran its own tool, so the turn would
+        // hang if `delegate_task` blocked.
+        let executor = FlagSettingExecutor::new(MockToolExecutor::new(), move || release.notify_one());
+        let (result, session) = run_async_delegation_turn(
+            Arc::new(GatedProvider { gate }),
+            vec![
+                orchestrator_calls(vec![
+                    make_tool_call(DELEGATE_TASK_TOOL_NAME, r#"{"instruction":"dig"}"#),
+                    make_tool_call("read_file", r#"{"path":"a.md"}"#),
+                ]),
+                worker_reply("the worker is still digging"),
+                worker_reply("all done"),
+            ],
+            Arc::new(executor),
+        )
+        .await;
+        assert!(result.tool_results[0].contains(r#""status":"running""#), "{}", result.tool_results[0]);
+        assert!(result.tool_results[1].starts_with("ok: read_file"));
+
+        let injected: Vec<&str> = session
+            .messages
+            .iter()
+            .filter(|m| m.role == "user" && m.content.starts_with("Background delegation"))
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(injected.len(), 1, "{:?}", session.messages);
+        assert!(injected[0].contains("done dig"), "{}", injected[0]);
+        assert_eq!(session.messages.last().unwrap().role, "assistant");
+        // Linked to the handle result (message 2), not to whatever was appended last.
+        assert_eq!(session.delegation_log.len(), 1);
+        assert_eq!(session.delegation_log[0].message_index, 2);
+    }
+
+    #[tokio::test]
+    async fn await_delegation_returns_result_without_injecting_it() {
+        let executor = Arc::new(MockToolExecutor::new());
+        let (result, session) = run_async_delegation_turn(
+            Arc::new(MockProvider::new(vec![worker_reply("found it")])),
+            vec![
+                orchestrator_calls(vec![
+                    make_tool_call(DELEGATE_TASK_TOOL_NAME, r#"{"instruction":"dig"}"#),
+                    make_tool_call(AWAIT_DELEGATION_TOOL_NAME, "{}"),
+                    make_tool_call(crate::orchestration::CHECK_DELEGATION_TOOL_NAME, r#"{"delegationId":"dlg-nope"}"#),
+                ]),
+                worker_reply("all done"),
+            ],
+            executor.clone(),
+        )
+        .await;
+        assert_eq!(result.content, "all done");
+        let awaited: serde_json::Value = serde_json::from_str(&result.tool_results[1]).unwrap();
+        assert_eq!(awaited["delegations"][0]["status"], "completed");
+        assert!(awaited["delegations"][0]["result"].to_string().contains("found it"));
+        assert!(result.tool_results[2].starts_with("error: unknown delegationId"));
+        assert!(executor.results.lock().unwrap().is_empty());
+        assert!(!session
+            .messages
+            .iter()
+            .any(|m| m.content.starts_with("Background delegation")));
+    }
+
+    const ROUTED_WORKERS: &str = r#"{
+        "providers":[
+            {"id":"ollama","endpointType":"ollama","defaultModel":"small","tier":"minimal"},
+            {"id":"cloud","endpointType":"openai-compat","baseUrl":"http://127.0.0.1:9/v1",
+             "modelTiers":[{"model":"big","tier":"moderate"}]}
+        ],
+        "agents":[
+            {"id":"orchestrator","role":"orchestrator","defaultProvider":"ollama","enabledWorkers":[],"maxToolLoopsPerTurn":1},
+            {"id":"reviewer","role":"worker","modelRouting":"tier","outputSchema":{
+                "type":"object","required":["verdict"],"properties":{"verdict":{"type":"string"}}
+            }},
+            {"id":"coder","role":"worker","modelRouting":"tier"}
+        ]
+    }"#;
+
+    async fn run_routed_delegation(worker: &str, small: Vec<ChatResponse>, big: Vec<ChatResponse>) -> serde_json::Value {
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert("ollama", Arc::new(MockProvider::new(small)));
+        clients.insert("cloud", Arc::new(MockProvider::new(big)));
+        let ctx = worker_delegate_context(ROUTED_WORKERS, clients);
+        let args = serde_json::json!({"instruction": "go", "workerId": worker});
+        let output = crate::orchestration::execute_delegate_task(&ctx, &args).await.unwrap().output;
+        serde_json::from_str(&output).unwrap_or_else(|_| serde_json::json!(output))
+    }
+
+    #[tokio::test]
+    async fn routed_worker_escalates_after_failed_validation() {
+        let v = run_routed_delegation(
+            "reviewer",
+            vec![worker_reply("looks fine"), worker_reply("still fine"), worker_reply("fine!")],
+            vec![worker_reply(r#"{"verdict": "approve"}"#)],
+        )
+        .await;
+        assert_eq!(v["output"]["verdict"], "approve");
+        assert_eq!(v["worker"]["provider"], "cloud");
+        assert_eq!(v["worker"]["model"], "big");
+    }
+
+    #[tokio::test]
+    async fn routed_worker_escalates_at_tool_loop_limit() {
+        let looping = || {
+            make_chat_response(
+                Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: String::new(),
+                    tool_calls: Some(vec![make_tool_call("search", "{}")]),
+                    tool_name: None,
+                }),
+                true,
+                Some(FinishReason::Stop),
+            )
+        };
+        let v = run_routed_delegation("coder", vec![looping(), looping()], vec![worker_reply("done")]).await;
+        assert_eq!(v["reply"], "done");
+        assert_eq!(v["worker"]["model"], "big");
+    }
+
+    const RESILIENT_WORKERS: &str = r#"{
+        "providers":[
+            {"id":"ollama","endpointType":"ollama"},
+            {"id":"backup","endpointType":"openai-compat","baseUrl":"http://127.0.0.1:9/v1"}
+        ],
+        "agents":[
+            {"id":"orchestrator","role":"orchestrator","defaultProvider":"ollama","defaultModel":"m","enabledWorkers":[]},
+            {"id":"coder","role":"worker","retries":1,"timeoutSecs":1,"fallback":[{"workerId":"spare"}]},
+            {"id":"spare","role":"worker","defaultProvider":"backup","defaultModel":"b"}
+        ]
+    }"#;
+
+    async fn run_resilient_delegation(primary: Arc<FlakyProvider>, backup: Arc<FlakyProvider>) -> String {
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert("ollama", primary);
+        clients.insert("backup", backup);
+        let ctx = worker_delegate_context(RESILIENT_WORKERS, clients);
+        let args = serde_json::json!({"instruction": "fix it", "workerId": "coder"});
+        crate::orchestration::execute_delegate_task(&ctx, &args).await.unwrap().output
+    }
+
+    #[tokio::test]
+    async fn delegation_retries_worker_after_provider_error() {
+        let output = run_resilient_delegation(FlakyProvider::new(1, 0, "fixed"), FlakyProvider::new(0, 0, "spare")).await;
+        let v: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(v["reply"], "fixed");
+        assert_eq!(v["worker"]["provider"], "ollama");
+    }
+
+    #[tokio::test]
+    async fn delegation_falls_back_after_retries_and_timeout() {
+        // First attempt errors, the retry times out, the fallback worker answers.
+        let output = run_resilient_delegation(FlakyProvider::new(1, 5_000, "late"), FlakyProvider::new(0, 0, "spare")).await;
+        let v: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(v["reply"], "spare");
+        assert_eq!(v["worker"]["provider"], "backup");
+        assert_eq!(v["worker"]["model"], "b");
+    }
+
+    #[tokio::test]
+    async fn delegation_reports_last_error_after_all_attempts() {
+        let output = run_resilient_delegation(FlakyProvider::new(9, 0, ""), FlakyProvider::new(9, 0, "")).await;
+        assert!(output.starts_with("error: "), "{output}");
+        assert!(output.contains("connection refused"), "{output}");
+        assert!(output.ends_with("(after 3 attempts)"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn delegation_cancel_returns_operator_result() {
+        use crate::orchestration::DelegationCancels;
+
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert("ollama", FlakyProvider::new(0, 60_000, "never"));
+        let mut ctx = worker_delegate_context(
+            r#"{"agents":[
+                {"id":"orchestrator","role":"orchestrator","enabledWorkers":[]},
+                {"id":"coder","role":"worker","retries":2}
+            ]}"#,
+            clients,
+        );
+        let cancels = DelegationCancels::default();
+        ctx.cancels = Some(cancels.clone());
+        let (event_tx, _event_rx) = tokio::sync::broadcast::channel(16);
+        ctx.observability = Some(DelegateObservability {
+            event_tx,
+            session_id: None,
+            orchestrator_id: None,
+            source: Some("orchestrator".to_string()),
+            tool_index_offset: 0,
+            delegation_id: Some("d-1".to_string()),
+            parent_delegation_id: None,
+            tool_index_counter: None,
+            last_tool_index: std::sync::atomic::AtomicUsize::new(0),
+        });
+        assert!(!cancels.cancel("d-1"));
+
+        let args = serde_json::json!({"instruction": "fix it", "workerId": "coder"});
+        let cancel = async {
+            while !cancels.cancel("d-1") {
+                tokio::task::yield_now().await;
+            }
+        };
+        let (result, ()) = tokio::join!(crate::orchestration::execute_delegate_task(&ctx, &args), cancel);
+        assert_eq!(result.unwrap().output, "error: cancelled by operator");
+        // The registration ends with the delegation, and retries do not run after a cancel.
+        assert!(!cancels.cancel("d-1"));
+    }
+
+    /// Tool executor whose first call outlives the attempt's timeout; records whether two calls
+    /// ever ran at once.
+    struct SlowFirstCall {
+        calls: std::sync::atomic::AtomicUsize,
+        running: std::sync::atomic::AtomicUsize,
+        overlapped: AtomicBool,
+    }
+
+    impl ToolExecutor for SlowFirstCall {
+        fn execute(&self, name: &str, _args: &serde_json::Value, _ctx: ToolCallContext<'_>) -> Result<String, String> {
+            if self.running.fetch_add(1, Ordering::SeqCst) > 0 {
+                self.overlapped.store(true, Ordering::SeqCst);
+            }
+            if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                std::thread::sleep(std::time::Duration::from_millis(1_500));
+            }
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(format!("ran {}", name))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn timed_out_attempt_finishes_its_tool_call_before_the_retry() {
+        let search = || {
+            make_chat_response(
+                Some(ChatMessage {
+                    role: "assistant".to_string(),
+                    content: String::new(),
+                    tool_calls: Some(vec![make_tool_call("search", "{}")]),
+                    tool_name: None,
+                }),
+                true,
+                Some(FinishReason::Stop),
+            )
+        };
+        let mut clients = crate::orchestration::ProviderClients::default();
+        clients.insert("ollama", Arc::new(MockProvider::new(vec![search(), search(), worker_reply("done")])));
+        let mut ctx = worker_delegate_context(
+            r#"{"agents":[
+                {"id":"orchestrator","role":"orchestrator","enabledWorkers":[]},
+                {"id":"coder","role":"worker","retries":1,"timeoutSecs":1}
+            ]}"#,
+            clients,
+        );
+        let executor = Arc::new(SlowFirstCall {
+            calls: Default::default(),
+            running: Default::default(),
+            overlapped: AtomicBool::new(false),
+        });
+        let runtimes = Arc::get_mut(ctx.worker_runtimes.as_mut().unwrap()).unwrap();
+        runtimes.get_mut("coder").unwrap().tool_executor = Some(executor.clone());
+
+        let args = serde_json::json!({"instruction": "fix it", "workerId": "coder"});
+        let output = crate::orchestration::execute_delegate_task(&ctx, &args).await.unwrap().output;
+        let v: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(v["reply"], "done");
+        assert_eq!(executor.calls.load(Ordering::SeqCst), 2);
+        assert!(!executor.overlapped.load(Ordering::SeqCst), "the retry ran alongside the abandoned call");
+    }
 }
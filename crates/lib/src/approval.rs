@@ -0,0 +1,540 @@
+//! Human-in-the-loop tool call approval.
+//!
+//! When an agent's effective [`ApprovalPolicy`] gates a tool, the tool loop registers a
+//! [`PendingApproval`] with the [`ApprovalStore`] and waits for an operator decision (gateway
+//! **`approvals.decide`**) before executing it. Denied, timed-out, and stopped calls are not
+//! executed; the model receives a short `error: …` tool result instead so the transcript stays
+//! valid.
+//!
+//! When a path is provided, pending approvals are persisted to **`approvals.json`** (write-through,
+//! atomic `.tmp` + rename) so they survive a gateway restart. Records restored from disk have no
+//! waiting turn (`live: false`); the gateway resolves them against the session transcript instead.
+
+use crate::config::ApprovalPolicy;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, oneshot, RwLock};
+
+/// WebSocket event name: a tool call is waiting for operator approval.
+pub const EVENT_APPROVAL_REQUESTED: &str = "approval.requested";
+/// WebSocket event name: a pending approval was approved, denied, timed out, or cancelled by a stop.
+pub const EVENT_APPROVAL_RESOLVED: &str = "approval.resolved";
+
+/// Tool result prefix returned to the model when the operator denies a call.
+pub const DENIED_RESULT: &str = "error: tool call denied by operator";
+
+/// Tool result for calls of the same response that never ran because the gateway restarted while
+/// an earlier call waited for approval.
+pub const INTERRUPTED_RESULT: &str = "error: tool call not run (interrupted by a gateway restart)";
+
+/// How often a waiting turn re-checks its stop flag and timeout.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A tool call waiting for an operator decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingApproval {
+    /// Opaque approval id (`appr-<uuid>`).
+    pub id: String,
+    /// Orchestrator session the call belongs to (worker calls use the delegating session).
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Orchestrator that owns the session.
+    #[serde(default)]
+    pub orchestrator_id: Option<String>,
+    /// Agent whose tool loop produced the call (orchestrator id or worker id).
+    pub agent_id: String,
+    pub tool_name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+    /// ISO 8601 timestamp when the approval was requested.
+    #[serde(default)]
+    pub created_at: String,
+    /// True while a running turn is waiting on this decision; false for records restored
+    /// from disk after a restart.
+    #[serde(skip)]
+    pub live: bool,
+}
+
+/// Operator decision for a pending approval.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    Approve,
+    /// Deny with an optional reason shown to the model.
+    Deny(Option<String>),
+}
+
+impl ApprovalDecision {
+    /// Wire label used in `approval.resolved` events and `approvals.decide` responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalDecision::Approve => "approved",
+            ApprovalDecision::Deny(_) => "denied",
+        }
+    }
+}
+
+/// Tool result returned to the model for a denied call.
+pub fn denial_result(reason: Option<&str>) -> String {
+    match reason.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(r) => format!("{}: {}", DENIED_RESULT, r),
+        None => DENIED_RESULT.to_string(),
+    }
+}
+
+/// Per-agent approval rule: the effective policy plus the agent's state-modifying tool names.
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalGate {
+    pub policy: ApprovalPolicy,
+    /// Tools gated under [`ApprovalPolicy::ApproveWrites`] (see
+    /// [`crate::skills::ToolDescriptor::write_tool_names`]).
+    pub write_tools: HashSet<String>,
+}
+
+impl ApprovalGate {
+    pub fn new(policy: ApprovalPolicy, write_tools: HashSet<String>) -> Self {
+        Self { policy, write_tools }
+    }
+
+    /// Whether a call to `tool_name` must wait for operator approval.
+    pub fn requires_approval(&self, tool_name: &str) -> bool {
+        match self.policy {
+            ApprovalPolicy::Auto => false,
+            ApprovalPolicy::ApproveWrites => self.write_tools.contains(tool_name),
+            ApprovalPolicy::ApproveAll => true,
+        }
+    }
+}
+
+/// Pending approvals (ordered by request time) plus the waiters of running turns.
+pub struct ApprovalStore {
+    pending: Arc<RwLock<Vec<PendingApproval>>>,
+    waiters: Arc<RwLock<HashMap<String, oneshot::Sender<ApprovalDecision>>>>,
+    /// Path of `approvals.json`. `None` = in-memory only.
+    path: Option<PathBuf>,
+    event_tx: Option<broadcast::Sender<String>>,
+    /// Deny a pending call after this long. `None` = wait until decided or stopped.
+    timeout: Option<Duration>,
+}
+
+impl Default for ApprovalStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApprovalStore {
+    /// Create an in-memory-only approval store (no disk I/O, no events).
+    pub fn new() -> Self {
+        Self {
+            pending: Arc::new(RwLock::new(Vec::new())),
+            waiters: Arc::new(RwLock::new(HashMap::new())),
+            path: None,
+            event_tx: None,
+            timeout: None,
+        }
+    }
+
+    /// Create a persistent approval store backed by `path` (e.g. `<profile>/approvals.json`).
+    /// Loads records left over from a previous run; they are restored with `live: false`.
+    pub fn with_path(path: PathBuf) -> Self {
+        let restored = Self::load_from_disk(&path);
+        if !restored.is_empty() {
+            log::info!(
+                "restored {} pending tool approval(s) from {}",
+                restored.len(),
+                path.display()
+            );
+        }
+        Self {
+            pending: Arc::new(RwLock::new(restored)),
+            waiters: Arc::new(RwLock::new(HashMap::new())),
+            path: Some(path),
+            event_tx: None,
+            timeout: None,
+        }
+    }
+
+    /// Broadcast `approval.*` events on the gateway event channel.
+    pub fn with_event_tx(mut self, event_tx: broadcast::Sender<String>) -> Self {
+        self.event_tx = Some(event_tx);
+        self
+    }
+
+    /// Deny pending calls automatically after `timeout`.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn load_from_disk(path: &PathBuf) -> Vec<PendingApproval> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("could not read approvals file {}: {}", path.display(), e);
+                }
+                return Vec::new();
+            }
+        };
+        match serde_json::from_str(&content) {
+            Ok(records) => records,
+            Err(e) => {
+                log::warn!(
+                    "corrupt approvals file {}, starting with no pending approvals: {}",
+                    path.display(),
+                    e
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    /// Persist the pending list to disk (atomic: .tmp then rename).
+    async fn persist_to_disk(&self) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        let json = match serde_json::to_string_pretty(&*self.pending.read().await) {
+            Ok(j) => j,
+            Err(e) => {
+                log::warn!("failed to serialize approvals: {}", e);
+                return;
+            }
+        };
+        let tmp_path = path.with_extension("json.tmp");
+        if let Err(e) = std::fs::write(&tmp_path, &json) {
+            log::warn!(
+                "failed to write approvals tmp file {}: {}",
+                tmp_path.display(),
+                e
+            );
+            return;
+        }
+        if let Err(e) = std::fs::rename(&tmp_path, path) {
+            log::warn!(
+                "failed to rename approvals file {} -> {}: {}",
+                tmp_path.display(),
+                path.display(),
+                e
+            );
+        }
+    }
+
+    fn send_event(&self, event: &str, payload: serde_json::Value) {
+        let Some(ref tx) = self.event_tx else {
+            return;
+        };
+        let frame = json!({
+            "type": "event",
+            "event": event,
+            "payload": payload,
+        });
+        if let Ok(text) = serde_json::to_string(&frame) {
+            if let Err(e) = tx.send(text) {
+                log::debug!("approvals: failed to send {} event: {}", event, e);
+            }
+        }
+    }
+
+    fn emit_resolved(&self, record: &PendingApproval, decision: &str, reason: Option<&str>) {
+        self.send_event(
+            EVENT_APPROVAL_RESOLVED,
+            json!({
+                "id": record.id,
+                "sessionId": record.session_id,
+                "orchestratorId": record.orchestrator_id,
+                "agentId": record.agent_id,
+                "toolName": record.tool_name,
+                "decision": decision,
+                "reason": reason,
+            }),
+        );
+    }
+
+    /// Pending approvals in request order, optionally filtered by session.
+    pub async fn list(&self, session_id: Option<&str>) -> Vec<PendingApproval> {
+        let waiters = self.waiters.read().await;
+        self.pending
+            .read()
+            .await
+            .iter()
+            .filter(|r| session_id.is_none() || r.session_id.as_deref() == session_id)
+            .map(|r| PendingApproval {
+                live: waiters.contains_key(&r.id),
+                ..r.clone()
+            })
+            .collect()
+    }
+
+    /// Register a pending call and return the receiver its turn waits on.
+    async fn request(&self, mut record: PendingApproval) -> oneshot::Receiver<ApprovalDecision> {
+        let (tx, rx) = oneshot::channel();
+        record.live = true;
+        self.waiters.write().await.insert(record.id.clone(), tx);
+        self.pending.write().await.push(record.clone());
+        self.persist_to_disk().await;
+        log::info!(
+            "approval pending: {} tool {} (agent {})",
+            record.id,
+            record.tool_name,
+            record.agent_id
+        );
+        self.send_event(
+            EVENT_APPROVAL_REQUESTED,
+            serde_json::to_value(&record).unwrap_or_else(|_| json!({})),
+        );
+        rx
+    }
+
+    /// Remove a record without a decision from the operator (timeout or stop).
+    async fn expire(&self, id: &str, label: &str) {
+        self.waiters.write().await.remove(id);
+        let removed = {
+            let mut pending = self.pending.write().await;
+            let pos = pending.iter().position(|r| r.id == id);
+            pos.map(|i| pending.remove(i))
+        };
+        if let Some(record) = removed {
+            self.persist_to_disk().await;
+            log::info!("approval {}: {} tool {}", label, record.id, record.tool_name);
+            self.emit_resolved(&record, label, None);
+        }
+    }
+
+    /// Apply an operator decision. Returns the resolved record; `live` is true when a running
+    /// turn received the decision and false for records restored after a restart (the caller
+    /// must resolve those against the session transcript).
+    pub async fn decide(
+        &self,
+        id: &str,
+        decision: ApprovalDecision,
+    ) -> Result<PendingApproval, String> {
+        let mut record = {
+            let mut pending = self.pending.write().await;
+            let pos = pending
+                .iter()
+                .position(|r| r.id == id)
+                .ok_or_else(|| format!("unknown approval id: {}", id))?;
+            pending.remove(pos)
+        };
+        self.persist_to_disk().await;
+        let reason = match &decision {
+            ApprovalDecision::Deny(r) => r.clone(),
+            ApprovalDecision::Approve => None,
+        };
+        let label = decision.as_str();
+        record.live = match self.waiters.write().await.remove(id) {
+            Some(tx) => tx.send(decision).is_ok(),
+            None => false,
+        };
+        log::info!("approval {}: {} tool {}", label, record.id, record.tool_name);
+        self.emit_resolved(&record, label, reason.as_deref());
+        Ok(record)
+    }
+}
+
+/// Approval context for one agent's tool loop: the shared store plus that agent's gate.
+/// Owned so it can travel with a delegation into a spawned task.
+#[derive(Clone)]
+pub struct ApprovalScope {
+    pub store: Arc<ApprovalStore>,
+    pub gate: ApprovalGate,
+    /// Agent whose policy applies (orchestrator id or worker id).
+    pub agent_id: String,
+    pub session_id: Option<String>,
+    pub orchestrator_id: Option<String>,
+}
+
+impl ApprovalScope {
+    /// Wait for approval when the gate requires it. Returns `None` when the call may run, or
+    /// `Some(tool_result)` explaining why it was not executed.
+    pub async fn check(
+        &self,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        stop_flag: Option<&Arc<AtomicBool>>,
+    ) -> Option<String> {
+        if !self.gate.requires_approval(tool_name) {
+            return None;
+        }
+        let record = PendingApproval {
+            id: format!("appr-{}", uuid::Uuid::new_v4()),
+            session_id: self.session_id.clone(),
+            orchestrator_id: self.orchestrator_id.clone(),
+            agent_id: self.agent_id.clone(),
+            tool_name: tool_name.to_string(),
+            arguments: arguments.clone(),
+            created_at: crate::session::chrono_now_iso8601(),
+            live: true,
+        };
+        let id = record.id.clone();
+        let mut rx = self.store.request(record).await;
+        let started = Instant::now();
+        loop {
+            match tokio::time::timeout(WAIT_POLL_INTERVAL, &mut rx).await {
+                Ok(Ok(ApprovalDecision::Approve)) => return None,
+                Ok(Ok(ApprovalDecision::Deny(reason))) => {
+                    return Some(denial_result(reason.as_deref()));
+                }
+                Ok(Err(_)) => {
+                    return Some(denial_result(Some("approval request was discarded")));
+                }
+                Err(_) => {}
+            }
+            if stop_flag.is_some_and(|f| f.load(Ordering::SeqCst)) {
+                self.store.expire(&id, "stopped").await;
+                return Some("error: tool call not executed: turn stopped while awaiting approval".to_string());
+            }
+            if let Some(timeout) = self.store.timeout {
+                if started.elapsed() >= timeout {
+                    self.store.expire(&id, "timeout").await;
+                    return Some(denial_result(Some(&format!(
+                        "approval timed out after {}s",
+                        timeout.as_secs()
+                    ))));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn gate(policy: ApprovalPolicy) -> ApprovalGate {
+        ApprovalGate::new(policy, ["files_write".to_string()].into_iter().collect())
+    }
+
+    fn scope(store: &Arc<ApprovalStore>, gate: &ApprovalGate) -> ApprovalScope {
+        ApprovalScope {
+            store: store.clone(),
+            gate: gate.clone(),
+            agent_id: "orchestrator".to_string(),
+            session_id: Some("sess-1".to_string()),
+            orchestrator_id: Some("orchestrator".to_string()),
+        }
+    }
+
+    #[test]
+    fn gate_policies() {
+        assert!(!gate(ApprovalPolicy::Auto).requires_approval("files_write"));
+        assert!(gate(ApprovalPolicy::ApproveWrites).requires_approval("files_write"));
+        assert!(!gate(ApprovalPolicy::ApproveWrites).requires_approval("files_read"));
+        assert!(gate(ApprovalPolicy::ApproveAll).requires_approval("files_read"));
+    }
+
+    #[test]
+    fn denial_result_includes_reason() {
+        assert_eq!(denial_result(None), DENIED_RESULT);
+        assert_eq!(denial_result(Some("  ")), DENIED_RESULT);
+        assert_eq!(
+            denial_result(Some("wrong file")),
+            "error: tool call denied by operator: wrong file"
+        );
+    }
+
+    #[tokio::test]
+    async fn ungated_call_does_not_register() {
+        let store = Arc::new(ApprovalStore::new());
+        let g = gate(ApprovalPolicy::ApproveWrites);
+        let out = scope(&store, &g)
+            .check("files_read", &json!({}), None)
+            .await;
+        assert!(out.is_none());
+        assert!(store.list(None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn approve_and_deny_resume_waiting_turn() {
+        let store = Arc::new(ApprovalStore::new());
+        let g = gate(ApprovalPolicy::ApproveAll);
+        for (decision, expected) in [
+            (ApprovalDecision::Approve, None),
+            (
+                ApprovalDecision::Deny(Some("not now".to_string())),
+                Some("error: tool call denied by operator: not now".to_string()),
+            ),
+        ] {
+            let s = store.clone();
+            let decider = tokio::spawn(async move {
+                loop {
+                    if let Some(r) = s.list(Some("sess-1")).await.first() {
+                        assert!(r.live);
+                        return s.decide(&r.id, decision).await.unwrap();
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            });
+            let out = scope(&store, &g)
+                .check("files_write", &json!({"path": "a.md"}), None)
+                .await;
+            assert_eq!(out, expected);
+            assert!(decider.await.unwrap().live);
+            assert!(store.list(None).await.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_flag_cancels_pending_call() {
+        let store = Arc::new(ApprovalStore::new());
+        let g = gate(ApprovalPolicy::ApproveAll);
+        let flag = Arc::new(AtomicBool::new(true));
+        let out = scope(&store, &g)
+            .check("files_write", &json!({}), Some(&flag))
+            .await
+            .expect("not executed");
+        assert!(out.contains("turn stopped"));
+        assert!(store.list(None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn timeout_denies_pending_call() {
+        let store = Arc::new(ApprovalStore::new().with_timeout(Some(Duration::from_millis(1))));
+        let g = gate(ApprovalPolicy::ApproveAll);
+        let out = scope(&store, &g)
+            .check("files_write", &json!({}), None)
+            .await
+            .expect("denied");
+        assert!(out.starts_with(DENIED_RESULT));
+        assert!(out.contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn pending_approvals_survive_restart() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("approvals.json");
+        {
+            let store = ApprovalStore::with_path(path.clone());
+            let _rx = store
+                .request(PendingApproval {
+                    id: "appr-1".to_string(),
+                    session_id: Some("sess-1".to_string()),
+                    orchestrator_id: Some("orchestrator".to_string()),
+                    agent_id: "orchestrator".to_string(),
+                    tool_name: "files_write".to_string(),
+                    arguments: json!({"path": "a.md"}),
+                    created_at: String::new(),
+                    live: true,
+                })
+                .await;
+        }
+        let store = ApprovalStore::with_path(path.clone());
+        let restored = store.list(Some("sess-1")).await;
+        assert_eq!(restored.len(), 1);
+        assert!(!restored[0].live);
+        assert_eq!(restored[0].arguments["path"], "a.md");
+
+        let decided = store.decide("appr-1", ApprovalDecision::Approve).await.unwrap();
+        assert!(!decided.live);
+        assert!(ApprovalStore::with_path(path).list(None).await.is_empty());
+        assert!(store.decide("appr-1", ApprovalDecision::Approve).await.is_err());
+    }
+}
@@ -3,7 +3,9 @@
 //! Config is loaded from a JSON file under the active profile (e.g. `~/.chai/profiles/assistant/config.json`) and environment.
 //! Top-level keys include `gateway`, `channels` (Telegram, Matrix, Signal), `providers` (JSON array of `id` + `endpointType` entries
 //! for model APIs), `sandbox` (sandbox enforcement settings), `agents` (JSON array of `id` / `role` entries; omit the key for a
-//! single default orchestrator), and `skills` (lock mode and shared skill settings). Skill **packages** are always loaded from
+//! single default orchestrator), `skills` (lock mode and shared skill settings), and `approval` (human-in-the-loop tool call
+//! approval policy; per-agent overrides live on each **`agents`** entry), `hooks` (operator scripts run before and after
+//! matching tool calls), and `workflows` (declarative multi-step worker DAGs). Skill **packages** are always loaded from
 //! **`~/.chai/skills`** (per-agent enablement is under **`agents`**).
 
 use anyhow::{Context, Result};
@@ -39,6 +41,26 @@ pub struct Config {
     /// Skill package settings: lock mode and shared skill configuration.
     #[serde(default)]
     pub skills: SkillsConfig,
+
+    /// Tool call approval settings: profile-wide policy and pending-approval timeout.
+    #[serde(default)]
+    pub approval: ApprovalConfig,
+
+    /// Profile-level tool hooks run before and after matching tool calls.
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Profile-wide bounds for skill tool commands (timeout, output cap).
+    #[serde(default)]
+    pub exec: ExecConfig,
+
+    /// Declarative multi-step workflows: named DAGs of worker steps an orchestrator runs as one tool.
+    #[serde(default)]
+    pub workflows: Vec<WorkflowConfig>,
+
+    /// Redaction rules applied to skill tool output when a provider in scope is configured.
+    #[serde(default)]
+    pub redaction: RedactionConfig,
 }
 
 /// Gateway bind, port, and auth settings.
@@ -83,6 +105,128 @@ pub struct SandboxConfig {
     /// are disabled.
     #[serde(default)]
     pub mode: SandboxMode,
+    /// Kernel enforcement of the sandbox for tool commands: `"none"` (default) or `"landlock"`.
+    #[serde(default)]
+    pub kernel: KernelSandbox,
+    /// With `kernel: "landlock"`, more paths tool commands may read (e.g. `~/.gitconfig` or a
+    /// toolchain outside the system directories). `~/` expands to the home directory.
+    #[serde(default)]
+    pub read_paths: Vec<String>,
+    /// With `kernel: "landlock"`, also deny tool commands outgoing TCP connections (Linux 6.7+).
+    #[serde(default)]
+    pub deny_tcp_connect: bool,
+    /// Limits on what agents store in the sandbox directory and scratch directories.
+    #[serde(default)]
+    pub quota: SandboxQuotaConfig,
+}
+
+/// Disk quota for the sandbox (`sandbox.quota`). Omitted fields are not limited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxQuotaConfig {
+    /// Total bytes of regular files.
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+    /// Entries (files, directories, and symlinks).
+    #[serde(default)]
+    pub max_files: Option<u64>,
+}
+
+impl SandboxQuotaConfig {
+    pub fn is_set(&self) -> bool {
+        self.max_bytes.is_some() || self.max_files.is_some()
+    }
+}
+
+impl SandboxConfig {
+    /// The kernel policy for tool commands confined to `sandbox`, or `None` when `kernel` is
+    /// `"none"`.
+    pub fn kernel_policy(&self, sandbox: &crate::exec::WriteSandbox) -> Option<crate::exec::KernelPolicy> {
+        if self.kernel == KernelSandbox::None {
+            return None;
+        }
+        let read_paths: Vec<PathBuf> = self.read_paths.iter().map(|p| expand_home(p)).collect();
+        Some(crate::exec::KernelPolicy::for_sandbox(
+            sandbox,
+            &read_paths,
+            self.deny_tcp_connect,
+        ))
+    }
+}
+
+/// Kernel enforcement of the sandbox (`sandbox.kernel`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KernelSandbox {
+    /// Path checks in the gateway only.
+    #[default]
+    None,
+    /// Also confine each tool command with Landlock (Linux 5.13+, no privileges needed): writes
+    /// only inside the sandbox's writable roots and the temp directory, reads only there, in
+    /// read-only roots, system directories, and `readPaths`. Older kernels run without it and log
+    /// a warning.
+    Landlock,
+}
+
+impl KernelSandbox {
+    /// String identifier for this mode (matches the serde value).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KernelSandbox::None => "none",
+            KernelSandbox::Landlock => "landlock",
+        }
+    }
+}
+
+/// Per-agent view of the profile sandbox (`sandbox` on an `agents` entry). When set, the agent's
+/// tools reach only the listed direct children of `<profileRoot>/sandbox/` and, with `scratch`,
+/// a private directory; without it the agent shares the whole sandbox.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentSandboxConfig {
+    /// Direct-child entry names (directories or symlinks) the agent may read and write.
+    #[serde(default)]
+    pub writable: Vec<String>,
+    /// Direct-child entry names the agent may read (`readPath`, `workingDir`) but not write.
+    #[serde(default)]
+    pub read_only: Vec<String>,
+    /// Give the agent a writable directory at `<profileRoot>/agents/<id>/scratch/`, used as its
+    /// working directory and as the base for relative paths that do not name a listed entry.
+    #[serde(default)]
+    pub scratch: bool,
+}
+
+impl AgentSandboxConfig {
+    /// Check entry names for agent `id`: plain names (no separators, `.` or `..`), each listed once,
+    /// and at least one grant.
+    fn validate(&self, id: &str) -> Result<(), String> {
+        if self.writable.is_empty() && self.read_only.is_empty() && !self.scratch {
+            return Err(format!(
+                "agent \"{id}\" has a \"sandbox\" block that grants nothing — list writable or readOnly entries, or set scratch"
+            ));
+        }
+        let mut seen = std::collections::HashSet::new();
+        for name in self.writable.iter().chain(&self.read_only) {
+            let plain = !name.is_empty()
+                && name != "."
+                && name != ".."
+                && !name.contains(['/', '\\']);
+            if !plain {
+                return Err(format!(
+                    "agent \"{id}\" has sandbox entry \"{name}\" — entries are names of direct children of the sandbox directory"
+                ));
+            }
+            if !seen.insert(name.as_str()) {
+                return Err(format!("agent \"{id}\" lists sandbox entry \"{name}\" more than once"));
+            }
+        }
+        if let Some(name) = self.writable.iter().find(|n| n.starts_with(crate::exec::READ_ONLY_PREFIX)) {
+            return Err(format!(
+                "agent \"{id}\" lists read-only sandbox entry \"{name}\" as writable — list it under readOnly"
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// How the gateway handles the sandbox at startup.
@@ -115,6 +259,279 @@ impl SandboxMode {
     }
 }
 
+/// Human-in-the-loop tool call approval settings.
+///
+/// The profile-wide **`policy`** applies to every agent unless the agent entry sets
+/// **`approvalPolicy`**. Pending approvals are persisted to **`approvals.json`** in the profile
+/// directory and resolved via the gateway **`approvals.decide`** method.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalConfig {
+    /// Default policy for all agents (default `"auto"`).
+    #[serde(default)]
+    pub policy: ApprovalPolicy,
+    /// Seconds to wait for a decision before the call is denied. Omitted = wait until decided
+    /// or the turn is stopped.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl ApprovalConfig {
+    /// Effective policy for an agent: the agent's `approvalPolicy` when set, else the profile policy.
+    pub fn policy_for(&self, agent_override: Option<ApprovalPolicy>) -> ApprovalPolicy {
+        agent_override.unwrap_or(self.policy)
+    }
+}
+
+/// When a model-requested tool call must wait for operator approval before it runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalPolicy {
+    /// Execute every tool call immediately (no approval).
+    #[default]
+    Auto,
+    /// Require approval for tools that can modify state: any execution spec with a `writePath`
+    /// argument or without `readOnly: true`. Read-only tools and `delegate_task` run immediately;
+    /// the delegated worker's own tool calls are still gated.
+    ApproveWrites,
+    /// Require approval for every tool call, including `delegate_task`.
+    ApproveAll,
+}
+
+impl ApprovalPolicy {
+    /// String identifier for this policy (matches the serde value).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalPolicy::Auto => "auto",
+            ApprovalPolicy::ApproveWrites => "approve_writes",
+            ApprovalPolicy::ApproveAll => "approve_all",
+        }
+    }
+}
+
+/// Profile-wide bounds for the commands skill tools run. An execution spec's own `timeoutSecs` /
+/// `maxOutputBytes` take precedence; `0` (here or on the spec) removes the bound.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecConfig {
+    /// Seconds before a command and its process group are killed (default 300).
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Bytes of stdout and stderr kept per command; the rest is discarded (default 1 MiB).
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+    /// Kernel resource limits for every command (Linux only); a spec's `limits` fields override
+    /// these one by one.
+    #[serde(default)]
+    pub limits: crate::exec::ResourceLimits,
+    /// Environment for every command beyond the baseline (`pass` / `set`); a spec's `env` adds
+    /// to it.
+    #[serde(default)]
+    pub env: crate::exec::EnvPolicy,
+}
+
+/// Patterns scrubbed from skill tool output before it reaches the model. Applied only when a
+/// provider in scope is configured, since results then may leave the machine.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionConfig {
+    /// Built-in rule names (`email`, `apiKey`, `iban`) or `{ name, pattern, replacement? }`
+    /// regex rules. Execution specs add their own with `redact`.
+    #[serde(default)]
+    pub rules: Vec<crate::redaction::RedactionRule>,
+    /// Provider ids whose use turns redaction on. Omitted = every provider whose base URL is not
+    /// a loopback address; empty = never.
+    #[serde(default)]
+    pub providers: Option<Vec<String>>,
+}
+
+impl RedactionConfig {
+    /// Whether output bound for provider `id` is redacted.
+    pub fn applies_to(&self, providers: &ProvidersConfig, id: &str) -> bool {
+        match &self.providers {
+            Some(list) => {
+                let id = canonical_provider_id(providers, id);
+                id.is_some()
+                    && list
+                        .iter()
+                        .any(|p| canonical_provider_id(providers, p) == id)
+            }
+            None => !provider_is_local(providers, id),
+        }
+    }
+
+    /// Configured providers for which [`RedactionConfig::applies_to`] is true.
+    pub fn providers_in_scope(&self, providers: &ProvidersConfig) -> Vec<String> {
+        providers
+            .ids()
+            .into_iter()
+            .filter(|id| self.applies_to(providers, id))
+            .collect()
+    }
+}
+
+/// Default for [`ExecConfig::timeout_secs`].
+pub const DEFAULT_EXEC_TIMEOUT_SECS: u64 = 300;
+
+/// Default for [`ExecConfig::max_output_bytes`].
+pub const DEFAULT_EXEC_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+impl ExecConfig {
+    /// Limits for a command whose execution spec sets `timeout_secs` / `max_output_bytes` /
+    /// `limits` (`None` = use the profile value, then the default).
+    pub fn limits_for(
+        &self,
+        timeout_secs: Option<u64>,
+        max_output_bytes: Option<usize>,
+        resources: Option<&crate::exec::ResourceLimits>,
+    ) -> crate::exec::ExecLimits {
+        let timeout_secs = timeout_secs
+            .or(self.timeout_secs)
+            .unwrap_or(DEFAULT_EXEC_TIMEOUT_SECS);
+        let max_output_bytes = max_output_bytes
+            .or(self.max_output_bytes)
+            .unwrap_or(DEFAULT_EXEC_MAX_OUTPUT_BYTES);
+        crate::exec::ExecLimits {
+            timeout: (timeout_secs > 0).then(|| std::time::Duration::from_secs(timeout_secs)),
+            max_output_bytes: (max_output_bytes > 0).then_some(max_output_bytes),
+            resources: resources.copied().unwrap_or_default().or(&self.limits),
+        }
+    }
+
+    /// Environment for a command whose execution spec sets `env` (`None` = the profile's alone).
+    pub fn env_for(&self, env: Option<&crate::exec::EnvPolicy>) -> crate::exec::EnvPolicy {
+        match env {
+            Some(env) => env.or(&self.env),
+            None => self.env.clone(),
+        }
+    }
+}
+
+/// Profile-level tool hooks: operator scripts run around matching tool calls for every agent.
+///
+/// Scripts live under **`<profileRoot>/hooks/`** and need no allowlist entry; hooks that run a
+/// binary instead must be listed in **`allowlist`** (binary → allowed subcommands), mirroring a
+/// skill's `allowlist.json`. Hooks run with the sandbox root as their working directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HooksConfig {
+    /// Run before execution, in order. A hook can veto the call or rewrite its arguments.
+    #[serde(default)]
+    pub pre: Vec<HookConfig>,
+    /// Run after successful execution, in order. A hook can append to or replace the result.
+    #[serde(default)]
+    pub post: Vec<HookConfig>,
+    /// Binaries hooks may run via `binary` / `subcommand` (binary → allowed subcommands).
+    #[serde(default)]
+    pub allowlist: HashMap<String, Vec<String>>,
+}
+
+impl HooksConfig {
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+}
+
+/// One hook: which tools it matches and what to run (same shape as a skill's `postProcess`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookConfig {
+    /// Tool names or globs (`*`, `?`), e.g. `["git_push", "files_*"]`.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Script name under `<profileRoot>/hooks/` (`.sh` is tried when the bare name is missing).
+    #[serde(default)]
+    pub script: Option<String>,
+    /// Binary to run instead of a script; must be in `hooks.allowlist`.
+    #[serde(default)]
+    pub binary: Option<String>,
+    /// Subcommand for `binary`. Required when `binary` is set.
+    #[serde(default)]
+    pub subcommand: Option<String>,
+    /// Extra arguments; `$param_name` is replaced with the tool call's argument value.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Post hooks only: how the hook's stdout combines with the tool result (default `append`).
+    #[serde(default)]
+    pub mode: HookResultMode,
+}
+
+/// How a post hook's output combines with the tool result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookResultMode {
+    /// Append the hook's stdout after the result (newline-separated).
+    #[default]
+    Append,
+    /// Use the hook's stdout as the result.
+    Replace,
+}
+
+/// One declarative workflow: a DAG of worker steps run in dependency order (see
+/// `orchestration::workflow`). Exposed to enabled orchestrators as the tool `workflow_<id>`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowConfig {
+    /// Stable workflow id (letters, digits, `-`, `_`); unique within `workflows`.
+    pub id: String,
+    /// Tool description shown to the orchestrator model.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Named string inputs, referenced from templates as `{{inputs.<name>}}`.
+    #[serde(default)]
+    pub inputs: Vec<WorkflowInput>,
+    /// Steps; a step runs after every step it depends on has finished.
+    #[serde(default)]
+    pub steps: Vec<WorkflowStep>,
+    /// Template for the workflow result. Omitted ⇒ the output of the last step that ran.
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+/// A workflow input: one string parameter of the workflow tool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// When true, the input may be omitted (it renders as an empty string).
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// A workflow step: one delegation to a worker.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowStep {
+    /// Step id (unique within the workflow); referenced as `{{steps.<id>.output}}`.
+    pub id: String,
+    /// Worker that runs the step.
+    pub worker_id: String,
+    /// Instruction template: `{{inputs.<name>}}` and `{{steps.<id>.output[.path]}}` are replaced.
+    pub instruction: String,
+    /// Steps that must finish first, in addition to those referenced by the templates.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Run the step only when this condition holds; otherwise it is skipped.
+    #[serde(default)]
+    pub when: Option<WorkflowCondition>,
+}
+
+/// Condition on a workflow value. With neither `equals` nor `notEquals`, the value must be
+/// present and not `false`, `null`, or an empty string.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowCondition {
+    /// Value reference without braces, e.g. `steps.review.output.verdict` or `inputs.mode`.
+    #[serde(rename = "ref")]
+    pub reference: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_equals: Option<serde_json::Value>,
+}
+
 /// Gateway auth: token or none (loopback-only when none).
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -480,6 +897,50 @@ pub enum SkillContextMode {
     ReadOnDemand,
 }
 
+/// Minimum model capability a skill declares (`capability_tier` in SKILL.md) and the capability a
+/// provider or model declares (`tier` / `modelTiers`). Ordered from least to most capable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CapabilityTier {
+    #[default]
+    Minimal,
+    Moderate,
+    Full,
+}
+
+impl CapabilityTier {
+    /// Parse a SKILL.md `capability_tier` value. Unknown values yield `None`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "minimal" => Some(Self::Minimal),
+            "moderate" => Some(Self::Moderate),
+            "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Minimal => "minimal",
+            Self::Moderate => "moderate",
+            Self::Full => "full",
+        }
+    }
+}
+
+/// How an agent's model is chosen (`modelRouting` on an `agents` entry).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelRouting {
+    /// Always use the agent's `defaultProvider` / `defaultModel`.
+    #[default]
+    Static,
+    /// Use the cheapest model with a declared tier that satisfies the agent's strictest enabled
+    /// skill, moving to a higher tier when that model fails output validation or hits the tool
+    /// loop limit.
+    Tier,
+}
+
 /// Orchestrator configuration: one entry per orchestrator in the `agents` array.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -501,6 +962,9 @@ pub struct OrchestratorConfig {
     /// Worker ids this orchestrator can delegate to. Omitted ⇒ no workers; empty array ⇒ all profile workers are available.
     #[serde(default)]
     pub enabled_workers: Option<Vec<String>>,
+    /// Workflow ids offered to this orchestrator as tools. Omitted ⇒ none; empty array ⇒ all profile workflows.
+    #[serde(default)]
+    pub enabled_workflows: Option<Vec<String>>,
     /// How this orchestrator's skill docs are inlined vs `read_skill`.
     #[serde(default)]
     pub context_mode: Option<SkillContextMode>,
@@ -516,8 +980,35 @@ pub struct OrchestratorConfig {
     /// Maximum number of tool loops per turn. Omitted = no limit.
     #[serde(default)]
     pub max_tool_loops_per_turn: Option<u32>,
+    /// How many `delegate_task` calls from one model response run concurrently. Omitted = 3; `1` = sequential.
+    #[serde(default)]
+    pub max_parallel_workflows: Option<usize>,
+    /// How deep delegation may nest below this orchestrator. Omitted = 1 (workers cannot delegate);
+    /// applies to every worker in the tree.
+    #[serde(default)]
+    pub max_delegation_depth: Option<usize>,
+    /// Overrides the profile `approval.policy` for this orchestrator's tool calls.
+    #[serde(default)]
+    pub approval_policy: Option<ApprovalPolicy>,
+    /// Restricts this orchestrator's tools to part of the profile sandbox. Omitted ⇒ whole sandbox.
+    #[serde(default)]
+    pub sandbox: Option<AgentSandboxConfig>,
+    /// How the model for this orchestrator's turns is chosen. Omitted ⇒ static.
+    #[serde(default)]
+    pub model_routing: Option<ModelRouting>,
+    /// When true, `delegate_task` returns a handle immediately and the worker runs in the
+    /// background while the orchestrator keeps working (`check_delegation` / `await_delegation`).
+    /// Omitted = false (the orchestrator waits for each delegation).
+    #[serde(default)]
+    pub async_delegation: Option<bool>,
 }
 
+/// Default for [`OrchestratorConfig::max_parallel_workflows`].
+pub const DEFAULT_MAX_PARALLEL_WORKFLOWS: usize = 3;
+
+/// Default for [`OrchestratorConfig::max_delegation_depth`]: the orchestrator delegates, workers do not.
+pub const DEFAULT_MAX_DELEGATION_DEPTH: usize = 1;
+
 impl Default for OrchestratorConfig {
     fn default() -> Self {
         Self {
@@ -527,16 +1018,42 @@ impl Default for OrchestratorConfig {
             enabled_providers: None,
             enabled_skills: None,
             enabled_workers: None,
+            enabled_workflows: None,
             context_mode: None,
             max_delegations_per_turn: None,
             max_delegations_per_session: None,
             max_delegations_per_worker: None,
             max_tool_loops_per_turn: None,
+            max_parallel_workflows: None,
+            max_delegation_depth: None,
+            approval_policy: None,
+            sandbox: None,
+            model_routing: None,
+            async_delegation: None,
         }
     }
 }
 
 impl OrchestratorConfig {
+    /// Whether `delegate_task` runs workers in the background (default false).
+    pub fn async_delegation(&self) -> bool {
+        self.async_delegation.unwrap_or(false)
+    }
+
+    /// Effective concurrent delegation limit (default [`DEFAULT_MAX_PARALLEL_WORKFLOWS`], at least 1).
+    pub fn max_parallel_workflows(&self) -> usize {
+        self.max_parallel_workflows
+            .unwrap_or(DEFAULT_MAX_PARALLEL_WORKFLOWS)
+            .max(1)
+    }
+
+    /// Effective delegation depth limit (default [`DEFAULT_MAX_DELEGATION_DEPTH`], at least 1).
+    pub fn max_delegation_depth(&self) -> usize {
+        self.max_delegation_depth
+            .unwrap_or(DEFAULT_MAX_DELEGATION_DEPTH)
+            .max(1)
+    }
+
     /// This orchestrator's skill context mode (default full).
     pub fn context_mode(&self) -> SkillContextMode {
         self.context_mode.unwrap_or_default()
@@ -551,6 +1068,13 @@ impl OrchestratorConfig {
     pub fn enabled_workers_list(&self) -> &[String] {
         self.enabled_workers.as_deref().unwrap_or(&[])
     }
+
+    /// Whether the workflow `id` is offered to this orchestrator (`enabledWorkflows`).
+    pub fn workflow_enabled(&self, id: &str) -> bool {
+        self.enabled_workflows
+            .as_ref()
+            .is_some_and(|ids| ids.is_empty() || ids.iter().any(|w| w == id))
+    }
 }
 
 /// Resolved agents configuration: orchestrator entries plus optional worker presets for `delegate_task`.
@@ -619,6 +1143,8 @@ struct AgentDefinition {
     #[serde(default)]
     enabled_workers: Option<Vec<String>>,
     #[serde(default)]
+    enabled_workflows: Option<Vec<String>>,
+    #[serde(default)]
     context_mode: Option<SkillContextMode>,
     #[serde(default)]
     max_delegations_per_turn: Option<usize>,
@@ -628,6 +1154,26 @@ struct AgentDefinition {
     max_delegations_per_worker: Option<HashMap<String, usize>>,
     #[serde(default)]
     max_tool_loops_per_turn: Option<u32>,
+    #[serde(default)]
+    max_parallel_workflows: Option<usize>,
+    #[serde(default)]
+    max_delegation_depth: Option<usize>,
+    #[serde(default)]
+    approval_policy: Option<ApprovalPolicy>,
+    #[serde(default)]
+    output_schema: Option<serde_json::Value>,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+    #[serde(default)]
+    retries: Option<u32>,
+    #[serde(default)]
+    fallback: Option<Vec<WorkerFallback>>,
+    #[serde(default)]
+    sandbox: Option<AgentSandboxConfig>,
+    #[serde(default)]
+    model_routing: Option<ModelRouting>,
+    #[serde(default)]
+    async_delegation: Option<bool>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -649,11 +1195,22 @@ fn agents_to_definitions(agents: &AgentsConfig) -> Vec<AgentDefinition> {
             enabled_providers: o.enabled_providers.clone(),
             enabled_skills: o.enabled_skills.clone(),
             enabled_workers: o.enabled_workers.clone(),
+            enabled_workflows: o.enabled_workflows.clone(),
             context_mode: o.context_mode,
             max_delegations_per_turn: o.max_delegations_per_turn,
             max_delegations_per_session: o.max_delegations_per_session,
             max_delegations_per_worker: o.max_delegations_per_worker.clone(),
             max_tool_loops_per_turn: o.max_tool_loops_per_turn,
+            max_parallel_workflows: o.max_parallel_workflows,
+            max_delegation_depth: o.max_delegation_depth,
+            approval_policy: o.approval_policy,
+            output_schema: None,
+            timeout_secs: None,
+            retries: None,
+            fallback: None,
+            sandbox: o.sandbox.clone(),
+            model_routing: o.model_routing,
+            async_delegation: o.async_delegation,
         })
         .collect();
     if let Some(ws) = &agents.workers {
@@ -665,12 +1222,23 @@ fn agents_to_definitions(agents: &AgentsConfig) -> Vec<AgentDefinition> {
                 default_model: w.default_model.clone(),
                 enabled_providers: None,
                 enabled_skills: w.enabled_skills.clone(),
-                enabled_workers: None,
+                enabled_workers: w.enabled_workers.clone(),
+                enabled_workflows: None,
                 context_mode: w.context_mode,
                 max_delegations_per_turn: None,
                 max_delegations_per_session: None,
                 max_delegations_per_worker: None,
                 max_tool_loops_per_turn: None,
+                max_parallel_workflows: None,
+                max_delegation_depth: None,
+                approval_policy: w.approval_policy,
+                output_schema: w.output_schema.clone(),
+                timeout_secs: w.timeout_secs,
+                retries: w.retries,
+                fallback: w.fallback.clone(),
+                sandbox: w.sandbox.clone(),
+                model_routing: w.model_routing,
+                async_delegation: None,
             });
         }
     }
@@ -690,9 +1258,32 @@ fn agents_from_array(entries: Vec<AgentDefinition>) -> Result<AgentsConfig, Stri
         if !seen.insert(id.clone()) {
             return Err(format!("duplicate agent id: {id}"));
         }
+        if let Some(ref sandbox) = e.sandbox {
+            sandbox.validate(&id)?;
+        }
 
         match e.role {
             AgentRole::Orchestrator => {
+                if e.output_schema.is_some() {
+                    return Err(format!(
+                        "orchestrator \"{id}\" has \"outputSchema\" — this field is worker-only"
+                    ));
+                }
+                if e.timeout_secs.is_some() {
+                    return Err(format!(
+                        "orchestrator \"{id}\" has \"timeoutSecs\" — this field is worker-only"
+                    ));
+                }
+                if e.retries.is_some() {
+                    return Err(format!(
+                        "orchestrator \"{id}\" has \"retries\" — this field is worker-only"
+                    ));
+                }
+                if e.fallback.is_some() {
+                    return Err(format!(
+                        "orchestrator \"{id}\" has \"fallback\" — this field is worker-only"
+                    ));
+                }
                 orchestrator_rows.push(OrchestratorConfig {
                     id,
                     default_provider: e.default_provider,
@@ -700,11 +1291,18 @@ fn agents_from_array(entries: Vec<AgentDefinition>) -> Result<AgentsConfig, Stri
                     enabled_providers: e.enabled_providers,
                     enabled_skills: e.enabled_skills,
                     enabled_workers: e.enabled_workers,
+                    enabled_workflows: e.enabled_workflows,
                     context_mode: e.context_mode,
                     max_delegations_per_turn: e.max_delegations_per_turn,
                     max_delegations_per_session: e.max_delegations_per_session,
                     max_delegations_per_worker: e.max_delegations_per_worker,
                     max_tool_loops_per_turn: e.max_tool_loops_per_turn,
+                    max_parallel_workflows: e.max_parallel_workflows,
+                    max_delegation_depth: e.max_delegation_depth,
+                    approval_policy: e.approval_policy,
+                    sandbox: e.sandbox,
+                    model_routing: e.model_routing,
+                    async_delegation: e.async_delegation,
                 });
             }
             AgentRole::Worker => {
@@ -718,13 +1316,6 @@ fn agents_from_array(entries: Vec<AgentDefinition>) -> Result<AgentsConfig, Stri
                         ));
                     }
                 }
-                if let Some(ref v) = e.enabled_workers {
-                    if !v.is_empty() {
-                        return Err(format!(
-                            "worker \"{id}\" has \"enabledWorkers\" — this field is orchestrator-only"
-                        ));
-                    }
-                }
                 if e.max_delegations_per_turn.is_some() {
                     return Err(format!(
                         "worker \"{id}\" has \"maxDelegationsPerTurn\" — this field is orchestrator-only"
@@ -742,17 +1333,67 @@ fn agents_from_array(entries: Vec<AgentDefinition>) -> Result<AgentsConfig, Stri
                         ));
                     }
                 }
+                if e.max_parallel_workflows.is_some() {
+                    return Err(format!(
+                        "worker \"{id}\" has \"maxParallelWorkflows\" — this field is orchestrator-only"
+                    ));
+                }
+                if e.enabled_workflows.is_some() {
+                    return Err(format!(
+                        "worker \"{id}\" has \"enabledWorkflows\" — this field is orchestrator-only"
+                    ));
+                }
+                if e.async_delegation.is_some() {
+                    return Err(format!(
+                        "worker \"{id}\" has \"asyncDelegation\" — this field is orchestrator-only"
+                    ));
+                }
+                if e.max_delegation_depth.is_some() {
+                    return Err(format!(
+                        "worker \"{id}\" has \"maxDelegationDepth\" — this field is orchestrator-only (applies to the whole delegation tree)"
+                    ));
+                }
                 if e.max_tool_loops_per_turn.is_some() {
                     return Err(format!(
                         "worker \"{id}\" has \"maxToolLoopsPerTurn\" — this field is orchestrator-only (applies globally to both orchestrator and worker turns)"
                     ));
                 }
+                if let Some(ref schema) = e.output_schema {
+                    if !schema.is_object() {
+                        return Err(format!(
+                            "worker \"{id}\" has an \"outputSchema\" that is not a JSON object"
+                        ));
+                    }
+                }
+                if e.timeout_secs == Some(0) {
+                    return Err(format!("worker \"{id}\" has \"timeoutSecs\": 0 — omit it for no timeout"));
+                }
+                for f in e.fallback.iter().flatten() {
+                    if f.worker_id.is_none() && f.provider.is_none() && f.model.is_none() {
+                        return Err(format!(
+                            "worker \"{id}\" has a \"fallback\" entry without workerId, provider, or model"
+                        ));
+                    }
+                    if f.worker_id.as_deref() == Some(id.as_str()) {
+                        return Err(format!(
+                            "worker \"{id}\" lists itself as a fallback workerId — omit workerId to retry it on another provider or model"
+                        ));
+                    }
+                }
                 worker_rows.push(WorkerConfig {
                     id,
                     default_provider: e.default_provider,
                     default_model: e.default_model,
                     enabled_skills: e.enabled_skills,
+                    enabled_workers: e.enabled_workers,
                     context_mode: e.context_mode,
+                    approval_policy: e.approval_policy,
+                    output_schema: e.output_schema,
+                    timeout_secs: e.timeout_secs,
+                    retries: e.retries,
+                    fallback: e.fallback,
+                    sandbox: e.sandbox,
+                    model_routing: e.model_routing,
                 });
             }
         }
@@ -778,6 +1419,26 @@ fn agents_from_array(entries: Vec<AgentDefinition>) -> Result<AgentsConfig, Stri
             }
         }
     }
+    for w in &worker_rows {
+        for wid in w.fallback.iter().flatten().filter_map(|f| f.worker_id.as_deref()) {
+            if !worker_ids.contains(wid) {
+                return Err(format!(
+                    "worker \"{}\" references unknown worker id \"{}\" in fallback",
+                    w.id, wid
+                ));
+            }
+        }
+        if let Some(ref ew) = w.enabled_workers {
+            for wid in ew {
+                if !worker_ids.contains(wid.as_str()) {
+                    return Err(format!(
+                        "worker \"{}\" references unknown worker id \"{}\" in enabledWorkers",
+                        w.id, wid
+                    ));
+                }
+            }
+        }
+    }
 
     Ok(AgentsConfig {
         orchestrators: orchestrator_rows,
@@ -833,9 +1494,61 @@ pub struct WorkerConfig {
     /// Skill package names enabled for this worker. Omitted or empty ⇒ no skills.
     #[serde(default)]
     pub enabled_skills: Option<Vec<String>>,
+    /// Workers this worker may delegate to (nested delegation). Omitted ⇒ none; empty array ⇒ all
+    /// workers. Only offered while the orchestrator's `maxDelegationDepth` allows another level.
+    #[serde(default)]
+    pub enabled_workers: Option<Vec<String>>,
     /// How this worker's skill docs are inlined vs `read_skill`.
     #[serde(default)]
     pub context_mode: Option<SkillContextMode>,
+    /// Overrides the profile `approval.policy` for this worker's tool calls during delegation.
+    #[serde(default)]
+    pub approval_policy: Option<ApprovalPolicy>,
+    /// JSON Schema the worker's final reply must satisfy. When set, the worker is asked to end
+    /// with a JSON value, the reply is validated (with retries), and the orchestrator receives
+    /// the parsed value instead of free text.
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
+    /// Wall-clock limit in seconds for one attempt of a delegation to this worker (the whole
+    /// worker turn, including tool calls). Omitted ⇒ no limit.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Extra attempts on this worker after an attempt fails (provider error, timeout, or a reply
+    /// that does not match `outputSchema`). Omitted ⇒ `0`.
+    #[serde(default)]
+    pub retries: Option<u32>,
+    /// Alternates tried in order, once each, after this worker's attempts are used up.
+    #[serde(default)]
+    pub fallback: Option<Vec<WorkerFallback>>,
+    /// Restricts this worker's tools to part of the profile sandbox. Omitted ⇒ whole sandbox.
+    #[serde(default)]
+    pub sandbox: Option<AgentSandboxConfig>,
+    /// How the model for delegations to this worker is chosen. Omitted ⇒ static.
+    #[serde(default)]
+    pub model_routing: Option<ModelRouting>,
+}
+
+impl WorkerConfig {
+    /// Attempts on this worker before moving to `fallback` (`1 + retries`).
+    pub fn attempts(&self) -> usize {
+        1 + self.retries.unwrap_or(0) as usize
+    }
+}
+
+/// One `fallback` entry on a worker: another worker, and/or another provider and model.
+/// Without `workerId` the same worker reruns on the given provider/model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerFallback {
+    /// Worker to delegate to instead. Must be a configured worker other than this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker_id: Option<String>,
+    /// Provider override; must be in the orchestrator's `enabledProviders`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Model override. Omitted ⇒ the (fallback) worker's `defaultModel` resolution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 /// Per-provider configuration: JSON array of provider definitions with `id`, `endpointType` type, and connection settings.
@@ -865,6 +1578,8 @@ fn default_providers_config() -> ProvidersConfig {
             default_model: None,
             model_discovery: ModelDiscovery::Auto,
             static_models: Vec::new(),
+            tier: None,
+            model_tiers: Vec::new(),
         }],
     }
 }
@@ -989,6 +1704,20 @@ pub struct ProviderDefinition {
     /// Static model list used when `modelDiscovery: "static"`. No polling.
     #[serde(default)]
     pub static_models: Vec<String>,
+    /// Capability tier of this provider's default model, for agents with `modelRouting: "tier"`.
+    #[serde(default)]
+    pub tier: Option<CapabilityTier>,
+    /// Models of this provider offered to tier routing, cheapest first.
+    #[serde(default)]
+    pub model_tiers: Vec<ModelTier>,
+}
+
+/// One `modelTiers` entry: a model of the provider and the capability tier it satisfies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTier {
+    pub model: String,
+    pub tier: CapabilityTier,
 }
 
 impl ProvidersConfig {
@@ -1038,6 +1767,27 @@ pub fn resolve_provider_base_url(providers: &ProvidersConfig, id: &str) -> Optio
     base.map(|s| s.trim_end_matches('/').to_string())
 }
 
+/// True when the provider's base URL points at a loopback host (`localhost`, `127.0.0.0/8`,
+/// `[::1]`). Unknown providers and unparsable URLs count as not local.
+pub fn provider_is_local(providers: &ProvidersConfig, id: &str) -> bool {
+    let Some(base) = resolve_provider_base_url(providers, id) else {
+        return false;
+    };
+    let rest = base.split_once("://").map_or(base.as_str(), |(_, r)| r);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let host = if let Some(v6) = host_port.strip_prefix('[') {
+        v6.split(']').next().unwrap_or("")
+    } else {
+        host_port.split(':').next().unwrap_or("")
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .map(|ip| ip.is_loopback())
+            .unwrap_or(false)
+}
+
 /// Resolve the API key for a provider. If the `apiKey` config value uses the `<VAR_NAME>`
 /// syntax, the named environment variable is read at runtime. Literal key strings are returned
 /// as-is. Returns `None` when the provider is not found, `apiKey` is unset, the env var
@@ -1251,6 +2001,19 @@ pub fn sessions_dir(profile_dir: &Path, agent_id: &str) -> PathBuf {
     agent_context_dir(profile_dir, agent_id).join("sessions")
 }
 
+/// Private scratch directory for an agent with `sandbox.scratch`: `<profile_dir>/agents/<agent_id>/scratch/`.
+pub fn scratch_dir(profile_dir: &Path, agent_id: &str) -> PathBuf {
+    agent_context_dir(profile_dir, agent_id).join("scratch")
+}
+
+/// Expand a leading `~/` to the home directory; other paths are returned unchanged.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
 /// Orchestrator skill context mode (default full). Uses the default (first) orchestrator.
 pub fn orchestrator_context_mode(agents: &AgentsConfig) -> SkillContextMode {
     agents.default_orchestrator().context_mode()
@@ -1566,6 +2329,36 @@ mod tests {
         assert_eq!(c.sandbox.mode, SandboxMode::Unsafe);
     }
 
+    #[test]
+    fn sandbox_kernel_landlock_builds_policy() {
+        let c = Config::default();
+        assert_eq!(c.sandbox.kernel, KernelSandbox::None);
+        let dir = tempfile::tempdir().expect("tempdir");
+        let sandbox = crate::exec::WriteSandbox::new(dir.path());
+        assert!(c.sandbox.kernel_policy(&sandbox).is_none());
+
+        let j = r#"{"sandbox":{"kernel":"landlock","readPaths":["/srv/docs"],"denyTcpConnect":true}}"#;
+        let c: Config = serde_json::from_str(j).expect("parse");
+        assert_eq!(c.sandbox.kernel, KernelSandbox::Landlock);
+        let policy = c.sandbox.kernel_policy(&sandbox).unwrap();
+        assert_eq!(policy.writable[0], sandbox.roots()[0]);
+        assert!(policy.writable.contains(&std::env::temp_dir()));
+        assert!(policy.readable.contains(&PathBuf::from("/srv/docs")));
+        assert!(policy.readable.contains(&PathBuf::from("/usr")));
+        assert!(policy.deny_tcp_connect);
+    }
+
+    #[test]
+    fn sandbox_quota_parses() {
+        let c = Config::default();
+        assert!(!c.sandbox.quota.is_set());
+        let j = r#"{"sandbox":{"quota":{"maxBytes":1048576}}}"#;
+        let c: Config = serde_json::from_str(j).expect("parse");
+        assert!(c.sandbox.quota.is_set());
+        assert_eq!(c.sandbox.quota.max_bytes, Some(1_048_576));
+        assert_eq!(c.sandbox.quota.max_files, None);
+    }
+
     #[test]
     fn sandbox_mode_as_str() {
         assert_eq!(SandboxMode::Strict.as_str(), "strict");
@@ -1602,7 +2395,15 @@ mod tests {
             default_provider: None,
             default_model: None,
             enabled_skills: None,
+            enabled_workers: None,
             context_mode: None,
+            approval_policy: None,
+            output_schema: None,
+            timeout_secs: None,
+            retries: None,
+            fallback: None,
+            sandbox: None,
+            model_routing: None,
         };
         assert_eq!(
             worker_context_dir(&w, prof),
@@ -1779,6 +2580,188 @@ mod tests {
         );
     }
 
+    #[test]
+    fn agents_worker_rejects_max_parallel_workflows() {
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator"},
+            {"id":"fast","role":"worker","maxParallelWorkflows":2}
+        ]}"#;
+        let err = serde_json::from_str::<Config>(j).unwrap_err();
+        assert!(
+            err.to_string().contains("maxParallelWorkflows") && err.to_string().contains("orchestrator-only"),
+            "unexpected: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn model_tiers_and_routing_parse() {
+        let j = r#"{"providers":[
+            {"id":"ollama","endpointType":"ollama","tier":"minimal",
+             "modelTiers":[{"model":"qwen2.5:14b","tier":"moderate"}]}
+        ],"agents":[
+            {"id":"orchestrator","role":"orchestrator","modelRouting":"tier"},
+            {"id":"coder","role":"worker","modelRouting":"static"}
+        ]}"#;
+        let c: Config = serde_json::from_str(j).expect("parse");
+        let p = c.providers.get("ollama").unwrap();
+        assert_eq!(p.tier, Some(CapabilityTier::Minimal));
+        assert_eq!(p.model_tiers[0].tier, CapabilityTier::Moderate);
+        assert_eq!(c.agents.default_orchestrator().model_routing, Some(ModelRouting::Tier));
+        let w = &c.agents.workers.as_ref().unwrap()[0];
+        assert_eq!(w.model_routing, Some(ModelRouting::Static));
+        assert!(CapabilityTier::Full > CapabilityTier::Moderate);
+        assert_eq!(CapabilityTier::parse(" Full "), Some(CapabilityTier::Full));
+        assert!(serde_json::from_str::<Config>(r#"{"providers":[{"id":"x","endpointType":"ollama","tier":"huge"}]}"#).is_err());
+    }
+
+    #[test]
+    fn agent_sandbox_subset_parses_and_validates_names() {
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator"},
+            {"id":"research","role":"worker","sandbox":{"readOnly":["notes"],"scratch":true}}
+        ]}"#;
+        let c: Config = serde_json::from_str(j).unwrap();
+        let sb = c.agents.workers.as_ref().unwrap()[0].sandbox.as_ref().unwrap();
+        assert_eq!(sb.read_only, vec!["notes"]);
+        assert!(sb.writable.is_empty() && sb.scratch);
+        assert!(c.agents.default_orchestrator().sandbox.is_none());
+
+        for (sandbox, needle) in [
+            (r#"{"writable":["../etc"]}"#, "direct children"),
+            (r#"{"writable":["repo"],"readOnly":["repo"]}"#, "more than once"),
+            (r#"{"writable":["ro.reference"]}"#, "list it under readOnly"),
+            (r#"{}"#, "grants nothing"),
+        ] {
+            let j = format!(r#"{{"agents":[{{"id":"main","role":"orchestrator","sandbox":{sandbox}}}]}}"#);
+            let err = serde_json::from_str::<Config>(&j).unwrap_err();
+            assert!(err.to_string().contains(needle), "unexpected: {}", err);
+        }
+    }
+
+    #[test]
+    fn workflows_parse_and_enabled_workflows_gate() {
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator","enabledWorkflows":["triage"]},
+            {"id":"all","role":"orchestrator","enabledWorkflows":[]},
+            {"id":"none","role":"orchestrator"},
+            {"id":"coder","role":"worker"}
+        ],"workflows":[{"id":"triage","steps":[
+            {"id":"a","workerId":"coder","instruction":"x","when":{"ref":"inputs.flag","notEquals":"no"}}
+        ]}]}"#;
+        let c: Config = serde_json::from_str(j).unwrap();
+        let step = &c.workflows[0].steps[0];
+        assert!(step.depends_on.is_empty());
+        assert_eq!(step.when.as_ref().unwrap().reference, "inputs.flag");
+        let enabled = |o: &str, w: &str| c.agents.orchestrator(Some(o)).unwrap().workflow_enabled(w);
+        assert!(enabled("main", "triage") && !enabled("main", "other"));
+        assert!(enabled("all", "other"));
+        assert!(!enabled("none", "triage"));
+
+        let j = r#"{"agents":[{"id":"main","role":"orchestrator"},{"id":"w","role":"worker","enabledWorkflows":[]}]}"#;
+        let err = serde_json::from_str::<Config>(j).unwrap_err();
+        assert!(err.to_string().contains("enabledWorkflows"), "unexpected: {}", err);
+    }
+
+    #[test]
+    fn async_delegation_is_orchestrator_only() {
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator","asyncDelegation":true},
+            {"id":"other","role":"orchestrator"}
+        ]}"#;
+        let c: Config = serde_json::from_str(j).expect("parse");
+        assert!(c.agents.orchestrator(Some("main")).unwrap().async_delegation());
+        assert!(!c.agents.orchestrator(Some("other")).unwrap().async_delegation());
+
+        let j = r#"{"agents":[{"id":"main","role":"orchestrator"},{"id":"w","role":"worker","asyncDelegation":true}]}"#;
+        let err = serde_json::from_str::<Config>(j).unwrap_err();
+        assert!(err.to_string().contains("asyncDelegation"), "unexpected: {}", err);
+    }
+
+    #[test]
+    fn exec_limits_prefer_spec_then_profile_then_default() {
+        use std::time::Duration;
+        let c: Config = serde_json::from_str(r#"{"exec":{"timeoutSecs":60,"maxOutputBytes":0}}"#).expect("parse");
+        let limits = c.exec.limits_for(None, None, None);
+        assert_eq!(limits.timeout, Some(Duration::from_secs(60)));
+        assert_eq!(limits.max_output_bytes, None);
+        let limits = c.exec.limits_for(Some(0), Some(4096), None);
+        assert_eq!(limits.timeout, None);
+        assert_eq!(limits.max_output_bytes, Some(4096));
+        let limits = Config::default().exec.limits_for(None, None, None);
+        assert_eq!(limits.timeout, Some(Duration::from_secs(DEFAULT_EXEC_TIMEOUT_SECS)));
+        assert_eq!(limits.max_output_bytes, Some(DEFAULT_EXEC_MAX_OUTPUT_BYTES));
+    }
+
+    #[test]
+    fn exec_resource_limits_merge_per_field() {
+        let c: Config =
+            serde_json::from_str(r#"{"exec":{"limits":{"cpuSecs":60,"openFiles":256}}}"#).expect("parse");
+        let spec = crate::exec::ResourceLimits {
+            cpu_secs: Some(5),
+            file_size_bytes: Some(1 << 20),
+            ..Default::default()
+        };
+        let resources = c.exec.limits_for(None, None, Some(&spec)).resources;
+        assert_eq!(resources.cpu_secs, Some(5));
+        assert_eq!(resources.file_size_bytes, Some(1 << 20));
+        assert_eq!(resources.open_files, Some(256));
+        assert_eq!(resources.processes, None);
+        assert!(Config::default().exec.limits_for(None, None, None).resources.is_empty());
+    }
+
+    #[test]
+    fn exec_env_parses_and_combines_with_spec() {
+        let c: Config = serde_json::from_str(r#"{"exec":{"env":{"pass":["SSH_AUTH_SOCK"],"set":{"NO_COLOR":"1"}}}}"#)
+            .expect("parse");
+        assert_eq!(c.exec.env_for(None), c.exec.env);
+        let spec: crate::exec::EnvPolicy = serde_json::from_str(r#"{"pass":["GIT_SSH_COMMAND"]}"#).expect("parse");
+        let env = c.exec.env_for(Some(&spec));
+        assert_eq!(env.pass, vec!["SSH_AUTH_SOCK", "GIT_SSH_COMMAND"]);
+        assert_eq!(env.set["NO_COLOR"], "1");
+        assert_eq!(Config::default().exec.env_for(None), crate::exec::EnvPolicy::default());
+    }
+
+    #[test]
+    fn redaction_scope_defaults_to_non_local_providers() {
+        let j = r#"{
+            "providers":[
+                {"id":"ollama","endpointType":"ollama"},
+                {"id":"lms","endpointType":"openai-compat","baseUrl":"http://localhost:1234/v1"},
+                {"id":"v6","endpointType":"openai-compat","baseUrl":"http://[::1]:8000/v1"},
+                {"id":"nim","endpointType":"openai-compat","baseUrl":"https://integrate.api.nvidia.com/v1"}
+            ],
+            "redaction":{"rules":["email",{"name":"ticket","pattern":"TCK-[0-9]+"}]}
+        }"#;
+        let c: Config = serde_json::from_str(j).expect("parse");
+        assert_eq!(c.redaction.rules.len(), 2);
+        assert!(provider_is_local(&c.providers, "ollama"));
+        assert!(provider_is_local(&c.providers, "lms"));
+        assert!(provider_is_local(&c.providers, "v6"));
+        assert!(!provider_is_local(&c.providers, "nim"));
+        assert_eq!(c.redaction.providers_in_scope(&c.providers), vec!["nim"]);
+
+        let mut explicit = c.redaction.clone();
+        explicit.providers = Some(vec!["lms".to_string()]);
+        assert_eq!(explicit.providers_in_scope(&c.providers), vec!["lms"]);
+        explicit.providers = Some(vec![]);
+        assert!(explicit.providers_in_scope(&c.providers).is_empty());
+    }
+
+    #[test]
+    fn orchestrator_max_parallel_workflows_defaults_and_floor() {
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator"},
+            {"id":"serial","role":"orchestrator","maxParallelWorkflows":1},
+            {"id":"zero","role":"orchestrator","maxParallelWorkflows":0}
+        ]}"#;
+        let c: Config = serde_json::from_str(j).expect("parse");
+        let parallel = |id: &str| c.agents.orchestrator(Some(id)).unwrap().max_parallel_workflows();
+        assert_eq!(parallel("main"), DEFAULT_MAX_PARALLEL_WORKFLOWS);
+        assert_eq!(parallel("serial"), 1);
+        assert_eq!(parallel("zero"), 1);
+    }
+
     #[test]
     fn agents_worker_with_valid_fields_passes() {
         let j = r#"{"agents":[
@@ -1794,17 +2777,102 @@ mod tests {
     }
 
     #[test]
-    fn agents_worker_rejects_enabled_workers() {
+    fn agents_worker_enabled_workers_for_nested_delegation() {
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator","enabledWorkers":["lead"],"maxDelegationDepth":2},
+            {"id":"lead","role":"worker","enabledWorkers":["other"]},
+            {"id":"other","role":"worker"}
+        ]}"#;
+        let c: Config = serde_json::from_str(j).expect("parse");
+        let w = c.agents.workers.as_ref().expect("workers");
+        assert_eq!(w[0].enabled_workers.as_deref(), Some(&["other".to_string()][..]));
+        assert!(w[1].enabled_workers.is_none());
+        assert_eq!(c.agents.default_orchestrator().max_delegation_depth(), 2);
+
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator"},
+            {"id":"lead","role":"worker","enabledWorkers":["missing"]}
+        ]}"#;
+        let err = serde_json::from_str::<Config>(j).unwrap_err();
+        assert!(err.to_string().contains("unknown worker id \"missing\""), "unexpected: {}", err);
+    }
+
+    #[test]
+    fn agents_worker_rejects_max_delegation_depth() {
         let j = r#"{"agents":[
             {"id":"main","role":"orchestrator"},
-            {"id":"fast","role":"worker","enabledWorkers":["other"]}
+            {"id":"fast","role":"worker","maxDelegationDepth":2}
         ]}"#;
         let err = serde_json::from_str::<Config>(j).unwrap_err();
         assert!(
-            err.to_string().contains("enabledWorkers") && err.to_string().contains("orchestrator-only"),
+            err.to_string().contains("maxDelegationDepth") && err.to_string().contains("orchestrator-only"),
             "unexpected: {}",
             err
         );
+        assert_eq!(OrchestratorConfig::default().max_delegation_depth(), DEFAULT_MAX_DELEGATION_DEPTH);
+    }
+
+    #[test]
+    fn agents_worker_output_schema() {
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator"},
+            {"id":"reviewer","role":"worker","outputSchema":{"type":"object","required":["verdict"]}}
+        ]}"#;
+        let c: Config = serde_json::from_str(j).unwrap();
+        let schema = c.agents.workers.as_ref().unwrap()[0].output_schema.as_ref().unwrap();
+        assert_eq!(schema["required"][0], "verdict");
+
+        let j = r#"{"agents":[{"id":"main","role":"orchestrator","outputSchema":{"type":"object"}}]}"#;
+        let err = serde_json::from_str::<Config>(j).unwrap_err();
+        assert!(err.to_string().contains("worker-only"), "unexpected: {}", err);
+
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator"},
+            {"id":"reviewer","role":"worker","outputSchema":"object"}
+        ]}"#;
+        let err = serde_json::from_str::<Config>(j).unwrap_err();
+        assert!(err.to_string().contains("not a JSON object"), "unexpected: {}", err);
+    }
+
+    #[test]
+    fn agents_worker_timeout_retries_fallback() {
+        let j = r#"{"agents":[
+            {"id":"main","role":"orchestrator"},
+            {"id":"coder","role":"worker","timeoutSecs":120,"retries":2,
+             "fallback":[{"workerId":"spare"},{"provider":"ollama","model":"qwen3:8b"}]},
+            {"id":"spare","role":"worker"}
+        ]}"#;
+        let c: Config = serde_json::from_str(j).unwrap();
+        let coder = &c.agents.workers.as_ref().unwrap()[0];
+        assert_eq!(coder.timeout_secs, Some(120));
+        assert_eq!(coder.attempts(), 3);
+        let fallback = coder.fallback.as_ref().unwrap();
+        assert_eq!(fallback[0].worker_id.as_deref(), Some("spare"));
+        assert_eq!(fallback[1].model.as_deref(), Some("qwen3:8b"));
+        assert_eq!(c.agents.workers.as_ref().unwrap()[1].attempts(), 1);
+
+        for (j, expected) in [
+            (r#"{"agents":[{"id":"main","role":"orchestrator","retries":1}]}"#, "worker-only"),
+            (
+                r#"{"agents":[{"id":"main","role":"orchestrator"},{"id":"w","role":"worker","timeoutSecs":0}]}"#,
+                "timeoutSecs",
+            ),
+            (
+                r#"{"agents":[{"id":"main","role":"orchestrator"},{"id":"w","role":"worker","fallback":[{}]}]}"#,
+                "without workerId",
+            ),
+            (
+                r#"{"agents":[{"id":"main","role":"orchestrator"},{"id":"w","role":"worker","fallback":[{"workerId":"w"}]}]}"#,
+                "lists itself",
+            ),
+            (
+                r#"{"agents":[{"id":"main","role":"orchestrator"},{"id":"w","role":"worker","fallback":[{"workerId":"x"}]}]}"#,
+                "unknown worker id \"x\" in fallback",
+            ),
+        ] {
+            let err = serde_json::from_str::<Config>(j).unwrap_err();
+            assert!(err.to_string().contains(expected), "unexpected: {}", err);
+        }
     }
 
     #[test]
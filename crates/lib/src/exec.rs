@@ -3,10 +3,29 @@
 //!
 //! Also provides `WriteSandbox` for path boundary enforcement: validates that
 //! write-target arguments fall within per-profile writable roots before execution.
+//!
+//! Commands can be bounded with [`ExecLimits`]: a timeout that kills the command's whole process
+//! group, and a cap on the output bytes kept in memory (output is read as it is produced, so a
+//! command printing gigabytes never buffers more than the cap). On Linux, [`ResourceLimits`] are
+//! also set on the child with `setrlimit` before it executes the binary.
+//!
+//! With `sandbox.kernel: "landlock"`, a [`KernelPolicy`] built from the sandbox roots is also
+//! enforced by the kernel on each command, so writes a tool's binary makes on its own (outside the
+//! validated arguments) cannot leave the sandbox.
+//!
+//! Commands never inherit the gateway's environment, which holds the gateway token and provider
+//! and channel credentials; an [`EnvPolicy`] decides what they see.
 
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
 
 /// Environment variable that overrides the `chai` binary path used by the
 /// allowlist executor. When set, any tool execution that references the
@@ -42,13 +61,20 @@ pub fn resolve_binary(binary: &str) -> String {
 /// (e.g. `nix develop --command`) that determines *how* the binary is invoked,
 /// not *what* is invoked — the allowlist still validates the declared binary
 /// and subcommand.
+///
+/// Non-empty `resources` are applied to the child between fork and exec on Linux (see
+/// [`ResourceLimits`]); they also bind the wrapper, as does `kernel` (see [`KernelPolicy`]).
+/// The environment is replaced by the one `env` describes (see [`EnvPolicy`]).
 fn build_command(
     resolved: &str,
     subcommand: &str,
     args: &[String],
     binary_wrapper: Option<&[String]>,
-) -> Command {
-    match binary_wrapper {
+    resources: &ResourceLimits,
+    kernel: Option<&KernelPolicy>,
+    env: &EnvPolicy,
+) -> Result<Command, String> {
+    let mut cmd = match binary_wrapper {
         Some(wrapper) => {
             let mut cmd = Command::new(&wrapper[0]);
             cmd.args(&wrapper[1..]);
@@ -63,9 +89,751 @@ fn build_command(
             cmd.args(args);
             cmd
         }
+    };
+    env.apply_to(&mut cmd);
+    if !resources.is_empty() {
+        resources.apply_to(&mut cmd);
+    }
+    if let Some(policy) = kernel {
+        policy.apply_to(&mut cmd)?;
+    }
+    Ok(cmd)
+}
+
+/// Kernel resource limits for a tool command (`limits` on an execution spec or under the profile's
+/// `exec`). Each set field becomes both the soft and hard `setrlimit` value of the child, capped at
+/// the gateway's own hard limit. Only applied on Linux; elsewhere they are ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    /// CPU time in seconds (`RLIMIT_CPU`); the command is killed when it uses more.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_secs: Option<u64>,
+    /// Virtual address space in bytes (`RLIMIT_AS`); allocations past it fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_space_bytes: Option<u64>,
+    /// Largest file the command may write, in bytes (`RLIMIT_FSIZE`); larger writes fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_size_bytes: Option<u64>,
+    /// Open file descriptors (`RLIMIT_NOFILE`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u64>,
+    /// Processes (`RLIMIT_NPROC`). The kernel counts every process of the gateway's user, not
+    /// only the command's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processes: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Each field from `self`, else from `fallback` (spec limits over profile limits).
+    pub fn or(self, fallback: &Self) -> Self {
+        Self {
+            cpu_secs: self.cpu_secs.or(fallback.cpu_secs),
+            address_space_bytes: self.address_space_bytes.or(fallback.address_space_bytes),
+            file_size_bytes: self.file_size_bytes.or(fallback.file_size_bytes),
+            open_files: self.open_files.or(fallback.open_files),
+            processes: self.processes.or(fallback.processes),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn apply_to(&self, cmd: &mut Command) {
+        use std::os::unix::process::CommandExt;
+        let limits = *self;
+        // SAFETY: the hook runs in the forked child before exec and only calls getrlimit and
+        // setrlimit, which are async-signal-safe; it does not allocate or take locks.
+        unsafe {
+            cmd.pre_exec(move || limits.set_current());
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn apply_to(&self, _cmd: &mut Command) {
+        log::debug!("exec: resource limits are only applied on Linux");
+    }
+
+    /// Set the limits on the calling process (the child, between fork and exec).
+    #[cfg(target_os = "linux")]
+    fn set_current(&self) -> std::io::Result<()> {
+        let limits = [
+            (libc::RLIMIT_CPU, self.cpu_secs),
+            (libc::RLIMIT_AS, self.address_space_bytes),
+            (libc::RLIMIT_FSIZE, self.file_size_bytes),
+            (libc::RLIMIT_NOFILE, self.open_files),
+            (libc::RLIMIT_NPROC, self.processes),
+        ];
+        for (resource, value) in limits {
+            let Some(value) = value else {
+                continue;
+            };
+            let mut current = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            // SAFETY: `current` is a valid, writable rlimit for the duration of the call.
+            if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            // An unprivileged process cannot raise its hard limit, so stay at or below it.
+            let value = (value as libc::rlim_t).min(current.rlim_max);
+            let wanted = libc::rlimit {
+                rlim_cur: value,
+                rlim_max: value,
+            };
+            // SAFETY: `wanted` is a valid rlimit for the duration of the call.
+            if unsafe { libc::setrlimit(resource, &wanted) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Variables every tool command gets from the gateway's environment (when set there): enough to
+/// find binaries, locate the home and Chai directories, pick a locale, and put temp files where
+/// the gateway (and its kernel policy) expects them.
+pub const BASELINE_ENV: &[&str] = &["PATH", "HOME", "LANG", "LC_ALL", "TMPDIR", "CHAI_HOME"];
+
+/// Environment of a tool command (`env` on an execution spec or under the profile's `exec`).
+/// The command starts from an empty environment and gets [`BASELINE_ENV`], the gateway variables
+/// named in `pass`, and the fixed `set` values, which win over passed values of the same name.
+/// The default is the baseline alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvPolicy {
+    /// Names of further gateway variables passed through when set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pass: Vec<String>,
+    /// Variables set to fixed values.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub set: BTreeMap<String, String>,
+}
+
+impl EnvPolicy {
+    /// `self` over `fallback` (spec env over profile env): `pass` lists are combined and `set`
+    /// values from `self` override those of `fallback`.
+    pub fn or(&self, fallback: &Self) -> Self {
+        let mut pass = fallback.pass.clone();
+        pass.extend(self.pass.iter().filter(|n| !fallback.pass.contains(n)).cloned());
+        let mut set = fallback.set.clone();
+        set.extend(self.set.clone());
+        Self { pass, set }
+    }
+
+    /// Names of gateway variables the command would get: the baseline, then `pass`, skipping
+    /// those not set in the gateway and those overridden by `set`.
+    pub fn passed(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for name in BASELINE_ENV.iter().copied().chain(self.pass.iter().map(String::as_str)) {
+            if !self.set.contains_key(name)
+                && !names.iter().any(|n| n == name)
+                && std::env::var_os(name).is_some()
+            {
+                names.push(name.to_string());
+            }
+        }
+        names
+    }
+
+    /// The full environment of the command.
+    pub fn resolve(&self) -> BTreeMap<String, OsString> {
+        let mut vars: BTreeMap<String, OsString> = self
+            .passed()
+            .into_iter()
+            .filter_map(|name| std::env::var_os(&name).map(|value| (name, value)))
+            .collect();
+        vars.extend(self.set.iter().map(|(k, v)| (k.clone(), OsString::from(v))));
+        vars
+    }
+
+    /// This policy plus the fixed values `vars` (e.g. `CHAI_EXIT_CODE` for a postProcess script).
+    pub fn with_set<I: IntoIterator<Item = (String, String)>>(&self, vars: I) -> Self {
+        let mut policy = self.clone();
+        policy.set.extend(vars);
+        policy
+    }
+
+    /// Replace `cmd`'s environment with [`EnvPolicy::resolve`]; call before any `Command::env`.
+    pub fn apply_to(&self, cmd: &mut Command) {
+        cmd.env_clear();
+        cmd.envs(self.resolve());
+    }
+}
+
+/// Time and output bounds for one command. The default has no bounds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecLimits {
+    /// Kill the command (and every process in its process group) after this long.
+    pub timeout: Option<Duration>,
+    /// Keep at most this many bytes of stdout and stderr combined; the rest is read and discarded.
+    pub max_output_bytes: Option<usize>,
+    /// Kernel limits set on the child (Linux only).
+    pub resources: ResourceLimits,
+}
+
+/// How [`run_program`] and [`Allowlist::run_with_options`] run a command. The default runs it in
+/// the gateway's working directory with no stdin, only exit code 0 as success, no wrapper, and no
+/// limits.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunOptions<'a> {
+    pub working_dir: Option<&'a Path>,
+    /// Bytes piped to the child's stdin; `None` gives the child an empty stdin.
+    pub stdin: Option<&'a [u8]>,
+    /// Exit codes treated as success in addition to 0.
+    pub success_exit_codes: &'a [i32],
+    pub binary_wrapper: Option<&'a [String]>,
+    pub limits: ExecLimits,
+    /// Kernel-enforced filesystem policy; the command's binary and wrapper are made readable.
+    pub kernel: Option<&'a KernelPolicy>,
+    /// Environment of the command; `None` gives it only [`BASELINE_ENV`].
+    pub env: Option<&'a EnvPolicy>,
+}
+
+/// Paths a tool command may reach, enforced by the kernel (`sandbox.kernel: "landlock"`) in
+/// addition to the gateway's own path checks. Anything not listed (with its subtree) cannot be
+/// read or written. Applied with Landlock on Linux; where the kernel lacks Landlock the command runs
+/// with the gateway's checks only and a warning is logged once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KernelPolicy {
+    /// Directories (or files) the command may read, write, create in, and remove from.
+    pub writable: Vec<PathBuf>,
+    /// Directories (or files) the command may read and execute.
+    pub readable: Vec<PathBuf>,
+    /// Also deny outgoing TCP connections (needs Landlock ABI 4, Linux 6.7).
+    pub deny_tcp_connect: bool,
+}
+
+impl KernelPolicy {
+    /// Locations every command needs to start and run: binaries, shared libraries, system
+    /// configuration, and the kernel's pseudo filesystems.
+    pub const SYSTEM_READ_PATHS: &'static [&'static str] = &[
+        "/bin", "/sbin", "/usr", "/lib", "/lib32", "/lib64", "/libx32", "/etc", "/opt", "/nix/store",
+        "/proc", "/sys", "/dev",
+    ];
+
+    /// Devices commands commonly write to.
+    const SYSTEM_WRITE_PATHS: &'static [&'static str] = &["/dev/null", "/dev/tty"];
+
+    /// Policy for a sandbox: its writable roots and the temp directory (tool temp files) are
+    /// writable; its read-only roots, [`KernelPolicy::SYSTEM_READ_PATHS`], and `extra_read` are
+    /// readable.
+    pub fn for_sandbox(sandbox: &WriteSandbox, extra_read: &[PathBuf], deny_tcp_connect: bool) -> Self {
+        let mut writable = sandbox.roots().to_vec();
+        writable.push(std::env::temp_dir());
+        writable.extend(Self::SYSTEM_WRITE_PATHS.iter().map(PathBuf::from));
+        let mut readable = sandbox.read_only_roots().to_vec();
+        readable.extend(Self::SYSTEM_READ_PATHS.iter().map(PathBuf::from));
+        readable.extend(extra_read.iter().cloned());
+        Self {
+            writable,
+            readable,
+            deny_tcp_connect,
+        }
+    }
+
+    /// This policy plus read access to `paths` (e.g. a skill directory with its scripts).
+    pub fn with_readable<I: IntoIterator<Item = PathBuf>>(&self, paths: I) -> Self {
+        let mut policy = self.clone();
+        policy.readable.extend(paths);
+        policy
+    }
+
+    /// This policy plus the files `binary` and the wrapper's program resolve to on `PATH`, so
+    /// binaries installed outside the system paths (e.g. `~/.cargo/bin`) still start.
+    fn for_command(&self, binary: &str, binary_wrapper: Option<&[String]>) -> Self {
+        let programs = std::iter::once(binary).chain(binary_wrapper.and_then(|w| w.first()).map(String::as_str));
+        self.with_readable(programs.filter_map(find_program))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn apply_to(&self, cmd: &mut Command) -> Result<(), String> {
+        use std::os::unix::process::CommandExt;
+        let Some(ruleset) = landlock::ruleset(self)? else {
+            return Ok(());
+        };
+        // SAFETY: the hook runs in the forked child before exec and only calls prctl and
+        // landlock_restrict_self on an fd it owns; it does not allocate or take locks.
+        unsafe {
+            cmd.pre_exec(move || landlock::restrict_self(&ruleset));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn apply_to(&self, _cmd: &mut Command) -> Result<(), String> {
+        static WARNED: std::sync::Once = std::sync::Once::new();
+        WARNED.call_once(|| log::warn!("exec: sandbox.kernel is only enforced on Linux; tool commands use path checks only"));
+        Ok(())
+    }
+}
+
+/// The Landlock ABI version of the running kernel, or `None` when Landlock is unavailable
+/// (not Linux, kernel older than 5.13, or disabled at boot).
+pub fn landlock_abi() -> Option<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        landlock::abi()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
     }
 }
 
+/// The file a program name runs: itself when it contains a `/`, else the first match on `PATH`.
+fn find_program(name: &str) -> Option<PathBuf> {
+    if name.contains('/') {
+        return std::fs::canonicalize(name).ok();
+    }
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+        .and_then(|found| std::fs::canonicalize(found).ok())
+}
+
+/// Landlock ruleset construction (parent) and enforcement (child, between fork and exec), using
+/// the raw syscalls.
+#[cfg(target_os = "linux")]
+mod landlock {
+    use super::KernelPolicy;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::Path;
+    use std::sync::OnceLock;
+
+    const CREATE_RULESET_VERSION: libc::c_uint = 1 << 0;
+    const RULE_PATH_BENEATH: libc::c_int = 1;
+
+    const ACCESS_FS_EXECUTE: u64 = 1 << 0;
+    const ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_FS_READ_FILE: u64 = 1 << 2;
+    const ACCESS_FS_READ_DIR: u64 = 1 << 3;
+    /// Every filesystem right of ABI 1 (execute through make-symlink).
+    const ACCESS_FS_ABI_1: u64 = (1 << 13) - 1;
+    const ACCESS_FS_REFER: u64 = 1 << 13;
+    const ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+    const ACCESS_NET_CONNECT_TCP: u64 = 1 << 1;
+
+    const ACCESS_READ: u64 = ACCESS_FS_EXECUTE | ACCESS_FS_READ_FILE | ACCESS_FS_READ_DIR;
+    /// Rights that apply to a file rather than a directory's contents.
+    const ACCESS_FILE: u64 = ACCESS_FS_EXECUTE | ACCESS_FS_WRITE_FILE | ACCESS_FS_READ_FILE | ACCESS_FS_TRUNCATE;
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+        handled_access_net: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: libc::c_int,
+    }
+
+    pub(super) fn abi() -> Option<u32> {
+        static ABI: OnceLock<Option<u32>> = OnceLock::new();
+        *ABI.get_or_init(|| {
+            // SAFETY: the version query takes no attribute pointer and returns an integer.
+            let version = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_create_ruleset,
+                    std::ptr::null::<RulesetAttr>(),
+                    0usize,
+                    CREATE_RULESET_VERSION,
+                )
+            };
+            u32::try_from(version).ok().filter(|v| *v > 0)
+        })
+    }
+
+    /// Build the ruleset for `policy`. `Ok(None)` when the kernel has no Landlock (warned once);
+    /// paths that do not exist are skipped.
+    pub(super) fn ruleset(policy: &KernelPolicy) -> Result<Option<OwnedFd>, String> {
+        static WARNED: std::sync::Once = std::sync::Once::new();
+        let Some(abi) = abi() else {
+            WARNED.call_once(|| {
+                log::warn!("exec: kernel has no Landlock support; tool commands use path checks only")
+            });
+            return Ok(None);
+        };
+        let mut handled_fs = ACCESS_FS_ABI_1;
+        if abi >= 2 {
+            handled_fs |= ACCESS_FS_REFER;
+        }
+        if abi >= 3 {
+            handled_fs |= ACCESS_FS_TRUNCATE;
+        }
+        let deny_connect = policy.deny_tcp_connect && abi >= 4;
+        if policy.deny_tcp_connect && !deny_connect {
+            static NET_WARNED: std::sync::Once = std::sync::Once::new();
+            NET_WARNED.call_once(|| {
+                log::warn!("exec: Landlock ABI {} cannot restrict TCP; denyTcpConnect is not enforced", abi)
+            });
+        }
+        let attr = RulesetAttr {
+            handled_access_fs: handled_fs,
+            handled_access_net: if deny_connect { ACCESS_NET_CONNECT_TCP } else { 0 },
+        };
+        // Kernels before ABI 4 only know the filesystem field.
+        let size = if abi >= 4 {
+            std::mem::size_of::<RulesetAttr>()
+        } else {
+            std::mem::size_of::<u64>()
+        };
+        // SAFETY: `attr` is a valid ruleset attribute of at least `size` bytes.
+        let fd = unsafe { libc::syscall(libc::SYS_landlock_create_ruleset, &attr as *const RulesetAttr, size, 0u32) };
+        let fd = libc::c_int::try_from(fd)
+            .ok()
+            .filter(|fd| *fd >= 0)
+            .ok_or_else(|| format!("landlock ruleset: {}", std::io::Error::last_os_error()))?;
+        // SAFETY: the syscall returned a new file descriptor that nothing else owns.
+        let ruleset = unsafe { OwnedFd::from_raw_fd(fd) };
+        for path in &policy.writable {
+            add_path(&ruleset, path, handled_fs)?;
+        }
+        for path in &policy.readable {
+            add_path(&ruleset, path, ACCESS_READ & handled_fs)?;
+        }
+        Ok(Some(ruleset))
+    }
+
+    /// Allow `access` beneath `path` (only file rights when it is a file).
+    fn add_path(ruleset: &OwnedFd, path: &Path, access: u64) -> Result<(), String> {
+        let Ok(meta) = std::fs::metadata(path) else {
+            return Ok(());
+        };
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_PATH)
+            .open(path)
+            .map_err(|e| format!("landlock rule for {}: {}", path.display(), e))?;
+        let attr = PathBeneathAttr {
+            allowed_access: if meta.is_dir() { access } else { access & ACCESS_FILE },
+            parent_fd: file.as_raw_fd(),
+        };
+        // SAFETY: both descriptors are open and `attr` is a valid path-beneath attribute.
+        let rc = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_add_rule,
+                ruleset.as_raw_fd(),
+                RULE_PATH_BENEATH,
+                &attr as *const PathBeneathAttr,
+                0u32,
+            )
+        };
+        if rc != 0 {
+            return Err(format!("landlock rule for {}: {}", path.display(), std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    /// Restrict the calling process (the child, between fork and exec) to `ruleset`.
+    pub(super) fn restrict_self(ruleset: &OwnedFd) -> std::io::Result<()> {
+        // SAFETY: prctl and landlock_restrict_self take plain integers; neither allocates.
+        unsafe {
+            if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if libc::syscall(libc::SYS_landlock_restrict_self, ruleset.as_raw_fd(), 0u32) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Output bytes still allowed across the stdout and stderr readers of one command.
+#[derive(Clone)]
+struct OutputBudget {
+    remaining: Option<Arc<AtomicUsize>>,
+    discarded: Arc<AtomicUsize>,
+}
+
+impl OutputBudget {
+    fn new(max_bytes: Option<usize>) -> Self {
+        Self {
+            remaining: max_bytes.map(|n| Arc::new(AtomicUsize::new(n))),
+            discarded: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// How many of `n` newly read bytes may be kept.
+    fn take(&self, n: usize) -> usize {
+        let Some(ref remaining) = self.remaining else {
+            return n;
+        };
+        let before = remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |r| Some(r.saturating_sub(n)))
+            .unwrap_or(0);
+        let kept = before.min(n);
+        self.discarded.fetch_add(n - kept, Ordering::SeqCst);
+        kept
+    }
+
+    fn discarded(&self) -> usize {
+        self.discarded.load(Ordering::SeqCst)
+    }
+}
+
+/// One output pipe, read to the end on a thread by [`spawn_reader`]. What was read so far stays
+/// available when the reader is abandoned.
+struct Reader {
+    kept: Arc<Mutex<Vec<u8>>>,
+    /// `None` once joined.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Reader {
+    fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Block until the pipe reached end of file.
+    fn wait(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// The bytes kept so far; the thread may still be blocked on the pipe.
+    fn into_output(self) -> Vec<u8> {
+        std::mem::take(&mut *self.kept.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Read `pipe` to the end on a thread, keeping what `budget` allows. Reading continues past the
+/// cap so the child never blocks on a full pipe.
+fn spawn_reader(pipe: Option<impl Read + Send + 'static>, budget: OutputBudget) -> Reader {
+    let kept = Arc::new(Mutex::new(Vec::new()));
+    let out = kept.clone();
+    let thread = std::thread::spawn(move || {
+        let Some(mut pipe) = pipe else {
+            return;
+        };
+        let mut buf = [0u8; 8192];
+        loop {
+            match pipe.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let take = budget.take(n);
+                    out.lock().unwrap_or_else(PoisonError::into_inner).extend_from_slice(&buf[..take]);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+    });
+    Reader {
+        kept,
+        thread: Some(thread),
+    }
+}
+
+/// How long output readers get to reach end of file after a bounded command exited (or was
+/// killed) before the rest of its process group is killed, and again before they are abandoned.
+const DRAIN_GRACE: Duration = Duration::from_millis(250);
+
+/// Wait until every reader reached end of file or `deadline` passed; true when they all did.
+fn wait_for_readers(readers: &[&Reader], deadline: Instant) -> bool {
+    let mut pause = Duration::from_millis(1);
+    loop {
+        if readers.iter().all(|r| r.is_finished()) {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        std::thread::sleep(pause.min(deadline - now));
+        pause = (pause * 2).min(Duration::from_millis(50));
+    }
+}
+
+/// Wait for `child` until `deadline`. Returns `None` when the deadline passed first.
+fn wait_until(child: &mut Child, deadline: Instant) -> std::io::Result<Option<ExitStatus>> {
+    let mut pause = Duration::from_millis(1);
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(pause.min(deadline - now));
+        pause = (pause * 2).min(Duration::from_millis(50));
+    }
+}
+
+/// Kill `child` and the rest of its process group (the child leads its own group, see
+/// [`run_program`]), so helpers it started do not outlive it.
+#[cfg(unix)]
+fn kill_process_group(child: &mut Child) {
+    match i32::try_from(child.id()) {
+        // SAFETY: kill(2) has no memory-safety preconditions; a negative pid names a group.
+        Ok(pid) => unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        },
+        Err(_) => {
+            let _ = child.kill();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+/// Raw outcome of [`run_program`].
+#[derive(Debug)]
+pub struct ProgramOutput {
+    /// Exit status; `None` when the timeout killed the command.
+    pub status: Option<ExitStatus>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    /// `[output capped at N bytes; M bytes discarded]` when output past the cap was dropped.
+    pub capped: Option<String>,
+}
+
+impl ProgramOutput {
+    /// Whether the command exited with status 0.
+    pub fn success(&self) -> bool {
+        self.status.is_some_and(|s| s.success())
+    }
+}
+
+/// Run `program subcommand args...` as `opts` describes, without an allowlist check: callers
+/// vet the program themselves (a skill's or hook's script under `sh`, or a binary they checked
+/// with [`Allowlist::is_allowed`]). `opts.success_exit_codes` is not consulted.
+///
+/// Output is read while the command runs; with `limits.max_output_bytes`, bytes past the cap are
+/// discarded. With `limits.timeout`, the command runs in its own process group and the whole
+/// group is killed when it does not exit in time. Processes the command leaves behind in its
+/// group are killed when they still hold its output open after it exits, so they cannot hold up
+/// the call.
+pub fn run_program(
+    program: &str,
+    subcommand: &str,
+    args: &[String],
+    opts: &RunOptions,
+) -> Result<ProgramOutput, String> {
+    use std::io::Write;
+
+    let resolved = resolve_binary(program);
+    let kernel = opts.kernel.map(|p| p.for_command(&resolved, opts.binary_wrapper));
+    let mut cmd = build_command(
+        &resolved,
+        subcommand,
+        args,
+        opts.binary_wrapper,
+        &opts.limits.resources,
+        kernel.as_ref(),
+        opts.env.unwrap_or(&EnvPolicy::default()),
+    )?;
+    cmd.stdin(if opts.stdin.is_some() { Stdio::piped() } else { Stdio::null() });
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    if let Some(dir) = opts.working_dir {
+        cmd.current_dir(dir);
+    }
+    #[cfg(unix)]
+    if opts.limits.timeout.is_some() {
+        std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    }
+    let started = Instant::now();
+    let mut child = cmd.spawn().map_err(|e| format!("exec failed: {}", e))?;
+
+    // Feed stdin from a thread so a child that writes a lot before reading cannot deadlock
+    // against us. Use explicit error handling instead of `if let Some` so that a missing pipe
+    // (which should never happen since Stdio::piped() is set above) surfaces as an error.
+    let writer = match opts.stdin {
+        Some(stdin) => {
+            let mut pipe = child.stdin.take().ok_or_else(|| {
+                "failed to acquire stdin pipe: Stdio::piped() was set but pipe is unavailable"
+                    .to_string()
+            })?;
+            let stdin = stdin.to_vec();
+            Some(std::thread::spawn(move || pipe.write_all(&stdin)))
+        }
+        None => None,
+    };
+    let budget = OutputBudget::new(opts.limits.max_output_bytes);
+    let mut stdout = spawn_reader(child.stdout.take(), budget.clone());
+    let mut stderr = spawn_reader(child.stderr.take(), budget.clone());
+
+    let status = match opts.limits.timeout {
+        Some(timeout) => wait_until(&mut child, started + timeout),
+        None => child.wait().map(Some),
+    };
+    let status = match status {
+        Ok(Some(status)) => Some(status),
+        Ok(None) => {
+            kill_process_group(&mut child);
+            let _ = child.wait();
+            None
+        }
+        Err(e) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("exec failed: {}", e));
+        }
+    };
+    // A descendant that inherited the pipes (a backgrounded server, a daemon) keeps them open
+    // after the command exits. With a timeout, kill the rest of the process group when the
+    // pipes do not close right away, and stop waiting for ones that stay open after that.
+    if opts.limits.timeout.is_some() {
+        if !wait_for_readers(&[&stdout, &stderr], Instant::now() + DRAIN_GRACE) {
+            kill_process_group(&mut child);
+            if !wait_for_readers(&[&stdout, &stderr], Instant::now() + DRAIN_GRACE) {
+                log::warn!("exec: {} {}: output still open after exit; a detached process holds it", program, subcommand);
+            }
+        }
+    } else {
+        stdout.wait();
+        stderr.wait();
+    }
+    let stdout = stdout.into_output();
+    let stderr = stderr.into_output();
+    // A writer still blocked feeds a descendant that never reads; its input no longer matters.
+    let write_result = writer.filter(|w| w.is_finished()).map(|w| w.join());
+
+    let capped = match (opts.limits.max_output_bytes, budget.discarded()) {
+        (Some(max), discarded) if discarded > 0 => Some(format!(
+            "[output capped at {} bytes; {} bytes discarded]",
+            max, discarded
+        )),
+        _ => None,
+    };
+    match (status, write_result) {
+        (Some(_), Some(Ok(Err(e)))) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+            return Err(format!("failed to write stdin: {}", e));
+        }
+        (Some(_), Some(Err(_))) => return Err("failed to write stdin: writer thread panicked".to_string()),
+        (None, _) => log::warn!(
+            "exec: {} {} timed out after {} s; process group killed",
+            program,
+            subcommand,
+            opts.limits.timeout.unwrap_or_default().as_secs()
+        ),
+        _ => {}
+    }
+    if let Some(ref notice) = capped {
+        log::warn!("exec: {} {}: {}", program, subcommand, notice);
+    }
+    Ok(ProgramOutput {
+        status,
+        stdout,
+        stderr,
+        capped,
+    })
+}
+
 /// Allowlist: binary name -> set of allowed subcommands (e.g. "git" -> ["search", "create", ...]).
 #[derive(Debug, Clone, Default)]
 pub struct Allowlist {
@@ -142,23 +910,17 @@ impl Allowlist {
         success_exit_codes: &[i32],
         binary_wrapper: Option<&[String]>,
     ) -> Result<(i32, String), String> {
-        let allowed = self
-            .bins
-            .get(binary)
-            .ok_or_else(|| format!("binary not allowlisted: {}", binary))?;
-        if !allowed.iter().any(|s| s == subcommand) {
-            return Err(format!(
-                "subcommand not allowlisted: {} {}",
-                binary, subcommand
-            ));
-        }
-        let resolved = resolve_binary(binary);
-        let mut cmd = build_command(&resolved, subcommand, args, binary_wrapper);
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
-        let output = cmd.output().map_err(|e| format!("exec failed: {}", e))?;
-        Self::collect_output_with_codes(output, success_exit_codes)
+        self.run_with_options(
+            binary,
+            subcommand,
+            args,
+            &RunOptions {
+                working_dir,
+                success_exit_codes,
+                binary_wrapper,
+                ..Default::default()
+            },
+        )
     }
 
     /// Run `binary subcommand args...` if allowed, piping `stdin` bytes to the child's stdin.
@@ -207,9 +969,34 @@ impl Allowlist {
         success_exit_codes: &[i32],
         binary_wrapper: Option<&[String]>,
     ) -> Result<(i32, String), String> {
-        use std::io::Write;
-        use std::process::Stdio;
+        self.run_with_options(
+            binary,
+            subcommand,
+            args,
+            &RunOptions {
+                working_dir,
+                stdin: Some(stdin),
+                success_exit_codes,
+                binary_wrapper,
+                ..Default::default()
+            },
+        )
+    }
 
+    /// Run `binary subcommand args...` if allowed, as described by `opts`. Returns the exit code
+    /// and combined output on success (see [`Allowlist::collect_output_with_codes`]).
+    ///
+    /// The command runs as [`run_program`] describes. With `limits.max_output_bytes`, a
+    /// `[output capped at N bytes; M bytes discarded]` line is appended to the result when output
+    /// was dropped. When the timeout kills the command, the error reads `timed out after N s`,
+    /// followed by the output collected until then.
+    pub fn run_with_options(
+        &self,
+        binary: &str,
+        subcommand: &str,
+        args: &[String],
+        opts: &RunOptions,
+    ) -> Result<(i32, String), String> {
         let allowed = self
             .bins
             .get(binary)
@@ -220,34 +1007,43 @@ impl Allowlist {
                 binary, subcommand
             ));
         }
-        let resolved = resolve_binary(binary);
-        let mut cmd = build_command(&resolved, subcommand, args, binary_wrapper);
-        cmd.stdin(Stdio::piped());
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
-        let mut child = cmd.spawn().map_err(|e| format!("exec failed: {}", e))?;
-
-        // Take the stdin pipe and write content. Use explicit error handling
-        // instead of `if let Some` so that a missing pipe (which should never
-        // happen since Stdio::piped() is set above) surfaces as an error
-        // rather than silently skipping stdin. Drop the pipe before waiting
-        // so the child sees EOF on stdin.
-        {
-            let mut pipe = child.stdin.take().ok_or_else(|| {
-                "failed to acquire stdin pipe: Stdio::piped() was set but pipe is unavailable"
-                    .to_string()
-            })?;
-            pipe.write_all(stdin)
-                .map_err(|e| format!("failed to write stdin: {}", e))?;
-        }
+        let ProgramOutput {
+            status,
+            stdout,
+            stderr,
+            capped,
+        } = run_program(binary, subcommand, args, opts)?;
+        let with_notice = |mut text: String| {
+            if let Some(ref notice) = capped {
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(notice);
+            }
+            text
+        };
 
-        let output = child
-            .wait_with_output()
-            .map_err(|e| format!("exec failed: {}", e))?;
-        Self::collect_output_with_codes(output, success_exit_codes)
+        let Some(status) = status else {
+            let secs = opts.limits.timeout.unwrap_or_default().as_secs();
+            let mut partial = String::from_utf8_lossy(&stdout).into_owned();
+            let stderr = String::from_utf8_lossy(&stderr);
+            if !stderr.is_empty() {
+                if !partial.is_empty() {
+                    partial.push('\n');
+                }
+                partial.push_str(&stderr);
+            }
+            let partial = with_notice(partial);
+            return Err(if partial.is_empty() {
+                format!("timed out after {} s", secs)
+            } else {
+                format!("timed out after {} s; output so far:\n{}", secs, partial)
+            });
+        };
+        let output = std::process::Output { status, stdout, stderr };
+        Self::collect_output_with_codes(output, opts.success_exit_codes)
+            .map(|(code, text)| (code, with_notice(text)))
+            .map_err(with_notice)
     }
 
     /// Collect stdout/stderr from a completed child output into a Result.
@@ -313,22 +1109,206 @@ impl Allowlist {
 /// - The sandbox directory itself is always a writable root
 /// - Direct-child symlinks are canonicalized and their targets become additional writable roots
 ///
-/// Users grant write access by creating symlinks in the sandbox directory.
+/// Users grant write access by creating symlinks in the sandbox directory, and read-only access by
+/// naming the symlink with the [`READ_ONLY_PREFIX`] (`ro.docs -> ~/Documents/reference`).
 /// The `ln` binary must never appear in any skill's allowlist.
+///
+/// The roots are scanned again whenever the sandbox directory's modification time changes
+/// (checked before each validation and by [`WriteSandbox::refresh`]), so adding a symlink grants
+/// access and removing one revokes it without a restart. Clones share one root set.
+///
+/// An agent with a `sandbox` block in config gets a [`WriteSandbox::subset`] instead: only the
+/// listed direct children (writable or read-only) and an optional private scratch directory.
 #[derive(Debug, Clone)]
 pub struct WriteSandbox {
+    /// Current roots, replaced as a whole by a re-scan.
+    roots: Arc<RwLock<SandboxRoots>>,
+    /// Where the roots come from; `None` for a fixed set ([`WriteSandbox::from_cwd`]).
+    source: Option<RootSource>,
+    /// Base for relative paths and the default working directory.
+    base: Option<PathBuf>,
+    /// Canonical sandbox directory and granted entry names of a subset sandbox: relative paths
+    /// starting with an entry resolve in the sandbox directory.
+    subset_entries: Option<(PathBuf, Vec<String>)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SandboxRoots {
     /// Canonical writable root paths. A write target is valid if its canonical
     /// path starts with any of these roots.
-    writable_roots: Vec<PathBuf>,
+    writable: Vec<PathBuf>,
+    /// Canonical roots that `readPath` / `workingDir` arguments may use but writes may not.
+    read_only: Vec<PathBuf>,
+    /// Modification time of the sandbox directory when these roots were scanned.
+    scanned_mtime: Option<SystemTime>,
+    /// When the scan started.
+    scanned_at: Option<SystemTime>,
 }
 
-impl WriteSandbox {
-    /// Build a sandbox from a profile's sandbox directory.
-    ///
-    /// If the directory does not exist, the sandbox has no writable roots
-    /// and all write-path validations will fail.
-    pub fn new(sandbox_dir: &Path) -> Self {
+/// A change within this long of a scan may share the directory's modification time with it
+/// (coarse filesystem timestamps), so an unchanged time is not trusted until the window passes.
+const MTIME_RACY_WINDOW: Duration = Duration::from_secs(2);
+
+impl SandboxRoots {
+    fn root_set(&self) -> RootSet {
+        RootSet {
+            writable: self.writable.clone(),
+            read_only: self.read_only.clone(),
+        }
+    }
+
+    /// Whether the scan started within [`MTIME_RACY_WINDOW`] of the directory's modification
+    /// time: a later change in the same timestamp tick would leave the time unchanged.
+    fn is_racy(&self) -> bool {
+        match (self.scanned_mtime, self.scanned_at) {
+            (Some(mtime), Some(at)) => at.duration_since(mtime).map_or(true, |d| d < MTIME_RACY_WINDOW),
+            _ => false,
+        }
+    }
+
+    /// The innermost root containing `canonical` and whether it is writable. A read-only root
+    /// inside a writable one (e.g. `ro.notes -> ./notes`) keeps its subtree read-only; when the
+    /// same directory is granted both ways, the write grant wins.
+    fn innermost(&self, canonical: &Path) -> Option<(&PathBuf, bool)> {
+        let writable = self.writable.iter().map(|r| (r, true));
+        let read_only = self.read_only.iter().map(|r| (r, false));
+        writable
+            .chain(read_only)
+            .filter(|(r, _)| canonical.starts_with(r))
+            .max_by_key(|(r, w)| (r.components().count(), *w))
+    }
+}
+
+/// Name prefix of a direct-child symlink in the sandbox directory that grants read-only access
+/// to its target.
+pub const READ_ONLY_PREFIX: &str = "ro.";
+
+/// How a sandbox's roots are scanned.
+#[derive(Debug, Clone)]
+struct RootSource {
+    sandbox_dir: PathBuf,
+    /// `None` for the profile sandbox (the directory and its direct-child symlinks).
+    subset: Option<SubsetGrants>,
+}
+
+/// The grants of a [`WriteSandbox::subset`].
+#[derive(Debug, Clone)]
+struct SubsetGrants {
+    writable: Vec<String>,
+    read_only: Vec<String>,
+    /// Canonical scratch directory, always the first writable root.
+    scratch: Option<PathBuf>,
+}
+
+/// WebSocket event name: a sandbox's roots changed after a symlink was added, removed, or
+/// retargeted in the sandbox directory.
+pub const EVENT_SANDBOX_ROOTS_CHANGED: &str = "sandbox.roots.changed";
+
+/// The writable and read-only roots of a sandbox at one scan.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RootSet {
+    pub writable: Vec<PathBuf>,
+    pub read_only: Vec<PathBuf>,
+}
+
+impl RootSet {
+    /// Whether `root` is granted writable (`Some(true)`; a write grant wins over a read-only
+    /// grant of the same directory) or read-only.
+    fn access(&self, root: &PathBuf) -> Option<bool> {
+        if self.writable.contains(root) {
+            Some(true)
+        } else {
+            self.read_only.contains(root).then_some(false)
+        }
+    }
+
+    fn all(&self) -> impl Iterator<Item = &PathBuf> {
+        self.writable.iter().chain(&self.read_only)
+    }
+}
+
+/// Roots gained, lost, or granted with different access between two scans of a sandbox (e.g.
+/// `repo` renamed to `ro.repo` makes its target read-only).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RootsChange {
+    pub added: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    pub made_writable: Vec<PathBuf>,
+    pub made_read_only: Vec<PathBuf>,
+}
+
+impl RootsChange {
+    /// The change from `before` to `after`, or `None` when both grant the same access.
+    pub fn between(before: &RootSet, after: &RootSet) -> Option<Self> {
+        let mut change = Self::default();
+        for root in after.all() {
+            let list = match (before.access(root), after.access(root)) {
+                (None, _) => &mut change.added,
+                (Some(false), Some(true)) => &mut change.made_writable,
+                (Some(true), Some(false)) => &mut change.made_read_only,
+                _ => continue,
+            };
+            if !list.contains(root) {
+                list.push(root.clone());
+            }
+        }
+        for root in before.all() {
+            if after.access(root).is_none() && !change.removed.contains(root) {
+                change.removed.push(root.clone());
+            }
+        }
+        (change != Self::default()).then_some(change)
+    }
+}
+
+fn dir_mtime(dir: &Path) -> Option<SystemTime> {
+    std::fs::metadata(dir).and_then(|m| m.modified()).ok()
+}
+
+impl RootSource {
+    /// Scan the roots. A subset entry that no longer resolves is left out (its access revoked);
+    /// with `strict` it is an error instead.
+    fn scan(&self, strict: bool) -> Result<SandboxRoots, String> {
+        let scanned_at = Some(SystemTime::now());
+        let scanned_mtime = dir_mtime(&self.sandbox_dir);
+        let Some(ref grants) = self.subset else {
+            let (writable, read_only) = Self::scan_profile(&self.sandbox_dir);
+            return Ok(SandboxRoots {
+                writable,
+                read_only,
+                scanned_mtime,
+                scanned_at,
+            });
+        };
+        let canonical_dir = std::fs::canonicalize(&self.sandbox_dir)
+            .map_err(|e| format!("sandbox directory {}: {}", self.sandbox_dir.display(), e))?;
+        let resolve = |names: &[String]| -> Result<Vec<PathBuf>, String> {
+            let mut roots = Vec::new();
+            for name in names {
+                match std::fs::canonicalize(canonical_dir.join(name)) {
+                    Ok(root) => roots.push(root),
+                    Err(e) if strict => return Err(format!("sandbox entry \"{}\": {}", name, e)),
+                    Err(e) => log::warn!("sandbox entry \"{}\" no longer resolves ({}); access revoked", name, e),
+                }
+            }
+            Ok(roots)
+        };
+        let mut writable: Vec<PathBuf> = grants.scratch.iter().cloned().collect();
+        writable.extend(resolve(&grants.writable)?);
+        Ok(SandboxRoots {
+            writable,
+            read_only: resolve(&grants.read_only)?,
+            scanned_mtime,
+            scanned_at,
+        })
+    }
+
+    /// The profile sandbox's writable roots (the directory itself, then the canonical targets of
+    /// its direct-child symlinks) and read-only roots (targets of symlinks named with
+    /// [`READ_ONLY_PREFIX`]). Both empty when the directory does not exist.
+    fn scan_profile(sandbox_dir: &Path) -> (Vec<PathBuf>, Vec<PathBuf>) {
         let mut writable_roots = Vec::new();
+        let mut read_only_roots: Vec<PathBuf> = Vec::new();
 
         // Canonicalize the sandbox directory itself as the primary writable root.
         if let Ok(canonical) = std::fs::canonicalize(sandbox_dir) {
@@ -342,9 +1322,11 @@ impl WriteSandbox {
                     // already covered by the sandbox root prefix check).
                     if path.symlink_metadata().map_or(false, |m| m.is_symlink()) {
                         if let Ok(target) = std::fs::canonicalize(&path) {
+                            let read_only = entry.file_name().to_string_lossy().starts_with(READ_ONLY_PREFIX);
+                            let roots = if read_only { &mut read_only_roots } else { &mut writable_roots };
                             // Avoid duplicates (e.g., symlink pointing back into sandbox).
-                            if !writable_roots.iter().any(|r| *r == target) {
-                                writable_roots.push(target);
+                            if !roots.iter().any(|r| *r == target) {
+                                roots.push(target);
                             }
                         }
                         // Broken symlinks are silently skipped — they grant no access.
@@ -352,33 +1334,199 @@ impl WriteSandbox {
                 }
             }
         }
+        (writable_roots, read_only_roots)
+    }
+}
+
+impl WriteSandbox {
+    /// Build a sandbox from a profile's sandbox directory.
+    ///
+    /// If the directory does not exist, the sandbox has no writable roots
+    /// and all write-path validations will fail.
+    pub fn new(sandbox_dir: &Path) -> Self {
+        let source = RootSource {
+            sandbox_dir: sandbox_dir.to_path_buf(),
+            subset: None,
+        };
+        let roots = source.scan(false).unwrap_or_default();
+        Self {
+            base: roots.writable.first().cloned(),
+            roots: Arc::new(RwLock::new(roots)),
+            source: Some(source),
+            subset_entries: None,
+        }
+    }
+
+    /// Build a sandbox that uses the current working directory as the sole
+    /// writable root. Used when `sandbox.mode` is `"current"` and the profile
+    /// sandbox directory does not exist.
+    ///
+    /// If the CWD cannot be canonicalized, the sandbox has no writable roots
+    /// and all write-path validations will fail.
+    pub fn from_cwd() -> Self {
+        let mut writable = Vec::new();
+        if let Ok(cwd) = std::env::current_dir().and_then(|p| std::fs::canonicalize(&p)) {
+            writable.push(cwd);
+        }
+        Self {
+            base: writable.first().cloned(),
+            roots: Arc::new(RwLock::new(SandboxRoots {
+                writable,
+                ..Default::default()
+            })),
+            source: None,
+            subset_entries: None,
+        }
+    }
+
+    /// Build one agent's view of a profile sandbox: the named direct children of `sandbox_dir`
+    /// (`writable` or `read_only`; a symlink grants its target, a directory itself) and, when
+    /// `scratch` is set, that directory (created if missing) as a writable root. Relative paths
+    /// that start with a listed name resolve in the sandbox directory; others resolve against the
+    /// scratch directory, else the first listed entry. Fails when the sandbox directory or a
+    /// listed entry does not resolve; an entry removed later loses its access on the next scan.
+    pub fn subset(
+        sandbox_dir: &Path,
+        writable: &[String],
+        read_only: &[String],
+        scratch: Option<&Path>,
+    ) -> Result<Self, String> {
+        let canonical_dir = std::fs::canonicalize(sandbox_dir)
+            .map_err(|e| format!("sandbox directory {}: {}", sandbox_dir.display(), e))?;
+        let scratch = match scratch {
+            Some(dir) => Some(
+                std::fs::create_dir_all(dir)
+                    .and_then(|_| std::fs::canonicalize(dir))
+                    .map_err(|e| format!("scratch directory {}: {}", dir.display(), e))?,
+            ),
+            None => None,
+        };
+        let source = RootSource {
+            sandbox_dir: sandbox_dir.to_path_buf(),
+            subset: Some(SubsetGrants {
+                writable: writable.to_vec(),
+                read_only: read_only.to_vec(),
+                scratch,
+            }),
+        };
+        let roots = source.scan(true)?;
+        let base = roots
+            .writable
+            .iter()
+            .chain(&roots.read_only)
+            .next()
+            .cloned()
+            .ok_or_else(|| "no sandbox entries or scratch directory granted".to_string())?;
+        Ok(Self {
+            roots: Arc::new(RwLock::new(roots)),
+            source: Some(source),
+            base: Some(base),
+            subset_entries: Some((canonical_dir, writable.iter().chain(read_only).cloned().collect())),
+        })
+    }
+
+    /// Scan the roots again if the sandbox directory changed since the last scan (its modification
+    /// time differs, or the last scan was too close to it to trust; see [`MTIME_RACY_WINDOW`]),
+    /// replacing them for every clone of this sandbox. Returns what changed; each change is logged.
+    pub fn refresh(&self) -> Option<RootsChange> {
+        let source = self.source.as_ref()?;
+        let mtime = dir_mtime(&source.sandbox_dir);
+        {
+            let roots = self.read_roots();
+            if roots.scanned_mtime == mtime && !roots.is_racy() {
+                return None;
+            }
+        }
+        let scanned = match source.scan(false) {
+            Ok(scanned) => scanned,
+            Err(e) => {
+                log::warn!("sandbox re-scan failed: {}", e);
+                SandboxRoots {
+                    scanned_mtime: mtime,
+                    scanned_at: Some(SystemTime::now()),
+                    ..Default::default()
+                }
+            }
+        };
+        let mut roots = self.roots.write().unwrap_or_else(|e| e.into_inner());
+        if roots.scanned_at > scanned.scanned_at {
+            // Another caller scanned later.
+            return None;
+        }
+        let change = RootsChange::between(&roots.root_set(), &scanned.root_set());
+        *roots = scanned;
+        drop(roots);
+        if let Some(ref change) = change {
+            for root in &change.added {
+                log::info!("sandbox root granted: {}", root.display());
+            }
+            for root in &change.removed {
+                log::info!("sandbox root revoked: {}", root.display());
+            }
+            for root in &change.made_writable {
+                log::info!("sandbox root now writable: {}", root.display());
+            }
+            for root in &change.made_read_only {
+                log::info!("sandbox root now read-only: {}", root.display());
+            }
+        }
+        change
+    }
 
-        Self { writable_roots }
+    fn read_roots(&self) -> std::sync::RwLockReadGuard<'_, SandboxRoots> {
+        self.roots.read().unwrap_or_else(|e| e.into_inner())
     }
 
-    /// Build a sandbox that uses the current working directory as the sole
-    /// writable root. Used when `sandbox.mode` is `"current"` and the profile
-    /// sandbox directory does not exist.
-    ///
-    /// If the CWD cannot be canonicalized, the sandbox has no writable roots
-    /// and all write-path validations will fail.
-    pub fn from_cwd() -> Self {
-        let mut writable_roots = Vec::new();
-        if let Ok(cwd) = std::env::current_dir().and_then(|p| std::fs::canonicalize(&p)) {
-            writable_roots.push(cwd);
+    /// The roots after picking up any change to the sandbox directory.
+    fn current(&self) -> SandboxRoots {
+        self.refresh();
+        self.read_roots().clone()
+    }
+
+    /// Base for relative paths and the default working directory for tool processes.
+    pub fn base(&self) -> Option<&Path> {
+        self.base.as_deref()
+    }
+
+    /// Innermost root (writable or read-only) that contains `canonical`.
+    pub fn root_for(&self, canonical: &Path) -> Option<PathBuf> {
+        self.current().innermost(canonical).map(|(r, _)| r.clone())
+    }
+
+    /// Innermost root that contains `canonical`, when that root is writable.
+    pub fn writable_root_for(&self, canonical: &Path) -> Option<PathBuf> {
+        match self.current().innermost(canonical) {
+            Some((root, true)) => Some(root.clone()),
+            _ => None,
+        }
+    }
+
+    /// Anchor a relative path: paths whose first component names a granted entry resolve against
+    /// the sandbox directory, others against [`WriteSandbox::base`].
+    fn anchor(&self, target: &Path) -> Option<PathBuf> {
+        if !target.is_relative() {
+            return Some(target.to_path_buf());
+        }
+        if let Some((ref sandbox_dir, ref entries)) = self.subset_entries {
+            let first = target.components().next().and_then(|c| c.as_os_str().to_str());
+            if first.is_some_and(|f| entries.iter().any(|e| e == f)) {
+                return Some(sandbox_dir.join(target));
+            }
         }
-        Self { writable_roots }
+        self.base().map(|b| b.join(target))
     }
 
     /// Validate that a path falls within a writable root and does not
-    /// target a `.git/` directory.
+    /// target a `.git/` directory. A path whose innermost root is read-only is
+    /// rejected even when an outer root is writable.
     ///
     /// Returns the canonical path on success. For paths that don't exist yet
     /// (new file creation), the parent directory is canonicalized and the
     /// filename is appended.
     ///
     /// Relative paths are resolved against the primary sandbox root (the
-    /// sandbox directory itself), not the process working directory. This
+    /// sandbox directory itself, or [`WriteSandbox::base`] for a subset), not
+    /// the process working directory. This
     /// ensures that tools providing sandbox-relative paths work correctly
     /// regardless of where the gateway process was launched.
     ///
@@ -387,21 +1535,49 @@ impl WriteSandbox {
     /// modified through the git skill's constrained tools, not through
     /// arbitrary file writes that bypass branch protection and hook safety.
     pub fn validate(&self, path: &str) -> Result<PathBuf, String> {
-        if self.writable_roots.is_empty() {
-            return Err("no writable roots configured (sandbox directory missing)".to_string());
+        let canonical = self.canonical_target(path)?;
+        match self.current().innermost(&canonical) {
+            Some((_, true)) => return Ok(canonical),
+            Some((_, false)) => {
+                return Err(format!("write path is read-only: {}", canonical.display()));
+            }
+            None => {}
+        }
+
+        Err(format!(
+            "write path outside sandbox: {}",
+            canonical.display()
+        ))
+    }
+
+    /// Validate a path for reading: like [`WriteSandbox::validate`], but read-only roots are
+    /// accepted too.
+    pub fn validate_read(&self, path: &str) -> Result<PathBuf, String> {
+        let canonical = self.canonical_target(path)?;
+        if self.root_for(&canonical).is_some() {
+            return Ok(canonical);
         }
+        Err(format!(
+            "read path outside sandbox: {}",
+            canonical.display()
+        ))
+    }
 
-        let target = Path::new(path);
+    /// Resolve `path` (anchored per [`WriteSandbox::base`]) to a canonical path that does not
+    /// target a `.git/` directory.
+    fn canonical_target(&self, path: &str) -> Result<PathBuf, String> {
+        let roots = self.current();
+        if roots.writable.is_empty() && roots.read_only.is_empty() {
+            return Err("no writable roots configured (sandbox directory missing)".to_string());
+        }
         // Relative paths must be anchored to the sandbox root, not the process
         // CWD. `std::fs::canonicalize` resolves relative paths against the
         // process CWD, which would cause validation to use the wrong base and
         // either incorrectly reject valid sandbox-relative paths or accept
         // paths that happen to exist relative to the gateway's launch directory.
-        let resolved_target = if target.is_relative() {
-            self.writable_roots[0].join(target)
-        } else {
-            target.to_path_buf()
-        };
+        let resolved_target = self
+            .anchor(Path::new(path))
+            .ok_or_else(|| format!("cannot resolve relative path without a sandbox base: {}", path))?;
         let canonical = Self::canonicalize_for_write(&resolved_target)?;
 
         // Reject writes that target a .git/ directory. The .git/ directory
@@ -415,27 +1591,27 @@ impl WriteSandbox {
                 canonical.display()
             ));
         }
-
-        for root in &self.writable_roots {
-            if canonical.starts_with(root) {
-                return Ok(canonical);
-            }
-        }
-
-        Err(format!(
-            "write path outside sandbox: {}",
-            canonical.display()
-        ))
+        Ok(canonical)
     }
 
     /// Returns true if this sandbox has at least one writable root.
     pub fn has_roots(&self) -> bool {
-        !self.writable_roots.is_empty()
+        !self.current().writable.is_empty()
     }
 
-    /// The writable roots (for diagnostics / status).
-    pub fn roots(&self) -> &[PathBuf] {
-        &self.writable_roots
+    /// The current writable roots (for diagnostics / status).
+    pub fn roots(&self) -> Vec<PathBuf> {
+        self.current().writable
+    }
+
+    /// The current read-only roots: `ro.` symlink targets, or a subset's `readOnly` entries.
+    pub fn read_only_roots(&self) -> Vec<PathBuf> {
+        self.current().read_only
+    }
+
+    /// The current writable and read-only roots.
+    pub fn root_set(&self) -> RootSet {
+        self.current().root_set()
     }
 
     /// Canonicalize a path for write validation. If the path doesn't exist yet,
@@ -479,6 +1655,143 @@ impl WriteSandbox {
     }
 }
 
+/// Disk usage counted against a [`SandboxQuota`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaUsage {
+    /// Total size of regular files, in bytes.
+    pub bytes: u64,
+    /// Entries (files, directories, and symlinks) below the counted directories.
+    pub files: u64,
+}
+
+/// Least time between two scans caused by write tool calls ([`SandboxQuota::record_write`]).
+const QUOTA_WRITE_RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Disk quota for content agents create (`sandbox.quota`): total bytes and entry count under the
+/// sandbox directory and the agents' scratch directories. Symlinks are counted but not followed,
+/// so directories granted by symlink do not count. Usage comes from a scan (periodically, and
+/// after commands of tools that may write, at most every [`QUOTA_WRITE_RESCAN_INTERVAL`]); clones
+/// share it.
+#[derive(Debug, Clone)]
+pub struct SandboxQuota {
+    dirs: Vec<PathBuf>,
+    max_bytes: Option<u64>,
+    max_files: Option<u64>,
+    state: Arc<RwLock<QuotaState>>,
+}
+
+#[derive(Debug, Default)]
+struct QuotaState {
+    usage: Option<QuotaUsage>,
+    /// When the last scan started.
+    scanned_at: Option<Instant>,
+    /// A write tool ran since the last scan started.
+    stale: bool,
+}
+
+impl QuotaState {
+    /// Whether a write since the last scan is due to be measured.
+    fn write_rescan_due(&self) -> bool {
+        self.stale && self.scanned_at.is_none_or(|at| at.elapsed() >= QUOTA_WRITE_RESCAN_INTERVAL)
+    }
+}
+
+impl SandboxQuota {
+    pub fn new(dirs: Vec<PathBuf>, max_bytes: Option<u64>, max_files: Option<u64>) -> Self {
+        Self {
+            dirs,
+            max_bytes,
+            max_files,
+            state: Arc::default(),
+        }
+    }
+
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.max_bytes
+    }
+
+    pub fn max_files(&self) -> Option<u64> {
+        self.max_files
+    }
+
+    /// Usage from the last scan (`None` before the first).
+    pub fn usage(&self) -> Option<QuotaUsage> {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).usage
+    }
+
+    /// Measure the counted directories and store the result.
+    pub fn scan(&self) -> QuotaUsage {
+        {
+            let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+            state.scanned_at = Some(Instant::now());
+            state.stale = false;
+        }
+        let usage = self.measure();
+        self.state.write().unwrap_or_else(|e| e.into_inner()).usage = Some(usage);
+        usage
+    }
+
+    /// Note that a tool that may write ran: scan now unless a scan started within
+    /// [`QUOTA_WRITE_RESCAN_INTERVAL`]; [`SandboxQuota::check`] picks up a deferred one.
+    pub fn record_write(&self) {
+        let due = {
+            let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+            state.stale = true;
+            state.write_rescan_due()
+        };
+        if due {
+            self.scan();
+        }
+    }
+
+    /// Walk the counted directories.
+    fn measure(&self) -> QuotaUsage {
+        let mut usage = QuotaUsage::default();
+        let mut pending: Vec<PathBuf> = self.dirs.clone();
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let Ok(meta) = entry.path().symlink_metadata() else {
+                    continue;
+                };
+                usage.files += 1;
+                if meta.is_dir() {
+                    pending.push(entry.path());
+                } else if meta.is_file() {
+                    usage.bytes += meta.len();
+                }
+            }
+        }
+        usage
+    }
+
+    /// Fail when the last measured usage is at or over a limit. Scans first when there is no
+    /// usage yet, or a write since the last scan is due to be measured.
+    pub fn check(&self) -> Result<(), String> {
+        let cached = {
+            let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+            state.usage.filter(|_| !state.write_rescan_due())
+        };
+        let usage = cached.unwrap_or_else(|| self.scan());
+        if let Some(max) = self.max_bytes.filter(|max| usage.bytes >= *max) {
+            return Err(format!(
+                "sandbox quota exceeded: {} bytes used of {} (sandbox.quota.maxBytes); delete files from the sandbox before writing more",
+                usage.bytes, max
+            ));
+        }
+        if let Some(max) = self.max_files.filter(|max| usage.files >= *max) {
+            return Err(format!(
+                "sandbox quota exceeded: {} files used of {} (sandbox.quota.maxFiles); delete files from the sandbox before writing more",
+                usage.files, max
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Check whether a canonical path intersects a `.git/` directory.
 ///
 /// Returns true if:
@@ -739,11 +2052,177 @@ mod sandbox_tests {
         cleanup(&base);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn symlink_changes_apply_without_rebuild() {
+        let (base, sandbox) = setup_sandbox("rescan");
+        let external = base.join("external-repo");
+        fs::create_dir_all(&external).expect("create external");
+        let file = external.join("src.rs");
+
+        let sb = WriteSandbox::new(&sandbox);
+        let shared = sb.clone();
+        assert!(sb.validate(file.to_str().unwrap()).is_err());
+
+        let link = sandbox.join("repo");
+        symlink(&external, &link).expect("create symlink");
+        let change = sb.refresh().expect("grant picked up");
+        assert_eq!(change.added, vec![fs::canonicalize(&external).unwrap()]);
+        assert!(change.removed.is_empty());
+        assert!(shared.validate(file.to_str().unwrap()).is_ok());
+        assert!(sb.refresh().is_none());
+
+        // Revocation takes effect on the next validation, without an explicit refresh.
+        fs::remove_file(&link).expect("remove symlink");
+        let err = shared.validate(file.to_str().unwrap()).unwrap_err();
+        assert!(err.contains("outside sandbox"), "{err}");
+        assert_eq!(sb.roots().len(), 1);
+
+        cleanup(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn renaming_a_grant_reports_its_access_change() {
+        let (base, sandbox) = setup_sandbox("rescan-access");
+        let external = fs::canonicalize({
+            let dir = base.join("external-repo");
+            fs::create_dir_all(&dir).expect("create external");
+            dir
+        })
+        .unwrap();
+        symlink(&external, sandbox.join("repo")).expect("create symlink");
+        let sb = WriteSandbox::new(&sandbox);
+
+        fs::rename(sandbox.join("repo"), sandbox.join("ro.repo")).expect("rename");
+        let change = sb.refresh().expect("access change picked up");
+        assert_eq!(change.made_read_only, vec![external.clone()]);
+        assert!(change.added.is_empty() && change.removed.is_empty() && change.made_writable.is_empty());
+        assert!(sb.validate("ro.repo/a.rs").unwrap_err().contains("read-only"));
+
+        fs::rename(sandbox.join("ro.repo"), sandbox.join("repo")).expect("rename back");
+        let change = sb.refresh().expect("access change picked up");
+        assert_eq!(change.made_writable, vec![external]);
+        assert!(sb.validate("repo/a.rs").is_ok());
+
+        cleanup(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn change_within_the_scanned_mtime_is_picked_up() {
+        let (base, sandbox) = setup_sandbox("rescan-same-mtime");
+        let external = base.join("external-repo");
+        fs::create_dir_all(&external).expect("create external");
+        let sb = WriteSandbox::new(&sandbox);
+        let scanned = fs::metadata(&sandbox).and_then(|m| m.modified()).expect("mtime");
+
+        // A symlink created in the same timestamp tick as the last scan leaves the time unchanged.
+        symlink(&external, sandbox.join("repo")).expect("create symlink");
+        fs::File::open(&sandbox)
+            .and_then(|dir| dir.set_modified(scanned))
+            .expect("reset mtime");
+        let change = sb.refresh().expect("grant picked up");
+        assert_eq!(change.added, vec![fs::canonicalize(&external).unwrap()]);
+
+        cleanup(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn subset_entry_removal_revokes_access() {
+        let (base, sandbox) = setup_sandbox("subset-rescan");
+        let external = base.join("notes");
+        fs::create_dir_all(&external).expect("create external");
+        fs::write(external.join("a.md"), "ref").expect("write");
+        symlink(&external, sandbox.join("notes")).expect("create symlink");
+        fs::create_dir_all(sandbox.join("repo")).expect("create repo");
+
+        let sb = WriteSandbox::subset(&sandbox, &["repo".to_string()], &["notes".to_string()], None).expect("subset");
+        assert!(sb.validate_read("notes/a.md").is_ok());
+
+        fs::remove_file(sandbox.join("notes")).expect("remove symlink");
+        let change = sb.refresh().expect("revocation picked up");
+        assert_eq!(change.removed, vec![fs::canonicalize(&external).unwrap()]);
+        assert!(sb.read_only_roots().is_empty());
+        assert!(sb.validate_read(external.join("a.md").to_str().unwrap()).is_err());
+        assert!(sb.validate("repo/src.rs").is_ok());
+
+        cleanup(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ro_prefix_symlink_grants_read_only_access() {
+        let (base, sandbox) = setup_sandbox("ro-prefix");
+        let reference = base.join("reference");
+        fs::create_dir_all(&reference).expect("create reference");
+        fs::write(reference.join("a.md"), "ref").expect("write");
+        symlink(&reference, sandbox.join("ro.reference")).expect("create symlink");
+        // A read-only grant inside the writable sandbox directory keeps its subtree read-only.
+        fs::create_dir_all(sandbox.join("notes")).expect("create notes");
+        symlink(sandbox.join("notes"), sandbox.join("ro.notes")).expect("create symlink");
+
+        let sb = WriteSandbox::new(&sandbox);
+        assert_eq!(sb.roots().len(), 1);
+        assert_eq!(sb.read_only_roots().len(), 2);
+
+        let err = sb.validate("ro.reference/a.md").unwrap_err();
+        assert!(err.contains("read-only"), "{err}");
+        assert!(sb.validate(reference.join("new.md").to_str().unwrap()).is_err());
+        assert!(sb.validate_read("ro.reference/a.md").is_ok());
+        assert!(sb.validate("notes/draft.md").unwrap_err().contains("read-only"));
+        assert!(sb.validate("draft.md").is_ok());
+
+        cleanup(&base);
+    }
+
+    #[test]
+    fn subset_limits_agent_to_granted_entries() {
+        let (base, sandbox) = setup_sandbox("subset");
+        for dir in ["notes", "repo", "private"] {
+            fs::create_dir_all(sandbox.join(dir)).expect("create entry");
+        }
+        fs::write(sandbox.join("notes").join("a.md"), "ref").expect("write");
+        let scratch = base.join("agents").join("research").join("scratch");
+        let sb = WriteSandbox::subset(
+            &sandbox,
+            &["repo".to_string()],
+            &["notes".to_string()],
+            Some(&scratch),
+        )
+        .expect("subset");
+        let scratch = fs::canonicalize(&scratch).expect("scratch created");
+        assert_eq!(sb.base(), Some(scratch.as_path()));
+        assert_eq!(sb.roots().len(), 2);
+
+        assert!(sb.validate("repo/src.rs").is_ok());
+        let err = sb.validate("notes/a.md").unwrap_err();
+        assert!(err.contains("read-only"), "{err}");
+        assert!(sb.validate_read("notes/a.md").is_ok());
+
+        // Unlisted entries are out of reach; other relative paths land in scratch.
+        let private = sandbox.join("private").join("s.txt");
+        assert!(sb.validate_read(private.to_str().unwrap()).unwrap_err().contains("read path outside sandbox"));
+        assert!(sb.validate("draft.md").unwrap().starts_with(&scratch));
+
+        cleanup(&base);
+    }
+
+    #[test]
+    fn subset_rejects_missing_entry() {
+        let (base, sandbox) = setup_sandbox("subset-missing");
+        let err = WriteSandbox::subset(&sandbox, &[], &["ghost".to_string()], None).unwrap_err();
+        assert!(err.contains("sandbox entry \"ghost\""), "{err}");
+
+        cleanup(&base);
+    }
+
     #[test]
     fn missing_sandbox_dir_has_no_roots() {
         let sb = WriteSandbox::new(Path::new("/nonexistent/chai/sandbox"));
         assert!(!sb.has_roots());
-        assert!(sb.writable_roots.is_empty());
+        assert!(sb.roots().is_empty());
 
         let result = sb.validate("/tmp/anything");
         assert!(result.is_err());
@@ -935,6 +2414,78 @@ mod sandbox_tests {
         assert!(!path_intersects_git_dir(Path::new("/home/user/project/.gitignore")));
         assert!(!path_intersects_git_dir(Path::new("/home/user/project/.gitmodules")));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn quota_scan_counts_entries_without_following_symlinks() {
+        let (base, sandbox) = setup_sandbox("quota-scan");
+        let outside = base.join("outside");
+        fs::create_dir_all(&outside).expect("create outside dir");
+        fs::write(outside.join("big.bin"), vec![0u8; 4096]).expect("write outside");
+        fs::create_dir_all(sandbox.join("notes")).expect("create notes");
+        fs::write(sandbox.join("notes/a.md"), "hello").expect("write a");
+        fs::write(sandbox.join("b.txt"), "abc").expect("write b");
+        symlink(&outside, sandbox.join("outside")).expect("symlink");
+
+        let quota = SandboxQuota::new(vec![sandbox.clone()], None, None);
+        assert!(quota.usage().is_none());
+        let usage = quota.scan();
+        assert_eq!(usage, QuotaUsage { bytes: 8, files: 4 });
+        assert_eq!(quota.usage(), Some(usage));
+
+        cleanup(&base);
+    }
+
+    #[test]
+    fn quota_check_fails_at_limit_until_rescanned() {
+        let (base, sandbox) = setup_sandbox("quota-check");
+        fs::write(sandbox.join("a.txt"), "0123456789").expect("write a");
+
+        assert!(SandboxQuota::new(vec![sandbox.clone()], Some(11), Some(2)).check().is_ok());
+        let err = SandboxQuota::new(vec![sandbox.clone()], Some(10), None)
+            .check()
+            .unwrap_err();
+        assert!(err.contains("10 bytes used of 10"), "{}", err);
+        assert!(err.contains("sandbox.quota.maxBytes"), "{}", err);
+
+        let quota = SandboxQuota::new(vec![sandbox.clone()], None, Some(2));
+        assert!(quota.check().is_ok());
+        fs::write(sandbox.join("b.txt"), "x").expect("write b");
+        // Usage is stale until the next scan.
+        assert!(quota.check().is_ok());
+        quota.scan();
+        let err = quota.check().unwrap_err();
+        assert!(err.contains("sandbox.quota.maxFiles"), "{}", err);
+
+        cleanup(&base);
+    }
+
+    #[test]
+    fn quota_write_rescans_are_spaced_out() {
+        let (base, sandbox) = setup_sandbox("quota-writes");
+        let quota = SandboxQuota::new(vec![sandbox.clone()], None, Some(2));
+        quota.scan();
+
+        // A write right after a scan is measured later, not at once.
+        fs::write(sandbox.join("a.txt"), "a").expect("write a");
+        fs::write(sandbox.join("b.txt"), "b").expect("write b");
+        quota.record_write();
+        assert_eq!(quota.usage().unwrap().files, 0);
+        assert!(quota.check().is_ok());
+
+        // Once the interval has passed, the next check measures it.
+        quota.state.write().unwrap().scanned_at = Some(Instant::now() - QUOTA_WRITE_RESCAN_INTERVAL);
+        let err = quota.check().unwrap_err();
+        assert!(err.contains("2 files used of 2"), "{}", err);
+
+        // And so does the next write.
+        fs::write(sandbox.join("c.txt"), "c").expect("write c");
+        quota.state.write().unwrap().scanned_at = Some(Instant::now() - QUOTA_WRITE_RESCAN_INTERVAL);
+        quota.record_write();
+        assert_eq!(quota.usage().unwrap().files, 3);
+
+        cleanup(&base);
+    }
 }
 
 #[cfg(test)]
@@ -1005,3 +2556,256 @@ mod collect_output_tests {
         assert!(result.is_err());
     }
 }
+
+#[cfg(test)]
+#[cfg(unix)]
+mod limits_tests {
+    use super::*;
+
+    fn sh(script: &str, limits: ExecLimits) -> Result<(i32, String), String> {
+        let mut allowlist = Allowlist::new();
+        allowlist.allow("sh", vec!["-c"]);
+        allowlist.run_with_options(
+            "sh",
+            "-c",
+            &[script.to_string()],
+            &RunOptions {
+                limits,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn timeout_kills_process_group() {
+        // The background sleep keeps stdout open; only killing the whole group lets the
+        // readers finish before it would exit on its own.
+        let started = Instant::now();
+        let err = sh(
+            "echo started; sleep 30 & sleep 30",
+            ExecLimits {
+                timeout: Some(Duration::from_secs(1)),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(10), "took {:?}", started.elapsed());
+        assert!(err.starts_with("timed out after 1 s"), "{err}");
+        assert!(err.contains("started"), "{err}");
+    }
+
+    #[test]
+    fn exited_command_is_not_held_up_by_background_processes() {
+        let limits = ExecLimits {
+            timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        assert_eq!(sh("sleep 100 & echo hi", limits), Ok((0, "hi\n".to_string())));
+        assert!(started.elapsed() < Duration::from_secs(5), "took {:?}", started.elapsed());
+        // A process in its own session escapes the group kill; its pipes are abandoned instead.
+        if find_program("setsid").is_some() {
+            let started = Instant::now();
+            assert_eq!(sh("setsid sleep 100 & echo hi", limits), Ok((0, "hi\n".to_string())));
+            assert!(started.elapsed() < Duration::from_secs(5), "took {:?}", started.elapsed());
+        }
+    }
+
+    #[test]
+    fn output_is_capped_while_reading() {
+        let (code, text) = sh(
+            "head -c 100000 /dev/zero | tr '\\0' x",
+            ExecLimits {
+                max_output_bytes: Some(1000),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(code, 0);
+        assert!(text.starts_with(&"x".repeat(1000)), "{}", &text[..20]);
+        assert!(text.ends_with("[output capped at 1000 bytes; 99000 bytes discarded]"), "{text}");
+        assert!(text.len() < 1100);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn resource_limits_are_set_on_the_child() {
+        let limits = ExecLimits {
+            timeout: Some(Duration::from_secs(20)),
+            resources: ResourceLimits {
+                cpu_secs: Some(1),
+                open_files: Some(64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (_, text) = sh("ulimit -n; ulimit -t", limits).unwrap();
+        assert_eq!(text, "64\n1\n");
+        // A busy loop is stopped by the CPU limit long before the timeout.
+        let err = sh("while :; do :; done", limits).unwrap_err();
+        assert!(!err.starts_with("timed out"), "{err}");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn file_size_limit_stops_large_writes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let target = dir.path().join("big");
+        let limits = ExecLimits {
+            resources: ResourceLimits {
+                file_size_bytes: Some(4096),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let script = format!("head -c 100000 /dev/zero > {}", target.display());
+        assert!(sh(&script, limits).is_err());
+        assert!(std::fs::metadata(&target).map(|m| m.len()).unwrap_or(0) <= 4096);
+    }
+
+    #[test]
+    fn no_limits_keeps_full_output() {
+        let (_, text) = sh("echo out; echo err >&2", ExecLimits::default()).unwrap();
+        assert_eq!(text, "out\n\nerr\n");
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod env_tests {
+    use super::*;
+    use crate::profile::env_guard;
+
+    fn sh(script: &str, env: Option<&EnvPolicy>) -> String {
+        let mut allowlist = Allowlist::new();
+        allowlist.allow("sh", vec!["-c"]);
+        allowlist
+            .run_with_options(
+                "sh",
+                "-c",
+                &[script.to_string()],
+                &RunOptions {
+                    env,
+                    ..Default::default()
+                },
+            )
+            .expect("run")
+            .1
+    }
+
+    #[test]
+    fn command_gets_only_baseline_by_default() {
+        let _env = env_guard::set_raw("CHAI_TEST_ENV_SECRET", "s3cret");
+        let out = sh("printenv", None);
+        assert!(!out.contains("s3cret"), "{}", out);
+        assert!(out.contains("PATH="), "{}", out);
+    }
+
+    #[test]
+    fn policy_passes_named_variables_and_sets_fixed_values() {
+        let _env = env_guard::set_all(&[
+            ("CHAI_TEST_ENV_PASSED", "passed"),
+            ("CHAI_TEST_ENV_HIDDEN", "hidden"),
+        ]);
+        let env = EnvPolicy {
+            pass: vec!["CHAI_TEST_ENV_PASSED".to_string(), "CHAI_TEST_ENV_UNSET".to_string()],
+            set: BTreeMap::from([("CHAI_TEST_ENV_FIXED".to_string(), "fixed".to_string())]),
+        };
+        let out = sh(
+            "echo \"$CHAI_TEST_ENV_PASSED/$CHAI_TEST_ENV_FIXED/$CHAI_TEST_ENV_HIDDEN\"",
+            Some(&env),
+        );
+        assert_eq!(out.trim(), "passed/fixed/");
+        let passed = env.passed();
+        assert!(passed.contains(&"CHAI_TEST_ENV_PASSED".to_string()));
+        assert!(!passed.contains(&"CHAI_TEST_ENV_UNSET".to_string()));
+    }
+
+    #[test]
+    fn set_overrides_passed_and_spec_combines_with_profile() {
+        let profile = EnvPolicy {
+            pass: vec!["SSH_AUTH_SOCK".to_string()],
+            set: BTreeMap::from([
+                ("GIT_TERMINAL_PROMPT".to_string(), "0".to_string()),
+                ("LANG".to_string(), "C.UTF-8".to_string()),
+            ]),
+        };
+        let spec = EnvPolicy {
+            pass: vec!["SSH_AUTH_SOCK".to_string(), "GIT_SSH_COMMAND".to_string()],
+            set: BTreeMap::from([("GIT_TERMINAL_PROMPT".to_string(), "1".to_string())]),
+        };
+        let merged = spec.or(&profile);
+        assert_eq!(merged.pass, vec!["SSH_AUTH_SOCK", "GIT_SSH_COMMAND"]);
+        assert_eq!(merged.set["GIT_TERMINAL_PROMPT"], "1");
+        assert_eq!(merged.set["LANG"], "C.UTF-8");
+        assert!(!merged.passed().contains(&"LANG".to_string()));
+        assert_eq!(merged.resolve()["LANG"], OsString::from("C.UTF-8"));
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod kernel_tests {
+    use super::*;
+
+    fn sh(script: &str, dir: &Path, policy: &KernelPolicy) -> Result<(i32, String), String> {
+        let mut allowlist = Allowlist::new();
+        allowlist.allow("sh", vec!["-c"]);
+        allowlist.run_with_options(
+            "sh",
+            "-c",
+            &[script.to_string()],
+            &RunOptions {
+                working_dir: Some(dir),
+                kernel: Some(policy),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn system_policy(writable: Vec<PathBuf>) -> KernelPolicy {
+        KernelPolicy {
+            writable,
+            readable: KernelPolicy::SYSTEM_READ_PATHS.iter().map(PathBuf::from).collect(),
+            deny_tcp_connect: false,
+        }
+    }
+
+    #[test]
+    fn landlock_confines_writes_and_reads() {
+        if landlock_abi().is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().expect("tempdir");
+        let allowed = dir.path().join("allowed");
+        let other = dir.path().join("other");
+        std::fs::create_dir_all(&allowed).unwrap();
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("secret"), "s").unwrap();
+        let policy = system_policy(vec![allowed.clone()]);
+
+        let (_, out) = sh("echo hi > a && cat a", &allowed, &policy).unwrap();
+        assert_eq!(out, "hi\n");
+        assert!(sh(&format!("echo hi > {}/b", other.display()), &allowed, &policy).is_err());
+        assert!(!other.join("b").exists());
+        assert!(sh(&format!("cat {}/secret", other.display()), &allowed, &policy).is_err());
+    }
+
+    #[test]
+    fn landlock_readable_paths_are_not_writable() {
+        if landlock_abi().is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().expect("tempdir");
+        let scratch = dir.path().join("scratch");
+        let docs = dir.path().join("docs");
+        std::fs::create_dir_all(&scratch).unwrap();
+        std::fs::create_dir_all(&docs).unwrap();
+        std::fs::write(docs.join("readme"), "docs").unwrap();
+        let policy = system_policy(vec![scratch.clone()]).with_readable([docs.clone()]);
+
+        let (_, out) = sh(&format!("cat {}/readme", docs.display()), &scratch, &policy).unwrap();
+        assert_eq!(out, "docs");
+        assert!(sh(&format!("rm {}/readme", docs.display()), &scratch, &policy).is_err());
+        assert!(docs.join("readme").exists());
+    }
+}
@@ -13,6 +13,10 @@
 //! **`maxDelegationsPerSession`**, **`maxDelegationsPerWorker`**; **`null`** for workers).
 //! Heavy per-agent data (**`systemContext`**, **`tools`**, **`skillsContext`**) is available via the
 //! on-demand **`agentDetail`** method, not included in the polling status response.
+//!
+//! **Tool approval**: **`approvals.list`** returns **`approvals`** (pending calls in request order; **`live`** is false for
+//! records restored after a restart) and **`approvals.decide`** resolves one. The **`approval.requested`** and
+//! **`approval.resolved`** events (see [`crate::approval`]) keep clients in sync without polling.
 //! Per-provider model lists live under **`payload.providers.<id>.models`** (each a flat string array); **`modelDiscovery`** is the discovery method (`"auto"`, `"lmstudio"`, `"static"`).
 use serde::{Deserialize, Serialize};
 
@@ -150,6 +154,39 @@ pub struct AgentParams {
     pub orchestrator_id: Option<String>,
 }
 
+/// Params for WS method "continue": resume a turn that hit `maxToolLoopsPerTurn` by running its
+/// pending tool calls (no new user message). Provider and model overrides work as for "agent".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinueParams {
+    pub session_id: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub orchestrator_id: Option<String>,
+}
+
+/// Params for WS method "agent.regenerate": roll the session back to a user message and rerun the
+/// turn from there. The removed messages are archived on the session. When `messageIndex` is
+/// omitted the last user message is used; `message` replaces that user message's text (edit).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegenerateParams {
+    pub session_id: String,
+    #[serde(default)]
+    pub message_index: Option<usize>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub orchestrator_id: Option<String>,
+}
+
 /// Params for WS method "stop": signal the agent to stop the current turn after the current iteration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -176,6 +213,60 @@ pub struct SessionsHistoryParams {
     pub offset: Option<usize>,
 }
 
+/// Params for WS method "sessions.truncate_after": keep messages up to and including
+/// `messageIndex` and archive the rest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionsTruncateAfterParams {
+    pub session_id: String,
+    pub message_index: usize,
+}
+
+/// Params for WS method "sessions.transfer": move (default) or copy a session to another
+/// orchestrator, optionally with a summary for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionsTransferParams {
+    pub session_id: String,
+    pub orchestrator_id: String,
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+/// Params for WS method "sessions.delegation": load the worker transcript of one delegation
+/// made in `sessionId`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionsDelegationParams {
+    pub session_id: String,
+    pub delegation_id: String,
+}
+
+/// Params for WS method "delegation.cancel": stop a running delegation; the orchestrator
+/// receives a "cancelled by operator" tool result and continues its turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationCancelParams {
+    pub delegation_id: String,
+}
+
+/// Params for WS method "workflow.run": run a configured workflow for an orchestrator. The
+/// result is appended to `sessionId` (a new session when omitted) as an assistant message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowRunParams {
+    pub workflow_id: String,
+    /// Workflow inputs by name; values must be strings.
+    #[serde(default)]
+    pub inputs: serde_json::Value,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub orchestrator_id: Option<String>,
+}
+
 /// Params for WS method "sessions.delete": delete a session by id.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -201,6 +292,27 @@ pub struct SessionsDeleteAllParams {
     pub orchestrator_id: Option<String>,
 }
 
+/// Params for WS method "approvals.list": pending tool call approvals, optionally for one session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalsListParams {
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+/// Params for WS method "approvals.decide": approve or deny one pending tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalsDecideParams {
+    /// Approval id from `approvals.list` or the `approval.requested` event.
+    pub id: String,
+    /// `"approve"` or `"deny"`.
+    pub decision: String,
+    /// Optional denial reason, included in the tool result the model receives.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
 impl WsResponse {
     pub fn ok(id: impl Into<String>, payload: serde_json::Value) -> Self {
         Self {
@@ -236,6 +348,21 @@ mod tests {
         assert_eq!(params.offset, None);
     }
 
+    #[test]
+    fn regenerate_params_defaults() {
+        let json = r#"{ "sessionId": "sess-abc" }"#;
+        let params: RegenerateParams = serde_json::from_str(json).expect("deserialize");
+        assert_eq!(params.session_id, "sess-abc");
+        assert_eq!(params.message_index, None);
+        assert_eq!(params.message, None);
+
+        let json = r#"{ "sessionId": "sess-abc", "messageIndex": 2, "message": "edited", "model": "m" }"#;
+        let params: RegenerateParams = serde_json::from_str(json).expect("deserialize");
+        assert_eq!(params.message_index, Some(2));
+        assert_eq!(params.message.as_deref(), Some("edited"));
+        assert_eq!(params.model.as_deref(), Some("m"));
+    }
+
     #[test]
     fn sessions_history_params_with_limit_offset() {
         let json = r#"{ "sessionId": "sess-abc", "limit": 10, "offset": 5 }"#;
@@ -245,6 +372,15 @@ mod tests {
         assert_eq!(params.offset, Some(5));
     }
 
+    #[test]
+    fn approvals_decide_params_deserialize() {
+        let json = r#"{ "id": "appr-1", "decision": "deny", "reason": "wrong file" }"#;
+        let params: ApprovalsDecideParams = serde_json::from_str(json).expect("deserialize");
+        assert_eq!(params.id, "appr-1");
+        assert_eq!(params.decision, "deny");
+        assert_eq!(params.reason.as_deref(), Some("wrong file"));
+    }
+
     #[test]
     fn sessions_delete_params_deserialize() {
         let json = r#"{ "sessionId": "sess-xyz" }"#;
@@ -2,6 +2,10 @@
 
 use crate::agent;
 use crate::agent_ctx;
+use crate::approval::{
+    denial_result, ApprovalDecision, ApprovalGate, ApprovalScope, ApprovalStore, PendingApproval,
+    INTERRUPTED_RESULT,
+};
 #[cfg(feature = "matrix")]
 use crate::channels::{connect_matrix_client, MatrixChannel};
 #[cfg(feature = "signal")]
@@ -20,17 +24,20 @@ use crate::config::{
 use crate::gateway::matrix_routes;
 use crate::gateway::pairing::PairingStore;
 use crate::gateway::protocol::{
-    AgentDetailParams, AgentParams, ConnectDevice, ConnectParams, HelloAuth, HelloOk,
-    SendParams, SessionsDeleteAllParams, SessionsDeleteParams, SessionsHistoryParams, SessionsListParams, StopParams,
-    WsRequest, WsResponse,
+    AgentDetailParams, AgentParams, ApprovalsDecideParams, ApprovalsListParams, ConnectDevice, ContinueParams,
+    ConnectParams, HelloAuth, HelloOk, RegenerateParams, SendParams, SessionsDeleteAllParams, SessionsDeleteParams, SessionsDelegationParams, DelegationCancelParams,
+    SessionsHistoryParams, SessionsListParams, SessionsTransferParams, SessionsTruncateAfterParams, StopParams,
+    WorkflowRunParams, WsRequest, WsResponse,
 };
 use crate::init;
 use crate::orchestration::{
     build_workers_context, effective_worker_defaults,
-    merge_delegate_task, resolve_model,
+    is_delegation_handle_tool, is_workflow_tool, merge_delegate_many, merge_delegate_task, merge_delegation_handle_tools, merge_workflow_tools, model_route, resolve_model, run_workflow, tier_candidates, validate_workflows, workflow_inputs,
     resolve_orchestrator_provider_choice, resolve_provider_choice, worker_tool_list, DelegateContext,
-    DelegateObservability, OrchestratorRuntime, ProviderChoice, ProviderClients,
-    WorkerDelegateRuntime,
+    DelegateObservability, DelegationCancels, DelegationTurn, OrchestratorRuntime, ProviderChoice, ProviderClients,
+    ModelRoute, RouteReason, RouteStep, WorkerDelegateRuntime, DELEGATE_MANY_TOOL_NAME, DELEGATE_TASK_TOOL_NAME,
+    EVENT_MODEL_ROUTE, EVENT_SESSION_TRANSFERRED, HANDOFF_TOOL_NAME, HandoffRequest, TransferMode,
+    merge_handoff_tool, requested_handoff,
 };
 use crate::profile::{self, ChaiPaths};
 use crate::providers::{
@@ -40,7 +47,7 @@ use crate::providers::{
 use crate::routing::SessionBindingStore;
 use crate::session::SessionStore;
 use crate::skills::{load_skills, validate_skill_composition, Skill, SkillEntry};
-use crate::tools::GenericToolExecutor;
+use crate::tools::{GenericToolExecutor, HookedExecutor, ToolHooks};
 use anyhow::{Context, Result};
 use axum::{
     body::Bytes,
@@ -56,6 +63,7 @@ use axum::{
 use serde::Deserialize;
 use serde_json::json;
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::broadcast;
@@ -292,13 +300,24 @@ pub struct GatewayState {
     pub skills_lock_generation: Option<u64>,
     /// Number of skills pinned in the lockfile (0 when no lockfile exists).
     pub skills_locked_count: usize,
-    /// Number of writable roots in the sandbox (0 when sandbox is missing).
-    pub sandbox_roots_count: usize,
+    /// The profile sandbox (`None` when missing in `unsafe` mode); its roots follow symlink
+    /// changes in the sandbox directory.
+    pub sandbox: Option<crate::exec::WriteSandbox>,
+    /// Disk quota over the sandbox and scratch directories, when `sandbox.quota` sets a limit.
+    pub sandbox_quota: Option<crate::exec::SandboxQuota>,
+    /// Per-agent subset sandboxes by agent id.
+    pub agent_sandboxes: Arc<Vec<(String, crate::exec::WriteSandbox)>>,
     /// Per-session stop flags. When set, the agent loop breaks after the current iteration.
     /// The flag is cleared at the start of each new turn.
     pub session_stop_flags: Arc<RwLock<HashMap<String, Arc<AtomicBool>>>>,
     /// Tracks authenticated WebSocket connections for maxConnections enforcement.
     pub connection_tracker: Arc<ConnectionTracker>,
+    /// Tool calls waiting for operator approval (persisted to `approvals.json`).
+    pub approvals: Arc<ApprovalStore>,
+    /// Profile root; worker transcripts are saved under `agents/<workerId>/sessions/`.
+    pub profile_dir: PathBuf,
+    /// Running delegations that `delegation.cancel` can stop.
+    pub delegation_cancels: DelegationCancels,
 }
 
 /// Per-provider runtime state: discovered model name list.
@@ -316,7 +335,7 @@ struct ReadOnDemandExecutor {
 }
 
 impl agent::ToolExecutor for ReadOnDemandExecutor {
-    fn execute(&self, name: &str, args: &serde_json::Value, session_id: Option<&str>) -> Result<String, String> {
+    fn execute(&self, name: &str, args: &serde_json::Value, ctx: agent::ToolCallContext<'_>) -> Result<String, String> {
         if name == "read_skill" {
             let obj = args
                 .as_object()
@@ -332,9 +351,21 @@ impl agent::ToolExecutor for ReadOnDemandExecutor {
                 .ok_or_else(|| format!("unknown skill: {}", skill_name))?;
             Ok(strip_skill_frontmatter(&skill.content).to_string())
         } else {
-            self.inner.execute(name, args, session_id)
+            self.inner.execute(name, args, ctx)
+        }
+    }
+
+    fn working_dir(&self, name: &str, args: &serde_json::Value) -> Result<Option<std::path::PathBuf>, String> {
+        if name == "read_skill" {
+            Ok(None)
+        } else {
+            self.inner.working_dir(name, args)
         }
     }
+
+    fn kernel_policy(&self) -> Option<crate::exec::KernelPolicy> {
+        self.inner.kernel_policy()
+    }
 }
 
 impl GatewayState {
@@ -355,6 +386,70 @@ impl GatewayState {
             .ok_or_else(|| format!("unknown orchestrator id: {id}"))
     }
 
+    /// Delegation context for one orchestrator turn in `session_id`.
+    fn delegate_context(
+        &self,
+        orch_config: &config::OrchestratorConfig,
+        orch_rt: &OrchestratorRuntime,
+        worker_tools: Option<Vec<ToolDefinition>>,
+        session_store: &Arc<SessionStore>,
+        session_id: &str,
+        stop_flag: Arc<AtomicBool>,
+    ) -> DelegateContext {
+        let system_context = &orch_rt.system_context;
+        DelegateContext {
+            clients: self.provider_clients.clone(),
+            config: self.config.clone(),
+            orchestrator_id: Some(orch_config.id.clone()),
+            orchestrator_system_context: if system_context.trim().is_empty() {
+                None
+            } else {
+                Some(Arc::new(system_context.clone()))
+            },
+            orchestrator_worker_tools: worker_tools.map(Arc::new),
+            orchestrator_tool_executor: orch_rt.tool_executor.clone(),
+            worker_runtimes: Some(self.worker_delegate_runtimes.clone()),
+            observability: Some(DelegateObservability {
+                event_tx: self.event_tx.clone(),
+                session_id: Some(session_id.to_string()),
+                orchestrator_id: Some(orch_config.id.clone()),
+                source: Some("orchestrator".to_string()),
+                tool_index_offset: 0,
+                delegation_id: None,
+                parent_delegation_id: None,
+                tool_index_counter: None,
+                last_tool_index: AtomicUsize::new(0),
+            }),
+            session_store: Some(session_store.clone()),
+            session_id: Some(session_id.to_string()),
+            stop_flag: Some(stop_flag),
+            turn: DelegationTurn::default(),
+            result_message_index: None,
+            depth: 0,
+            delegating_worker: None,
+            approval: Some(ApprovalScope {
+                store: self.approvals.clone(),
+                gate: orch_rt.approval.clone(),
+                agent_id: orch_config.id.clone(),
+                session_id: Some(session_id.to_string()),
+                orchestrator_id: Some(orch_config.id.clone()),
+            }),
+            profile_dir: Some(self.profile_dir.clone()),
+            cancels: Some(self.delegation_cancels.clone()),
+            redact_tool_output: false,
+            tool_calls_in_flight: None,
+        }
+    }
+
+    /// Whether a turn on `provider` redacts skill tool output (the profile's `redaction` scope).
+    /// A turn on a tier `route` redacts when any of its tiers does (see
+    /// [`ModelRoute::redacts_tool_output`]).
+    fn redacts_tool_output(&self, provider: &ProviderChoice, route: Option<&ModelRoute>) -> bool {
+        let (redaction, providers) = (&self.config.redaction, &self.config.providers);
+        redaction.applies_to(providers, provider.as_str())
+            || route.is_some_and(|r| r.redacts_tool_output(redaction, providers))
+    }
+
     /// Look up the session store for an orchestrator. Returns the default orchestrator's
     /// session store when `id` is `None`.
     pub fn session_store_for(&self, orchestrator_id: Option<&str>) -> Result<&Arc<SessionStore>, String> {
@@ -474,6 +569,7 @@ fn build_system_context(
     agents: &config::AgentsConfig,
     skill_catalog: &[SkillEntry],
     enabled_workers: Option<&[String]>,
+    max_parallel_workflows: usize,
 ) -> String {
     let mut out = String::new();
     if let Some(ctx) = agent_ctx {
@@ -483,7 +579,7 @@ fn build_system_context(
             out.push_str("\n\n");
         }
     }
-    let workers_ctx = build_workers_context(agents, skill_catalog, enabled_workers);
+    let workers_ctx = build_workers_context(agents, skill_catalog, enabled_workers, max_parallel_workflows);
     if !workers_ctx.trim().is_empty() {
         out.push_str(&workers_ctx);
     }
@@ -521,16 +617,91 @@ fn build_worker_system_context(
     out
 }
 
+/// Write sandbox for one agent: the shared profile sandbox, or the subset its `sandbox` block
+/// grants (see [`crate::exec::WriteSandbox::subset`]), which is also added to `subsets`.
+fn agent_sandbox(
+    paths: &ChaiPaths,
+    agent_id: &str,
+    subset: Option<&config::AgentSandboxConfig>,
+    shared: &Option<crate::exec::WriteSandbox>,
+    subsets: &mut Vec<(String, crate::exec::WriteSandbox)>,
+) -> anyhow::Result<Option<crate::exec::WriteSandbox>> {
+    let Some(subset) = subset else {
+        return Ok(shared.clone());
+    };
+    let scratch = subset
+        .scratch
+        .then(|| config::scratch_dir(&paths.profile_dir, agent_id));
+    let sandbox = crate::exec::WriteSandbox::subset(
+        &paths.sandbox_dir(),
+        &subset.writable,
+        &subset.read_only,
+        scratch.as_deref(),
+    )
+    .map_err(|e| anyhow::anyhow!("invalid sandbox for agent {}: {}", agent_id, e))?;
+    log::info!(
+        "agent {} sandbox: {} writable, {} read-only root(s)",
+        agent_id,
+        sandbox.roots().len(),
+        sandbox.read_only_roots().len()
+    );
+    subsets.push((agent_id.to_string(), sandbox.clone()));
+    Ok(Some(sandbox))
+}
+
+/// Tier route for an agent with `modelRouting: "tier"` (`None` for static routing), over the
+/// models of `enabled_providers` (all providers when `None` or empty).
+fn agent_model_route(
+    config: &Config,
+    agent_id: &str,
+    routing: Option<config::ModelRouting>,
+    enabled_providers: Option<&[String]>,
+    entries: &[SkillEntry],
+) -> anyhow::Result<Option<ModelRoute>> {
+    if routing != Some(config::ModelRouting::Tier) {
+        return Ok(None);
+    }
+    let candidates = tier_candidates(&config.providers, enabled_providers);
+    let route = model_route(&candidates, entries)
+        .map_err(|e| anyhow::anyhow!("modelRouting for agent {}: {}", agent_id, e))?;
+    let steps: Vec<String> = route
+        .steps
+        .iter()
+        .map(|s| format!("{}={}/{}", s.tier.as_str(), s.provider_id, s.model))
+        .collect();
+    log::info!(
+        "agent {} model routing: requires {}, route {}",
+        agent_id,
+        route.required.as_str(),
+        steps.join(" -> ")
+    );
+    Ok(Some(route))
+}
+
 struct BuiltSkillRuntime {
     skills: Vec<Skill>,
     tools_list: Option<Vec<ToolDefinition>>,
     tool_executor: Option<Arc<dyn agent::ToolExecutor>>,
+    /// Tools gated under the `approve_writes` approval policy.
+    write_tools: HashSet<String>,
+}
+
+/// Profile-wide settings shared by every agent's skill tool executor.
+struct ExecutorSettings<'a> {
+    exec: &'a config::ExecConfig,
+    sandbox_config: &'a config::SandboxConfig,
+    quota: Option<&'a crate::exec::SandboxQuota>,
+    secrets: &'a Arc<crate::secrets::Secrets>,
+    /// Profile redaction rules, applied to calls of turns whose provider is in `redaction` scope.
+    redaction: &'a Arc<crate::redaction::Redactor>,
 }
 
 fn build_skill_runtime_for_entries(
     skill_entries: Vec<SkillEntry>,
     context_mode: SkillContextMode,
     sandbox: Option<crate::exec::WriteSandbox>,
+    hooks: Option<&Arc<ToolHooks>>,
+    settings: &ExecutorSettings,
 ) -> BuiltSkillRuntime {
     let skills: Vec<Skill> = skill_entries.iter().map(Skill::from).collect();
     let descriptors: Vec<(String, crate::skills::ToolDescriptor)> = skill_entries
@@ -549,8 +720,16 @@ fn build_skill_runtime_for_entries(
                 .map(|_| (e.name.clone(), e.path.clone()))
         })
         .collect();
-    let generic_executor =
-        GenericToolExecutor::from_descriptors(&descriptors, &skill_dirs, sandbox);
+    let write_tools: HashSet<String> = descriptors
+        .iter()
+        .flat_map(|(_, d)| d.write_tool_names())
+        .collect();
+    let generic_executor = GenericToolExecutor::from_descriptors(&descriptors, &skill_dirs, sandbox)
+        .with_exec_config(settings.exec.clone())
+        .with_kernel_sandbox(settings.sandbox_config)
+        .with_quota(settings.quota.cloned())
+        .with_secrets(Arc::clone(settings.secrets))
+        .with_redaction(Arc::clone(settings.redaction));
     let mut skill_layer_tools: Vec<ToolDefinition> = Vec::new();
     if context_mode == SkillContextMode::ReadOnDemand && !skills.is_empty() {
         skill_layer_tools.push(read_skill_tool_definition());
@@ -571,6 +750,12 @@ fn build_skill_runtime_for_entries(
     } else {
         None
     };
+    let tool_executor = match (tool_executor, hooks) {
+        (Some(inner), Some(hooks)) => {
+            Some(Arc::new(HookedExecutor::new(inner, Arc::clone(hooks))) as Arc<dyn agent::ToolExecutor>)
+        }
+        (executor, _) => executor,
+    };
     let tools_list = if skill_layer_tools.is_empty() {
         None
     } else {
@@ -580,6 +765,7 @@ fn build_skill_runtime_for_entries(
         skills,
         tools_list,
         tool_executor,
+        write_tools,
     }
 }
 
@@ -604,6 +790,92 @@ fn read_skill_tool_definition() -> ToolDefinition {
     }
 }
 
+/// Unanswered tool calls of the session's last assistant response: the calls and the number
+/// already answered by the `tool` messages that follow it. `None` when every call has a result.
+fn unanswered_tool_calls(messages: &[crate::session::SessionMessage]) -> Option<(&[crate::providers::ToolCall], usize)> {
+    let pos = messages.iter().rposition(|m| m.role == "assistant")?;
+    let calls = messages[pos].tool_calls.as_deref().filter(|c| !c.is_empty())?;
+    let answered = messages[pos + 1..].iter().take_while(|m| m.role == "tool").count();
+    (answered < calls.len()).then_some((calls, answered))
+}
+
+/// Resolve an approval restored from disk (no running turn waits on it). For orchestrator calls,
+/// run the tool when approved (or record the denial) and append its `tool` result to the session;
+/// every other unanswered call of the same response gets [`INTERRUPTED_RESULT`], so the transcript
+/// stays valid and the operator continues by sending a new message. Worker calls cannot be
+/// resumed (the worker transcript was in memory only) and are only removed. Returns the call's
+/// result, or `None` when the session no longer waits on it.
+async fn resolve_restored_approval(
+    state: &GatewayState,
+    record: &PendingApproval,
+    decision: &ApprovalDecision,
+) -> Option<String> {
+    let orch_id = record.orchestrator_id.as_deref()?;
+    if record.agent_id != orch_id {
+        return None;
+    }
+    let session_id = record.session_id.as_deref()?;
+    let store = state.session_store_for(Some(orch_id)).ok()?;
+    let session = store.get(session_id).await?;
+    let (calls, answered) = unanswered_tool_calls(&session.messages)?;
+    let calls = calls.to_vec();
+    let target = (answered..calls.len()).find(|&i| {
+        calls[i].function.name == record.tool_name && calls[i].function.arguments == record.arguments
+    })?;
+    let result = match decision {
+        ApprovalDecision::Deny(reason) => denial_result(reason.as_deref()),
+        ApprovalDecision::Approve
+            if record.tool_name == DELEGATE_TASK_TOOL_NAME
+                || record.tool_name == DELEGATE_MANY_TOOL_NAME
+                || is_workflow_tool(&record.tool_name)
+                || is_delegation_handle_tool(&record.tool_name)
+                || record.tool_name == HANDOFF_TOOL_NAME =>
+        {
+            format!("error: {} cannot be resumed after a gateway restart", record.tool_name)
+        }
+        ApprovalDecision::Approve => {
+            let orch_rt = state.orchestrator_runtime(Some(orch_id)).ok();
+            // No turn is running; the result is read next by the orchestrator's default provider.
+            let redact_output = match (state.config.agents.orchestrator(Some(orch_id)), orch_rt) {
+                (Ok(orch_config), Some(orch_rt)) => {
+                    let (provider_choice, _, route) = orchestrator_turn_model(state, orch_config, orch_rt, None, None);
+                    state.redacts_tool_output(&provider_choice, route)
+                }
+                _ => false,
+            };
+            match orch_rt.and_then(|rt| rt.tool_executor.clone()) {
+                Some(exec) => {
+                    // Tool commands block; keep them off the WebSocket handler's task.
+                    let (name, args, sid) =
+                        (record.tool_name.clone(), record.arguments.clone(), session_id.to_string());
+                    tokio::task::spawn_blocking(move || {
+                        let ctx = agent::ToolCallContext {
+                            session_id: Some(&sid),
+                            redact_output,
+                        };
+                        exec.execute(&name, &args, ctx)
+                    })
+                        .await
+                        .unwrap_or_else(|e| Err(format!("tool task failed: {}", e)))
+                        .unwrap_or_else(|e| format!("error: {}", e))
+                }
+                None => format!("error: no executor for tool {}", record.tool_name),
+            }
+        }
+    };
+    for (i, call) in calls.iter().enumerate().skip(answered) {
+        let content = if i == target { result.as_str() } else { INTERRUPTED_RESULT };
+        if let Err(e) = store
+            .append_message_full(session_id, "tool", content, None, Some(call.function.name.clone()))
+            .await
+        {
+            log::warn!("approvals: failed to append restored tool result: {}", e);
+            return None;
+        }
+    }
+    Some(result)
+}
+
 /// Reply text to send to the channel. Matches OpenClaw: send the model's content only; when empty (e.g. tool-calls-only or silent), no placeholder — caller may skip sending.
 fn channel_reply_text(result: &agent::AgentTurnResult) -> Option<String> {
     let text = result.content.trim();
@@ -617,6 +889,9 @@ fn channel_reply_text(result: &agent::AgentTurnResult) -> Option<String> {
 /// Message that starts a new session (clear history) when sent via Telegram or other channels. Case-insensitive.
 const NEW_SESSION_TRIGGER: &str = "/new";
 
+/// Message that resumes a turn stopped by the tool loop limit (runs the pending tool calls). Case-insensitive.
+const CONTINUE_TRIGGER: &str = "/continue";
+
 /// Broadcast a session.message event over WebSocket to connected clients.
 /// `orchestrator_id` is included in the payload so clients can filter events by orchestrator.
 fn broadcast_session_message(
@@ -665,6 +940,209 @@ fn broadcast_session_message(
     }
 }
 
+/// Broadcast a `session.truncated` event so clients reload history after a rollback.
+fn broadcast_session_truncated(
+    state: &GatewayState,
+    session_id: &str,
+    orchestrator_id: &str,
+    message_count: usize,
+) {
+    let event = json!({
+        "type": "event",
+        "event": "session.truncated",
+        "payload": {
+            "sessionId": session_id,
+            "orchestratorId": orchestrator_id,
+            "messageCount": message_count,
+        },
+    });
+    if let Ok(text) = serde_json::to_string(&event) {
+        let _ = state.event_tx.send(text);
+    }
+}
+
+/// Broadcast an `orchestration.model.route` event for an orchestrator turn moved to `step`.
+fn broadcast_model_route(
+    state: &GatewayState,
+    session_id: &str,
+    orchestrator_id: &str,
+    route: &ModelRoute,
+    step: &RouteStep,
+    reason: RouteReason,
+) {
+    let mut payload = route.event_payload(orchestrator_id, step, reason);
+    payload["sessionId"] = json!(session_id);
+    payload["orchestratorId"] = json!(orchestrator_id);
+    let event = json!({
+        "type": "event",
+        "event": EVENT_MODEL_ROUTE,
+        "payload": payload,
+    });
+    if let Ok(text) = serde_json::to_string(&event) {
+        let _ = state.event_tx.send(text);
+    }
+}
+
+/// Provider and model for an orchestrator turn: the request's `provider` / `model`, else the first
+/// step of the orchestrator's tier route, else its defaults. The route is returned when the turn
+/// runs on it (and may escalate; see [`escalate_routed_turn`]).
+fn orchestrator_turn_model<'a>(
+    state: &GatewayState,
+    orch_config: &config::OrchestratorConfig,
+    orch_rt: &'a OrchestratorRuntime,
+    provider: Option<&str>,
+    model: Option<&str>,
+) -> (ProviderChoice, String, Option<&'a ModelRoute>) {
+    if provider.is_none() && model.is_none() {
+        if let Some(route) = orch_rt.model_route.as_ref() {
+            let step = &route.steps[0];
+            return (ProviderChoice::new(&step.provider_id), step.model.clone(), Some(route));
+        }
+    }
+    let provider_choice = provider
+        .and_then(|s| config::canonical_provider_id(&state.config.providers, s))
+        .map(ProviderChoice::new)
+        .unwrap_or_else(|| resolve_orchestrator_provider_choice(&state.config.providers, orch_config));
+    let model_name = resolve_model(
+        &state.config.providers,
+        orch_config.default_model.as_deref(),
+        model,
+        &provider_choice,
+    );
+    (provider_choice, model_name, None)
+}
+
+/// Continue a tier-routed orchestrator turn on the next tier of `route` for as long as it stops at
+/// the tool loop limit with calls pending. Tool calls and results of every part are kept in the
+/// returned result.
+async fn escalate_routed_turn(
+    state: &GatewayState,
+    orch_config: &config::OrchestratorConfig,
+    orch_rt: &OrchestratorRuntime,
+    route: &ModelRoute,
+    session_store: &SessionStore,
+    delegate: DelegateContext,
+    mut result: agent::AgentTurnResult,
+) -> Result<agent::AgentTurnResult, crate::providers::ProviderError> {
+    let session_id = delegate.session_id.clone().unwrap_or_default();
+    let system_context = Some(orch_rt.system_context.as_str()).filter(|s| !s.trim().is_empty());
+    let has_workers = !state.worker_delegate_runtimes.is_empty();
+    let redact_tool_output = state.redacts_tool_output(&ProviderChoice::new(&route.steps[0].provider_id), Some(route));
+    for step in route.steps.iter().skip(1) {
+        if !result.loop_limit_reached || result.pending_tool_calls.is_empty() || result.stopped {
+            break;
+        }
+        let Some(provider) = state.provider_clients.get(&ProviderChoice::new(&step.provider_id)) else {
+            log::warn!("model routing: no client for provider '{}'", step.provider_id);
+            break;
+        };
+        log::info!(
+            "orchestrator {}: tool loop limit reached, continuing on tier {} ({}/{})",
+            orch_config.id,
+            step.tier.as_str(),
+            step.provider_id,
+            step.model
+        );
+        broadcast_model_route(state, &session_id, &orch_config.id, route, step, RouteReason::LoopLimit);
+        let next = agent::continue_turn_dyn(
+            agent::SessionTurn {
+                store: session_store,
+                session_id: &session_id,
+                provider,
+                model: &step.model,
+                system_context,
+                max_tool_loops_per_turn: orch_config.max_tool_loops_per_turn,
+                tools: merge_delegate_task(orch_rt.tools_list.clone(), has_workers),
+                tool_executor: orch_rt.tool_executor.clone(),
+                delegate: Some(delegate.clone()),
+                stop_flag: delegate.stop_flag.clone(),
+                redact_tool_output,
+            },
+            None,
+        )
+        .await?;
+        result.tool_calls.extend(next.tool_calls);
+        result.tool_results.extend(next.tool_results);
+        result = agent::AgentTurnResult {
+            tool_calls: result.tool_calls,
+            tool_results: result.tool_results,
+            truncated: result.truncated || next.truncated,
+            ..next
+        };
+    }
+    Ok(result)
+}
+
+/// Resolve the user message an "agent.regenerate" request reruns from: the requested index
+/// (which must be a user message) or, when omitted, the last user message in the session.
+fn regenerate_from_index(
+    session: &crate::session::Session,
+    requested: Option<usize>,
+) -> Result<usize, String> {
+    match requested {
+        Some(i) => match session.messages.get(i) {
+            Some(m) if m.role == "user" => Ok(i),
+            Some(m) => Err(format!("message {} is a {} message, not a user message", i, m.role)),
+            None => Err(format!(
+                "message index out of range (session has {} messages)",
+                session.messages.len()
+            )),
+        },
+        None => session
+            .messages
+            .iter()
+            .rposition(|m| m.role == "user")
+            .ok_or_else(|| "session has no user message to regenerate".to_string()),
+    }
+}
+
+/// Payload for `sessions.delegation`: the transcript of `delegation_id` when `session` links it
+/// (live or archived), with the delegations its worker started listed under `nested`.
+fn delegation_transcript_json(
+    profile_dir: &std::path::Path,
+    session: &crate::session::Session,
+    delegation_id: &str,
+) -> Option<serde_json::Value> {
+    let links: Vec<&crate::session::DelegationTranscriptLink> = session
+        .delegation_transcripts
+        .iter()
+        .chain(session.archived_branches.iter().flat_map(|b| b.delegation_transcripts.iter()))
+        .collect();
+    let link = links.iter().find(|l| l.delegation_id == delegation_id)?;
+    let t = crate::session::read_delegation_transcript(&sessions_dir(profile_dir, &link.worker_id), delegation_id)?;
+    let nested: Vec<serde_json::Value> = links
+        .iter()
+        .filter(|l| l.parent_delegation_id.as_deref() == Some(delegation_id))
+        .map(|l| transcript_link_to_json(l))
+        .collect();
+    Some(json!({
+        "delegationId": t.id,
+        "workerId": t.worker_id,
+        "provider": t.provider,
+        "model": t.model,
+        "parentSessionId": t.parent_session_id,
+        "parentDelegationId": t.parent_delegation_id,
+        "messageIndex": t.message_index,
+        "createdAt": t.created_at,
+        "stopped": t.stopped,
+        "error": t.error,
+        "messages": t.messages.iter().map(session_message_to_json).collect::<Vec<_>>(),
+        "nested": nested,
+    }))
+}
+
+fn transcript_link_to_json(l: &crate::session::DelegationTranscriptLink) -> serde_json::Value {
+    let mut obj = json!({
+        "delegationId": l.delegation_id,
+        "workerId": l.worker_id,
+        "messageIndex": l.message_index,
+    });
+    if let Some(ref parent) = l.parent_delegation_id {
+        obj["parentDelegationId"] = json!(parent);
+    }
+    obj
+}
+
 /// Convert a `SessionMessage` to a JSON value with camelCase keys for the wire protocol.
 /// Uses manual construction (not `serde_json::to_value`) because `SessionMessage` serializes
 /// with snake_case keys for on-disk storage, but the WebSocket protocol uses camelCase.
@@ -681,6 +1159,91 @@ fn session_message_to_json(m: &crate::session::SessionMessage) -> serde_json::Va
     serde_json::Value::Object(obj)
 }
 
+/// How often the sandbox watcher checks the sandbox directory for symlink changes.
+const SANDBOX_RESCAN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Re-scan the profile sandbox and per-agent subsets every [`SANDBOX_RESCAN_INTERVAL`] and
+/// broadcast `sandbox.roots.changed` when their roots differ from the last broadcast, whether
+/// this task or a tool call's path validation picked up the change.
+fn spawn_sandbox_watcher(state: GatewayState) {
+    let mut watched: Vec<(Option<String>, crate::exec::WriteSandbox, crate::exec::RootSet)> = state
+        .sandbox
+        .iter()
+        .map(|sb| (None, sb.clone()))
+        .chain(state.agent_sandboxes.iter().map(|(id, sb)| (Some(id.clone()), sb.clone())))
+        .map(|(id, sb)| {
+            let roots = sb.root_set();
+            (id, sb, roots)
+        })
+        .collect();
+    if watched.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SANDBOX_RESCAN_INTERVAL);
+        loop {
+            interval.tick().await;
+            // Scanning reads the sandbox directory and resolves its links; keep it off the runtime.
+            let sandboxes: Vec<_> = watched.iter().map(|(_, sb, _)| sb.clone()).collect();
+            let Ok(scanned) = tokio::task::spawn_blocking(move || {
+                sandboxes
+                    .iter()
+                    .map(|sb| {
+                        sb.refresh();
+                        sb.root_set()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await
+            else {
+                continue;
+            };
+            for ((agent_id, _, last), now) in watched.iter_mut().zip(scanned) {
+                let Some(change) = crate::exec::RootsChange::between(last, &now) else {
+                    continue;
+                };
+                let event = json!({
+                    "type": "event",
+                    "event": crate::exec::EVENT_SANDBOX_ROOTS_CHANGED,
+                    "payload": {
+                        "agentId": agent_id,
+                        "added": change.added,
+                        "removed": change.removed,
+                        "madeWritable": change.made_writable,
+                        "madeReadOnly": change.made_read_only,
+                        "writable": now.writable,
+                        "readOnly": now.read_only,
+                    },
+                });
+                if let Ok(text) = serde_json::to_string(&event) {
+                    let _ = state.event_tx.send(text);
+                }
+                *last = now;
+            }
+        }
+    });
+}
+
+/// How often the sandbox quota usage is measured outside of write tool calls.
+const QUOTA_SCAN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Re-measure the sandbox quota every [`QUOTA_SCAN_INTERVAL`], so files removed or added outside
+/// of tool calls are reflected in checks and `status.sandbox.quota`.
+fn spawn_quota_scanner(state: GatewayState) {
+    let Some(quota) = state.sandbox_quota.clone() else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(QUOTA_SCAN_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let quota = quota.clone();
+            let _ = tokio::task::spawn_blocking(move || quota.scan()).await;
+        }
+    });
+}
+
 /// Broadcast a `gateway.config.changed` event to connected WebSocket clients.
 /// Called when model discovery updates the provider model list so the desktop
 /// can refresh its status display immediately instead of waiting for the next poll.
@@ -697,7 +1260,8 @@ fn broadcast_config_changed(event_tx: &tokio::sync::broadcast::Sender<String>) {
 
 /// Process one inbound channel message: get or create session, bind, append user message, run agent, send reply.
 /// If the message is the new-session trigger (e.g. /new), rebind the conversation to a fresh session and confirm.
-/// Channel messages always use the default orchestrator.
+/// Channel messages use the default orchestrator unless the conversation's session was handed off
+/// to another one; a `handoff` requested during the turn runs after the reply is sent.
 async fn process_inbound_message(state: GatewayState, msg: InboundMessage) {
     log::info!(
         "inbound: channel={}, conversation={}, text_len={}",
@@ -706,33 +1270,46 @@ async fn process_inbound_message(state: GatewayState, msg: InboundMessage) {
         msg.text.len()
     );
 
-    // Channel messages always use the default orchestrator.
-    let orch_rt = match state.orchestrator_runtime(None) {
+    let bound_id = state
+        .bindings
+        .get_session_id(&msg.channel_id, &msg.conversation_id)
+        .await;
+    let owner = match bound_id {
+        Some(ref id) => state.bindings.get_orchestrator_id(id).await,
+        None => None,
+    };
+    let owner = owner.filter(|id| {
+        let known = state.config.agents.orchestrator(Some(id)).is_ok();
+        if !known {
+            log::warn!("inbound: session owner {} is not configured; using the default orchestrator", id);
+        }
+        known
+    });
+    let orch_rt = match state.orchestrator_runtime(owner.as_deref()) {
         Ok(rt) => rt,
         Err(e) => {
-            log::error!("inbound: failed to resolve default orchestrator: {}", e);
+            log::error!("inbound: failed to resolve orchestrator: {}", e);
             return;
         }
     };
-    let session_store = match state.session_store_for(None) {
+    let session_store = match state.session_store_for(owner.as_deref()) {
         Ok(s) => s,
         Err(e) => {
-            log::error!("inbound: failed to resolve default session store: {}", e);
+            log::error!("inbound: failed to resolve session store: {}", e);
             return;
         }
     };
-    let orch_config = state.config.agents.default_orchestrator();
+    let Ok(orch_config) = state.config.agents.orchestrator(owner.as_deref()) else {
+        return;
+    };
 
     let trimmed = msg.text.trim();
     if trimmed.eq_ignore_ascii_case(NEW_SESSION_TRIGGER) {
-        let old_id = state
-            .bindings
-            .get_session_id(&msg.channel_id, &msg.conversation_id)
-            .await;
+        let old_id = bound_id;
         let new_id = session_store.create().await;
         state
             .bindings
-            .bind(&msg.channel_id, &msg.conversation_id, &new_id)
+            .bind_for(&msg.channel_id, &msg.conversation_id, &new_id, owner.as_deref())
             .await;
         if let Some(id) = old_id {
             state.bindings.remove_binding(&id).await;
@@ -749,11 +1326,7 @@ async fn process_inbound_message(state: GatewayState, msg: InboundMessage) {
         return;
     }
 
-    let session_id = state
-        .bindings
-        .get_session_id(&msg.channel_id, &msg.conversation_id)
-        .await;
-    let session_id = match session_id {
+    let session_id = match bound_id {
         Some(id) => {
             // Ensure the session is loaded (lazy-load from disk if needed).
             if session_store.get(&id).await.is_some() {
@@ -763,7 +1336,7 @@ async fn process_inbound_message(state: GatewayState, msg: InboundMessage) {
                 let new_id = session_store.create().await;
                 state
                     .bindings
-                    .bind(&msg.channel_id, &msg.conversation_id, &new_id)
+                    .bind_for(&msg.channel_id, &msg.conversation_id, &new_id, owner.as_deref())
                     .await;
                 new_id
             }
@@ -777,36 +1350,37 @@ async fn process_inbound_message(state: GatewayState, msg: InboundMessage) {
             id
         }
     };
-    if session_store
-        .append_message(&session_id, "user", &msg.text)
-        .await
-        .is_err()
-    {
-        log::warn!("inbound: failed to append message");
-        return;
+    let continuing = trimmed.eq_ignore_ascii_case(CONTINUE_TRIGGER);
+    if !continuing {
+        if session_store
+            .append_message(&session_id, "user", &msg.text)
+            .await
+            .is_err()
+        {
+            log::warn!("inbound: failed to append message");
+            return;
+        }
+        broadcast_session_message(
+            &state,
+            &session_id,
+            &orch_config.id,
+            "user",
+            &msg.text,
+            None,
+            None,
+            Some(&msg.channel_id),
+            Some(&msg.conversation_id),
+        );
+    }
+    let (provider_choice, model_name, route) =
+        orchestrator_turn_model(&state, orch_config, orch_rt, None, None);
+    if let Some(route) = route {
+        broadcast_model_route(&state, &session_id, &orch_config.id, route, &route.steps[0], RouteReason::Initial);
     }
-    broadcast_session_message(
-        &state,
-        &session_id,
-        &orch_config.id,
-        "user",
-        &msg.text,
-        None,
-        None,
-        Some(&msg.channel_id),
-        Some(&msg.conversation_id),
-    );
-    let provider_choice = resolve_orchestrator_provider_choice(&state.config.providers, orch_config);
-    let model_name = resolve_model(
-        &state.config.providers,
-        orch_config.default_model.as_deref(),
-        None,
-        &provider_choice,
-    );
     let has_workers = !state.worker_delegate_runtimes.is_empty();
     let system_context = &orch_rt.system_context;
     let tools = merge_delegate_task(orch_rt.tools_list.clone(), has_workers);
-    let tool_executor = orch_rt.tool_executor.as_deref();
+    let tool_executor = orch_rt.tool_executor.clone();
     let worker_tools = worker_tool_list(tools.as_ref());
     // Get or create the stop flag for this session before building DelegateContext
     // so the worker turn can also be stopped (used by channel stop if needed).
@@ -817,57 +1391,54 @@ async fn process_inbound_message(state: GatewayState, msg: InboundMessage) {
             .or_insert_with(|| Arc::new(AtomicBool::new(false)))
             .clone()
     };
-    let delegate = Some(DelegateContext {
-        clients: &state.provider_clients,
-        providers: &state.config.providers,
-        agents: &state.config.agents,
-        orchestrator_id: Some(orch_config.id.as_str()),
-        orchestrator_system_context: if system_context.trim().is_empty() {
-            None
-        } else {
-            Some(system_context.as_str())
-        },
-        orchestrator_worker_tools: worker_tools,
-        orchestrator_tool_executor: tool_executor,
-        worker_runtimes: Some(state.worker_delegate_runtimes.as_ref()),
-        observability: Some(DelegateObservability {
-            event_tx: state.event_tx.clone(),
-            session_id: Some(session_id.clone()),
-            orchestrator_id: Some(orch_config.id.clone()),
-            source: Some("orchestrator".to_string()),
-            tool_index_offset: 0,
-            emitted_tool_calls: AtomicUsize::new(0),
-        }),
-        session_store: Some(session_store),
-        session_id: Some(session_id.as_str()),
-        stop_flag: Some(stop_flag.clone()),
-        tool_index_offset: 0,
-    });
+    let delegate = Some(state.delegate_context(
+        orch_config,
+        orch_rt,
+        worker_tools,
+        session_store,
+        &session_id,
+        stop_flag.clone(),
+    ));
+    let escalation = route.zip(delegate.clone());
     let provider_dyn = state.provider_clients.get(&provider_choice)
         .ok_or_else(|| format!("no client for provider '{}'", provider_choice))
         .expect("provider client should exist");
-    let result = agent::run_turn_dyn(
-        session_store,
-        &session_id,
-        provider_dyn,
-        &model_name,
-        Some(system_context),
-        orch_config.max_tool_loops_per_turn,
+    let turn = agent::SessionTurn {
+        store: session_store,
+        session_id: &session_id,
+        provider: provider_dyn,
+        model: &model_name,
+        system_context: Some(system_context),
+        max_tool_loops_per_turn: orch_config.max_tool_loops_per_turn,
         tools,
         tool_executor,
         delegate,
-        None,
-        Some(stop_flag),
-    )
-    .await;
+        stop_flag: Some(stop_flag),
+        redact_tool_output: state.redacts_tool_output(&provider_choice, route),
+    };
+    let result = if continuing {
+        agent::continue_turn_dyn(turn, None).await
+    } else {
+        agent::run_turn_dyn(turn, None).await
+    };
+    let result = match (result, escalation) {
+        (Ok(r), Some((route, ctx))) => {
+            escalate_routed_turn(&state, orch_config, orch_rt, route, session_store, ctx, r).await
+        }
+        (result, _) => result,
+    };
     let result = match result {
         Ok(r) => r,
         Err(e) => {
             log::warn!("inbound: agent turn failed: {}", e);
-            let fallback = format!(
-                "something went wrong: {}. check the gateway logs for details.",
-                e
-            );
+            let fallback = if continuing {
+                format!("cannot continue: {}", e)
+            } else {
+                format!(
+                    "something went wrong: {}. check the gateway logs for details.",
+                    e
+                )
+            };
             if let Some(handle) = state.channel_registry.get(&msg.channel_id).await {
                 let _ = handle.send_message(&msg.conversation_id, &fallback).await;
             }
@@ -904,6 +1475,104 @@ async fn process_inbound_message(state: GatewayState, msg: InboundMessage) {
             }
         }
     }
+    if result.loop_limit_reached && !result.pending_tool_calls.is_empty() {
+        let notice = format!(
+            "tool loop limit reached with {} pending tool call(s). send {} to run them.",
+            result.pending_tool_calls.len(),
+            CONTINUE_TRIGGER
+        );
+        if let Some(handle) = state.channel_registry.get(&msg.channel_id).await {
+            let _ = handle.send_message(&msg.conversation_id, &notice).await;
+        }
+    }
+    if let Some(req) = requested_handoff(&state.config.agents, &orch_config.id, &result) {
+        if let Err(e) = transfer_session(&state, &session_id, &req).await {
+            log::warn!("inbound: handoff to {} failed: {}", req.orchestrator_id, e);
+        }
+    }
+}
+
+/// Move or copy `session_id` to orchestrator `req.orchestrator_id` (WebSocket `sessions.transfer`
+/// and the `handoff` tool). A move keeps the session id; a copy gets a new id and leaves the source
+/// in place. The summary, when given, is appended for the target orchestrator, and a channel
+/// binding follows the session so the conversation's next messages go to the target. Broadcasts
+/// `session.transferred` and returns its payload.
+async fn transfer_session(
+    state: &GatewayState,
+    session_id: &str,
+    req: &HandoffRequest,
+) -> Result<serde_json::Value, String> {
+    let target = state.session_store_for(Some(&req.orchestrator_id))?;
+    let mut source = None;
+    for (orch_id, store) in state.session_stores.iter() {
+        if store.get(session_id).await.is_some() {
+            source = Some((orch_id.clone(), store));
+            break;
+        }
+    }
+    let (from, source) = source.ok_or_else(|| "session not found".to_string())?;
+    if from == req.orchestrator_id {
+        return Err(format!("the session already belongs to orchestrator {}", from));
+    }
+
+    let target_id = match req.mode {
+        TransferMode::Move => {
+            let session = source.remove_for_transfer(session_id).await?;
+            if let Err(e) = target.insert(session.clone()).await {
+                let _ = source.insert(session).await;
+                return Err(e);
+            }
+            session_id.to_string()
+        }
+        TransferMode::Copy => {
+            let mut session = source.get_for_transfer(session_id).await?;
+            let now = crate::session::chrono_now_iso8601();
+            session.id = format!("sess-{}", uuid::Uuid::new_v4());
+            session.created_at = now.clone();
+            session.updated_at = now;
+            let id = session.id.clone();
+            target.insert(session).await?;
+            id
+        }
+    };
+    if let Some(ref summary) = req.summary {
+        let note = format!("Handoff from orchestrator {}: {}", from, summary);
+        if let Err(e) = target.append_message(&target_id, "user", note).await {
+            log::warn!("sessions: failed to append handoff summary to {}: {}", target_id, e);
+        }
+    }
+    if let Some((channel_id, conversation_id)) = state.bindings.get_channel_binding(session_id).await {
+        let owner = Some(req.orchestrator_id.as_str())
+            .filter(|id| *id != state.config.agents.default_orchestrator().id);
+        state
+            .bindings
+            .bind_for(channel_id, conversation_id, &target_id, owner)
+            .await;
+    }
+    log::info!(
+        "sessions: {} {} from {} to {} as {}",
+        req.mode.as_str(),
+        session_id,
+        from,
+        req.orchestrator_id,
+        target_id
+    );
+    let payload = json!({
+        "sessionId": session_id,
+        "fromOrchestratorId": from,
+        "toOrchestratorId": req.orchestrator_id,
+        "targetSessionId": target_id,
+        "mode": req.mode.as_str(),
+    });
+    let event = json!({
+        "type": "event",
+        "event": EVENT_SESSION_TRANSFERRED,
+        "payload": payload,
+    });
+    if let Ok(text) = serde_json::to_string(&event) {
+        let _ = state.event_tx.send(text);
+    }
+    Ok(payload)
 }
 
 /// Run the gateway server; binds to config.gateway.bind:config.gateway.port.
@@ -1060,14 +1729,111 @@ pub async fn run_gateway(config: Config, paths: ChaiPaths) -> Result<()> {
             }
         }
     };
-    // Recount roots after potential CWD fallback.
-    let sandbox_roots_count = sandbox_opt
-        .as_ref()
-        .map(|s| s.roots().len())
-        .unwrap_or(0);
+    if config.sandbox.kernel == config::KernelSandbox::Landlock {
+        match (crate::exec::landlock_abi(), sandbox_opt.is_some()) {
+            (_, false) => log::warn!("sandbox.kernel is \"landlock\" but there is no sandbox; tool commands are not confined"),
+            (Some(abi), true) => log::info!(
+                "kernel sandbox: landlock ABI {}{}",
+                abi,
+                if config.sandbox.deny_tcp_connect && abi >= 4 { ", TCP connect denied" } else { "" }
+            ),
+            (None, true) => log::warn!("sandbox.kernel is \"landlock\" but the kernel has no Landlock support; tool commands use path checks only"),
+        }
+    }
+    // Quota over the sandbox directory and every agent's scratch directory.
+    let sandbox_quota = match sandbox_opt.as_ref().and_then(|s| s.base()) {
+        Some(base) if config.sandbox.quota.is_set() => {
+            let orchestrators = config.agents.orchestrators.iter().map(|o| (&o.id, &o.sandbox));
+            let workers = config.agents.workers.iter().flatten().map(|w| (&w.id, &w.sandbox));
+            let mut dirs = vec![base.to_path_buf()];
+            dirs.extend(
+                orchestrators
+                    .chain(workers)
+                    .filter(|(_, sb)| sb.as_ref().is_some_and(|sb| sb.scratch))
+                    .map(|(id, _)| config::scratch_dir(&paths.profile_dir, id)),
+            );
+            let quota = crate::exec::SandboxQuota::new(
+                dirs,
+                config.sandbox.quota.max_bytes,
+                config.sandbox.quota.max_files,
+            );
+            let usage = quota.scan();
+            log::info!(
+                "sandbox quota: {} of {} bytes, {} of {} files",
+                usage.bytes,
+                quota.max_bytes().map_or("unlimited".to_string(), |m| m.to_string()),
+                usage.files,
+                quota.max_files().map_or("unlimited".to_string(), |m| m.to_string()),
+            );
+            Some(quota)
+        }
+        Some(_) => None,
+        None => {
+            if config.sandbox.quota.is_set() {
+                log::warn!("sandbox.quota is set but there is no sandbox; it is not enforced");
+            }
+            None
+        }
+    };
+
+    validate_workflows(&config).map_err(|e| anyhow::anyhow!("invalid workflows config: {}", e))?;
+    if !config.workflows.is_empty() {
+        log::info!("workflows: {} configured", config.workflows.len());
+    }
+
+    // Secrets for `{{secret:name}}` placeholders in execution specs.
+    let secrets = crate::secrets::Secrets::load(&paths.secrets_json())
+        .map_err(|e| anyhow::anyhow!("invalid secrets file: {}", e))?;
+    if !secrets.is_empty() {
+        log::info!("secrets: {} loaded from {}", secrets.len(), paths.secrets_json().display());
+    }
+    let secrets = Arc::new(secrets);
+
+    // Tool output redaction: each turn decides from its provider (`redacts_tool_output`) whether
+    // the executors apply these rules to its calls.
+    let redactor = crate::redaction::Redactor::compile(&config.redaction.rules)
+        .map_err(|e| anyhow::anyhow!("invalid redaction config: {}", e))?;
+    let redaction_scope = config.redaction.providers_in_scope(&config.providers);
+    if redaction_scope.is_empty() {
+        if !redactor.is_empty() {
+            log::info!("redaction: {} rule(s) not applied (no provider in scope)", redactor.len());
+        }
+    } else {
+        log::info!(
+            "redaction: {} profile rule(s) applied to tool output of turns on: {}",
+            redactor.len(),
+            redaction_scope.join(", ")
+        );
+    }
+    let redaction = Arc::new(redactor);
+
+    // Profile-level tool hooks run in the working directory of the call they wrap.
+    let tool_hooks = if config.hooks.is_empty() {
+        None
+    } else {
+        let hooks = ToolHooks::from_config(&config.hooks, paths.hooks_dir())
+            .map_err(|e| anyhow::anyhow!("invalid hooks config: {}", e))?
+            .with_exec_config(&config.exec)
+            .with_redaction(Arc::clone(&redaction));
+        log::info!(
+            "tool hooks: {} pre, {} post from {}",
+            config.hooks.pre.len(),
+            config.hooks.post.len(),
+            paths.hooks_dir().display()
+        );
+        Some(Arc::new(hooks))
+    };
+    let executor_settings = ExecutorSettings {
+        exec: &config.exec,
+        sandbox_config: &config.sandbox,
+        quota: sandbox_quota.as_ref(),
+        secrets: &secrets,
+        redaction: &redaction,
+    };
 
     // Build per-orchestrator runtimes and session stores.
     let mut orchestrator_runtimes: HashMap<String, OrchestratorRuntime> = HashMap::new();
+    let mut agent_sandboxes: Vec<(String, crate::exec::WriteSandbox)> = Vec::new();
     let mut session_stores: HashMap<String, Arc<SessionStore>> = HashMap::new();
     for orch in &config.agents.orchestrators {
         let orch_id = orch.id.trim();
@@ -1098,8 +1864,16 @@ pub async fn run_gateway(config: Config, paths: ChaiPaths) -> Result<()> {
                 orch_id
             );
         }
+        let orch_sandbox = agent_sandbox(&paths, orch_id, orch.sandbox.as_ref(), &sandbox_opt, &mut agent_sandboxes)?;
+        let orch_route = agent_model_route(
+            &config,
+            orch_id,
+            orch.model_routing,
+            orch.enabled_providers.as_deref(),
+            &orchestrator_entries,
+        )?;
         let orch_built =
-            build_skill_runtime_for_entries(orchestrator_entries, orch_ctx_mode, sandbox_opt.clone());
+            build_skill_runtime_for_entries(orchestrator_entries, orch_ctx_mode, orch_sandbox, tool_hooks.as_ref(), &executor_settings);
         let skills = orch_built.skills.clone();
         let agent_ctx = agent_ctx::load_agent_ctx(Some(orch_context_dir.as_path()));
 
@@ -1119,10 +1893,29 @@ pub async fn run_gateway(config: Config, paths: ChaiPaths) -> Result<()> {
             &config.agents,
             &all_entries,
             effective_workers.as_deref(),
+            orch.max_parallel_workflows(),
         );
 
-        let tools_list = merge_delegate_task(orch_built.tools_list.clone(), has_effective_workers);
+        let tools_list = merge_handoff_tool(merge_workflow_tools(
+            merge_delegation_handle_tools(
+                merge_delegate_many(
+                    merge_delegate_task(orch_built.tools_list.clone(), has_effective_workers),
+                    has_effective_workers,
+                ),
+                has_effective_workers && orch.async_delegation(),
+            ),
+            &config,
+            orch_id,
+        ), &config.agents, orch_id);
         let tool_executor = orch_built.tool_executor.clone();
+        let approval_policy = config.approval.policy_for(orch.approval_policy);
+        if approval_policy != config::ApprovalPolicy::Auto {
+            log::info!(
+                "orchestrator {} tool approval policy: {}",
+                orch_id,
+                approval_policy.as_str()
+            );
+        }
 
         orchestrator_runtimes.insert(
             orch_id.to_string(),
@@ -1132,6 +1925,8 @@ pub async fn run_gateway(config: Config, paths: ChaiPaths) -> Result<()> {
                 tools_list,
                 tool_executor,
                 context_mode: orch_ctx_mode,
+                approval: ApprovalGate::new(approval_policy, orch_built.write_tools),
+                model_route: orch_route,
             },
         );
 
@@ -1157,21 +1952,41 @@ pub async fn run_gateway(config: Config, paths: ChaiPaths) -> Result<()> {
             let w_label = format!("worker:{}", w.id);
             validate_skill_composition(&w_label, &w_entries, w.default_model.as_deref());
             let w_ctx_mode = worker_context_mode(w);
+            let w_sandbox = agent_sandbox(&paths, &w.id, w.sandbox.as_ref(), &sandbox_opt, &mut agent_sandboxes)?;
+            let w_route = agent_model_route(&config, &w.id, w.model_routing, None, &w_entries)?;
             let w_built =
-                build_skill_runtime_for_entries(w_entries, w_ctx_mode, sandbox_opt.clone());
+                build_skill_runtime_for_entries(w_entries, w_ctx_mode, w_sandbox, tool_hooks.as_ref(), &executor_settings);
             let w_context = build_worker_system_context(
                 w_agent_ctx.as_deref(),
                 &w_built.skills,
                 w_ctx_mode,
             );
+            // Roster for nested delegation; worker turns run their delegate_task calls one at a time.
+            let w_workers_context = build_workers_context(
+                &config.agents,
+                &all_entries,
+                w.enabled_workers.as_deref(),
+                1,
+            );
+            let w_approval_policy = config.approval.policy_for(w.approval_policy);
+            if w_approval_policy != config::ApprovalPolicy::Auto {
+                log::info!(
+                    "worker {} tool approval policy: {}",
+                    w.id,
+                    w_approval_policy.as_str()
+                );
+            }
             worker_map.insert(
                 w.id.clone(),
                 WorkerDelegateRuntime {
                     system_context: w_context,
+                    workers_context: w_workers_context,
                     skills: Arc::new(w_built.skills),
                     tools_list: w_built.tools_list,
                     tool_executor: w_built.tool_executor,
                     context_mode: w_ctx_mode,
+                    approval: ApprovalGate::new(w_approval_policy, w_built.write_tools),
+                    model_route: w_route,
                 },
             );
         }
@@ -1187,6 +2002,11 @@ pub async fn run_gateway(config: Config, paths: ChaiPaths) -> Result<()> {
     };
     let default_sessions_path = sessions_dir(&paths.profile_dir, default_orch_id);
     let binding_store = Arc::new(SessionBindingStore::with_data_dir(default_sessions_path.clone()));
+    let approvals = Arc::new(
+        ApprovalStore::with_path(paths.approvals_json())
+            .with_event_tx(event_tx.clone())
+            .with_timeout(config.approval.timeout_secs.map(std::time::Duration::from_secs)),
+    );
 
     #[cfg_attr(not(feature = "matrix"), allow(unused_mut))]
     let mut state = GatewayState {
@@ -1209,11 +2029,16 @@ pub async fn run_gateway(config: Config, paths: ChaiPaths) -> Result<()> {
         skills_lock_mode: config.skills.lock_mode,
         skills_lock_generation,
         skills_locked_count,
-        sandbox_roots_count,
+        sandbox: sandbox_opt.clone(),
+        sandbox_quota: sandbox_quota.clone(),
+        agent_sandboxes: Arc::new(agent_sandboxes),
         session_stop_flags: Arc::new(RwLock::new(HashMap::new())),
         connection_tracker: Arc::new(ConnectionTracker::new(
             config::effective_max_connections(&config.gateway.bind, &config.gateway),
         )),
+        approvals,
+        profile_dir: paths.profile_dir.clone(),
+        delegation_cancels: DelegationCancels::default(),
     };
 
     // Scan persisted sessions on startup (populates disk index for lazy loading).
@@ -1370,6 +2195,9 @@ pub async fn run_gateway(config: Config, paths: ChaiPaths) -> Result<()> {
         }
     }
 
+    spawn_sandbox_watcher(state.clone());
+    spawn_quota_scanner(state.clone());
+
     {
         let state_inbound = state.clone();
         tokio::spawn(async move {
@@ -2042,8 +2870,13 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                         "contextMode": orch_context_mode_wire,
                         "maxToolLoopsPerTurn": orch.max_tool_loops_per_turn,
                         "maxDelegationsPerTurn": orch.max_delegations_per_turn,
+                        "maxParallelWorkflows": orch.max_parallel_workflows(),
+                        "asyncDelegation": orch.async_delegation(),
+                        "maxDelegationDepth": orch.max_delegation_depth(),
                         "maxDelegationsPerSession": orch.max_delegations_per_session,
                         "maxDelegationsPerWorker": serde_json::to_value(&orch.max_delegations_per_worker).unwrap_or_else(|_| serde_json::Value::Null),
+                        "approvalPolicy": state.config.approval.policy_for(orch.approval_policy).as_str(),
+                        "outputSchema": serde_json::Value::Null,
                     }));
                 }
 
@@ -2058,6 +2891,12 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                             SkillContextMode::Full => "full",
                             SkillContextMode::ReadOnDemand => "readOnDemand",
                         };
+                        let w_config = state
+                            .config
+                            .agents
+                            .workers
+                            .as_ref()
+                            .and_then(|ws| ws.iter().find(|w| &w.id == wid));
                         entries.push(json!({
                             "id": wid,
                             "role": "worker",
@@ -2065,12 +2904,21 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                             "defaultModel": w_model,
                             "enabledProviders": serde_json::Value::Null,
                             "enabledSkills": w_enabled_skills,
-                            "enabledWorkers": serde_json::Value::Null,
+                            "enabledWorkers": w_config
+                                .and_then(|w| serde_json::to_value(&w.enabled_workers).ok())
+                                .unwrap_or(serde_json::Value::Null),
                             "contextMode": w_context_mode_wire,
                             "maxToolLoopsPerTurn": serde_json::Value::Null,
                             "maxDelegationsPerTurn": serde_json::Value::Null,
+                            "maxParallelWorkflows": serde_json::Value::Null,
+                            "asyncDelegation": serde_json::Value::Null,
+                            "maxDelegationDepth": serde_json::Value::Null,
                             "maxDelegationsPerSession": serde_json::Value::Null,
                             "maxDelegationsPerWorker": serde_json::Value::Null,
+                            "approvalPolicy": rt.approval.policy.as_str(),
+                            "outputSchema": w_config
+                                .and_then(|w| w.output_schema.clone())
+                                .unwrap_or(serde_json::Value::Null),
                         }));
                     }
                 }
@@ -2096,9 +2944,29 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                         &state.config.gateway,
                     ),
                 });
+                let writable = state.sandbox.as_ref().map(|s| s.roots()).unwrap_or_default();
+                let agent_roots: serde_json::Map<String, serde_json::Value> = state
+                    .agent_sandboxes
+                    .iter()
+                    .map(|(id, sb)| (id.clone(), json!({ "writable": sb.roots(), "readOnly": sb.read_only_roots() })))
+                    .collect();
                 let sandbox_block = json!({
                     "mode": state.config.sandbox.mode.as_str(),
-                    "roots": state.sandbox_roots_count,
+                    "roots": writable.len(),
+                    "writable": writable,
+                    "readOnly": state.sandbox.as_ref().map(|s| s.read_only_roots()).unwrap_or_default(),
+                    "agents": agent_roots,
+                    "quota": state.sandbox_quota.as_ref().map(|q| {
+                        let usage = q.usage().unwrap_or_default();
+                        json!({
+                            "maxBytes": q.max_bytes(),
+                            "maxFiles": q.max_files(),
+                            "usedBytes": usage.bytes,
+                            "usedFiles": usage.files,
+                        })
+                    }),
+                    "kernel": state.config.sandbox.kernel.as_str(),
+                    "landlockAbi": crate::exec::landlock_abi(),
                 });
                 // Key order matches `base/spec/GATEWAY_STATUS.md` and config cross-check:
                 // gateway → channels → providers → sandbox → agents → skills.
@@ -2204,11 +3072,39 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                     }
                 }
             }
-            "agent" => {
-                let params: AgentParams = match serde_json::from_value(req.params.clone()) {
+            "agent" | "continue" | "agent.regenerate" => {
+                // "continue" resumes a turn that hit the loop limit: same flow as "agent" but
+                // runs the pending tool calls instead of appending a user message.
+                // "agent.regenerate" rolls the session back to a user message first and reruns
+                // from there, appending a user message only when its text was edited.
+                let continuing = req.method == "continue";
+                let mut regenerate: Option<(Option<usize>, bool)> = None;
+                let parsed = if req.method == "agent.regenerate" {
+                    serde_json::from_value::<RegenerateParams>(req.params.clone()).map(|p| {
+                        regenerate = Some((p.message_index, p.message.is_some()));
+                        AgentParams {
+                            session_id: Some(p.session_id),
+                            message: p.message.unwrap_or_default(),
+                            provider: p.provider,
+                            model: p.model,
+                            orchestrator_id: p.orchestrator_id,
+                        }
+                    })
+                } else if continuing {
+                    serde_json::from_value::<ContinueParams>(req.params.clone()).map(|p| AgentParams {
+                        session_id: Some(p.session_id),
+                        message: String::new(),
+                        provider: p.provider,
+                        model: p.model,
+                        orchestrator_id: p.orchestrator_id,
+                    })
+                } else {
+                    serde_json::from_value::<AgentParams>(req.params.clone())
+                };
+                let params = match parsed {
                     Ok(p) => p,
                     Err(_) => {
-                        let res = WsResponse::err(&req.id, "invalid agent params");
+                        let res = WsResponse::err(&req.id, format!("invalid {} params", req.method));
                         let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
                         continue;
                     }
@@ -2238,48 +3134,80 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                         continue;
                     }
                 };
-                let session_id = if let Some(ref id) = params.session_id {
-                    session_store.get_or_create(id.clone()).await
-                } else {
-                    session_store.create().await
+                let session_id = match params.session_id {
+                    Some(ref id) if continuing || regenerate.is_some() => {
+                        if session_store.get(id).await.is_none() {
+                            let res = WsResponse::err(&req.id, "session not found");
+                            let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                            continue;
+                        }
+                        id.clone()
+                    }
+                    Some(ref id) => session_store.get_or_create(id.clone()).await,
+                    None => session_store.create().await,
                 };
-                let user_message = params.message.clone();
-                if let Err(e) = session_store
-                    .append_message(&session_id, "user", &params.message)
-                    .await
-                {
-                    let res = WsResponse::err(&req.id, e);
-                    let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
-                    continue;
+                let mut append_user = !continuing;
+                if let Some((message_index, edited)) = regenerate {
+                    let rolled_back = match session_store.get(&session_id).await {
+                        Some(session) => match regenerate_from_index(&session, message_index) {
+                            // An edit replaces the user message itself; a plain retry keeps it.
+                            Ok(index) => session_store
+                                .truncate(&session_id, if edited { index } else { index + 1 })
+                                .await
+                                .map(|removed| session.messages.len() - removed),
+                            Err(e) => Err(e),
+                        },
+                        None => Err("session not found".to_string()),
+                    };
+                    match rolled_back {
+                        Ok(message_count) => {
+                            broadcast_session_truncated(&state, &session_id, &orch_config.id, message_count);
+                        }
+                        Err(e) => {
+                            let res = WsResponse::err(&req.id, e);
+                            let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                            continue;
+                        }
+                    }
+                    append_user = edited;
                 }
-                broadcast_session_message(
+                if append_user {
+                    let user_message = params.message.clone();
+                    if let Err(e) = session_store
+                        .append_message(&session_id, "user", &params.message)
+                        .await
+                    {
+                        let res = WsResponse::err(&req.id, e);
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                    broadcast_session_message(
+                        &state,
+                        &session_id,
+                        &orch_config.id,
+                        "user",
+                        &user_message,
+                        None,
+                        None,
+                        None,
+                        None,
+                    );
+                }
+                // Use request provider override when valid, else the tier route or orchestrator default.
+                let (provider_choice, model_name, route) = orchestrator_turn_model(
                     &state,
-                    &session_id,
-                    &orch_config.id,
-                    "user",
-                    &user_message,
-                    None,
-                    None,
-                    None,
-                    None,
-                );
-                // Use request provider override when valid, else orchestrator default.
-                let provider_choice = params
-                    .provider
-                    .as_deref()
-                    .and_then(|s| config::canonical_provider_id(&state.config.providers, s))
-                    .map(ProviderChoice::new)
-                    .unwrap_or_else(|| resolve_orchestrator_provider_choice(&state.config.providers, orch_config));
-                let model_name = resolve_model(
-                    &state.config.providers,
-                    orch_config.default_model.as_deref(),
+                    orch_config,
+                    orch_rt,
+                    params.provider.as_deref(),
                     params.model.as_deref(),
-                    &provider_choice,
                 );
+                if let Some(route) = route {
+                    broadcast_model_route(&state, &session_id, &orch_config.id, route, &route.steps[0], RouteReason::Initial);
+                }
                 let has_workers = !state.worker_delegate_runtimes.is_empty();
                 let system_context = &orch_rt.system_context;
                 let tools = merge_delegate_task(orch_rt.tools_list.clone(), has_workers);
-                let tool_executor = orch_rt.tool_executor.as_deref();
+                let tool_executor = orch_rt.tool_executor.clone();
                 let worker_tools = worker_tool_list(tools.as_ref());
                 // Get or create the stop flag for this session. The flag is cleared
                 // at the start of each new turn inside execute_turn_main. The same
@@ -2292,32 +3220,15 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                         .or_insert_with(|| Arc::new(AtomicBool::new(false)))
                         .clone()
                 };
-                let delegate = Some(DelegateContext {
-                    clients: &state.provider_clients,
-                    providers: &state.config.providers,
-                    agents: &state.config.agents,
-                    orchestrator_id: Some(orch_config.id.as_str()),
-                    orchestrator_system_context: if system_context.trim().is_empty() {
-                        None
-                    } else {
-                        Some(system_context.as_str())
-                    },
-                    orchestrator_worker_tools: worker_tools,
-                    orchestrator_tool_executor: tool_executor,
-                    worker_runtimes: Some(state.worker_delegate_runtimes.as_ref()),
-                    observability: Some(DelegateObservability {
-                        event_tx: state.event_tx.clone(),
-                        session_id: Some(session_id.clone()),
-                        orchestrator_id: Some(orch_config.id.clone()),
-                        source: Some("orchestrator".to_string()),
-                        tool_index_offset: 0,
-                        emitted_tool_calls: AtomicUsize::new(0),
-                    }),
-                    session_store: Some(session_store),
-                    session_id: Some(session_id.as_str()),
-                    stop_flag: Some(stop_flag.clone()),
-                    tool_index_offset: 0,
-                });
+                let delegate = Some(state.delegate_context(
+                    orch_config,
+                    orch_rt,
+                    worker_tools,
+                    session_store,
+                    &session_id,
+                    stop_flag.clone(),
+                ));
+                let escalation = route.zip(delegate.clone());
                 let provider_dyn = state.provider_clients.get(&provider_choice)
                     .ok_or_else(|| format!("no client for provider '{}'", provider_choice))
                     .expect("provider client should exist");
@@ -2326,20 +3237,30 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                 } else {
                     Some(system_context.as_str())
                 };
-                let run_result = agent::run_turn_dyn(
-                    session_store,
-                    &session_id,
-                    provider_dyn,
-                    &model_name,
-                    system_context_opt,
-                    orch_config.max_tool_loops_per_turn,
+                let turn = agent::SessionTurn {
+                    store: session_store,
+                    session_id: &session_id,
+                    provider: provider_dyn,
+                    model: &model_name,
+                    system_context: system_context_opt,
+                    max_tool_loops_per_turn: orch_config.max_tool_loops_per_turn,
                     tools,
                     tool_executor,
                     delegate,
-                    None,
-                    Some(stop_flag),
-                )
-                .await;
+                    stop_flag: Some(stop_flag),
+                    redact_tool_output: state.redacts_tool_output(&provider_choice, route),
+                };
+                let run_result = if continuing {
+                    agent::continue_turn_dyn(turn, None).await
+                } else {
+                    agent::run_turn_dyn(turn, None).await
+                };
+                let run_result = match (run_result, escalation) {
+                    (Ok(r), Some((route, ctx))) => {
+                        escalate_routed_turn(&state, orch_config, orch_rt, route, session_store, ctx, r).await
+                    }
+                    (run_result, _) => run_result,
+                };
                 match run_result
                 {
                     Ok(result) => {
@@ -2398,6 +3319,15 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                                 .unwrap_or_else(|_| json!([]));
                             payload["pendingToolCalls"] = pending;
                         }
+                        if let Some(handoff) = requested_handoff(&state.config.agents, &orch_config.id, &result) {
+                            payload["handoff"] = match transfer_session(&state, &session_id, &handoff).await {
+                                Ok(transfer) => transfer,
+                                Err(e) => {
+                                    log::warn!("agent: handoff to {} failed: {}", handoff.orchestrator_id, e);
+                                    json!({ "toOrchestratorId": handoff.orchestrator_id, "error": e })
+                                }
+                            };
+                        }
                         let res = WsResponse::ok(&req.id, payload);
                         let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
                     }
@@ -2511,12 +3441,31 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                                 .map(session_message_to_json)
                                 .collect()
                         };
-                        let res = WsResponse::ok(&req.id, json!({
+                        let mut payload = json!({
                             "id": session.id,
                             "messages": messages,
                             "createdAt": session.created_at,
                             "updatedAt": session.updated_at,
-                        }));
+                        });
+                        if !session.archived_branches.is_empty() {
+                            let branches: Vec<serde_json::Value> = session.archived_branches.iter()
+                                .map(|b| json!({
+                                    "archivedAt": b.archived_at,
+                                    "fromIndex": b.from_index,
+                                    "messages": b.messages.iter().map(session_message_to_json).collect::<Vec<_>>(),
+                                    "delegationTranscripts": b.delegation_transcripts.iter().map(transcript_link_to_json).collect::<Vec<_>>(),
+                                }))
+                                .collect();
+                            payload["archivedBranches"] = json!(branches);
+                        }
+                        if !session.delegation_transcripts.is_empty() {
+                            payload["delegationTranscripts"] = json!(session
+                                .delegation_transcripts
+                                .iter()
+                                .map(transcript_link_to_json)
+                                .collect::<Vec<_>>());
+                        }
+                        let res = WsResponse::ok(&req.id, payload);
                         let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
                     }
                     None => {
@@ -2525,6 +3474,157 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                     }
                 }
             }
+            "sessions.delegation" => {
+                let params: SessionsDelegationParams = match serde_json::from_value(req.params.clone()) {
+                    Ok(p) => p,
+                    Err(_) => {
+                        let res = WsResponse::err(&req.id, "invalid sessions.delegation params");
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                };
+                let mut session = None;
+                for store in state.session_stores.values() {
+                    if let Some(s) = store.get(&params.session_id).await {
+                        session = Some(s);
+                        break;
+                    }
+                }
+                let res = match session {
+                    Some(session) => match delegation_transcript_json(&state.profile_dir, &session, &params.delegation_id) {
+                        Some(payload) => WsResponse::ok(&req.id, payload),
+                        None => WsResponse::err(&req.id, "delegation transcript not found"),
+                    },
+                    None => WsResponse::err(&req.id, "session not found"),
+                };
+                let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+            }
+            "delegation.cancel" => {
+                let params: DelegationCancelParams = match serde_json::from_value(req.params.clone()) {
+                    Ok(p) => p,
+                    Err(_) => {
+                        let res = WsResponse::err(&req.id, "invalid delegation.cancel params");
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                };
+                let res = if state.delegation_cancels.cancel(&params.delegation_id) {
+                    WsResponse::ok(
+                        &req.id,
+                        serde_json::json!({ "delegationId": params.delegation_id, "cancelled": true }),
+                    )
+                } else {
+                    WsResponse::err(&req.id, "delegation not running")
+                };
+                let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+            }
+            "workflow.run" => {
+                let params: WorkflowRunParams = match serde_json::from_value(req.params.clone()) {
+                    Ok(p) => p,
+                    Err(_) => {
+                        let res = WsResponse::err(&req.id, "invalid workflow.run params");
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                };
+                let resolved = state
+                    .orchestrator_runtime(params.orchestrator_id.as_deref())
+                    .and_then(|rt| Ok((rt, state.session_store_for(params.orchestrator_id.as_deref())?)))
+                    .and_then(|(rt, store)| {
+                        let orch = state.config.agents.orchestrator(params.orchestrator_id.as_deref())?;
+                        let workflow = state
+                            .config
+                            .workflows
+                            .iter()
+                            .find(|w| w.id == params.workflow_id && orch.workflow_enabled(&w.id))
+                            .ok_or_else(|| format!("unknown workflow: {}", params.workflow_id))?;
+                        let inputs = workflow_inputs(workflow, &params.inputs)?;
+                        Ok((rt, store, orch, workflow, inputs))
+                    });
+                let (orch_rt, session_store, orch_config, workflow, inputs) = match resolved {
+                    Ok(r) => r,
+                    Err(e) => {
+                        let res = WsResponse::err(&req.id, e);
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                };
+                let session_id = match params.session_id {
+                    Some(ref id) => session_store.get_or_create(id.clone()).await,
+                    None => session_store.create().await,
+                };
+                let stop_flag = {
+                    let mut flags = state.session_stop_flags.write().await;
+                    flags
+                        .entry(session_id.clone())
+                        .or_insert_with(|| Arc::new(AtomicBool::new(false)))
+                        .clone()
+                };
+                stop_flag.store(false, Ordering::SeqCst);
+                let ctx = state.delegate_context(
+                    orch_config,
+                    orch_rt,
+                    worker_tool_list(orch_rt.tools_list.as_ref()),
+                    session_store,
+                    &session_id,
+                    stop_flag,
+                );
+                let run = run_workflow(&ctx, workflow, inputs).await;
+                // Step delegations are recorded against this message (the next one appended).
+                let summary = run.summary();
+                if let Err(e) = session_store.append_message(&session_id, "assistant", &summary).await {
+                    log::warn!("workflow.run: failed to append result: {}", e);
+                }
+                broadcast_session_message(
+                    &state,
+                    &session_id,
+                    &orch_config.id,
+                    "assistant",
+                    &summary,
+                    None,
+                    None,
+                    None,
+                    None,
+                );
+                let mut payload = serde_json::to_value(&run).unwrap_or_else(|_| json!({}));
+                payload["sessionId"] = json!(session_id);
+                let res = WsResponse::ok(&req.id, payload);
+                let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+            }
+            "sessions.truncate_after" => {
+                let params: SessionsTruncateAfterParams = match serde_json::from_value(req.params.clone()) {
+                    Ok(p) => p,
+                    Err(_) => {
+                        let res = WsResponse::err(&req.id, "invalid sessions.truncate_after params");
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                };
+                let mut found = None;
+                for (orch_id, store) in state.session_stores.iter() {
+                    if let Some(s) = store.get(&params.session_id).await {
+                        found = Some((orch_id.clone(), store, s.messages.len()));
+                        break;
+                    }
+                }
+                let res = match found {
+                    Some((orch_id, store, len)) => {
+                        match store.truncate(&params.session_id, params.message_index.saturating_add(1)).await {
+                            Ok(removed) => {
+                                broadcast_session_truncated(&state, &params.session_id, &orch_id, len - removed);
+                                WsResponse::ok(&req.id, json!({
+                                    "sessionId": params.session_id,
+                                    "removed": removed,
+                                    "messageCount": len - removed,
+                                }))
+                            }
+                            Err(e) => WsResponse::err(&req.id, e),
+                        }
+                    }
+                    None => WsResponse::err(&req.id, "session not found"),
+                };
+                let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+            }
             "sessions.delete" => {
                 let params: SessionsDeleteParams = match serde_json::from_value(req.params.clone()) {
                     Ok(p) => p,
@@ -2568,6 +3668,33 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                     let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
                 }
             }
+            "sessions.transfer" => {
+                let params: SessionsTransferParams = match serde_json::from_value(req.params.clone()) {
+                    Ok(p) => p,
+                    Err(_) => {
+                        let res = WsResponse::err(&req.id, "invalid sessions.transfer params");
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                };
+                let transfer = match TransferMode::parse(params.mode.as_deref()) {
+                    Ok(mode) => HandoffRequest {
+                        orchestrator_id: params.orchestrator_id,
+                        summary: params.summary.filter(|s| !s.trim().is_empty()),
+                        mode,
+                    },
+                    Err(e) => {
+                        let res = WsResponse::err(&req.id, e);
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                };
+                let res = match transfer_session(&state, &params.session_id, &transfer).await {
+                    Ok(payload) => WsResponse::ok(&req.id, payload),
+                    Err(e) => WsResponse::err(&req.id, e),
+                };
+                let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+            }
             "sessions.delete_all" => {
                 let params: SessionsDeleteAllParams = match serde_json::from_value(req.params.clone()) {
                     Ok(p) => p,
@@ -2612,6 +3739,65 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
                 let res = WsResponse::ok(&req.id, json!({ "deletedCount": total }));
                 let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
             }
+            "approvals.list" => {
+                let params: ApprovalsListParams =
+                    serde_json::from_value(req.params.clone()).unwrap_or(ApprovalsListParams { session_id: None });
+                let approvals: Vec<serde_json::Value> = state
+                    .approvals
+                    .list(params.session_id.as_deref())
+                    .await
+                    .into_iter()
+                    .map(|r| {
+                        let mut v = serde_json::to_value(&r).unwrap_or_else(|_| json!({}));
+                        v["live"] = json!(r.live);
+                        v
+                    })
+                    .collect();
+                let res = WsResponse::ok(&req.id, json!({ "approvals": approvals }));
+                let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+            }
+            "approvals.decide" => {
+                let params: ApprovalsDecideParams = match serde_json::from_value(req.params.clone()) {
+                    Ok(p) => p,
+                    Err(_) => {
+                        let res = WsResponse::err(&req.id, "invalid approvals.decide params");
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                };
+                let decision = match params.decision.as_str() {
+                    "approve" => ApprovalDecision::Approve,
+                    "deny" => ApprovalDecision::Deny(params.reason.clone()),
+                    other => {
+                        let res = WsResponse::err(
+                            &req.id,
+                            format!("invalid decision \"{}\": expected \"approve\" or \"deny\"", other),
+                        );
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                        continue;
+                    }
+                };
+                match state.approvals.decide(&params.id, decision.clone()).await {
+                    Ok(record) => {
+                        let mut payload = json!({
+                            "id": record.id,
+                            "decision": decision.as_str(),
+                            "live": record.live,
+                        });
+                        if !record.live {
+                            if let Some(result) = resolve_restored_approval(&state, &record, &decision).await {
+                                payload["toolResult"] = json!(result);
+                            }
+                        }
+                        let res = WsResponse::ok(&req.id, payload);
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                    }
+                    Err(e) => {
+                        let res = WsResponse::err(&req.id, e);
+                        let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
+                    }
+                }
+            }
             _ => {
                 let res = WsResponse::err(&req.id, format!("unknown method: {}", req.method));
                 let _ = socket.send(Message::Text(serde_json::to_string(&res).unwrap_or_default().into())).await;
@@ -2638,6 +3824,120 @@ async fn handle_socket(mut socket: WebSocket, state: GatewayState) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn regenerate_from_index_defaults_to_last_user_message() {
+        use crate::session::{Session, SessionMessage};
+        let session = Session {
+            id: "s".to_string(),
+            messages: vec![
+                SessionMessage::user("one"),
+                SessionMessage::assistant("a"),
+                SessionMessage::user("two"),
+                SessionMessage::assistant("b"),
+            ],
+            delegation_count: 0,
+            delegation_by_worker: HashMap::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            delegation_log: Vec::new(),
+            archived_branches: Vec::new(),
+            delegation_transcripts: Vec::new(),
+        };
+        assert_eq!(regenerate_from_index(&session, None), Ok(2));
+        assert_eq!(regenerate_from_index(&session, Some(0)), Ok(0));
+        assert!(regenerate_from_index(&session, Some(1)).is_err());
+        assert!(regenerate_from_index(&session, Some(9)).is_err());
+    }
+
+    #[test]
+    fn unanswered_tool_calls_lists_calls_without_results() {
+        use crate::providers::{ToolCall, ToolCallFunction};
+        use crate::session::SessionMessage;
+        let call = |name: &str| ToolCall {
+            typ: "function".to_string(),
+            function: ToolCallFunction {
+                index: None,
+                name: name.to_string(),
+                arguments: json!({}),
+            },
+        };
+        let tool = |name: &str| SessionMessage {
+            role: "tool".to_string(),
+            content: "ok".to_string(),
+            tool_calls: None,
+            tool_name: Some(name.to_string()),
+        };
+        let mut messages = vec![
+            SessionMessage::user("go"),
+            SessionMessage {
+                tool_calls: Some(vec![call("files_read"), call("files_write"), call("git_commit")]),
+                ..SessionMessage::assistant("")
+            },
+            tool("files_read"),
+        ];
+        let (calls, answered) = unanswered_tool_calls(&messages).expect("unanswered");
+        assert_eq!(calls.len(), 3);
+        assert_eq!(answered, 1);
+
+        messages.push(tool("files_write"));
+        messages.push(tool("git_commit"));
+        assert!(unanswered_tool_calls(&messages).is_none());
+        messages.push(SessionMessage::assistant("done"));
+        assert!(unanswered_tool_calls(&messages).is_none());
+    }
+
+    #[test]
+    fn delegation_transcript_json_finds_archived_links_and_nested() {
+        use crate::session::{
+            write_delegation_transcript, ArchivedBranch, DelegationTranscript, DelegationTranscriptLink, Session,
+            SessionMessage,
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let link = |id: &str, worker: &str, parent: Option<&str>| DelegationTranscriptLink {
+            delegation_id: id.to_string(),
+            worker_id: worker.to_string(),
+            message_index: 2,
+            parent_delegation_id: parent.map(str::to_string),
+        };
+        let transcript = DelegationTranscript {
+            id: "dlg-a".to_string(),
+            worker_id: "search".to_string(),
+            provider: "ollama".to_string(),
+            model: "llama3".to_string(),
+            parent_session_id: Some("s".to_string()),
+            parent_delegation_id: None,
+            message_index: Some(2),
+            created_at: String::new(),
+            stopped: false,
+            error: None,
+            messages: vec![SessionMessage::user("find it")],
+        };
+        write_delegation_transcript(&sessions_dir(dir.path(), "search"), &transcript).unwrap();
+        let session = Session {
+            id: "s".to_string(),
+            messages: vec![SessionMessage::user("one")],
+            delegation_count: 0,
+            delegation_by_worker: HashMap::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            delegation_log: Vec::new(),
+            archived_branches: vec![ArchivedBranch {
+                archived_at: String::new(),
+                from_index: 1,
+                messages: Vec::new(),
+                delegation_transcripts: vec![link("dlg-a", "search", None), link("dlg-b", "code", Some("dlg-a"))],
+            }],
+            delegation_transcripts: Vec::new(),
+        };
+
+        let v = delegation_transcript_json(dir.path(), &session, "dlg-a").expect("transcript");
+        assert_eq!(v["workerId"], "search");
+        assert_eq!(v["messages"][0]["content"], "find it");
+        assert_eq!(v["nested"][0]["delegationId"], "dlg-b");
+        assert!(delegation_transcript_json(dir.path(), &session, "dlg-b").is_none());
+        assert!(delegation_transcript_json(dir.path(), &session, "dlg-x").is_none());
+    }
+
     #[tokio::test]
     async fn register_same_client_multiple_connections_no_kick() {
         // A single client should be able to hold multiple concurrent connections
@@ -3,6 +3,7 @@
 
 pub mod agent;
 pub mod agent_ctx;
+pub mod approval;
 pub mod channels;
 pub mod config;
 pub mod device;
@@ -13,7 +14,9 @@ pub mod logging;
 pub mod orchestration;
 pub mod profile;
 pub mod providers;
+pub mod redaction;
 pub mod routing;
+pub mod secrets;
 pub mod session;
 pub mod skills;
 pub mod tools;
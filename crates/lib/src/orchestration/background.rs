@@ -0,0 +1,360 @@
+//! Background delegation for orchestrators with **`asyncDelegation: true`**: `delegate_task`
+//! starts the worker turn in a spawned task and returns a handle right away, so the orchestrator
+//! keeps calling its own tools while workers run.
+//!
+//! Finished results are added to the conversation at the start of the next loop iteration (or
+//! returned earlier by **`check_delegation`** / **`await_delegation`**). A turn does not end while
+//! delegations are running: when the model replies without tool calls, the loop waits for them and
+//! calls the model again with their results. Workers share the turn's stop flag, so a stopped turn
+//! waits for them to stop and records their results before returning.
+
+use super::delegate::{execute_delegate_task, DelegateContext, DelegateTaskResult};
+use crate::providers::{ToolDefinition, ToolFunctionDefinition};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+pub const CHECK_DELEGATION_TOOL_NAME: &str = "check_delegation";
+pub const AWAIT_DELEGATION_TOOL_NAME: &str = "await_delegation";
+
+/// True for `check_delegation` and `await_delegation`.
+pub fn is_delegation_handle_tool(name: &str) -> bool {
+    name == CHECK_DELEGATION_TOOL_NAME || name == AWAIT_DELEGATION_TOOL_NAME
+}
+
+fn delegation_id_parameter() -> Value {
+    json!({
+        "type": "string",
+        "description": "delegationId returned by delegate_task; omit for every background delegation of this turn"
+    })
+}
+
+pub fn check_delegation_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        typ: "function".to_string(),
+        function: ToolFunctionDefinition {
+            name: CHECK_DELEGATION_TOOL_NAME.to_string(),
+            description: Some("Check on background delegations without waiting: each is running, completed (with its result), or failed.".to_string()),
+            parameters: json!({
+                "type": "object",
+                "properties": { "delegationId": delegation_id_parameter() }
+            }),
+        },
+    }
+}
+
+pub fn await_delegation_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        typ: "function".to_string(),
+        function: ToolFunctionDefinition {
+            name: AWAIT_DELEGATION_TOOL_NAME.to_string(),
+            description: Some("Wait for background delegations to finish and return their results. Call this when you cannot continue without a worker's result.".to_string()),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "delegationId": delegation_id_parameter(),
+                    "timeoutSecs": {
+                        "type": "integer",
+                        "description": "stop waiting after this many seconds and report what is still running"
+                    }
+                }
+            }),
+        },
+    }
+}
+
+/// Add `check_delegation` and `await_delegation` after the other delegation tools when the
+/// orchestrator uses `asyncDelegation` and has workers, unless the list already contains them.
+pub fn merge_delegation_handle_tools(
+    tools: Option<Vec<ToolDefinition>>,
+    enabled: bool,
+) -> Option<Vec<ToolDefinition>> {
+    if !enabled {
+        return tools;
+    }
+    let mut v = tools.unwrap_or_default();
+    let at = v
+        .iter()
+        .rposition(|t| {
+            let name = t.function.name.as_str();
+            name == super::DELEGATE_TASK_TOOL_NAME || name == super::DELEGATE_MANY_TOOL_NAME
+        })
+        .map_or(0, |i| i + 1);
+    let missing: Vec<ToolDefinition> = [check_delegation_tool_definition(), await_delegation_tool_definition()]
+        .into_iter()
+        .filter(|d| !v.iter().any(|t| t.function.name == d.function.name))
+        .collect();
+    v.splice(at..at, missing);
+    Some(v)
+}
+
+/// One background `delegate_task` call.
+struct Background {
+    id: String,
+    task: Option<JoinHandle<(String, bool)>>,
+    /// Tool output of the finished delegation and whether its worker was stopped.
+    outcome: Option<(String, bool)>,
+    /// Whether the result has been given to the model (by a tool result or an injected message).
+    delivered: bool,
+}
+
+impl Background {
+    /// Collect the task's outcome; waits when the task is still running.
+    async fn settle(&mut self) {
+        if let Some(task) = self.task.take() {
+            self.outcome = Some(task.await.unwrap_or_else(|e| {
+                log::warn!("orchestration: background delegation {} failed: {}", self.id, e);
+                (format!("error: delegation task failed: {}", e), false)
+            }));
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.task.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// `{delegationId, status}` plus the `result` (or `error`) once finished.
+    fn status(&self) -> Value {
+        match self.outcome {
+            None => json!({ "delegationId": self.id, "status": "running" }),
+            Some((ref output, _)) => match output.strip_prefix("error: ") {
+                Some(e) => json!({ "delegationId": self.id, "status": "failed", "error": e }),
+                None => json!({
+                    "delegationId": self.id,
+                    "status": "completed",
+                    "result": serde_json::from_str::<Value>(output).unwrap_or_else(|_| json!(output)),
+                }),
+            },
+        }
+    }
+}
+
+/// Background delegations of one orchestrator turn. At most `maxParallelWorkflows` worker turns
+/// run at once; further ones wait for a slot. Tasks still running when this is dropped (the turn
+/// failed) are aborted.
+pub struct BackgroundDelegations {
+    slots: Arc<Semaphore>,
+    entries: Vec<Background>,
+}
+
+impl BackgroundDelegations {
+    pub fn new(max_parallel: usize) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(max_parallel.max(1))),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Start a `delegate_task` call in the background and return the handle tool result.
+    /// `ctx` should come from [`DelegateContext::for_call`] so its events carry the same
+    /// `delegationId` as the handle.
+    pub fn start(&mut self, ctx: DelegateContext, args: Value) -> String {
+        let id = ctx
+            .observability
+            .as_ref()
+            .and_then(|o| o.delegation_id.clone())
+            .unwrap_or_else(|| format!("dlg-{}", uuid::Uuid::new_v4()));
+        let slots = self.slots.clone();
+        let task = tokio::spawn(async move {
+            let _slot = slots.acquire_owned().await.ok();
+            match execute_delegate_task(&ctx, &args).await {
+                Ok(DelegateTaskResult { output, stopped }) => (output, stopped),
+                Err(e) => {
+                    log::warn!("agent: delegate_task failed: {}", e);
+                    (format!("error: {}", e), false)
+                }
+            }
+        });
+        log::info!("orchestration: delegation {} running in the background", id);
+        let handle = json!({
+            "delegationId": id,
+            "status": "running",
+            "note": "The worker runs in the background. Its result is added to the conversation when it finishes; call await_delegation to wait for it or check_delegation to poll.",
+        });
+        self.entries.push(Background {
+            id,
+            task: Some(task),
+            outcome: None,
+            delivered: false,
+        });
+        handle.to_string()
+    }
+
+    /// True while some delegation is running or has a result the model has not seen.
+    pub fn has_undelivered(&self) -> bool {
+        self.entries.iter().any(|e| !e.delivered)
+    }
+
+    /// Wait for every running delegation.
+    pub async fn wait_all(&mut self) {
+        for e in &mut self.entries {
+            e.settle().await;
+        }
+    }
+
+    /// Results finished since they were last reported, as messages to add to the conversation,
+    /// and whether any of those workers was stopped.
+    pub async fn take_finished(&mut self) -> (Vec<String>, bool) {
+        let mut messages = Vec::new();
+        let mut stopped = false;
+        for e in self.entries.iter_mut().filter(|e| !e.delivered && e.is_finished()) {
+            e.settle().await;
+            e.delivered = true;
+            stopped |= e.outcome.as_ref().is_some_and(|(_, s)| *s);
+            messages.push(format!("Background delegation {} finished:\n{}", e.id, e.status()));
+        }
+        (messages, stopped)
+    }
+
+    /// Run a `check_delegation` (`wait` false) or `await_delegation` (`wait` true) call. Returns
+    /// the tool result and whether a reported worker was stopped.
+    pub async fn handle_call(&mut self, args: &Value, wait: bool) -> (String, bool) {
+        let wanted = args
+            .get("delegationId")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+        if let Some(id) = wanted {
+            if !self.entries.iter().any(|e| e.id == id) {
+                return (format!("error: unknown delegationId {} (not started in this turn)", id), false);
+            }
+        }
+        let timeout = args
+            .get("timeoutSecs")
+            .and_then(Value::as_u64)
+            .map(Duration::from_secs);
+        let deadline = timeout.map(|t| tokio::time::Instant::now() + t);
+
+        let mut statuses = Vec::new();
+        let mut stopped = false;
+        for e in self
+            .entries
+            .iter_mut()
+            .filter(|e| wanted.is_none_or(|id| e.id == id))
+        {
+            if wait {
+                match (deadline, e.task.as_mut()) {
+                    (Some(deadline), Some(task)) => {
+                        if let Ok(joined) = tokio::time::timeout_at(deadline, task).await {
+                            e.task = None;
+                            e.outcome = Some(joined.unwrap_or_else(|err| {
+                                (format!("error: delegation task failed: {}", err), false)
+                            }));
+                        }
+                    }
+                    _ => e.settle().await,
+                }
+            } else if e.is_finished() {
+                e.settle().await;
+            }
+            if let Some((_, s)) = e.outcome {
+                e.delivered = true;
+                stopped |= s;
+            }
+            statuses.push(e.status());
+        }
+        (json!({ "delegations": statuses }).to_string(), stopped)
+    }
+}
+
+impl Drop for BackgroundDelegations {
+    fn drop(&mut self) {
+        for task in self.entries.iter().filter_map(|e| e.task.as_ref()) {
+            task.abort();
+        }
+    }
+}
+
+/// Delegations that run together and are collected together (the queued `delegate_task` calls
+/// of one assistant response). Each runs in its own spawned task, at most `max_parallel` at once;
+/// tasks still running when this is dropped are aborted.
+pub struct DelegationBatch<T> {
+    slots: Arc<Semaphore>,
+    tasks: Vec<JoinHandle<T>>,
+}
+
+impl<T: Send + 'static> DelegationBatch<T> {
+    pub fn new(max_parallel: usize) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(max_parallel.max(1))),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Spawn `fut`; it starts once a slot is free.
+    pub fn spawn<F>(&mut self, fut: F)
+    where
+        F: std::future::Future<Output = T> + Send + 'static,
+    {
+        let slots = self.slots.clone();
+        self.tasks.push(tokio::spawn(async move {
+            let _slot = slots.acquire_owned().await.ok();
+            fut.await
+        }));
+    }
+
+    /// Wait for every task; results are in spawn order. A task that panicked yields `Err`.
+    pub async fn join(mut self) -> Vec<Result<T, String>> {
+        let mut results = Vec::with_capacity(self.tasks.len());
+        for task in self.tasks.iter_mut() {
+            results.push(task.await.map_err(|e| format!("delegation task failed: {}", e)));
+        }
+        results
+    }
+}
+
+impl<T> Drop for DelegationBatch<T> {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_places_handle_tools_after_delegation_tools() {
+        assert!(merge_delegation_handle_tools(None, false).is_none());
+        let tools = super::super::merge_delegate_task(None, true);
+        let tools = super::super::merge_delegate_many(tools, true);
+        let tools = merge_delegation_handle_tools(tools, true).unwrap();
+        let names: Vec<&str> = tools.iter().map(|t| t.function.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                super::super::DELEGATE_TASK_TOOL_NAME,
+                super::super::DELEGATE_MANY_TOOL_NAME,
+                CHECK_DELEGATION_TOOL_NAME,
+                AWAIT_DELEGATION_TOOL_NAME,
+            ]
+        );
+        assert_eq!(merge_delegation_handle_tools(Some(tools), true).unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn status_reports_results_once() {
+        let mut bg = BackgroundDelegations::new(1);
+        bg.entries.push(Background {
+            id: "dlg-1".to_string(),
+            task: Some(tokio::spawn(async { (r#"{"reply":"done"}"#.to_string(), false) })),
+            outcome: None,
+            delivered: false,
+        });
+        bg.wait_all().await;
+        assert!(bg.has_undelivered());
+        let (messages, stopped) = bg.take_finished().await;
+        assert!(!stopped);
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains(r#""result":{"reply":"done"}"#), "{}", messages[0]);
+        assert!(!bg.has_undelivered());
+        assert!(bg.take_finished().await.0.is_empty());
+
+        let (out, _) = bg.handle_call(&json!({"delegationId": "dlg-2"}), false).await;
+        assert!(out.starts_with("error: unknown delegationId"), "{out}");
+    }
+}
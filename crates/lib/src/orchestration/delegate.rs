@@ -1,22 +1,34 @@
 //! Built-in `delegate_task` tool: run a worker turn on the worker's single `(provider, model)` pair (see `base/adr/ORCHESTRATION.md`).
 
+use super::background::is_delegation_handle_tool;
 use super::choice::ProviderChoice;
 use super::dispatch::ProviderClients;
+use super::fan_out::DELEGATE_MANY_TOOL_NAME;
+use super::handoff::HANDOFF_TOOL_NAME;
 use super::model::resolve_model;
+use super::output_schema::{self, OUTPUT_SCHEMA_MAX_RETRIES};
 use super::policy::{apply_delegation_bracket_match, assert_session_delegation_limits};
-use crate::agent::{run_turn_with_messages_dyn, ToolExecutor};
+use super::routing::{ModelRoute, RouteReason, RouteStep, EVENT_MODEL_ROUTE};
+use super::workflow::is_workflow_tool;
+use crate::agent::{run_turn_with_transcript, AgentTurnResult, ToolCallsInFlight, ToolExecutor, WorkerTurn};
+use crate::approval::{ApprovalGate, ApprovalScope};
 use crate::config::{
-    canonical_provider_id, AgentsConfig, OrchestratorConfig,
-    ProvidersConfig, SkillContextMode,
+    canonical_provider_id, sessions_dir, Config, OrchestratorConfig,
+    ProvidersConfig, SkillContextMode, WorkerConfig,
+};
+use crate::providers::{ChatMessage, Provider, ToolDefinition, ToolFunctionDefinition};
+use crate::session::{
+    chrono_now_iso8601, write_delegation_transcript, DelegationTranscript, SessionMessage,
+    SessionStore,
 };
-use crate::providers::{ChatMessage, ToolDefinition, ToolFunctionDefinition};
-use crate::session::SessionStore;
 use crate::skills::Skill;
 use serde_json::json;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use std::time::Duration;
+use tokio::sync::{broadcast, Notify};
 
 pub const DELEGATE_TASK_TOOL_NAME: &str = "delegate_task";
 
@@ -28,6 +40,11 @@ pub const EVENT_DELEGATE_COMPLETE: &str = "orchestration.delegate.complete";
 pub const EVENT_DELEGATE_ERROR: &str = "orchestration.delegate.error";
 /// WebSocket event name: delegation rejected (policy limit).
 pub const EVENT_DELEGATE_REJECTED: &str = "orchestration.delegate.rejected";
+/// WebSocket event name: a delegation attempt failed and the next one (retry or fallback) starts.
+pub const EVENT_DELEGATE_RETRY: &str = "orchestration.delegate.retry";
+
+/// Error text of a delegation cancelled through [`DelegationCancels::cancel`].
+pub const DELEGATION_CANCELLED: &str = "cancelled by operator";
 
 /// WebSocket event name: tool call started (tool about to execute).
 pub const EVENT_TOOL_CALL: &str = "session.tool_call";
@@ -53,18 +70,21 @@ pub struct DelegateObservability {
     /// display the author and style worker messages differently.
     pub source: Option<String>,
     /// Offset added to tool call/result `index` values emitted by this observability instance.
-    /// Copied from [`DelegateContext::tool_index_offset`] when the worker is spawned. The
-    /// orchestrator accumulates this value after each delegation so that successive workers
-    /// produce non-overlapping tool indices.
+    /// Ignored when `tool_index_counter` is set, which is how delegated workers number their
+    /// tool calls so that successive and concurrent workers produce non-overlapping indices.
     pub tool_index_offset: usize,
-    /// Number of tool_call events emitted through this observability instance.
-    /// Incremented atomically by `emit_tool_call` so the count is available even
-    /// when the worker turn fails partway through (e.g. provider timeout after
-    /// some tool calls have already been emitted). The orchestrator reads this
-    /// via [`DelegateObservability::emitted_tool_call_count`] to accumulate
-    /// `tool_index_offset` correctly in the error path.
-    /// Always initialize to 0 when constructing a new instance.
-    pub emitted_tool_calls: AtomicUsize,
+    /// Stable id of the delegation these events belong to (`dlg-<uuid>`), included in every
+    /// payload as `delegationId` so events from concurrent delegations can be attributed.
+    pub delegation_id: Option<String>,
+    /// For nested delegations, the `delegationId` of the delegation whose worker started this
+    /// one; included as `parentDelegationId` so clients can render the delegation tree.
+    pub parent_delegation_id: Option<String>,
+    /// When set, tool call indices are drawn from this shared counter instead of
+    /// `tool_index_offset + index` (see [`DelegationTurn`]); each result reuses the index of the
+    /// call before it, which holds because a worker runs its tool calls one at a time.
+    pub tool_index_counter: Option<Arc<AtomicUsize>>,
+    /// Index drawn for the most recent tool call when `tool_index_counter` is set.
+    pub last_tool_index: AtomicUsize,
 }
 
 impl Clone for DelegateObservability {
@@ -75,9 +95,10 @@ impl Clone for DelegateObservability {
             orchestrator_id: self.orchestrator_id.clone(),
             source: self.source.clone(),
             tool_index_offset: self.tool_index_offset,
-            // A cloned observability is a fresh instance for a new worker;
-            // the emitted count starts at zero.
-            emitted_tool_calls: AtomicUsize::new(0),
+            delegation_id: self.delegation_id.clone(),
+            parent_delegation_id: self.parent_delegation_id.clone(),
+            tool_index_counter: self.tool_index_counter.clone(),
+            last_tool_index: AtomicUsize::new(0),
         }
     }
 }
@@ -95,11 +116,17 @@ impl DelegateObservability {
             if let Some(ref oid) = self.orchestrator_id {
                 obj.insert("orchestratorId".to_string(), json!(oid));
             }
+            if let Some(ref did) = self.delegation_id {
+                obj.insert("delegationId".to_string(), json!(did));
+            }
+            if let Some(ref pid) = self.parent_delegation_id {
+                obj.insert("parentDelegationId".to_string(), json!(pid));
+            }
         }
         base
     }
 
-    fn merge_base(&self, extra: serde_json::Value) -> serde_json::Value {
+    pub(super) fn merge_base(&self, extra: serde_json::Value) -> serde_json::Value {
         let mut base = self.base_payload();
         if let Some(obj) = base.as_object_mut() {
             if let Some(e) = extra.as_object() {
@@ -111,7 +138,7 @@ impl DelegateObservability {
         base
     }
 
-    fn send(&self, event: &str, payload: serde_json::Value) {
+    pub(super) fn send(&self, event: &str, payload: serde_json::Value) {
         let frame = json!({
             "type": "event",
             "event": event,
@@ -159,23 +186,20 @@ impl DelegateObservability {
         tool_args: &serde_json::Value,
         index: usize,
     ) {
-        let effective_index = self.tool_index_offset + index;
+        let effective_index = match self.tool_index_counter {
+            Some(ref counter) => {
+                let i = counter.fetch_add(1, Ordering::Relaxed);
+                self.last_tool_index.store(i, Ordering::Relaxed);
+                i
+            }
+            None => self.tool_index_offset + index,
+        };
         let payload = self.merge_base(json!({
             "toolName": tool_name,
             "toolArgs": tool_args,
             "index": effective_index,
         }));
         self.send(EVENT_TOOL_CALL, payload);
-        self.emitted_tool_calls.fetch_add(1, Ordering::Relaxed);
-    }
-
-    /// Returns the number of tool_call events emitted through this observability
-    /// instance. Used by the orchestrator to accumulate `tool_index_offset` when
-    /// a worker turn fails partway through — the worker may have already emitted
-    /// some tool calls before the error, and those indices must not collide with
-    /// the next delegation's indices.
-    pub fn emitted_tool_call_count(&self) -> usize {
-        self.emitted_tool_calls.load(Ordering::Relaxed)
     }
 
     /// Emits [`EVENT_TOOL_RESULT`] when a tool execution completes.
@@ -185,7 +209,10 @@ impl DelegateObservability {
         tool_result: &str,
         index: usize,
     ) {
-        let effective_index = self.tool_index_offset + index;
+        let effective_index = match self.tool_index_counter {
+            Some(_) => self.last_tool_index.load(Ordering::Relaxed),
+            None => self.tool_index_offset + index,
+        };
         let payload = self.merge_base(json!({
             "toolName": tool_name,
             "toolResult": tool_result,
@@ -225,14 +252,6 @@ impl DelegateObservability {
     }
 }
 
-fn optional_worker_id_from_args(args: &serde_json::Value) -> Option<String> {
-    args.as_object()
-        .and_then(|o| o.get("workerId"))
-        .and_then(|v| v.as_str())
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-}
-
 /// Per-orchestrator runtime state (built at gateway startup, keyed by orchestrator id).
 pub struct OrchestratorRuntime {
     /// System context built from the orchestrator's **`AGENT.md`**, worker roster, and skills.
@@ -247,57 +266,209 @@ pub struct OrchestratorRuntime {
     pub tool_executor: Option<Arc<dyn ToolExecutor>>,
     /// Skill context mode for this orchestrator.
     pub context_mode: SkillContextMode,
+    /// Effective tool call approval rule for this orchestrator.
+    pub approval: ApprovalGate,
+    /// Tier route for this orchestrator's turns when it uses `modelRouting: "tier"`.
+    pub model_route: Option<ModelRoute>,
 }
 
 /// Per-worker skill bundle for `delegate_task` when `workerId` is set (built at gateway startup).
 pub struct WorkerDelegateRuntime {
     /// Static system context (no orchestrator roster block).
     pub system_context: String,
+    /// `## Workers` roster from this worker's `enabledWorkers`, appended to the system message
+    /// when nested delegation is allowed at the worker's depth. Empty when the worker cannot delegate.
+    pub workers_context: String,
     pub skills: Arc<Vec<Skill>>,
     pub tools_list: Option<Vec<ToolDefinition>>,
     pub tool_executor: Option<Arc<dyn ToolExecutor>>,
     pub context_mode: SkillContextMode,
+    /// Effective tool call approval rule for this worker's tool calls.
+    pub approval: ApprovalGate,
+    /// Tier route for delegations to this worker when it uses `modelRouting: "tier"`.
+    pub model_route: Option<ModelRoute>,
 }
 
-/// References needed to run a worker turn from the main agent loop.
+/// Everything needed to run a worker turn from the main agent loop. Owned (`Arc`-backed) so a
+/// clone can move into a spawned task when several delegations run concurrently.
 #[derive(Clone)]
-pub struct DelegateContext<'a> {
-    pub clients: &'a ProviderClients,
-    pub providers: &'a ProvidersConfig,
-    pub agents: &'a AgentsConfig,
+pub struct DelegateContext {
+    pub clients: ProviderClients,
+    pub config: Arc<Config>,
     /// The orchestrator id that owns this delegation context. Used by
     /// `resolve_delegate_target()` to enforce `enabledWorkers` and
     /// `enabledProviders`, and by policy enforcement to look up the
     /// correct orchestrator's delegation caps.
-    pub orchestrator_id: Option<&'a str>,
+    pub orchestrator_id: Option<String>,
     /// Full orchestrator system message for `delegate_task` without `workerId`.
-    pub orchestrator_system_context: Option<&'a str>,
+    pub orchestrator_system_context: Option<Arc<String>>,
     /// Skill tools for the orchestrator path (no `delegate_task`); used when `workerId` is absent.
-    pub orchestrator_worker_tools: Option<Vec<ToolDefinition>>,
-    pub orchestrator_tool_executor: Option<&'a dyn ToolExecutor>,
+    pub orchestrator_worker_tools: Option<Arc<Vec<ToolDefinition>>>,
+    pub orchestrator_tool_executor: Option<Arc<dyn ToolExecutor>>,
     /// When `workerId` is set, the worker turn uses this bundle instead of the orchestrator copies above.
-    pub worker_runtimes: Option<&'a HashMap<String, WorkerDelegateRuntime>>,
+    pub worker_runtimes: Option<Arc<HashMap<String, WorkerDelegateRuntime>>>,
     /// When set, emits gateway WebSocket events for delegate lifecycle (see [`DelegateObservability`]).
     pub observability: Option<DelegateObservability>,
     /// When set with [`DelegateContext::session_id`], session policy caps and [`SessionStore::record_delegation`] apply.
-    pub session_store: Option<&'a SessionStore>,
-    pub session_id: Option<&'a str>,
+    pub session_store: Option<Arc<SessionStore>>,
+    pub session_id: Option<String>,
     /// When set, the worker turn checks this flag at the top of each loop iteration
     /// and stops gracefully when the flag becomes true.
     pub stop_flag: Option<Arc<AtomicBool>>,
-    /// Offset added to tool call/result `index` values emitted by the worker's
-    /// observability. Initialized to 0 and accumulated by the orchestrator after
-    /// each delegation by adding the worker's `tool_call_count`. This ensures
-    /// successive delegations produce non-overlapping tool indices even when
-    /// workers share the same `source` label.
-    pub tool_index_offset: usize,
+    /// State shared by every delegation of one orchestrator turn (tool event indices,
+    /// delegations admitted by policy but not yet recorded).
+    pub turn: DelegationTurn,
+    /// Session message index this delegation's tool result will occupy. Set by the orchestrator
+    /// when a parallel batch appends its results after all delegations finish; `None` = the next
+    /// message appended.
+    pub result_message_index: Option<usize>,
+    /// Delegations above the agent using this context: 0 for the orchestrator, 1 for a worker
+    /// the orchestrator delegated to, and so on. Compared against `maxDelegationDepth`.
+    pub depth: usize,
+    /// Worker that owns this context when it delegates (nested delegation); `None` for the
+    /// orchestrator. Its `enabledWorkers` decides which workers it may delegate to.
+    pub delegating_worker: Option<String>,
+    /// When set, the orchestrator's gated tool calls wait for operator approval. Worker turns
+    /// reuse the store with the worker's own [`WorkerDelegateRuntime::approval`] rule (the
+    /// orchestrator's rule when `workerId` is absent).
+    pub approval: Option<ApprovalScope>,
+    /// Profile root. When set, every worker turn is saved as a [`DelegationTranscript`] under
+    /// `<profileRoot>/agents/<workerId>/sessions/` and linked from the parent session.
+    pub profile_dir: Option<PathBuf>,
+    /// When set, delegations that carry a `delegationId` can be cancelled through this registry.
+    pub cancels: Option<DelegationCancels>,
+    /// The delegating turn redacts skill tool output (its provider is in the profile's
+    /// `redaction` scope). Set by the turn that runs with this context; worker turns then redact
+    /// too, since their replies reach that provider.
+    pub redact_tool_output: bool,
+    /// Tool calls of the worker attempt that owns this context (nested delegation). Nested
+    /// attempts register theirs here too, so abandoning the outer attempt waits for them.
+    pub tool_calls_in_flight: Option<ToolCallsInFlight>,
+}
+
+/// Running delegations an operator can cancel (`delegation.cancel`), keyed by delegation id.
+/// Shared by every [`DelegateContext`] of a gateway.
+#[derive(Clone, Default)]
+pub struct DelegationCancels {
+    running: Arc<std::sync::Mutex<HashMap<String, Arc<Notify>>>>,
+}
+
+impl DelegationCancels {
+    /// Make `delegation_id` cancellable until the returned guard is dropped.
+    fn register(&self, delegation_id: &str) -> CancelRegistration {
+        let notify = Arc::new(Notify::new());
+        if let Ok(mut running) = self.running.lock() {
+            running.insert(delegation_id.to_string(), notify.clone());
+        }
+        CancelRegistration {
+            cancels: self.clone(),
+            delegation_id: delegation_id.to_string(),
+            notify,
+        }
+    }
+
+    /// Cancel a running delegation. Returns false when no delegation with that id is running.
+    pub fn cancel(&self, delegation_id: &str) -> bool {
+        let notify = self.running.lock().ok().and_then(|r| r.get(delegation_id).cloned());
+        match notify {
+            Some(n) => {
+                // `notify_one` keeps the permit when the delegation is between attempts.
+                n.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Entry in [`DelegationCancels`]; removes itself when the delegation ends.
+struct CancelRegistration {
+    cancels: DelegationCancels,
+    delegation_id: String,
+    notify: Arc<Notify>,
+}
+
+impl CancelRegistration {
+    fn notify(&self) -> &Notify {
+        &self.notify
+    }
 }
 
-/// Tool list passed to the worker: same definitions as the orchestrator minus `delegate_task` (nested delegation is disabled).
+impl Drop for CancelRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.cancels.running.lock() {
+            running.remove(&self.delegation_id);
+        }
+    }
+}
+
+/// Per-turn delegation state shared by all clones of a [`DelegateContext`].
+#[derive(Clone, Default)]
+pub struct DelegationTurn {
+    /// Next `index` for worker tool call/result events. Every worker draws from the same
+    /// counter, so successive or concurrent delegations never emit overlapping indices
+    /// even when they share a `source` label.
+    tool_index: Arc<AtomicUsize>,
+    /// Delegations admitted by session policy but not yet recorded, keyed by policy worker id.
+    /// Held across the policy check so concurrent delegations cannot overshoot the caps.
+    pending: Arc<tokio::sync::Mutex<HashMap<String, usize>>>,
+}
+
+impl DelegationTurn {
+    /// Check `maxDelegationsPerSession` / `maxDelegationsPerWorker` (counting delegations still
+    /// running) and reserve a slot for `worker_id` on success.
+    async fn admit(
+        &self,
+        store: &SessionStore,
+        session_id: &str,
+        orchestrator: &OrchestratorConfig,
+        worker_id: &str,
+    ) -> Result<(), String> {
+        let mut pending = self.pending.lock().await;
+        assert_session_delegation_limits(store, session_id, orchestrator, worker_id, &pending).await?;
+        *pending.entry(worker_id.to_string()).or_insert(0) += 1;
+        Ok(())
+    }
+
+    /// Release a slot taken by [`DelegationTurn::admit`], recording the delegation when it
+    /// succeeded (see [`SessionStore::record_delegation`] for `message_index`).
+    async fn release(
+        &self,
+        store: &SessionStore,
+        session_id: &str,
+        worker_id: &str,
+        succeeded: bool,
+        message_index: Option<usize>,
+    ) {
+        let mut pending = self.pending.lock().await;
+        if succeeded {
+            if let Err(e) = store.record_delegation(session_id, worker_id, message_index).await {
+                log::warn!("orchestration: record_delegation failed: {}", e);
+            }
+        }
+        if let Some(n) = pending.get_mut(worker_id) {
+            *n = n.saturating_sub(1);
+            if *n == 0 {
+                pending.remove(worker_id);
+            }
+        }
+    }
+}
+
+/// Tool list passed to the worker: same definitions as the orchestrator minus `delegate_task`, which
+/// [`execute_delegate_task`] adds back only when the worker may delegate further, and minus the
+/// orchestrator-only `delegate_many`, `workflow_*` and `handoff` tools.
 pub fn worker_tool_list(tools: Option<&Vec<ToolDefinition>>) -> Option<Vec<ToolDefinition>> {
     let v: Vec<ToolDefinition> = tools?
         .iter()
-        .filter(|t| t.function.name != DELEGATE_TASK_TOOL_NAME)
+        .filter(|t| {
+            let name = t.function.name.as_str();
+            name != DELEGATE_TASK_TOOL_NAME
+                && name != DELEGATE_MANY_TOOL_NAME
+                && !is_workflow_tool(name)
+                && !is_delegation_handle_tool(name)
+                && name != HANDOFF_TOOL_NAME
+        })
         .cloned()
         .collect();
     if v.is_empty() {
@@ -363,17 +534,112 @@ fn format_delegate_result(
     payload.to_string()
 }
 
-#[derive(Debug)]
+/// Tool output for a worker with `outputSchema`: the validated JSON value instead of free text.
+fn format_structured_delegate_result(
+    output: serde_json::Value,
+    provider_id: &str,
+    model: &str,
+) -> String {
+    let payload = serde_json::json!({
+        "output": output,
+        "worker": {
+            "provider": provider_id,
+            "model": model,
+        }
+    });
+    payload.to_string()
+}
+
+/// Parse and validate a worker reply against its `outputSchema`. On a mismatch the worker is
+/// re-asked up to [`OUTPUT_SCHEMA_MAX_RETRIES`] times: the validation error is appended to its
+/// full conversation in `messages` (tool results included) and the turn continues with the same
+/// tools and limits. `reply` is updated to the last reply received.
+async fn structured_worker_output(
+    provider: &dyn Provider,
+    model: &str,
+    messages: &mut Vec<ChatMessage>,
+    reply: &mut String,
+    schema: &serde_json::Value,
+    turn: &WorkerTurn<'_>,
+) -> Result<serde_json::Value, String> {
+    let mut attempts = 1;
+    loop {
+        let error = match output_schema::parse_output(reply, schema) {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        if attempts > OUTPUT_SCHEMA_MAX_RETRIES {
+            return Err(format!(
+                "{} after {} attempts: {}",
+                OUTPUT_SCHEMA_MISMATCH, attempts, error
+            ));
+        }
+        attempts += 1;
+        log::info!("orchestration: worker reply does not match outputSchema ({}); retrying", error);
+        messages.push(ChatMessage {
+            role: "user".to_string(),
+            content: output_schema::retry_instruction(&error),
+            tool_calls: None,
+            tool_name: None,
+        });
+        let result = run_turn_with_transcript(provider, model, messages, turn.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        if result.stopped {
+            return Err("worker stopped before its reply matched outputSchema".to_string());
+        }
+        *reply = result.content;
+    }
+}
+
+/// Start of the error for a worker reply that never matched its `outputSchema`.
+const OUTPUT_SCHEMA_MISMATCH: &str = "worker reply does not match outputSchema";
+
+#[derive(Debug, Clone)]
 struct DelegateTarget {
     provider_id: String,
     provider_choice: ProviderChoice,
     model: String,
 }
 
+impl From<&RouteStep> for DelegateTarget {
+    fn from(step: &RouteStep) -> Self {
+        Self {
+            provider_id: step.provider_id.clone(),
+            provider_choice: ProviderChoice::new(&step.provider_id),
+            model: step.model.clone(),
+        }
+    }
+}
+
+/// Reject `provider_id` unless it is in the orchestrator's `enabledProviders` (when set).
+fn check_enabled_provider(
+    providers: &ProvidersConfig,
+    orchestrator: &OrchestratorConfig,
+    provider_id: &str,
+) -> Result<(), String> {
+    if let Some(ref enabled) = orchestrator.enabled_providers {
+        if !enabled.is_empty() {
+            let canonical = canonical_provider_id(providers, provider_id);
+            let allowed = enabled
+                .iter()
+                .any(|p| canonical_provider_id(providers, p).as_ref() == canonical.as_ref());
+            if !allowed {
+                return Err(format!(
+                    "provider {} is not in this orchestrator's enabledProviders",
+                    provider_id
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn resolve_delegate_target(
     providers: &ProvidersConfig,
     orchestrator: &OrchestratorConfig,
     workers: &Option<Vec<crate::config::WorkerConfig>>,
+    delegating_worker: Option<&crate::config::WorkerConfig>,
     args: &serde_json::Value,
 ) -> Result<DelegateTarget, String> {
     let obj = args
@@ -386,22 +652,35 @@ fn resolve_delegate_target(
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty());
 
-    // Enforce enabledWorkers: reject delegation based on the orchestrator's enabled worker set.
+    // Enforce enabledWorkers: reject delegation based on the delegating agent's enabled worker set
+    // (the orchestrator's, or the worker's for nested delegation).
     // None = no workers allowed; Some([]) = all workers allowed; Some(list) = only listed workers.
+    let (enabled_workers, owner) = match delegating_worker {
+        Some(w) => {
+            if worker_id.is_none() {
+                return Err(format!(
+                    "nested delegate_task from worker {} requires a workerId",
+                    w.id
+                ));
+            }
+            (&w.enabled_workers, format!("worker {}'s", w.id))
+        }
+        None => (&orchestrator.enabled_workers, "this orchestrator's".to_string()),
+    };
     if let Some(ref wid) = worker_id {
-        match &orchestrator.enabled_workers {
+        match enabled_workers {
             None => {
                 return Err(format!(
-                    "worker {} is not in this orchestrator's enabledWorkers (none configured)",
-                    wid
+                    "worker {} is not in {} enabledWorkers (none configured)",
+                    wid, owner
                 ));
             }
             Some(enabled) if enabled.is_empty() => {} // empty = all workers
             Some(enabled) => {
                 if !enabled.iter().any(|w| w == wid.as_str()) {
                     return Err(format!(
-                        "worker {} is not in this orchestrator's enabledWorkers",
-                        wid
+                        "worker {} is not in {} enabledWorkers",
+                        wid, owner
                     ));
                 }
             }
@@ -430,20 +709,7 @@ fn resolve_delegate_target(
 
         // Enforce enabledProviders: reject if the resolved provider is not in the
         // orchestrator's enabledProviders (when set).
-        if let Some(ref enabled) = orchestrator.enabled_providers {
-            if !enabled.is_empty() {
-                let canonical = canonical_provider_id(providers, &provider_id);
-                let allowed = enabled.iter().any(|p| {
-                    canonical_provider_id(providers, p).as_ref() == canonical.as_ref()
-                });
-                if !allowed {
-                    return Err(format!(
-                        "provider {} is not in this orchestrator's enabledProviders",
-                        provider_id
-                    ));
-                }
-            }
-        }
+        check_enabled_provider(providers, orchestrator, &provider_id)?;
 
         let provider_choice = ProviderChoice::new(&provider_id);
         let config_model = worker
@@ -456,20 +722,7 @@ fn resolve_delegate_target(
         let provider_id = global_default_provider;
 
         // Enforce enabledProviders for the orchestrator default path too.
-        if let Some(ref enabled) = orchestrator.enabled_providers {
-            if !enabled.is_empty() {
-                let canonical = canonical_provider_id(providers, &provider_id);
-                let allowed = enabled.iter().any(|p| {
-                    canonical_provider_id(providers, p).as_ref() == canonical.as_ref()
-                });
-                if !allowed {
-                    return Err(format!(
-                        "provider {} is not in this orchestrator's enabledProviders",
-                        provider_id
-                    ));
-                }
-            }
-        }
+        check_enabled_provider(providers, orchestrator, &provider_id)?;
 
         let provider_choice = ProviderChoice::new(&provider_id);
         let model = resolve_model(
@@ -489,24 +742,461 @@ fn resolve_delegate_target(
     })
 }
 
+/// Apply a `fallback` entry's `provider` / `model` to a resolved target. A provider without a
+/// model uses that provider's default model.
+fn override_target(
+    providers: &ProvidersConfig,
+    orchestrator: &OrchestratorConfig,
+    target: DelegateTarget,
+    provider: Option<&str>,
+    model: Option<&str>,
+) -> Result<DelegateTarget, String> {
+    let Some(provider) = provider else {
+        return Ok(match model {
+            Some(m) => DelegateTarget {
+                model: m.trim().to_string(),
+                ..target
+            },
+            None => target,
+        });
+    };
+    let provider_id =
+        canonical_provider_id(providers, provider).ok_or_else(|| format!("unknown provider: {}", provider))?;
+    check_enabled_provider(providers, orchestrator, &provider_id)?;
+    let provider_choice = ProviderChoice::new(&provider_id);
+    let model = resolve_model(providers, None, model, &provider_choice);
+    Ok(DelegateTarget {
+        provider_id,
+        provider_choice,
+        model,
+    })
+}
+
+/// One way to run a delegation: the requested worker, a retry of it, or a `fallback` entry.
+#[derive(Clone)]
+struct DelegateAttempt {
+    worker_id: Option<String>,
+    target: DelegateTarget,
+    /// The attempt's worker `timeoutSecs`.
+    timeout: Option<Duration>,
+    /// Index into the worker's tier route when the attempt runs on a routed model.
+    route_step: Option<usize>,
+}
+
+impl DelegateAttempt {
+    /// `provider`, `model`, and `workerId` fields of this attempt's lifecycle events.
+    fn event_payload(&self) -> serde_json::Value {
+        let mut extra = json!({
+            "provider": self.target.provider_id,
+            "model": self.target.model,
+        });
+        if let Some(ref w) = self.worker_id {
+            extra["workerId"] = json!(w);
+        }
+        extra
+    }
+}
+
+/// Attempts for one delegation, in order: the resolved target `1 + retries` times, then each
+/// `fallback` entry of the worker once. Fallback entries that do not resolve (e.g. a provider
+/// outside `enabledProviders`) are skipped with a warning. `routed` marks the target as the first
+/// step of the worker's tier route.
+fn delegation_attempts(
+    config: &Config,
+    orchestrator: &OrchestratorConfig,
+    delegating_worker: Option<&WorkerConfig>,
+    worker_id: Option<&str>,
+    target: DelegateTarget,
+    routed: bool,
+) -> Vec<DelegateAttempt> {
+    let workers = &config.agents.workers;
+    let find = |id: &str| workers.as_ref()?.iter().find(|w| w.id == id);
+    let timeout = |w: Option<&WorkerConfig>| w.and_then(|w| w.timeout_secs).map(Duration::from_secs);
+    let worker = worker_id.and_then(find);
+    let mut attempts: Vec<DelegateAttempt> = (0..worker.map_or(1, WorkerConfig::attempts))
+        .map(|_| DelegateAttempt {
+            worker_id: worker_id.map(str::to_string),
+            target: target.clone(),
+            timeout: timeout(worker),
+            route_step: routed.then_some(0),
+        })
+        .collect();
+    for f in worker.and_then(|w| w.fallback.as_ref()).into_iter().flatten() {
+        let fallback_id = f.worker_id.as_deref().or(worker_id);
+        let args = json!({ "workerId": fallback_id });
+        let resolved = resolve_delegate_target(&config.providers, orchestrator, workers, delegating_worker, &args)
+            .and_then(|t| override_target(&config.providers, orchestrator, t, f.provider.as_deref(), f.model.as_deref()));
+        match resolved {
+            Ok(target) => attempts.push(DelegateAttempt {
+                worker_id: fallback_id.map(str::to_string),
+                target,
+                timeout: timeout(fallback_id.and_then(find)),
+                route_step: None,
+            }),
+            Err(e) => log::warn!(
+                "orchestration: skipping fallback of worker {}: {}",
+                worker_id.unwrap_or_default(),
+                e
+            ),
+        }
+    }
+    attempts
+}
+
+/// Move a routed delegation one tier up after `attempt` failed `outputSchema` validation or hit
+/// the tool loop limit: attempts from `next` on that still use the same route step switch to the
+/// next step, or one attempt on it is inserted at `next` when none are left. Emits
+/// [`EVENT_MODEL_ROUTE`]. Returns `false` when the attempt was not routed or no higher tier exists.
+fn escalate(
+    ctx: &DelegateContext,
+    route: Option<&ModelRoute>,
+    attempts: &mut Vec<DelegateAttempt>,
+    next: usize,
+    attempt: &DelegateAttempt,
+    reason: RouteReason,
+) -> bool {
+    let (Some(route), Some(step)) = (route, attempt.route_step) else {
+        return false;
+    };
+    let Some(to) = route.steps.get(step + 1) else {
+        return false;
+    };
+    let mut moved = false;
+    for a in attempts[next..].iter_mut().filter(|a| a.route_step == Some(step)) {
+        a.target = to.into();
+        a.route_step = Some(step + 1);
+        moved = true;
+    }
+    if !moved {
+        let escalated = DelegateAttempt {
+            target: to.into(),
+            route_step: Some(step + 1),
+            ..attempt.clone()
+        };
+        attempts.insert(next, escalated);
+    }
+    let worker_id = attempt.worker_id.as_deref().unwrap_or_default();
+    log::info!(
+        "orchestration: routing worker {} to tier {} ({}/{}) after {}",
+        worker_id,
+        to.tier.as_str(),
+        to.provider_id,
+        to.model,
+        reason.as_str()
+    );
+    if let Some(ref obs) = ctx.observability {
+        obs.send(EVENT_MODEL_ROUTE, obs.merge_base(route.event_payload(worker_id, to, reason)));
+    }
+    true
+}
+
+enum AttemptOutcome<T> {
+    Finished(Result<T, String>),
+    Cancelled,
+}
+
+/// Await one delegation attempt, failing it after `timeout` and abandoning it when `cancel` fires.
+/// An abandoned attempt returns once its tool calls in `in_flight` have: they run on the blocking
+/// pool and cannot be interrupted.
+async fn guard_attempt<T>(
+    attempt: impl std::future::Future<Output = Result<T, String>>,
+    timeout: Option<Duration>,
+    cancel: Option<&Notify>,
+    in_flight: &ToolCallsInFlight,
+) -> AttemptOutcome<T> {
+    let run = async {
+        match timeout {
+            Some(limit) => match tokio::time::timeout(limit, attempt).await {
+                Ok(r) => r,
+                Err(_) => {
+                    in_flight.wait().await;
+                    Err(format!("worker timed out after {}s", limit.as_secs()))
+                }
+            },
+            None => attempt.await,
+        }
+    };
+    let cancelled = match cancel {
+        Some(cancel) => tokio::select! {
+            r = run => return AttemptOutcome::Finished(r),
+            _ = cancel.notified() => AttemptOutcome::Cancelled,
+        },
+        None => return AttemptOutcome::Finished(run.await),
+    };
+    in_flight.wait().await;
+    cancelled
+}
+
 /// Result of a `delegate_task` tool execution.
 pub struct DelegateTaskResult {
     /// Formatted JSON result string returned to the orchestrator as the tool output.
     pub output: String,
     /// Whether the worker turn was stopped by a stop signal mid-execution.
     pub stopped: bool,
-    /// Number of tool calls executed by the worker during this delegation.
-    /// The orchestrator uses this to accumulate `tool_index_offset` so that
-    /// successive delegations produce non-overlapping tool indices.
-    pub tool_call_count: usize,
 }
 
-/// Run a worker turn: delegates to [`crate::agent::run_turn_with_messages_dyn`] (nested `delegate_task` is disabled there).
+impl DelegateContext {
+    /// Config of the orchestrator that owns this context (`None` when the id no longer resolves).
+    pub fn orchestrator(&self) -> Option<&OrchestratorConfig> {
+        self.config.agents.orchestrator(self.orchestrator_id.as_deref()).ok()
+    }
+
+    /// Clone for one `delegate_task` call: events emitted through the clone carry a fresh
+    /// `delegationId` so concurrent delegations can be told apart.
+    pub fn for_call(&self) -> Self {
+        let mut ctx = self.clone();
+        if let Some(ref mut obs) = ctx.observability {
+            obs.delegation_id = Some(format!("dlg-{}", uuid::Uuid::new_v4()));
+        }
+        ctx
+    }
+
+    /// Tier route of `worker_id` when it uses `modelRouting: "tier"`, keeping only the steps whose
+    /// provider is in `orchestrator`'s `enabledProviders`.
+    fn worker_route(&self, orchestrator: &OrchestratorConfig, worker_id: &str) -> Option<ModelRoute> {
+        let route = self.worker_runtimes.as_ref()?.get(worker_id)?.model_route.as_ref()?;
+        let steps: Vec<RouteStep> = route
+            .steps
+            .iter()
+            .filter(|s| check_enabled_provider(&self.config.providers, orchestrator, &s.provider_id).is_ok())
+            .cloned()
+            .collect();
+        if steps.is_empty() {
+            log::warn!(
+                "orchestration: no routed model of worker {} is in orchestrator {}'s enabledProviders; using its defaults",
+                worker_id,
+                orchestrator.id
+            );
+            return None;
+        }
+        Some(ModelRoute {
+            required: route.required,
+            steps,
+        })
+    }
+
+    /// Save a finished worker conversation as a [`DelegationTranscript`] in `agent_id`'s sessions
+    /// directory and link it from the parent session. Failures are logged and otherwise ignored.
+    async fn save_transcript(
+        &self,
+        agent_id: &str,
+        target: &DelegateTarget,
+        messages: &[ChatMessage],
+        stopped: bool,
+        error: Option<&str>,
+    ) {
+        let Some(ref profile_dir) = self.profile_dir else {
+            return;
+        };
+        let obs = self.observability.as_ref();
+        let id = obs
+            .and_then(|o| o.delegation_id.clone())
+            .unwrap_or_else(|| format!("dlg-{}", uuid::Uuid::new_v4()));
+        let parent_delegation_id = obs.and_then(|o| o.parent_delegation_id.clone());
+        let mut message_index = self.result_message_index;
+        if let (Some(store), Some(sid)) = (self.session_store.as_deref(), self.session_id.as_deref()) {
+            match store
+                .link_delegation_transcript(sid, &id, agent_id, message_index, parent_delegation_id.as_deref())
+                .await
+            {
+                Ok(index) => message_index = Some(index),
+                Err(e) => log::warn!("orchestration: could not link transcript {}: {}", id, e),
+            }
+        }
+        let transcript = DelegationTranscript {
+            id,
+            worker_id: agent_id.to_string(),
+            provider: target.provider_id.clone(),
+            model: target.model.clone(),
+            parent_session_id: self.session_id.clone(),
+            parent_delegation_id,
+            message_index,
+            created_at: chrono_now_iso8601(),
+            stopped,
+            error: error.map(str::to_string),
+            messages: messages
+                .iter()
+                .map(|m| SessionMessage {
+                    role: m.role.clone(),
+                    content: m.content.clone(),
+                    tool_calls: m.tool_calls.clone(),
+                    tool_name: m.tool_name.clone(),
+                })
+                .collect(),
+        };
+        if let Err(e) = write_delegation_transcript(&sessions_dir(profile_dir, agent_id), &transcript) {
+            log::warn!("orchestration: could not save transcript {}: {}", transcript.id, e);
+        }
+    }
+
+    /// Run one attempt of a delegation: build the worker's messages and tools, then run its turn
+    /// (and `outputSchema` validation) under the attempt's timeout until it ends or `cancel` fires.
+    /// `messages` holds the worker conversation afterwards, whatever the outcome.
+    async fn run_attempt(
+        &self,
+        attempt: &DelegateAttempt,
+        instruction: &str,
+        depth: usize,
+        max_depth: usize,
+        messages: &mut Vec<ChatMessage>,
+        cancel: Option<&Notify>,
+    ) -> AttemptOutcome<(AgentTurnResult, Option<serde_json::Value>)> {
+        let worker_id = attempt.worker_id.as_deref();
+        let output_schema = worker_id
+            .and_then(|id| self.config.agents.workers.as_ref()?.iter().find(|w| w.id == id))
+            .and_then(|w| w.output_schema.as_ref());
+        let mut worker_approval = self.approval.clone();
+        let mut nested = false;
+        let (worker_tools, tool_exec): (Option<Vec<ToolDefinition>>, Option<Arc<dyn ToolExecutor>>) =
+            if let Some(wid) = worker_id {
+                let rt = match self.worker_runtimes.as_ref().and_then(|m| m.get(wid)) {
+                    Some(rt) => rt,
+                    None => return AttemptOutcome::Finished(Err(format!("no worker runtime for workerId: {}", wid))),
+                };
+                // The worker may delegate further only while another level fits under maxDelegationDepth.
+                nested = depth < max_depth && !rt.workers_context.trim().is_empty();
+                let mut sys = rt.system_context.trim().to_string();
+                if nested {
+                    if !sys.is_empty() {
+                        sys.push_str("\n\n");
+                    }
+                    sys.push_str(rt.workers_context.trim());
+                }
+                if let Some(schema) = output_schema {
+                    if !sys.is_empty() {
+                        sys.push_str("\n\n");
+                    }
+                    sys.push_str(&output_schema::output_instruction(schema));
+                }
+                if !sys.is_empty() {
+                    messages.push(ChatMessage {
+                        role: "system".to_string(),
+                        content: sys,
+                        tool_calls: None,
+                        tool_name: None,
+                    });
+                }
+                worker_approval = self.approval.clone().map(|scope| ApprovalScope {
+                    gate: rt.approval.clone(),
+                    agent_id: wid.to_string(),
+                    ..scope
+                });
+                (
+                    merge_delegate_task(worker_tool_list(rt.tools_list.as_ref()), nested),
+                    rt.tool_executor.clone(),
+                )
+            } else {
+                if let Some(ref sys) = self.orchestrator_system_context {
+                    let s = sys.trim();
+                    if !s.is_empty() {
+                        messages.push(ChatMessage {
+                            role: "system".to_string(),
+                            content: s.to_string(),
+                            tool_calls: None,
+                            tool_name: None,
+                        });
+                    }
+                }
+                (
+                    self.orchestrator_worker_tools.as_deref().cloned(),
+                    self.orchestrator_tool_executor.clone(),
+                )
+            };
+        messages.push(ChatMessage {
+            role: "user".to_string(),
+            content: instruction.to_string(),
+            tool_calls: None,
+            tool_name: None,
+        });
+        let choice = &attempt.target.provider_choice;
+        let provider = match self.clients.get(choice) {
+            Some(p) => p,
+            None => {
+                return AttemptOutcome::Finished(Err(format!(
+                    "no client registered for provider '{}'",
+                    choice.as_str()
+                )))
+            }
+        };
+        let max_iterations = self.orchestrator().and_then(|o| o.max_tool_loops_per_turn);
+        let redact_tool_output = self.redact_tool_output
+            || self
+                .config
+                .redaction
+                .applies_to(&self.config.providers, choice.as_str());
+        let worker_obs = self.observability.as_ref().map(|obs| DelegateObservability {
+            event_tx: obs.event_tx.clone(),
+            session_id: obs.session_id.clone(),
+            orchestrator_id: obs.orchestrator_id.clone(),
+            source: Some(worker_id.unwrap_or("worker").to_string()),
+            tool_index_offset: 0,
+            delegation_id: obs.delegation_id.clone(),
+            parent_delegation_id: obs.parent_delegation_id.clone(),
+            tool_index_counter: Some(self.turn.tool_index.clone()),
+            last_tool_index: AtomicUsize::new(0),
+        });
+        let in_flight = self.tool_calls_in_flight.clone().unwrap_or_default();
+        // Context for the worker's own `delegate_task` calls: same turn state (so session caps
+        // count the whole tree), one level deeper, events parented to this delegation.
+        let child = nested.then(|| DelegateContext {
+            observability: worker_obs.as_ref().map(|obs| DelegateObservability {
+                delegation_id: None,
+                parent_delegation_id: obs.delegation_id.clone(),
+                ..obs.clone()
+            }),
+            depth,
+            delegating_worker: worker_id.map(str::to_string),
+            redact_tool_output,
+            tool_calls_in_flight: Some(in_flight.clone()),
+            ..self.clone()
+        });
+        let model = attempt.target.model.as_str();
+        let stop_flag = self.stop_flag.clone();
+        let turn = async {
+            let worker = WorkerTurn {
+                tools: worker_tools,
+                tool_executor: tool_exec,
+                max_tool_loops_per_turn: max_iterations,
+                observability: worker_obs.as_ref(),
+                approval: worker_approval.as_ref(),
+                delegate: child.as_ref(),
+                stop_flag: stop_flag.clone(),
+                redact_tool_output,
+                in_flight: Some(in_flight.clone()),
+            };
+            let mut result = run_turn_with_transcript(provider, model, messages, worker.clone())
+                .await
+                .map_err(|e| e.to_string())?;
+            let structured = match output_schema {
+                Some(schema) if !result.stopped => {
+                    Some(structured_worker_output(provider, model, messages, &mut result.content, schema, &worker).await?)
+                }
+                _ => None,
+            };
+            Ok((result, structured))
+        };
+        guard_attempt(turn, attempt.timeout, cancel, &in_flight).await
+    }
+
+    /// Release the session policy slot taken for `worker_id` (no-op without a session).
+    async fn release_delegation(&self, worker_id: &str, succeeded: bool) {
+        if let (Some(store), Some(sid)) = (self.session_store.as_deref(), self.session_id.as_deref()) {
+            self.turn
+                .release(store, sid, worker_id, succeeded, self.result_message_index)
+                .await;
+        }
+    }
+}
+
+/// Run a worker turn: delegates to [`crate::agent::run_turn_with_transcript`]. A worker with its
+/// own `enabledWorkers` is offered `delegate_task` while another level fits under the
+/// orchestrator's `maxDelegationDepth`; calls past that depth are rejected.
 pub async fn execute_delegate_task(
-    ctx: &DelegateContext<'_>,
+    ctx: &DelegateContext,
     args: &serde_json::Value,
 ) -> Result<DelegateTaskResult, String> {
-    let merged = apply_delegation_bracket_match(ctx.agents, args);
+    let agents = &ctx.config.agents;
+    let merged = apply_delegation_bracket_match(agents, args);
     let obj = merged
         .as_object()
         .ok_or_else(|| "arguments must be an object".to_string())?;
@@ -527,12 +1217,27 @@ pub async fn execute_delegate_task(
         .filter(|s| !s.is_empty());
 
     // Resolve the orchestrator config for this delegation context.
-    let orch = ctx
-        .agents
-        .orchestrator(ctx.orchestrator_id)
+    let orch = agents
+        .orchestrator(ctx.orchestrator_id.as_deref())
         .map_err(|e| e.to_string())?;
 
-    let target = match resolve_delegate_target(ctx.providers, orch, &ctx.agents.workers, &merged) {
+    let delegating_worker = ctx
+        .delegating_worker
+        .as_deref()
+        .and_then(|id| agents.workers.as_ref()?.iter().find(|w| w.id == id));
+    let depth = ctx.depth + 1;
+    let max_depth = orch.max_delegation_depth();
+    if depth > max_depth {
+        if let Some(ref obs) = ctx.observability {
+            obs.emit_rejected(&merged, "max_delegation_depth", None);
+        }
+        return Err(format!(
+            "max delegation depth exceeded (maxDelegationDepth={})",
+            max_depth
+        ));
+    }
+
+    let target = match resolve_delegate_target(&ctx.config.providers, orch, &agents.workers, delegating_worker, &merged) {
         Ok(t) => t,
         Err(e) => {
             if let Some(ref obs) = ctx.observability {
@@ -545,15 +1250,23 @@ pub async fn execute_delegate_task(
             return Err(e);
         }
     };
-    let provider_id = &target.provider_id;
-    let choice = &target.provider_choice;
-    let model = target.model;
-
-    if let (Some(store), Some(sid)) = (ctx.session_store, ctx.session_id) {
-        let wid_for_policy = worker_id.unwrap_or(provider_id);
-        if let Err(e) =
-            assert_session_delegation_limits(store, sid, orch, wid_for_policy).await
-        {
+    // Tier routing replaces the worker's static (provider, model) with the first route step.
+    let route = worker_id.and_then(|w| ctx.worker_route(orch, w));
+    let target = match route.as_ref() {
+        Some(route) => {
+            let step = &route.steps[0];
+            if let Some(ref obs) = ctx.observability {
+                let payload = route.event_payload(worker_id.unwrap_or_default(), step, RouteReason::Initial);
+                obs.send(EVENT_MODEL_ROUTE, obs.merge_base(payload));
+            }
+            step.into()
+        }
+        None => target,
+    };
+    let wid_for_policy = worker_id.unwrap_or(&target.provider_id).to_string();
+
+    if let (Some(store), Some(sid)) = (ctx.session_store.as_deref(), ctx.session_id.as_deref()) {
+        if let Err(e) = ctx.turn.admit(store, sid, orch, &wid_for_policy).await {
             if let Some(ref obs) = ctx.observability {
                 let reason = if e.contains("maxDelegationsPerSession") {
                     "max_delegations_per_session"
@@ -568,8 +1281,8 @@ pub async fn execute_delegate_task(
 
     if let Some(ref obs) = ctx.observability {
         let mut extra = json!({
-            "provider": provider_id,
-            "model": model,
+            "provider": target.provider_id,
+            "model": target.model,
         });
         if let Some(wid) = worker_id {
             extra["workerId"] = json!(wid);
@@ -581,146 +1294,142 @@ pub async fn execute_delegate_task(
         log::info!(
             "orchestration: delegate_task workerId={} provider={} model={}",
             wid,
-            provider_id,
-            model
+            target.provider_id,
+            target.model
         );
     } else {
         log::info!(
             "orchestration: delegate_task provider={} model={}",
-            provider_id,
-            model
+            target.provider_id,
+            target.model
         );
     }
 
-    let mut messages: Vec<ChatMessage> = Vec::new();
-    let (worker_tools, tool_exec): (Option<Vec<ToolDefinition>>, Option<&dyn ToolExecutor>) =
-        if let Some(wid) = worker_id {
-            let rt = ctx
-                .worker_runtimes
-                .and_then(|m| m.get(wid))
-                .ok_or_else(|| format!("no worker runtime for workerId: {}", wid))?;
-            let sys = rt.system_context.trim();
-            if !sys.is_empty() {
-                messages.push(ChatMessage {
-                    role: "system".to_string(),
-                    content: sys.to_string(),
-                    tool_calls: None,
-                    tool_name: None,
-                });
-            }
-            (
-                worker_tool_list(rt.tools_list.as_ref()),
-                rt.tool_executor.as_deref(),
-            )
-        } else {
-            if let Some(sys) = ctx.orchestrator_system_context {
-                let s = sys.trim();
-                if !s.is_empty() {
-                    messages.push(ChatMessage {
-                        role: "system".to_string(),
-                        content: s.to_string(),
-                        tool_calls: None,
-                        tool_name: None,
-                    });
+    let mut attempts =
+        delegation_attempts(&ctx.config, orch, delegating_worker, worker_id, target, route.is_some());
+    // Registered for the whole delegation so `delegation.cancel` also lands between attempts.
+    let cancel = match (
+        ctx.cancels.as_ref(),
+        ctx.observability.as_ref().and_then(|o| o.delegation_id.as_deref()),
+    ) {
+        (Some(cancels), Some(id)) => Some(cancels.register(id)),
+        _ => None,
+    };
+    let mut failure: Option<(String, DelegateAttempt, Vec<ChatMessage>)> = None;
+    let mut n = 0;
+    while n < attempts.len() {
+        let attempt = attempts[n].clone();
+        n += 1;
+        if let (Some((ref error, _, _)), Some(ref obs)) = (&failure, &ctx.observability) {
+            let mut extra = attempt.event_payload();
+            extra["attempt"] = json!(n);
+            extra["error"] = json!(error);
+            obs.send(EVENT_DELEGATE_RETRY, obs.merge_base(extra));
+        }
+        // Transcripts of delegations without `workerId` are kept under the orchestrator.
+        let transcript_agent = attempt.worker_id.as_deref().unwrap_or(orch.id.as_str());
+        let mut messages: Vec<ChatMessage> = Vec::new();
+        let outcome = ctx
+            .run_attempt(&attempt, instruction, depth, max_depth, &mut messages, cancel.as_ref().map(|c| c.notify()))
+            .await;
+        let (result, structured) = match outcome {
+            AttemptOutcome::Finished(Ok(done)) => done,
+            AttemptOutcome::Finished(Err(e)) => {
+                log::warn!("orchestration: delegation attempt {} of {} failed: {}", n, attempts.len(), e);
+                if e.starts_with(OUTPUT_SCHEMA_MISMATCH) {
+                    escalate(ctx, route.as_ref(), &mut attempts, n, &attempt, RouteReason::Validation);
                 }
+                failure = Some((e, attempt, messages));
+                continue;
             }
-            (
-                ctx.orchestrator_worker_tools.clone(),
-                ctx.orchestrator_tool_executor,
-            )
-        };
-    messages.push(ChatMessage {
-        role: "user".to_string(),
-        content: instruction.to_string(),
-        tool_calls: None,
-        tool_name: None,
-    });
-    let provider = ctx.clients.get(choice).ok_or_else(|| {
-        format!("no client registered for provider '{}'", choice.as_str())
-    })?;
-    let max_iterations = orch.max_tool_loops_per_turn;
-    let worker_obs = ctx.observability.as_ref().map(|obs| DelegateObservability {
-        event_tx: obs.event_tx.clone(),
-        session_id: obs.session_id.clone(),
-        orchestrator_id: obs.orchestrator_id.clone(),
-        source: Some(worker_id.unwrap_or("worker").to_string()),
-        tool_index_offset: ctx.tool_index_offset,
-        emitted_tool_calls: AtomicUsize::new(0),
-    });
-    let result =
-        match run_turn_with_messages_dyn(provider, &model, messages, worker_tools, tool_exec, max_iterations, worker_obs.as_ref(), ctx.stop_flag.clone()).await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                let msg = e.to_string();
-                let partial_tool_calls = worker_obs
-                    .as_ref()
-                    .map(|obs| obs.emitted_tool_call_count())
-                    .unwrap_or(0);
+            AttemptOutcome::Cancelled => {
+                log::info!("orchestration: delegation cancelled by operator");
+                ctx.release_delegation(&wid_for_policy, false).await;
+                ctx.save_transcript(transcript_agent, &attempt.target, &messages, false, Some(DELEGATION_CANCELLED))
+                    .await;
                 if let Some(ref obs) = ctx.observability {
-                    let mut extra = json!({
-                        "error": msg,
-                        "provider": provider_id,
-                        "model": model,
-                    });
-                    if let Some(w) = optional_worker_id_from_args(&merged) {
-                        extra["workerId"] = json!(w);
-                    }
+                    let mut extra = attempt.event_payload();
+                    extra["error"] = json!(DELEGATION_CANCELLED);
+                    extra["cancelled"] = json!(true);
                     obs.send(EVENT_DELEGATE_ERROR, obs.merge_base(extra));
                 }
                 return Ok(DelegateTaskResult {
-                    output: format!("error: {}", msg),
+                    output: format!("error: {}", DELEGATION_CANCELLED),
                     stopped: false,
-                    tool_call_count: partial_tool_calls,
                 });
             }
         };
 
-    if let (Some(store), Some(sid)) = (ctx.session_store, ctx.session_id) {
-        let wid_for_record = worker_id.unwrap_or(provider_id);
-        if let Err(e) = store.record_delegation(sid, wid_for_record).await {
-            log::warn!("orchestration: record_delegation failed: {}", e);
+        if result.loop_limit_reached
+            && !result.stopped
+            && escalate(ctx, route.as_ref(), &mut attempts, n, &attempt, RouteReason::LoopLimit)
+        {
+            failure = Some(("worker hit the tool loop limit".to_string(), attempt, messages));
+            continue;
         }
+
+        ctx.release_delegation(&wid_for_policy, true).await;
+        ctx.save_transcript(transcript_agent, &attempt.target, &messages, result.stopped, None).await;
+
+        if let Some(ref obs) = ctx.observability {
+            // When the worker was stopped mid-loop, the last iteration's content was
+            // already emitted via `session.assistant_progress` (because that iteration
+            // had tool calls + non-empty content). Since the worker didn't get to make
+            // another model request, `result.content` is the same content — including it
+            // here would duplicate what the desktop already displayed. Omit `reply` when
+            // stopped so the desktop only shows the `assistant_progress` version.
+            let mut extra = attempt.event_payload();
+            extra["workerToolCalls"] = json!(result.tool_calls.len());
+            extra["workerToolResults"] = json!(result.tool_results.len());
+            if result.stopped {
+                extra["stopped"] = json!(true);
+            } else {
+                extra["reply"] = json!(result.content);
+            }
+            if let Some(ref value) = structured {
+                extra["output"] = value.clone();
+            }
+            if n > 1 {
+                extra["attempts"] = json!(n);
+            }
+            obs.send(EVENT_DELEGATE_COMPLETE, obs.merge_base(extra));
+        }
+
+        let provider_id = &attempt.target.provider_id;
+        let model = &attempt.target.model;
+        let output = match structured {
+            Some(value) => format_structured_delegate_result(value, provider_id, model),
+            None => format_delegate_result(result.content, provider_id, model),
+        };
+        return Ok(DelegateTaskResult {
+            output,
+            stopped: result.stopped,
+        });
     }
 
+    // Every attempt failed; report the last error.
+    ctx.release_delegation(&wid_for_policy, false).await;
+    let Some((msg, attempt, messages)) = failure else {
+        return Err("no delegation attempt ran".to_string());
+    };
+    let transcript_agent = attempt.worker_id.as_deref().unwrap_or(orch.id.as_str());
+    ctx.save_transcript(transcript_agent, &attempt.target, &messages, false, Some(&msg)).await;
     if let Some(ref obs) = ctx.observability {
-        // When the worker was stopped mid-loop, the last iteration's content was
-        // already emitted via `session.assistant_progress` (because that iteration
-        // had tool calls + non-empty content). Since the worker didn't get to make
-        // another model request, `result.content` is the same content — including it
-        // here would duplicate what the desktop already displayed. Omit `reply` when
-        // stopped so the desktop only shows the `assistant_progress` version.
-        let mut extra = if result.stopped {
-            json!({
-                "provider": provider_id,
-                "model": model,
-                "workerToolCalls": result.tool_calls.len(),
-                "workerToolResults": result.tool_results.len(),
-                "stopped": true,
-            })
-        } else {
-            json!({
-                "provider": provider_id,
-                "model": model,
-                "workerToolCalls": result.tool_calls.len(),
-                "workerToolResults": result.tool_results.len(),
-                "reply": result.content,
-            })
-        };
-        if let Some(w) = optional_worker_id_from_args(&merged) {
-            extra["workerId"] = json!(w);
+        let mut extra = attempt.event_payload();
+        extra["error"] = json!(msg);
+        if attempts.len() > 1 {
+            extra["attempts"] = json!(attempts.len());
         }
-        obs.send(EVENT_DELEGATE_COMPLETE, obs.merge_base(extra));
+        obs.send(EVENT_DELEGATE_ERROR, obs.merge_base(extra));
     }
-
+    let msg = if attempts.len() > 1 {
+        format!("{} (after {} attempts)", msg, attempts.len())
+    } else {
+        msg
+    };
     Ok(DelegateTaskResult {
-        output: format_delegate_result(
-            result.content,
-            provider_id,
-            &model,
-        ),
-        stopped: result.stopped,
-        tool_call_count: result.tool_calls.len(),
+        output: format!("error: {}", msg),
+        stopped: false,
     })
 }
 
@@ -751,6 +1460,8 @@ mod tests {
                     default_model: None,
                     model_discovery: Default::default(),
                     static_models: Vec::new(),
+                    tier: None,
+                    model_tiers: Vec::new(),
                 }
             }).collect(),
         }
@@ -829,7 +1540,15 @@ mod tests {
             default_provider: Some("lmstudio".to_string()),
             default_model: Some("worker-model".to_string()),
             enabled_skills: None,
+            enabled_workers: None,
             context_mode: None,
+            approval_policy: None,
+            output_schema: None,
+            timeout_secs: None,
+            retries: None,
+            fallback: None,
+            sandbox: None,
+            model_routing: None,
         }]);
 
         let args = json!({
@@ -837,7 +1556,7 @@ mod tests {
             "instruction": "do the thing"
         });
 
-        let target = resolve_delegate_target(&providers, &orch, &workers, &args).expect("resolved");
+        let target = resolve_delegate_target(&providers, &orch, &workers, None, &args).expect("resolved");
         assert_eq!(target.provider_id, "lmstudio");
         assert_eq!(target.model, "worker-model");
     }
@@ -856,14 +1575,30 @@ mod tests {
                 default_provider: None,
                 default_model: None,
                 enabled_skills: None,
+                enabled_workers: None,
                 context_mode: None,
+                approval_policy: None,
+                output_schema: None,
+                timeout_secs: None,
+                retries: None,
+                fallback: None,
+                sandbox: None,
+                model_routing: None,
             },
             WorkerConfig {
                 id: "engineer".to_string(),
                 default_provider: None,
                 default_model: None,
                 enabled_skills: None,
+                enabled_workers: None,
                 context_mode: None,
+                approval_policy: None,
+                output_schema: None,
+                timeout_secs: None,
+                retries: None,
+                fallback: None,
+                sandbox: None,
+                model_routing: None,
             },
         ]);
 
@@ -872,7 +1607,7 @@ mod tests {
             "instruction": "do the thing"
         });
 
-        let err = resolve_delegate_target(&providers, &orch, &workers, &args).unwrap_err();
+        let err = resolve_delegate_target(&providers, &orch, &workers, None, &args).unwrap_err();
         assert!(err.contains("enabledWorkers"), "expected enabledWorkers error, got: {}", err);
     }
 
@@ -889,7 +1624,15 @@ mod tests {
             default_provider: None,
             default_model: None,
             enabled_skills: None,
+            enabled_workers: None,
             context_mode: None,
+            approval_policy: None,
+            output_schema: None,
+            timeout_secs: None,
+            retries: None,
+            fallback: None,
+            sandbox: None,
+            model_routing: None,
         }]);
 
         let args = json!({
@@ -897,7 +1640,7 @@ mod tests {
             "instruction": "do the thing"
         });
 
-        let err = resolve_delegate_target(&providers, &orch, &workers, &args).unwrap_err();
+        let err = resolve_delegate_target(&providers, &orch, &workers, None, &args).unwrap_err();
         assert!(err.contains("enabledWorkers"), "expected enabledWorkers error, got: {}", err);
         assert!(err.contains("none configured"), "expected 'none configured', got: {}", err);
     }
@@ -917,7 +1660,15 @@ mod tests {
             default_provider: Some("lmstudio".to_string()),
             default_model: None,
             enabled_skills: None,
+            enabled_workers: None,
             context_mode: None,
+            approval_policy: None,
+            output_schema: None,
+            timeout_secs: None,
+            retries: None,
+            fallback: None,
+            sandbox: None,
+            model_routing: None,
         }]);
 
         let args = json!({
@@ -925,7 +1676,7 @@ mod tests {
             "instruction": "do the thing"
         });
 
-        let err = resolve_delegate_target(&providers, &orch, &workers, &args).unwrap_err();
+        let err = resolve_delegate_target(&providers, &orch, &workers, None, &args).unwrap_err();
         assert!(err.contains("enabledProviders"), "expected enabledProviders error, got: {}", err);
     }
 
@@ -945,7 +1696,15 @@ mod tests {
             default_provider: Some("lmstudio".to_string()),
             default_model: Some("fast-model".to_string()),
             enabled_skills: None,
+            enabled_workers: None,
             context_mode: None,
+            approval_policy: None,
+            output_schema: None,
+            timeout_secs: None,
+            retries: None,
+            fallback: None,
+            sandbox: None,
+            model_routing: None,
         }]);
 
         let args = json!({
@@ -953,7 +1712,7 @@ mod tests {
             "instruction": "do the thing"
         });
 
-        let target = resolve_delegate_target(&providers, &orch, &workers, &args).expect("resolved");
+        let target = resolve_delegate_target(&providers, &orch, &workers, None, &args).expect("resolved");
         assert_eq!(target.provider_id, "lmstudio");
         assert_eq!(target.model, "fast-model");
     }
@@ -967,7 +1726,10 @@ mod tests {
             orchestrator_id: Some("orchestrator".to_string()),
             source: None,
             tool_index_offset: 0,
-            emitted_tool_calls: AtomicUsize::new(0),
+            delegation_id: None,
+            parent_delegation_id: None,
+            tool_index_counter: None,
+            last_tool_index: AtomicUsize::new(0),
         };
         obs.send(
             EVENT_DELEGATE_START,
@@ -993,7 +1755,10 @@ mod tests {
             orchestrator_id: Some("orchestrator".to_string()),
             source: Some("worker".to_string()),
             tool_index_offset: 0,
-            emitted_tool_calls: AtomicUsize::new(0),
+            delegation_id: None,
+            parent_delegation_id: None,
+            tool_index_counter: None,
+            last_tool_index: AtomicUsize::new(0),
         };
         let merged = obs.merge_base(json!({ "toolName": "read_file" }));
         assert_eq!(merged["sessionId"], "sess-2");
@@ -1011,7 +1776,10 @@ mod tests {
             orchestrator_id: None,
             source: None,
             tool_index_offset: 0,
-            emitted_tool_calls: AtomicUsize::new(0),
+            delegation_id: None,
+            parent_delegation_id: None,
+            tool_index_counter: None,
+            last_tool_index: AtomicUsize::new(0),
         };
         obs.send(
             EVENT_DELEGATE_COMPLETE,
@@ -1041,7 +1809,10 @@ mod tests {
             orchestrator_id: None,
             source: None,
             tool_index_offset: 0,
-            emitted_tool_calls: AtomicUsize::new(0),
+            delegation_id: None,
+            parent_delegation_id: None,
+            tool_index_counter: None,
+            last_tool_index: AtomicUsize::new(0),
         };
         // When the worker was stopped, `delegate.complete` omits `reply` and
         // includes `stopped: true` to avoid duplicating the content already
@@ -0,0 +1,243 @@
+//! Fan-out delegation: the **`delegate_many`** tool runs one worker turn per input item and
+//! returns the indexed results, optionally combined by one more worker turn (`reduce`).
+//!
+//! Every item is an ordinary [`execute_delegate_task`] call with its own `delegationId`, so
+//! worker resolution (bracket prefix), session caps, retries, transcripts, and events apply per
+//! item. Items run at most `maxParallelWorkflows` at a time and results keep the item order.
+
+use super::delegate::{execute_delegate_task, DelegateContext, DelegateTaskResult};
+use crate::providers::{ToolDefinition, ToolFunctionDefinition};
+use futures_util::{stream, StreamExt};
+use serde_json::{json, Value};
+
+pub const DELEGATE_MANY_TOOL_NAME: &str = "delegate_many";
+
+/// Most items one `delegate_many` call accepts.
+pub const DELEGATE_MANY_MAX_ITEMS: usize = 50;
+
+pub fn delegate_many_tool_definition() -> ToolDefinition {
+    ToolDefinition {
+        typ: "function".to_string(),
+        function: ToolFunctionDefinition {
+            name: DELEGATE_MANY_TOOL_NAME.to_string(),
+            description: Some(format!("Run the same worker task once per item (for example, summarize each of several files) and get all results back in one list, in item order. Start the instruction with the worker's bracket prefix to target that worker. Use reduce to have a worker combine the results. At most {} items.", DELEGATE_MANY_MAX_ITEMS)),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "instruction": {
+                        "type": "string",
+                        "description": "instructions for each worker turn; {{item}} is replaced by the item (appended on a new line when absent)"
+                    },
+                    "items": {
+                        "type": "array",
+                        "description": "one worker turn per item"
+                    },
+                    "reduce": {
+                        "type": "string",
+                        "description": "optional instructions for one more worker turn that combines the results; {{results}} is replaced by the result list as JSON (appended when absent)"
+                    }
+                },
+                "required": ["instruction", "items"]
+            }),
+        },
+    }
+}
+
+/// Add `delegate_many` after `delegate_task` when at least one worker is configured, unless the
+/// list already contains it.
+pub fn merge_delegate_many(
+    tools: Option<Vec<ToolDefinition>>,
+    has_workers: bool,
+) -> Option<Vec<ToolDefinition>> {
+    if !has_workers {
+        return tools;
+    }
+    let mut v = tools.unwrap_or_default();
+    if v.iter().any(|t| t.function.name == DELEGATE_MANY_TOOL_NAME) {
+        return Some(v);
+    }
+    let at = v
+        .iter()
+        .position(|t| t.function.name == super::DELEGATE_TASK_TOOL_NAME)
+        .map_or(0, |i| i + 1);
+    v.insert(at, delegate_many_tool_definition());
+    Some(v)
+}
+
+/// Delegations a `delegate_many` call makes (one per item, plus one for `reduce`); counted
+/// against `maxDelegationsPerTurn`.
+pub fn delegate_many_count(args: &Value) -> usize {
+    let items = args
+        .get("items")
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len);
+    let reduce = args
+        .get("reduce")
+        .and_then(Value::as_str)
+        .is_some_and(|r| !r.trim().is_empty());
+    items + usize::from(reduce)
+}
+
+/// Replace `{{<name>}}` in `template` with `value`, or append `value` on a new line when the
+/// placeholder is absent.
+fn render(template: &str, name: &str, value: &str) -> String {
+    let placeholder = format!("{{{{{}}}}}", name);
+    if template.contains(&placeholder) {
+        template.replace(&placeholder, value)
+    } else {
+        format!("{}\n\n{}", template.trim_end(), value)
+    }
+}
+
+/// Strings are inserted as-is, other items as JSON.
+fn item_text(item: &Value) -> String {
+    match item {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+/// Result entry for one delegation: the worker's `reply` or `output` and `worker`, or `error`.
+fn delegation_entry(outcome: Result<DelegateTaskResult, String>) -> (Value, bool) {
+    let (output, stopped) = match outcome {
+        Ok(r) => (r.output, r.stopped),
+        Err(e) => return (json!({ "error": e }), false),
+    };
+    if let Some(e) = output.strip_prefix("error: ") {
+        return (json!({ "error": e }), stopped);
+    }
+    match serde_json::from_str::<Value>(&output) {
+        Ok(Value::Object(o)) => (Value::Object(o), stopped),
+        _ => (json!({ "reply": output }), stopped),
+    }
+}
+
+/// Run a `delegate_many` call from the orchestrator. The tool output lists `results` (each with
+/// `index`, `item`, and the worker's `reply`/`output` or an `error`), `completed` and `failed`
+/// counts, and `reduced` when a reduce step ran. A failed item does not stop the others; the
+/// reduce step is skipped when the session was stopped.
+pub async fn execute_delegate_many(
+    ctx: &DelegateContext,
+    args: &Value,
+) -> Result<DelegateTaskResult, String> {
+    let instruction = args
+        .get("instruction")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| "missing instruction".to_string())?;
+    let items = args
+        .get("items")
+        .and_then(Value::as_array)
+        .filter(|items| !items.is_empty())
+        .ok_or_else(|| "items must be a non-empty array".to_string())?;
+    if items.len() > DELEGATE_MANY_MAX_ITEMS {
+        return Err(format!(
+            "too many items: {} (at most {})",
+            items.len(),
+            DELEGATE_MANY_MAX_ITEMS
+        ));
+    }
+    let reduce = args
+        .get("reduce")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+
+    let max_parallel = ctx.orchestrator().map_or(1, |o| o.max_parallel_workflows());
+    log::info!(
+        "orchestration: delegate_many with {} item(s), {} at a time",
+        items.len(),
+        max_parallel
+    );
+    let calls: Vec<_> = items
+        .iter()
+        .map(|item| {
+            let call_ctx = ctx.for_call();
+            let call_args = json!({ "instruction": render(instruction, "item", &item_text(item)) });
+            async move { delegation_entry(execute_delegate_task(&call_ctx, &call_args).await) }
+        })
+        .collect();
+    let finished: Vec<(Value, bool)> = stream::iter(calls).buffered(max_parallel).collect().await;
+
+    let mut stopped = false;
+    let mut failed = 0;
+    let results: Vec<Value> = finished
+        .into_iter()
+        .zip(items)
+        .enumerate()
+        .map(|(index, ((entry, item_stopped), item))| {
+            stopped |= item_stopped;
+            if entry.get("error").is_some() {
+                failed += 1;
+            }
+            let mut out = json!({ "index": index, "item": item });
+            if let (Some(o), Value::Object(e)) = (out.as_object_mut(), entry) {
+                o.extend(e);
+            }
+            out
+        })
+        .collect();
+
+    let mut output = json!({
+        "results": results,
+        "completed": items.len() - failed,
+        "failed": failed,
+    });
+    if let Some(reduce) = reduce.filter(|_| !stopped) {
+        let call_args =
+            json!({ "instruction": render(reduce, "results", &output["results"].to_string()) });
+        let (reduced, reduce_stopped) =
+            delegation_entry(execute_delegate_task(&ctx.for_call(), &call_args).await);
+        stopped |= reduce_stopped;
+        output["reduced"] = reduced;
+    }
+    Ok(DelegateTaskResult {
+        output: output.to_string(),
+        stopped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_replaces_or_appends_placeholder() {
+        assert_eq!(
+            render("Summarize {{item}} briefly", "item", "a.md"),
+            "Summarize a.md briefly"
+        );
+        assert_eq!(render("Summarize:\n", "item", "a.md"), "Summarize:\n\na.md");
+        assert_eq!(item_text(&json!({"path": "a.md"})), r#"{"path":"a.md"}"#);
+    }
+
+    #[test]
+    fn count_includes_reduce_step() {
+        assert_eq!(delegate_many_count(&json!({"items": [1, 2, 3]})), 3);
+        assert_eq!(
+            delegate_many_count(&json!({"items": [1, 2], "reduce": "combine"})),
+            3
+        );
+        assert_eq!(
+            delegate_many_count(&json!({"items": "x", "reduce": " "})),
+            0
+        );
+    }
+
+    #[test]
+    fn merge_places_tool_after_delegate_task() {
+        assert!(merge_delegate_many(None, false).is_none());
+        let tools = super::super::merge_delegate_task(None, true);
+        let tools = merge_delegate_many(tools, true).unwrap();
+        let names: Vec<&str> = tools.iter().map(|t| t.function.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                super::super::DELEGATE_TASK_TOOL_NAME,
+                DELEGATE_MANY_TOOL_NAME
+            ]
+        );
+        assert_eq!(merge_delegate_many(Some(tools), true).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,237 @@
+//! Session handoff between orchestrators: the built-in **`handoff`** tool asks the gateway to move
+//! (or copy) the current session to another orchestrator once the turn ends, the same transfer
+//! WebSocket **`sessions.transfer`** performs.
+//!
+//! The tool only validates the target and records the request in its result; the session cannot
+//! move while the turn that requested it is still writing to it. After the turn, the gateway finds
+//! the request with [`requested_handoff`].
+
+use crate::agent::AgentTurnResult;
+use crate::config::AgentsConfig;
+use crate::providers::{ToolDefinition, ToolFunctionDefinition};
+use serde_json::{json, Value};
+
+pub const HANDOFF_TOOL_NAME: &str = "handoff";
+
+/// WebSocket event name: a session was moved or copied to another orchestrator.
+pub const EVENT_SESSION_TRANSFERRED: &str = "session.transferred";
+
+/// Whether a transfer keeps the session id (`move`, the source loses it) or creates a new session
+/// with the same history (`copy`, the source keeps it).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransferMode {
+    #[default]
+    Move,
+    Copy,
+}
+
+impl TransferMode {
+    /// Parse `"move"` / `"copy"`; absent means move.
+    pub fn parse(raw: Option<&str>) -> Result<Self, String> {
+        match raw.map(str::trim) {
+            None | Some("") | Some("move") => Ok(Self::Move),
+            Some("copy") => Ok(Self::Copy),
+            Some(other) => Err(format!("unknown transfer mode: {} (use move or copy)", other)),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Move => "move",
+            Self::Copy => "copy",
+        }
+    }
+}
+
+/// A checked `handoff` call: the target orchestrator, an optional summary for it, and the mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandoffRequest {
+    pub orchestrator_id: String,
+    pub summary: Option<String>,
+    pub mode: TransferMode,
+}
+
+impl HandoffRequest {
+    /// Parse a `handoff` call made by orchestrator `from` (`None` = the default orchestrator).
+    /// The target must be another configured orchestrator.
+    pub fn from_args(agents: &AgentsConfig, from: Option<&str>, args: &Value) -> Result<Self, String> {
+        let target = args
+            .get("orchestratorId")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| "missing orchestratorId".to_string())?;
+        let target = agents.orchestrator(Some(target)).map_err(|e| e.to_string())?;
+        let from = agents.orchestrator(from).map_err(|e| e.to_string())?;
+        if target.id == from.id {
+            return Err(format!("the session already belongs to orchestrator {}", from.id));
+        }
+        Ok(Self {
+            orchestrator_id: target.id.clone(),
+            summary: args
+                .get("summary")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string),
+            mode: TransferMode::parse(args.get("mode").and_then(Value::as_str))?,
+        })
+    }
+
+    /// Tool result telling the model the handoff is scheduled.
+    pub fn tool_result(&self) -> String {
+        json!({
+            "handoff": self.orchestrator_id,
+            "mode": self.mode.as_str(),
+            "status": "scheduled",
+            "note": format!("The conversation moves to {} when this turn ends. Tell the user, then finish your reply without further tool calls.", self.orchestrator_id),
+        })
+        .to_string()
+    }
+}
+
+/// `handoff` for orchestrator `from`, listing the other orchestrators as targets.
+pub fn handoff_tool_definition(agents: &AgentsConfig, from: &str) -> ToolDefinition {
+    let targets: Vec<&str> = agents
+        .orchestrators
+        .iter()
+        .map(|o| o.id.as_str())
+        .filter(|id| *id != from)
+        .collect();
+    ToolDefinition {
+        typ: "function".to_string(),
+        function: ToolFunctionDefinition {
+            name: HANDOFF_TOOL_NAME.to_string(),
+            description: Some("Hand this conversation to another orchestrator that is better suited to the request. After this turn, the user's next messages go to that orchestrator.".to_string()),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "orchestratorId": {
+                        "type": "string",
+                        "enum": targets,
+                        "description": "orchestrator to hand the conversation to"
+                    },
+                    "summary": {
+                        "type": "string",
+                        "description": "what the user needs, for the other orchestrator"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["move", "copy"],
+                        "description": "move (default) gives the session away; copy keeps this orchestrator's copy"
+                    }
+                },
+                "required": ["orchestratorId"]
+            }),
+        },
+    }
+}
+
+/// Add `handoff` at the end of orchestrator `from`'s tools when the profile has another
+/// orchestrator, unless the list already contains it.
+pub fn merge_handoff_tool(
+    tools: Option<Vec<ToolDefinition>>,
+    agents: &AgentsConfig,
+    from: &str,
+) -> Option<Vec<ToolDefinition>> {
+    if agents.orchestrators.len() < 2 {
+        return tools;
+    }
+    let mut v = tools.unwrap_or_default();
+    if !v.iter().any(|t| t.function.name == HANDOFF_TOOL_NAME) {
+        v.push(handoff_tool_definition(agents, from));
+    }
+    Some(v)
+}
+
+/// The handoff orchestrator `from` asked for during a turn: its last accepted `handoff` call.
+pub fn requested_handoff(agents: &AgentsConfig, from: &str, result: &AgentTurnResult) -> Option<HandoffRequest> {
+    result
+        .tool_calls
+        .iter()
+        .zip(&result.tool_results)
+        .rev()
+        .filter(|(call, output)| call.function.name == HANDOFF_TOOL_NAME && !output.starts_with("error: "))
+        .find_map(|(call, _)| HandoffRequest::from_args(agents, Some(from), &call.function.arguments).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::providers::{ToolCall, ToolCallFunction};
+
+    fn agents() -> AgentsConfig {
+        let c: Config = serde_json::from_str(
+            r#"{"agents":[
+                {"id":"assistant","role":"orchestrator"},
+                {"id":"developer","role":"orchestrator"}
+            ]}"#,
+        )
+        .unwrap();
+        c.agents
+    }
+
+    fn turn(calls: &[(&str, Value, &str)]) -> AgentTurnResult {
+        AgentTurnResult {
+            content: String::new(),
+            tool_calls: calls
+                .iter()
+                .map(|(name, args, _)| ToolCall {
+                    typ: "function".to_string(),
+                    function: ToolCallFunction {
+                        index: None,
+                        name: name.to_string(),
+                        arguments: args.clone(),
+                    },
+                })
+                .collect(),
+            tool_results: calls.iter().map(|(_, _, out)| out.to_string()).collect(),
+            truncated: false,
+            loop_limit_reached: false,
+            pending_tool_calls: Vec::new(),
+            stopped: false,
+        }
+    }
+
+    #[test]
+    fn request_checks_target() {
+        let agents = agents();
+        let req = HandoffRequest::from_args(
+            &agents,
+            Some("assistant"),
+            &json!({"orchestratorId": "developer", "summary": " fix the build ", "mode": "copy"}),
+        )
+        .unwrap();
+        assert_eq!(req.orchestrator_id, "developer");
+        assert_eq!(req.summary.as_deref(), Some("fix the build"));
+        assert_eq!(req.mode, TransferMode::Copy);
+
+        let err = HandoffRequest::from_args(&agents, None, &json!({"orchestratorId": "assistant"})).unwrap_err();
+        assert!(err.contains("already belongs"), "{err}");
+        assert!(HandoffRequest::from_args(&agents, None, &json!({"orchestratorId": "nobody"})).is_err());
+        assert!(HandoffRequest::from_args(&agents, None, &json!({"orchestratorId": "developer", "mode": "swap"})).is_err());
+    }
+
+    #[test]
+    fn requested_handoff_uses_last_accepted_call() {
+        let agents = agents();
+        let result = turn(&[
+            ("handoff", json!({"orchestratorId": "developer", "summary": "first"}), "{}"),
+            ("read_file", json!({}), "ok"),
+            ("handoff", json!({"orchestratorId": "developer", "summary": "denied"}), "error: denied by operator"),
+        ]);
+        let req = requested_handoff(&agents, "assistant", &result).unwrap();
+        assert_eq!(req.summary.as_deref(), Some("first"));
+        assert!(requested_handoff(&agents, "assistant", &turn(&[])).is_none());
+    }
+
+    #[test]
+    fn tool_offered_only_with_several_orchestrators() {
+        let agents = agents();
+        let tools = merge_handoff_tool(None, &agents, "assistant").unwrap();
+        assert_eq!(tools[0].function.parameters["properties"]["orchestratorId"]["enum"], json!(["developer"]));
+        assert_eq!(merge_handoff_tool(Some(tools), &agents, "assistant").unwrap().len(), 1);
+        assert!(merge_handoff_tool(None, &Config::default().agents, "orchestrator").is_none());
+    }
+}
@@ -7,30 +7,72 @@
 //!
 //! **Orchestrator loop** — [`DELEGATE_TASK_TOOL_NAME`], [`merge_delegate_task`], [`execute_delegate_task`]:
 //! when workers are configured, the orchestrator may delegate via `delegate_task`; the worker uses a per-worker system context and skill tools when
-//! `workerId` is set (nested `delegate_task` disabled). Gateway inbound and WebSocket `agent` both pass [`DelegateContext`].
+//! `workerId` is set (nested `delegate_task` only when the worker has `enabledWorkers` and `maxDelegationDepth` allows it). Gateway inbound and WebSocket `agent` both pass [`DelegateContext`].
+//!
+//! **Fan-out** — [`DELEGATE_MANY_TOOL_NAME`], [`execute_delegate_many`]: one worker turn per
+//! item of a `delegate_many` call, with indexed results and an optional reduce turn.
+//!
+//! **Background delegation** — [`BackgroundDelegations`]: with `asyncDelegation`, `delegate_task`
+//! returns a handle and the orchestrator follows up with `check_delegation` / `await_delegation`.
+//!
+//! **Handoff** — [`HANDOFF_TOOL_NAME`], [`requested_handoff`]: an orchestrator passes the session
+//! to another orchestrator; the gateway moves it after the turn (as WebSocket `sessions.transfer`).
+//!
+//! **Model routing** — [`routing`]: per-agent [`ModelRoute`]s for `modelRouting: "tier"`, from the
+//! capability tiers declared on providers and the agent's enabled skills.
+//!
+//! **Workflows** — [`workflow`]: configured DAGs of worker steps, offered to the orchestrator as
+//! `workflow_<id>` tools and to clients through WebSocket `workflow.run`.
 
+mod background;
 mod choice;
 pub mod delegate;
 mod dispatch;
+mod fan_out;
+mod handoff;
 mod model;
+mod output_schema;
 mod policy;
+pub mod routing;
 mod workers_context;
+pub mod workflow;
 
+pub use background::{
+    await_delegation_tool_definition, check_delegation_tool_definition, is_delegation_handle_tool,
+    merge_delegation_handle_tools, BackgroundDelegations, DelegationBatch,
+    AWAIT_DELEGATION_TOOL_NAME, CHECK_DELEGATION_TOOL_NAME,
+};
 pub use choice::{resolve_orchestrator_provider_choice, resolve_provider_choice, ProviderChoice};
 pub use delegate::{
     delegate_task_tool_definition, execute_delegate_task, merge_delegate_task,
-    worker_tool_list, DelegateContext, DelegateObservability, DelegateTaskResult,
-    OrchestratorRuntime, WorkerDelegateRuntime, EVENT_DELEGATE_ERROR, EVENT_DELEGATE_REJECTED,
+    worker_tool_list, DelegateContext, DelegateObservability, DelegateTaskResult, DelegationCancels, DelegationTurn,
+    OrchestratorRuntime, WorkerDelegateRuntime, EVENT_DELEGATE_ERROR, EVENT_DELEGATE_REJECTED, EVENT_DELEGATE_RETRY,
     EVENT_DELEGATE_START, EVENT_TOOL_CALL, DELEGATE_TASK_TOOL_NAME,
     EVENT_ASSISTANT_PROGRESS, EVENT_DELEGATE_COMPLETE, EVENT_TOOL_RESULT,
     EVENT_TOOL_LOOP_LIMIT,
 };
 pub use dispatch::ProviderClients;
+pub use fan_out::{
+    delegate_many_count, delegate_many_tool_definition, execute_delegate_many, merge_delegate_many,
+    DELEGATE_MANY_MAX_ITEMS, DELEGATE_MANY_TOOL_NAME,
+};
+pub use handoff::{
+    handoff_tool_definition, merge_handoff_tool, requested_handoff, HandoffRequest, TransferMode,
+    EVENT_SESSION_TRANSFERRED, HANDOFF_TOOL_NAME,
+};
 pub use model::{resolve_model, DEFAULT_MODEL_FALLBACK};
 pub use policy::{apply_delegation_bracket_match, assert_session_delegation_limits};
+pub use routing::{
+    model_route, tier_candidates, ModelRoute, RouteReason, RouteStep, EVENT_MODEL_ROUTE,
+};
 
 pub use workers_context::{build_workers_context, effective_worker_defaults};
+pub use workflow::{
+    is_workflow_tool, merge_workflow_tools, run_workflow, run_workflow_call, validate_workflows,
+    workflow_inputs, WorkflowRunResult, EVENT_WORKFLOW_COMPLETE, EVENT_WORKFLOW_ERROR,
+    EVENT_WORKFLOW_START, EVENT_WORKFLOW_STEP,
+};
 
 pub use crate::agent::{
-    run_turn_dyn, run_turn_with_messages, run_turn_with_messages_dyn, AgentTurnResult,
+    run_turn_dyn, run_turn_with_messages, run_turn_with_messages_dyn, AgentTurnResult, WorkerTurn,
 };
@@ -0,0 +1,135 @@
+//! Worker `outputSchema`: instruct the worker to end with JSON, extract that JSON from the reply,
+//! and validate it against the schema before it is returned to the orchestrator.
+//!
+//! Validation uses the same JSON Schema validator as tool arguments ([`SchemaValidator`]), with
+//! standard semantics: properties a schema does not declare are allowed unless
+//! `additionalProperties` is `false`.
+
+use crate::tools::SchemaValidator;
+use serde_json::Value;
+
+/// Times a worker is re-asked for conforming JSON after its reply fails to parse or validate.
+pub(crate) const OUTPUT_SCHEMA_MAX_RETRIES: usize = 2;
+
+/// System-prompt addition telling the worker how its final reply must look.
+pub(crate) fn output_instruction(schema: &Value) -> String {
+    format!(
+        "## Output\n\nEnd your final reply with a single JSON value that matches this JSON Schema. \
+         Do not add any text after the JSON.\n\n```json\n{}\n```",
+        serde_json::to_string_pretty(schema).unwrap_or_else(|_| schema.to_string())
+    )
+}
+
+/// Follow-up user message sent when the previous reply did not match the schema.
+pub(crate) fn retry_instruction(error: &str) -> String {
+    format!(
+        "Your reply did not match the required output schema ({}). \
+         Reply again with only the corrected JSON value.",
+        error
+    )
+}
+
+/// Extract the trailing JSON value from `reply` and validate it against `schema`.
+pub(crate) fn parse_output(reply: &str, schema: &Value) -> Result<Value, String> {
+    let value = extract_json(reply).ok_or_else(|| "reply does not end with a JSON value".to_string())?;
+    validate(schema, &value)?;
+    Ok(value)
+}
+
+/// Find the outermost JSON object or array that the reply ends with. A surrounding Markdown code
+/// fence is tolerated.
+fn extract_json(reply: &str) -> Option<Value> {
+    let text = reply.trim();
+    for (start, c) in text.char_indices() {
+        if c != '{' && c != '[' {
+            continue;
+        }
+        let mut stream = serde_json::Deserializer::from_str(&text[start..]).into_iter::<Value>();
+        if let Some(Ok(value)) = stream.next() {
+            let rest = &text[start + stream.byte_offset()..];
+            if rest.trim().trim_matches('`').trim().is_empty() {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
+
+/// Validate `value` against `schema`; the error names the JSON Pointer of the first mismatch.
+pub(crate) fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+    SchemaValidator::default().validate(schema, value, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn summary_schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["summary", "files"],
+            "properties": {
+                "summary": { "type": "string" },
+                "status": { "enum": ["ok", "failed"] },
+                "files": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["path"],
+                        "properties": { "path": { "type": "string" }, "lines": { "type": "integer" } },
+                        "additionalProperties": false
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn parse_output_accepts_trailing_json_after_prose() {
+        let reply = "Here is what I found.\n\n```json\n{\"summary\": \"two files\", \"files\": [{\"path\": \"a.rs\", \"lines\": 3}]}\n```";
+        let v = parse_output(reply, &summary_schema()).expect("valid");
+        assert_eq!(v["files"][0]["path"], "a.rs");
+    }
+
+    #[test]
+    fn parse_output_rejects_reply_without_json() {
+        let err = parse_output("I could not finish.", &summary_schema()).unwrap_err();
+        assert!(err.contains("does not end with a JSON value"), "{err}");
+    }
+
+    #[test]
+    fn parse_output_rejects_text_after_json() {
+        let err = parse_output("{\"summary\": \"x\", \"files\": []} Let me know!", &summary_schema()).unwrap_err();
+        assert!(err.contains("does not end with a JSON value"), "{err}");
+    }
+
+    #[test]
+    fn validate_reports_path_of_first_mismatch() {
+        let schema = summary_schema();
+        let err = validate(&schema, &json!({"summary": "x", "files": [{"path": "a"}, {"path": 1}]})).unwrap_err();
+        assert_eq!(err, "/files/1/path: expected string, got number");
+
+        let err = validate(&schema, &json!({"summary": "x"})).unwrap_err();
+        assert_eq!(err, "/: missing required property 'files'");
+
+        let err = validate(&schema, &json!({"summary": "x", "files": [{"path": "a", "extra": true}]})).unwrap_err();
+        assert_eq!(err, "/files/0: undeclared property 'extra' (not in schema)");
+
+        let err = validate(&schema, &json!({"summary": "x", "files": [], "status": "maybe"})).unwrap_err();
+        assert!(err.starts_with("/status: must be one of"), "{err}");
+    }
+
+    #[test]
+    fn validate_type_unions_and_integer_numbers() {
+        let schema = json!({"type": ["integer", "null"]});
+        assert!(validate(&schema, &json!(2)).is_ok());
+        assert!(validate(&schema, &json!(2.0)).is_ok());
+        assert!(validate(&schema, &Value::Null).is_ok());
+        assert_eq!(validate(&schema, &json!(2.5)).unwrap_err(), "/: expected integer or null, got number");
+
+        // Undeclared properties are allowed unless `additionalProperties` is false.
+        let schema = json!({"type": "object", "properties": {"summary": {"type": "string"}}});
+        assert!(validate(&schema, &json!({"summary": "x", "notes": "y"})).is_ok());
+    }
+}
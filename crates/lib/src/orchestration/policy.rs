@@ -2,6 +2,8 @@
 
 use crate::config::AgentsConfig;
 use crate::session::SessionStore;
+use std::collections::HashMap;
+
 /// Match `[workerId]` at the start of the instruction, inject `workerId`, and strip the bracketed prefix.
 ///
 /// Every worker with a non-empty ID gets an automatic delegation prefix `[workerId]`. The system
@@ -64,12 +66,15 @@ pub fn apply_delegation_bracket_match(agents: &AgentsConfig, args: &serde_json::
 }
 
 /// Enforces **`maxDelegationsPerSession`** and **`maxDelegationsPerWorker`** before a delegation runs.
-/// Uses the provided orchestrator's delegation policy.
+/// Uses the provided orchestrator's delegation policy. `pending` holds delegations already admitted
+/// but not yet recorded (concurrent delegations in the same turn), keyed by policy worker id; they
+/// count against the caps so a parallel batch cannot overshoot them.
 pub async fn assert_session_delegation_limits(
     store: &SessionStore,
     session_id: &str,
     orchestrator: &crate::config::OrchestratorConfig,
     worker_id: &str,
+    pending: &HashMap<String, usize>,
 ) -> Result<(), String> {
     let session = store
         .get(session_id)
@@ -77,7 +82,8 @@ pub async fn assert_session_delegation_limits(
         .ok_or_else(|| "session not found".to_string())?;
 
     if let Some(max) = orchestrator.max_delegations_per_session {
-        if session.delegation_count >= max {
+        let in_flight: usize = pending.values().sum();
+        if session.delegation_count + in_flight >= max {
             return Err(format!(
                 "max delegations per session reached (maxDelegationsPerSession={})",
                 max
@@ -91,7 +97,8 @@ pub async fn assert_session_delegation_limits(
                 .delegation_by_worker
                 .get(worker_id)
                 .copied()
-                .unwrap_or(0);
+                .unwrap_or(0)
+                + pending.get(worker_id).copied().unwrap_or(0);
             if n >= limit {
                 return Err(format!(
                     "max delegations to worker {} for this session reached (configure maxDelegationsPerWorker.{})",
@@ -119,7 +126,15 @@ mod tests {
                 default_provider: None,
                 default_model: None,
                 enabled_skills: None,
+                enabled_workers: None,
                 context_mode: None,
+                approval_policy: None,
+                output_schema: None,
+                timeout_secs: None,
+                retries: None,
+                fallback: None,
+                sandbox: None,
+                model_routing: None,
             }]),
         };
         let args = json!({ "instruction": "[read-only] search the files" });
@@ -138,7 +153,15 @@ mod tests {
                 default_provider: None,
                 default_model: None,
                 enabled_skills: None,
+                enabled_workers: None,
                 context_mode: None,
+                approval_policy: None,
+                output_schema: None,
+                timeout_secs: None,
+                retries: None,
+                fallback: None,
+                sandbox: None,
+                model_routing: None,
             }]),
         };
         let args = json!({ "instruction": "search the files" });
@@ -158,14 +181,30 @@ mod tests {
                     default_provider: None,
                     default_model: None,
                     enabled_skills: None,
+                    enabled_workers: None,
                     context_mode: None,
+                    approval_policy: None,
+                    output_schema: None,
+                    timeout_secs: None,
+                    retries: None,
+                    fallback: None,
+                    sandbox: None,
+                    model_routing: None,
                 },
                 WorkerConfig {
                     id: "code-review".to_string(),
                     default_provider: None,
                     default_model: None,
                     enabled_skills: None,
+                    enabled_workers: None,
                     context_mode: None,
+                    approval_policy: None,
+                    output_schema: None,
+                    timeout_secs: None,
+                    retries: None,
+                    fallback: None,
+                    sandbox: None,
+                    model_routing: None,
                 },
             ]),
         };
@@ -185,7 +224,15 @@ mod tests {
                 default_provider: None,
                 default_model: None,
                 enabled_skills: None,
+                enabled_workers: None,
                 context_mode: None,
+                approval_policy: None,
+                output_schema: None,
+                timeout_secs: None,
+                retries: None,
+                fallback: None,
+                sandbox: None,
+                model_routing: None,
             }]),
         };
         let args = json!({ "instruction": "[w]   do thing  " });
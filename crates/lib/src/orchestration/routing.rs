@@ -0,0 +1,199 @@
+//! Capability-tier model routing for agents with **`modelRouting: "tier"`**.
+//!
+//! Providers declare the tier of their default model (`tier`) and of further models
+//! (`modelTiers`). An agent's required tier is the strictest `capability_tier` among its enabled
+//! skills. Its [`ModelRoute`] starts at the cheapest model satisfying that tier and lists one model
+//! per higher tier to escalate to. Lower tiers count as cheaper; within a tier, `providers` order
+//! and then `modelTiers` order decide.
+
+use crate::config::{resolve_provider_default_model, CapabilityTier, ProvidersConfig, RedactionConfig};
+use crate::skills::SkillEntry;
+use serde_json::json;
+
+/// WebSocket event name: a routed agent was given a model (at the start of a turn or delegation,
+/// or when escalating to a higher tier).
+pub const EVENT_MODEL_ROUTE: &str = "orchestration.model.route";
+
+/// Why a routed agent moved to a model: the first step, a reply that failed `outputSchema`
+/// validation, or a turn that hit the tool loop limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteReason {
+    Initial,
+    Validation,
+    LoopLimit,
+}
+
+impl RouteReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Initial => "initial",
+            Self::Validation => "validation",
+            Self::LoopLimit => "loop_limit",
+        }
+    }
+}
+
+/// One model a route can use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteStep {
+    pub provider_id: String,
+    pub model: String,
+    pub tier: CapabilityTier,
+}
+
+/// Models an agent with tier routing uses, in escalation order: at most one per tier, starting at
+/// the cheapest model that satisfies `required`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelRoute {
+    pub required: CapabilityTier,
+    pub steps: Vec<RouteStep>,
+}
+
+impl ModelRoute {
+    /// Payload of an [`EVENT_MODEL_ROUTE`] event for moving `agent_id` to `step`.
+    pub fn event_payload(&self, agent_id: &str, step: &RouteStep, reason: RouteReason) -> serde_json::Value {
+        json!({
+            "agentId": agent_id,
+            "requiredTier": self.required.as_str(),
+            "tier": step.tier.as_str(),
+            "provider": step.provider_id,
+            "model": step.model,
+            "reason": reason.as_str(),
+        })
+    }
+
+    /// Whether a turn on this route redacts skill tool output. Escalating replays the session to
+    /// the next tier, so results read on a local tier are redacted when any step's provider is in
+    /// the `redaction` scope.
+    pub fn redacts_tool_output(&self, redaction: &RedactionConfig, providers: &ProvidersConfig) -> bool {
+        self.steps.iter().any(|s| redaction.applies_to(providers, &s.provider_id))
+    }
+}
+
+/// Strictest `capability_tier` among `entries`; [`CapabilityTier::Minimal`] when none declares
+/// one. Unknown values are ignored with a warning.
+pub fn required_tier(entries: &[SkillEntry]) -> CapabilityTier {
+    entries
+        .iter()
+        .filter_map(|e| {
+            let raw = e.capability_tier.as_deref()?;
+            let tier = CapabilityTier::parse(raw);
+            if tier.is_none() {
+                log::warn!("skill {}: unknown capability_tier '{}' ignored for routing", e.name, raw);
+            }
+            tier
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+/// Every model with a declared tier, cheapest first. `enabled_providers` (when non-empty) limits
+/// the providers considered, as an orchestrator's `enabledProviders` does.
+pub fn tier_candidates(providers: &ProvidersConfig, enabled_providers: Option<&[String]>) -> Vec<RouteStep> {
+    let mut out: Vec<RouteStep> = Vec::new();
+    for p in &providers.entries {
+        let id = p.id.trim();
+        if let Some(enabled) = enabled_providers.filter(|e| !e.is_empty()) {
+            if !enabled.iter().any(|e| e.trim() == id) {
+                continue;
+            }
+        }
+        let default = p.tier.map(|tier| (resolve_provider_default_model(providers, id), tier));
+        let listed = p.model_tiers.iter().map(|m| (m.model.trim().to_string(), m.tier));
+        for (model, tier) in default.into_iter().chain(listed) {
+            if model.is_empty() || out.iter().any(|c| c.provider_id == id && c.model == model) {
+                continue;
+            }
+            out.push(RouteStep {
+                provider_id: id.to_string(),
+                model,
+                tier,
+            });
+        }
+    }
+    // Stable: config order is kept within a tier.
+    out.sort_by_key(|c| c.tier);
+    out
+}
+
+/// Route for an agent whose enabled skills are `entries`. Fails when no candidate reaches the
+/// required tier.
+pub fn model_route(candidates: &[RouteStep], entries: &[SkillEntry]) -> Result<ModelRoute, String> {
+    let required = required_tier(entries);
+    let mut steps: Vec<RouteStep> = Vec::new();
+    for c in candidates.iter().filter(|c| c.tier >= required) {
+        if steps.last().is_none_or(|s| s.tier < c.tier) {
+            steps.push(c.clone());
+        }
+    }
+    if steps.is_empty() {
+        return Err(format!(
+            "no provider model declares capability tier {} or higher (set tier or modelTiers on a provider)",
+            required.as_str()
+        ));
+    }
+    Ok(ModelRoute { required, steps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn skill(name: &str, tier: Option<&str>) -> SkillEntry {
+        SkillEntry {
+            name: name.to_string(),
+            description: String::new(),
+            path: std::path::PathBuf::new(),
+            content: String::new(),
+            tool_descriptor: None,
+            capability_tier: tier.map(str::to_string),
+            variant_of: None,
+            matched_bin_group: None,
+        }
+    }
+
+    fn providers() -> ProvidersConfig {
+        let c: Config = serde_json::from_str(
+            r#"{"providers":[
+                {"id":"ollama","endpointType":"ollama","defaultModel":"llama3.2:3b","tier":"minimal",
+                 "modelTiers":[{"model":"qwen2.5:14b","tier":"moderate"},{"model":"qwen2.5:32b","tier":"moderate"}]},
+                {"id":"cloud","endpointType":"openai-compat","baseUrl":"https://api.example.com/v1",
+                 "modelTiers":[{"model":"big","tier":"full"}]}
+            ]}"#,
+        )
+        .expect("parse");
+        c.providers
+    }
+
+    #[test]
+    fn candidates_are_ordered_by_tier_then_config_order() {
+        let models: Vec<String> = tier_candidates(&providers(), None)
+            .into_iter()
+            .map(|c| format!("{}/{}", c.provider_id, c.model))
+            .collect();
+        assert_eq!(
+            models,
+            vec!["ollama/llama3.2:3b", "ollama/qwen2.5:14b", "ollama/qwen2.5:32b", "cloud/big"]
+        );
+        let enabled = vec!["cloud".to_string()];
+        assert_eq!(tier_candidates(&providers(), Some(&enabled)).len(), 1);
+    }
+
+    #[test]
+    fn route_starts_at_strictest_skill_tier() {
+        let candidates = tier_candidates(&providers(), None);
+        let route = model_route(&candidates, &[skill("notes", None), skill("git", Some("moderate"))]).unwrap();
+        assert_eq!(route.required, CapabilityTier::Moderate);
+        let models: Vec<&str> = route.steps.iter().map(|s| s.model.as_str()).collect();
+        assert_eq!(models, vec!["qwen2.5:14b", "big"]);
+
+        let route = model_route(&candidates, &[]).unwrap();
+        assert_eq!(route.steps.len(), 3);
+        assert_eq!(route.steps[0].model, "llama3.2:3b");
+
+        let minimal_only = &candidates[..1];
+        let err = model_route(minimal_only, &[skill("git", Some("full"))]).unwrap_err();
+        assert!(err.contains("capability tier full"), "{err}");
+    }
+}
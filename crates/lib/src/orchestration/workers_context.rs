@@ -49,11 +49,14 @@ pub fn effective_worker_defaults(
 ///
 /// When `enabled_workers` is `Some(list)`, only workers whose id appears in the
 /// list are included. When `None`, no workers are included. When `Some([])` (empty),
-/// all workers are included.
+/// all workers are included. `max_parallel_workflows` is the orchestrator's
+/// concurrent delegation limit; the roster tells the model whether several
+/// `delegate_task` calls in one response run sequentially or concurrently.
 pub fn build_workers_context(
     agents: &AgentsConfig,
     skill_catalog: &[SkillEntry],
     enabled_workers: Option<&[String]>,
+    max_parallel_workflows: usize,
 ) -> String {
     let Some(workers) = agents.workers.as_ref() else {
         return String::new();
@@ -76,6 +79,14 @@ pub fn build_workers_context(
     out.push_str("You can call `delegate_task` to delegate a task to a worker agent.\n\n");
     out.push_str("The worker does not share session history — each worker turn begins with no history.\n\n");
     out.push_str("Only delegate a task to a worker if the worker has the relevant skills.\n\n");
+    if max_parallel_workflows > 1 {
+        out.push_str(&format!(
+            "Several `delegate_task` calls in one response run concurrently, up to {} at a time. Delegate independent tasks together.\n\n",
+            max_parallel_workflows
+        ));
+    } else {
+        out.push_str("Several `delegate_task` calls in one response run sequentially, one at a time.\n\n");
+    }
     for w in workers {
         // Filter by enabled_workers: empty list means all workers; non-empty list means only listed ids.
         if !enabled.is_empty() && !enabled.iter().any(|id| id == w.id.as_str()) {
@@ -125,6 +136,12 @@ fn lines_for_worker(
         out.push_str("\n");
     }
 
+    if let Some(ref schema) = w.output_schema {
+        out.push_str("This worker returns JSON matching this schema:\n\n```json\n");
+        out.push_str(&schema.to_string());
+        out.push_str("\n```\n\n");
+    }
+
     out.push_str("Start your instruction with `[");
     out.push_str(id);
     out.push_str("]` to delegate to this worker.\n\n");
@@ -150,7 +167,15 @@ mod tests {
                 default_provider: Some("ollama".to_string()),
                 default_model: Some("llama3.2:3b".to_string()),
                 enabled_skills: None,
+                enabled_workers: None,
                 context_mode: None,
+                approval_policy: None,
+                output_schema: None,
+                timeout_secs: None,
+                retries: None,
+                fallback: None,
+                sandbox: None,
+                model_routing: None,
             }]),
         }
     }
@@ -158,7 +183,7 @@ mod tests {
     #[test]
     fn no_workers_yields_empty() {
         let a = AgentsConfig::default();
-        assert!(build_workers_context(&a, &[], None).is_empty());
+        assert!(build_workers_context(&a, &[], None, 1).is_empty());
     }
 
     #[test]
@@ -167,13 +192,13 @@ mod tests {
             orchestrators: vec![OrchestratorConfig::default()],
             workers: Some(vec![]),
         };
-        assert!(build_workers_context(&a, &[], None).is_empty());
+        assert!(build_workers_context(&a, &[], None, 1).is_empty());
     }
 
     #[test]
     fn includes_orchestrator_and_worker() {
         let empty: Vec<String> = vec![];
-        let s = build_workers_context(&sample_agents(), &[], Some(&empty));
+        let s = build_workers_context(&sample_agents(), &[], Some(&empty), 1);
         assert!(s.contains("You can call `delegate_task`"));
         assert!(s.contains("The worker does not share session history"));
         assert!(s.contains("Only delegate a task to a worker"));
@@ -196,16 +221,35 @@ mod tests {
                 default_provider: None,
                 default_model: None,
                 enabled_skills: None,
+                enabled_workers: None,
                 context_mode: None,
+                approval_policy: None,
+                output_schema: None,
+                timeout_secs: None,
+                retries: None,
+                fallback: None,
+                sandbox: None,
+                model_routing: None,
             }]),
         };
         let empty: Vec<String> = vec![];
-        let s = build_workers_context(&a, &[], Some(&empty));
+        let s = build_workers_context(&a, &[], Some(&empty), 1);
         assert!(s.contains("bob"));
         assert!(s.contains("### bob"));
         assert!(s.contains("Start your instruction with `[bob]`"));
     }
 
+    #[test]
+    fn worker_output_schema_listed_in_roster() {
+        let mut a = sample_agents();
+        a.workers.as_mut().unwrap()[0].output_schema =
+            Some(serde_json::json!({"type": "object", "required": ["verdict"]}));
+        let empty: Vec<String> = vec![];
+        let s = build_workers_context(&a, &[], Some(&empty), 1);
+        assert!(s.contains("This worker returns JSON matching this schema:"));
+        assert!(s.contains(r#""required":["verdict"]"#));
+    }
+
     #[test]
     fn worker_skill_lists_description_from_catalog() {
         use std::path::PathBuf;
@@ -223,7 +267,15 @@ mod tests {
                 default_provider: None,
                 default_model: None,
                 enabled_skills: Some(vec!["my-skill".to_string()]),
+                enabled_workers: None,
                 context_mode: None,
+                approval_policy: None,
+                output_schema: None,
+                timeout_secs: None,
+                retries: None,
+                fallback: None,
+                sandbox: None,
+                model_routing: None,
             }]),
         };
 
@@ -238,7 +290,7 @@ mod tests {
             matched_bin_group: None,
         }];
         let empty: Vec<String> = vec![];
-        let s = build_workers_context(&a, &catalog, Some(&empty));
+        let s = build_workers_context(&a, &catalog, Some(&empty), 1);
         assert!(!s.contains("provider"));
         assert!(s.contains("- does a thing"));
     }
@@ -246,14 +298,14 @@ mod tests {
     #[test]
     fn bracket_prefix_rendered_per_worker() {
         let empty: Vec<String> = vec![];
-        let s = build_workers_context(&sample_agents(), &[], Some(&empty));
+        let s = build_workers_context(&sample_agents(), &[], Some(&empty), 1);
         assert!(s.contains("Start your instruction with `[bob]`"));
     }
 
     #[test]
     fn no_provider_model_in_context() {
         let empty: Vec<String> = vec![];
-        let s = build_workers_context(&sample_agents(), &[], Some(&empty));
+        let s = build_workers_context(&sample_agents(), &[], Some(&empty), 1);
         assert!(!s.contains("provider"));
         assert!(!s.contains("model"));
     }
@@ -271,26 +323,42 @@ mod tests {
                     default_provider: None,
                     default_model: None,
                     enabled_skills: None,
+                    enabled_workers: None,
                     context_mode: None,
+                    approval_policy: None,
+                    output_schema: None,
+                    timeout_secs: None,
+                    retries: None,
+                    fallback: None,
+                    sandbox: None,
+                    model_routing: None,
                 },
                 WorkerConfig {
                     id: "engineer".to_string(),
                     default_provider: None,
                     default_model: None,
                     enabled_skills: None,
+                    enabled_workers: None,
                     context_mode: None,
+                    approval_policy: None,
+                    output_schema: None,
+                    timeout_secs: None,
+                    retries: None,
+                    fallback: None,
+                    sandbox: None,
+                    model_routing: None,
                 },
             ]),
         };
         let allowed = vec!["reader".to_string()];
-        let s = build_workers_context(&a, &[], Some(&allowed));
+        let s = build_workers_context(&a, &[], Some(&allowed), 1);
         assert!(s.contains("### reader"));
         assert!(!s.contains("### engineer"));
     }
 
     #[test]
     fn enabled_workers_none_includes_none() {
-        let s = build_workers_context(&sample_agents(), &[], None);
+        let s = build_workers_context(&sample_agents(), &[], None, 1);
         assert!(s.is_empty());
     }
 
@@ -303,11 +371,28 @@ mod tests {
                 default_provider: None,
                 default_model: None,
                 enabled_skills: None,
+                enabled_workers: None,
                 context_mode: None,
+                approval_policy: None,
+                output_schema: None,
+                timeout_secs: None,
+                retries: None,
+                fallback: None,
+                sandbox: None,
+                model_routing: None,
             }]),
         };
         let empty: Vec<String> = vec![];
-        let s = build_workers_context(&a, &[], Some(&empty));
+        let s = build_workers_context(&a, &[], Some(&empty), 1);
         assert!(s.contains("### bob"));
     }
+
+    #[test]
+    fn roster_describes_parallel_delegation() {
+        let empty: Vec<String> = vec![];
+        let s = build_workers_context(&sample_agents(), &[], Some(&empty), 1);
+        assert!(s.contains("run sequentially"));
+        let s = build_workers_context(&sample_agents(), &[], Some(&empty), 3);
+        assert!(s.contains("run concurrently, up to 3 at a time"));
+    }
 }
@@ -0,0 +1,701 @@
+//! Declarative workflows: named DAGs of worker steps from the profile's `workflows` config.
+//!
+//! Each step is one [`execute_delegate_task`] call with a rendered instruction, so steps get the
+//! same worker resolution, session caps, retries, transcripts, and `orchestration.delegate.*`
+//! events as a model-issued `delegate_task`. Steps run in dependency waves: every step whose
+//! dependencies have finished runs in the next wave (up to `maxParallelWorkflows` at a time, in
+//! declaration order), so a run does not depend on model planning or on which worker is faster.
+
+use super::delegate::{execute_delegate_task, DelegateContext};
+use crate::config::{Config, WorkflowCondition, WorkflowConfig};
+use crate::providers::{ToolDefinition, ToolFunctionDefinition};
+use futures_util::{stream, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::Ordering;
+
+/// Tool name prefix; a workflow `id` is offered to the orchestrator as `workflow_<id>`.
+pub const WORKFLOW_TOOL_PREFIX: &str = "workflow_";
+
+/// WebSocket event name: workflow run started.
+pub const EVENT_WORKFLOW_START: &str = "orchestration.workflow.start";
+/// WebSocket event name: a step started, finished, failed, or was skipped (`status`).
+pub const EVENT_WORKFLOW_STEP: &str = "orchestration.workflow.step";
+/// WebSocket event name: workflow run finished with an output.
+pub const EVENT_WORKFLOW_COMPLETE: &str = "orchestration.workflow.complete";
+/// WebSocket event name: workflow run failed (invalid inputs or a failed step).
+pub const EVENT_WORKFLOW_ERROR: &str = "orchestration.workflow.error";
+
+/// Tool name for a workflow id.
+pub fn workflow_tool_name(id: &str) -> String {
+    format!("{}{}", WORKFLOW_TOOL_PREFIX, id)
+}
+
+/// Whether `name` is a workflow tool (the `workflow_` prefix is reserved for workflows).
+pub fn is_workflow_tool(name: &str) -> bool {
+    name.starts_with(WORKFLOW_TOOL_PREFIX)
+}
+
+/// Tool definition for one workflow: one string property per input.
+pub fn workflow_tool_definition(workflow: &WorkflowConfig) -> ToolDefinition {
+    let mut properties = Map::new();
+    for input in &workflow.inputs {
+        let mut prop = json!({ "type": "string" });
+        if let Some(ref d) = input.description {
+            prop["description"] = json!(d);
+        }
+        properties.insert(input.name.clone(), prop);
+    }
+    let required: Vec<&str> = workflow
+        .inputs
+        .iter()
+        .filter(|i| !i.optional)
+        .map(|i| i.name.as_str())
+        .collect();
+    let description = workflow.description.clone().unwrap_or_else(|| {
+        format!(
+            "Run the {} workflow ({} worker steps) and return its result.",
+            workflow.id,
+            workflow.steps.len()
+        )
+    });
+    ToolDefinition {
+        typ: "function".to_string(),
+        function: ToolFunctionDefinition {
+            name: workflow_tool_name(&workflow.id),
+            description: Some(description),
+            parameters: json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            }),
+        },
+    }
+}
+
+/// Append the tools of the workflows enabled for `orchestrator_id` (see `enabledWorkflows`).
+pub fn merge_workflow_tools(
+    tools: Option<Vec<ToolDefinition>>,
+    config: &Config,
+    orchestrator_id: &str,
+) -> Option<Vec<ToolDefinition>> {
+    let Ok(orch) = config.agents.orchestrator(Some(orchestrator_id)) else {
+        return tools;
+    };
+    let defs: Vec<ToolDefinition> = config
+        .workflows
+        .iter()
+        .filter(|w| orch.workflow_enabled(&w.id))
+        .map(workflow_tool_definition)
+        .collect();
+    if defs.is_empty() {
+        return tools;
+    }
+    let mut v = tools.unwrap_or_default();
+    v.extend(defs);
+    Some(v)
+}
+
+/// A `{{...}}` reference in a workflow template.
+#[derive(Debug, PartialEq)]
+enum Reference<'a> {
+    Input(&'a str),
+    /// Step id and the path into its output.
+    Step(&'a str, Vec<&'a str>),
+}
+
+fn parse_reference(reference: &str) -> Result<Reference<'_>, String> {
+    let mut parts = reference.trim().split('.');
+    match (parts.next(), parts.next()) {
+        (Some("inputs"), Some(name)) if parts.clone().next().is_none() => Ok(Reference::Input(name)),
+        (Some("steps"), Some(step)) if parts.next() == Some("output") => {
+            Ok(Reference::Step(step, parts.collect()))
+        }
+        _ => Err(format!(
+            "invalid reference \"{}\" (expected inputs.<name> or steps.<id>.output[.path])",
+            reference.trim()
+        )),
+    }
+}
+
+/// References in `template`, in order.
+fn template_references(template: &str) -> Result<Vec<Reference<'_>>, String> {
+    let mut refs = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| "unclosed \"{{\" in template".to_string())?;
+        refs.push(parse_reference(&after[..end])?);
+        rest = &after[end + 2..];
+    }
+    Ok(refs)
+}
+
+/// Values available to templates while a workflow runs.
+struct Scope<'a> {
+    inputs: &'a Map<String, Value>,
+    outputs: HashMap<String, Value>,
+}
+
+impl Scope<'_> {
+    fn lookup(&self, reference: &Reference) -> Option<Value> {
+        match reference {
+            Reference::Input(name) => self.inputs.get(*name).cloned(),
+            Reference::Step(id, path) => {
+                let mut value = self.outputs.get(*id)?;
+                for segment in path {
+                    value = match value {
+                        Value::Object(o) => o.get(*segment)?,
+                        Value::Array(a) => a.get(segment.parse::<usize>().ok()?)?,
+                        _ => return None,
+                    };
+                }
+                Some(value.clone())
+            }
+        }
+    }
+
+    /// Replace every reference in `template`; strings are inserted as-is, other values as JSON,
+    /// and missing values (skipped steps, omitted optional inputs) as an empty string.
+    fn render(&self, template: &str) -> String {
+        let mut out = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            match parse_reference(&after[..end]).ok().and_then(|r| self.lookup(&r)) {
+                Some(Value::String(s)) => out.push_str(&s),
+                Some(Value::Null) | None => {}
+                Some(v) => out.push_str(&v.to_string()),
+            }
+            rest = &after[end + 2..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn holds(&self, condition: &WorkflowCondition) -> bool {
+        let value = parse_reference(&condition.reference)
+            .ok()
+            .and_then(|r| self.lookup(&r))
+            .unwrap_or(Value::Null);
+        if let Some(ref expected) = condition.equals {
+            return &value == expected;
+        }
+        if let Some(ref unexpected) = condition.not_equals {
+            return &value != unexpected;
+        }
+        !matches!(value, Value::Null | Value::Bool(false)) && value != json!("")
+    }
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Steps each step waits for: its `dependsOn` plus steps referenced by its instruction or
+/// condition. Also checks every reference.
+fn step_dependencies(workflow: &WorkflowConfig) -> Result<Vec<Vec<usize>>, String> {
+    let index: HashMap<&str, usize> = workflow
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.id.as_str(), i))
+        .collect();
+    let inputs: HashSet<&str> = workflow.inputs.iter().map(|i| i.name.as_str()).collect();
+    let check = |r: &Reference, step: &str| -> Result<Option<usize>, String> {
+        match r {
+            Reference::Input(name) if inputs.contains(name) => Ok(None),
+            Reference::Input(name) => Err(format!("step \"{}\" references unknown input \"{}\"", step, name)),
+            Reference::Step(id, _) => index
+                .get(id)
+                .copied()
+                .map(Some)
+                .ok_or_else(|| format!("step \"{}\" references unknown step \"{}\"", step, id)),
+        }
+    };
+    let mut deps = Vec::with_capacity(workflow.steps.len());
+    for step in &workflow.steps {
+        let mut d: Vec<usize> = Vec::new();
+        for id in &step.depends_on {
+            let i = *index
+                .get(id.as_str())
+                .ok_or_else(|| format!("step \"{}\" depends on unknown step \"{}\"", step.id, id))?;
+            d.push(i);
+        }
+        let mut refs = template_references(&step.instruction).map_err(|e| format!("step \"{}\": {}", step.id, e))?;
+        if let Some(ref when) = step.when {
+            refs.push(parse_reference(&when.reference).map_err(|e| format!("step \"{}\": {}", step.id, e))?);
+        }
+        for r in &refs {
+            if let Some(i) = check(r, &step.id)? {
+                d.push(i);
+            }
+        }
+        d.sort_unstable();
+        d.dedup();
+        deps.push(d);
+    }
+    Ok(deps)
+}
+
+/// Execution waves: step indices whose dependencies all ran in earlier waves, in declaration
+/// order. Fails on a dependency cycle.
+fn plan(workflow: &WorkflowConfig) -> Result<Vec<Vec<usize>>, String> {
+    let deps = step_dependencies(workflow)?;
+    let mut done = vec![false; deps.len()];
+    let mut waves = Vec::new();
+    while done.iter().any(|d| !d) {
+        let wave: Vec<usize> = (0..deps.len())
+            .filter(|&i| !done[i] && deps[i].iter().all(|&d| done[d]))
+            .collect();
+        if wave.is_empty() {
+            let stuck: Vec<&str> = (0..deps.len())
+                .filter(|&i| !done[i])
+                .map(|i| workflow.steps[i].id.as_str())
+                .collect();
+            return Err(format!("dependency cycle between steps {}", stuck.join(", ")));
+        }
+        for &i in &wave {
+            done[i] = true;
+        }
+        waves.push(wave);
+    }
+    Ok(waves)
+}
+
+/// Check the `workflows` block and `enabledWorkflows` references (run at gateway startup).
+pub fn validate_workflows(config: &Config) -> Result<(), String> {
+    let workers: HashSet<&str> = config
+        .agents
+        .workers
+        .iter()
+        .flatten()
+        .map(|w| w.id.as_str())
+        .collect();
+    let mut seen = HashSet::new();
+    for wf in &config.workflows {
+        if !valid_id(&wf.id) {
+            return Err(format!(
+                "workflow id \"{}\" must be non-empty and use only letters, digits, '-', '_'",
+                wf.id
+            ));
+        }
+        if !seen.insert(wf.id.as_str()) {
+            return Err(format!("duplicate workflow id: {}", wf.id));
+        }
+        let err = |e: String| format!("workflow \"{}\": {}", wf.id, e);
+        let mut input_names = HashSet::new();
+        for input in &wf.inputs {
+            if !valid_id(&input.name) || !input_names.insert(input.name.as_str()) {
+                return Err(err(format!("invalid or duplicate input name \"{}\"", input.name)));
+            }
+        }
+        if wf.steps.is_empty() {
+            return Err(err("has no steps".to_string()));
+        }
+        let mut step_ids = HashSet::new();
+        for step in &wf.steps {
+            if !valid_id(&step.id) || !step_ids.insert(step.id.as_str()) {
+                return Err(err(format!("invalid or duplicate step id \"{}\"", step.id)));
+            }
+            if !workers.contains(step.worker_id.as_str()) {
+                return Err(err(format!(
+                    "step \"{}\" references unknown worker id \"{}\"",
+                    step.id, step.worker_id
+                )));
+            }
+            if step.instruction.trim().is_empty() {
+                return Err(err(format!("step \"{}\" has an empty instruction", step.id)));
+            }
+        }
+        plan(wf).map_err(err)?;
+        if let Some(ref output) = wf.output {
+            for r in template_references(output).map_err(|e| err(format!("output: {}", e)))? {
+                let known = match r {
+                    Reference::Input(name) => input_names.contains(name),
+                    Reference::Step(id, _) => step_ids.contains(id),
+                };
+                if !known {
+                    return Err(err(format!("output references unknown {:?}", r)));
+                }
+            }
+        }
+    }
+    for orch in &config.agents.orchestrators {
+        for id in orch.enabled_workflows.iter().flatten() {
+            if !seen.contains(id.as_str()) {
+                return Err(format!(
+                    "orchestrator \"{}\" references unknown workflow id \"{}\" in enabledWorkflows",
+                    orch.id, id
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How a step ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StepStatus {
+    Completed,
+    Skipped,
+    Failed,
+}
+
+impl StepStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            StepStatus::Completed => "completed",
+            StepStatus::Skipped => "skipped",
+            StepStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One step of a finished run. Steps after a failure are not listed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowStepResult {
+    pub id: String,
+    pub worker_id: String,
+    pub status: StepStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegation_id: Option<String>,
+    /// Worker result: the validated value for an `outputSchema` worker, else the reply text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of [`run_workflow`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowRunResult {
+    pub workflow_id: String,
+    pub run_id: String,
+    /// Rendered `output` template, or the last completed step's output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// True when the session was stopped; remaining steps did not run.
+    pub stopped: bool,
+    pub steps: Vec<WorkflowStepResult>,
+}
+
+impl WorkflowRunResult {
+    /// Tool result for the orchestrator: `{"workflow", "output"}` or an `error:` string.
+    pub fn tool_output(&self) -> String {
+        match (&self.error, &self.output) {
+            (Some(e), _) => format!("error: {}", e),
+            (None, output) if !self.stopped => {
+                json!({ "workflow": self.workflow_id, "output": output }).to_string()
+            }
+            _ => format!("error: workflow {} stopped before it finished", self.workflow_id),
+        }
+    }
+
+    /// Session message for a run started with `workflow.run`: the output text, or why it ended.
+    pub fn summary(&self) -> String {
+        if let Some(ref e) = self.error {
+            return format!("Workflow {} failed: {}", self.workflow_id, e);
+        }
+        if self.stopped {
+            return format!("Workflow {} stopped.", self.workflow_id);
+        }
+        match self.output {
+            Some(Value::String(ref s)) => s.clone(),
+            Some(ref v) => v.to_string(),
+            None => format!("Workflow {} finished without output.", self.workflow_id),
+        }
+    }
+}
+
+/// Validate tool-call arguments against the workflow inputs; every value must be a string.
+pub fn workflow_inputs(workflow: &WorkflowConfig, args: &Value) -> Result<Map<String, Value>, String> {
+    let empty = Map::new();
+    let obj = match args {
+        Value::Object(o) => o,
+        Value::Null => &empty,
+        _ => return Err("arguments must be an object".to_string()),
+    };
+    let mut inputs = Map::new();
+    for input in &workflow.inputs {
+        match obj.get(&input.name) {
+            Some(Value::String(s)) => {
+                inputs.insert(input.name.clone(), json!(s));
+            }
+            Some(Value::Null) | None if input.optional => {}
+            Some(Value::Null) | None => return Err(format!("missing input: {}", input.name)),
+            Some(_) => return Err(format!("input {} must be a string", input.name)),
+        }
+    }
+    Ok(inputs)
+}
+
+/// Worker result value from a `delegate_task` tool output, or the error it reports.
+fn step_output(output: &str) -> Result<Value, String> {
+    if let Some(e) = output.strip_prefix("error: ") {
+        return Err(e.to_string());
+    }
+    let parsed: Value = serde_json::from_str(output).unwrap_or_else(|_| json!(output));
+    Ok(match parsed {
+        Value::Object(mut o) => o
+            .remove("output")
+            .or_else(|| o.remove("reply"))
+            .unwrap_or(Value::Object(o)),
+        v => v,
+    })
+}
+
+/// Run one workflow under `ctx` (the calling orchestrator's delegation context). Returns the run
+/// result; a failed step ends the run with `error` set.
+pub async fn run_workflow(
+    ctx: &DelegateContext,
+    workflow: &WorkflowConfig,
+    inputs: Map<String, Value>,
+) -> WorkflowRunResult {
+    let run_id = format!("wfr-{}", uuid::Uuid::new_v4());
+    let obs = ctx.observability.as_ref();
+    let emit = |event: &str, extra: Value| {
+        if let Some(obs) = obs {
+            let mut payload = json!({ "workflowId": workflow.id, "runId": run_id });
+            if let (Some(p), Some(e)) = (payload.as_object_mut(), extra.as_object()) {
+                p.extend(e.clone());
+            }
+            obs.send(event, obs.merge_base(payload));
+        }
+    };
+    let mut result = WorkflowRunResult {
+        workflow_id: workflow.id.clone(),
+        run_id: run_id.clone(),
+        output: None,
+        error: None,
+        stopped: false,
+        steps: Vec::new(),
+    };
+    let waves = match plan(workflow) {
+        Ok(w) => w,
+        Err(e) => {
+            emit(EVENT_WORKFLOW_ERROR, json!({ "error": e }));
+            result.error = Some(e);
+            return result;
+        }
+    };
+    emit(EVENT_WORKFLOW_START, json!({ "steps": workflow.steps.len() }));
+    log::info!("orchestration: workflow {} started ({})", workflow.id, run_id);
+
+    let max_parallel = ctx.orchestrator().map_or(1, |o| o.max_parallel_workflows());
+    let mut scope = Scope {
+        inputs: &inputs,
+        outputs: HashMap::new(),
+    };
+    let mut last_output = None;
+    'waves: for wave in waves {
+        if ctx.stop_flag.as_ref().is_some_and(|f| f.load(Ordering::SeqCst)) {
+            result.stopped = true;
+            break;
+        }
+        let mut runnable = Vec::new();
+        for i in wave {
+            let step = &workflow.steps[i];
+            if step.when.as_ref().is_some_and(|c| !scope.holds(c)) {
+                emit(EVENT_WORKFLOW_STEP, json!({ "stepId": step.id, "workerId": step.worker_id, "status": "skipped" }));
+                result.steps.push(WorkflowStepResult {
+                    id: step.id.clone(),
+                    worker_id: step.worker_id.clone(),
+                    status: StepStatus::Skipped,
+                    delegation_id: None,
+                    output: None,
+                    error: None,
+                });
+                continue;
+            }
+            let call_ctx = ctx.for_call();
+            let delegation_id = call_ctx.observability.as_ref().and_then(|o| o.delegation_id.clone());
+            let args = json!({ "instruction": scope.render(&step.instruction), "workerId": step.worker_id });
+            runnable.push((step, call_ctx, delegation_id, args));
+        }
+        let emit = &emit;
+        let calls: Vec<_> = runnable
+            .into_iter()
+            .map(|(step, call_ctx, delegation_id, args)| async move {
+                emit(
+                    EVENT_WORKFLOW_STEP,
+                    json!({ "stepId": step.id, "workerId": step.worker_id, "status": "started", "delegationId": delegation_id }),
+                );
+                let outcome = execute_delegate_task(&call_ctx, &args).await;
+                (step, delegation_id, outcome)
+            })
+            .collect();
+        let finished: Vec<_> = stream::iter(calls).buffered(max_parallel).collect().await;
+        for (step, delegation_id, outcome) in finished {
+            let (value, stopped) = match outcome {
+                Ok(r) => (step_output(&r.output), r.stopped),
+                Err(e) => (Err(e), false),
+            };
+            result.stopped |= stopped;
+            let (status, output, error) = match value {
+                Ok(v) => (StepStatus::Completed, Some(v), None),
+                Err(e) => (StepStatus::Failed, None, Some(e)),
+            };
+            let mut extra = json!({ "stepId": step.id, "workerId": step.worker_id, "status": status.as_str() });
+            if let Some(ref id) = delegation_id {
+                extra["delegationId"] = json!(id);
+            }
+            if let Some(ref e) = error {
+                extra["error"] = json!(e);
+            }
+            emit(EVENT_WORKFLOW_STEP, extra);
+            if let Some(ref v) = output {
+                scope.outputs.insert(step.id.clone(), v.clone());
+                last_output = Some(v.clone());
+            }
+            if let Some(ref e) = error {
+                result.error.get_or_insert_with(|| format!("workflow {} failed at step {}: {}", workflow.id, step.id, e));
+            }
+            result.steps.push(WorkflowStepResult {
+                id: step.id.clone(),
+                worker_id: step.worker_id.clone(),
+                status,
+                delegation_id,
+                output,
+                error,
+            });
+        }
+        if result.error.is_some() || result.stopped {
+            break 'waves;
+        }
+    }
+
+    if let Some(ref e) = result.error {
+        log::warn!("orchestration: {}", e);
+        emit(EVENT_WORKFLOW_ERROR, json!({ "error": e }));
+    } else if result.stopped {
+        emit(EVENT_WORKFLOW_ERROR, json!({ "error": "stopped", "stopped": true }));
+    } else {
+        result.output = match workflow.output {
+            Some(ref template) => Some(json!(scope.render(template))),
+            None => last_output,
+        };
+        emit(EVENT_WORKFLOW_COMPLETE, json!({ "output": result.output }));
+        log::info!("orchestration: workflow {} finished ({})", workflow.id, run_id);
+    }
+    result
+}
+
+/// Run the workflow behind a `workflow_<id>` tool call, returning the tool result and whether
+/// the session was stopped.
+pub async fn run_workflow_call(ctx: &DelegateContext, name: &str, args: &Value) -> (String, bool) {
+    let id = name.strip_prefix(WORKFLOW_TOOL_PREFIX).unwrap_or(name);
+    let enabled = ctx.orchestrator().is_some_and(|o| o.workflow_enabled(id));
+    let Some(workflow) = ctx.config.workflows.iter().find(|w| w.id == id).filter(|_| enabled) else {
+        return (format!("error: unknown workflow: {}", id), false);
+    };
+    match workflow_inputs(workflow, args) {
+        Ok(inputs) => {
+            let run = run_workflow(ctx, workflow, inputs).await;
+            (run.tool_output(), run.stopped)
+        }
+        Err(e) => (format!("error: {}", e), false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(json: &str) -> WorkflowConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn review_workflow() -> WorkflowConfig {
+        workflow(
+            r#"{"id":"review-fix","inputs":[{"name":"patch"}],"steps":[
+                {"id":"review","workerId":"reviewer","instruction":"Review:\n{{inputs.patch}}"},
+                {"id":"fix","workerId":"coder","instruction":"Fix {{steps.review.output.issues}}",
+                 "when":{"ref":"steps.review.output.verdict","equals":"reject"}},
+                {"id":"notes","workerId":"writer","instruction":"Write notes","dependsOn":["review"]}
+            ]}"#,
+        )
+    }
+
+    #[test]
+    fn plan_groups_steps_into_dependency_waves() {
+        assert_eq!(plan(&review_workflow()).unwrap(), vec![vec![0], vec![1, 2]]);
+        let cyclic = workflow(
+            r#"{"id":"c","steps":[
+                {"id":"a","workerId":"w","instruction":"{{steps.b.output}}"},
+                {"id":"b","workerId":"w","instruction":"x","dependsOn":["a"]}
+            ]}"#,
+        );
+        assert!(plan(&cyclic).unwrap_err().contains("cycle between steps a, b"));
+    }
+
+    #[test]
+    fn templates_render_inputs_outputs_and_paths() {
+        let inputs: Map<String, Value> = [("patch".to_string(), json!("diff"))].into_iter().collect();
+        let mut scope = Scope {
+            inputs: &inputs,
+            outputs: HashMap::new(),
+        };
+        scope
+            .outputs
+            .insert("review".to_string(), json!({"verdict": "reject", "issues": ["a", "b"]}));
+        assert_eq!(
+            scope.render("{{ inputs.patch }} / {{steps.review.output.issues.1}} / {{steps.review.output.issues}} / {{steps.fix.output}}."),
+            r#"diff / b / ["a","b"] / ."#
+        );
+        let cond = |json: &str| -> WorkflowCondition { serde_json::from_str(json).unwrap() };
+        assert!(scope.holds(&cond(r#"{"ref":"steps.review.output.verdict","equals":"reject"}"#)));
+        assert!(!scope.holds(&cond(r#"{"ref":"steps.review.output.verdict","notEquals":"reject"}"#)));
+        assert!(!scope.holds(&cond(r#"{"ref":"steps.fix.output"}"#)));
+        assert!(parse_reference("steps.review.reply").is_err());
+    }
+
+    #[test]
+    fn validate_workflows_checks_references() {
+        let base = r#"{"agents":[
+            {"id":"main","role":"orchestrator","enabledWorkflows":["review-fix"]},
+            {"id":"reviewer","role":"worker"},{"id":"coder","role":"worker"},{"id":"writer","role":"worker"}
+        ],"workflows":[]}"#;
+        let mut config: Config = serde_json::from_str(base).unwrap();
+        config.workflows = vec![review_workflow()];
+        assert!(validate_workflows(&config).is_ok());
+
+        let mut bad = review_workflow();
+        bad.steps[1].instruction = "Fix {{inputs.diff}}".to_string();
+        config.workflows = vec![bad];
+        assert!(validate_workflows(&config).unwrap_err().contains("unknown input \"diff\""));
+
+        let mut bad = review_workflow();
+        bad.steps[2].worker_id = "ghost".to_string();
+        config.workflows = vec![bad];
+        assert!(validate_workflows(&config).unwrap_err().contains("unknown worker id \"ghost\""));
+
+        config.workflows = Vec::new();
+        assert!(validate_workflows(&config).unwrap_err().contains("unknown workflow id \"review-fix\""));
+    }
+
+    #[test]
+    fn workflow_tool_lists_inputs_and_parses_args() {
+        let wf = review_workflow();
+        let def = workflow_tool_definition(&wf);
+        assert_eq!(def.function.name, "workflow_review-fix");
+        assert_eq!(def.function.parameters["required"], json!(["patch"]));
+        assert!(workflow_inputs(&wf, &json!({})).unwrap_err().contains("missing input: patch"));
+        assert_eq!(workflow_inputs(&wf, &json!({"patch": "x", "extra": 1})).unwrap()["patch"], "x");
+        assert!(step_output("error: boom").is_err());
+        assert_eq!(step_output(r#"{"reply":"hi","worker":{}}"#).unwrap(), json!("hi"));
+        assert_eq!(step_output(r#"{"output":{"a":1},"worker":{}}"#).unwrap(), json!({"a": 1}));
+    }
+}
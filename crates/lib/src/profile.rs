@@ -31,6 +31,21 @@ impl ChaiPaths {
         self.profile_dir.join("paired.json")
     }
 
+    /// Pending tool call approvals (see [`crate::approval::ApprovalStore`]).
+    pub fn approvals_json(&self) -> PathBuf {
+        self.profile_dir.join("approvals.json")
+    }
+
+    /// Operator hook scripts referenced by `hooks` in config.json.
+    pub fn hooks_dir(&self) -> PathBuf {
+        self.profile_dir.join("hooks")
+    }
+
+    /// Values for `{{secret:name}}` placeholders in skill execution specs (see [`crate::secrets`]).
+    pub fn secrets_json(&self) -> PathBuf {
+        self.profile_dir.join("secrets.json")
+    }
+
     /// Per-profile write sandbox directory.
     pub fn sandbox_dir(&self) -> PathBuf {
         self.profile_dir.join("sandbox")
@@ -436,58 +451,61 @@ mod chai_home_tests {
 }
 
 /// Test utility for temporarily setting/removing environment variables.
-/// Restores the original value when dropped.
+/// Restores the original values when dropped.
 #[cfg(test)]
-mod env_guard {
+pub(crate) mod env_guard {
     use std::sync::Mutex;
 
     // Serialize env var mutations across tests to avoid race conditions.
     static LOCK: Mutex<()> = Mutex::new(());
 
     pub struct EnvGuard {
-        key: String,
-        original: Option<String>,
+        originals: Vec<(String, Option<String>)>,
         _lock: std::sync::MutexGuard<'static, ()>,
     }
 
     impl Drop for EnvGuard {
         fn drop(&mut self) {
-            match &self.original {
-                Some(val) => std::env::set_var(&self.key, val),
-                None => std::env::remove_var(&self.key),
+            for (key, original) in self.originals.iter().rev() {
+                match original {
+                    Some(val) => std::env::set_var(key, val),
+                    None => std::env::remove_var(key),
+                }
             }
         }
     }
 
     pub fn set(key: &str, value: &std::path::Path) -> EnvGuard {
-        let lock = LOCK.lock().unwrap();
-        let original = std::env::var(key).ok();
-        std::env::set_var(key, value);
-        EnvGuard {
-            key: key.to_string(),
-            original,
-            _lock: lock,
-        }
+        set_raw(key, &value.to_string_lossy())
     }
 
     pub fn set_raw(key: &str, value: &str) -> EnvGuard {
-        let lock = LOCK.lock().unwrap();
-        let original = std::env::var(key).ok();
-        std::env::set_var(key, value);
+        set_all(&[(key, value)])
+    }
+
+    /// Set several variables under one lock (a second guard in the same test would deadlock).
+    pub fn set_all(vars: &[(&str, &str)]) -> EnvGuard {
+        let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let originals = vars
+            .iter()
+            .map(|(key, value)| {
+                let original = std::env::var(key).ok();
+                std::env::set_var(key, value);
+                (key.to_string(), original)
+            })
+            .collect();
         EnvGuard {
-            key: key.to_string(),
-            original,
+            originals,
             _lock: lock,
         }
     }
 
     pub fn remove(key: &str) -> EnvGuard {
-        let lock = LOCK.lock().unwrap();
+        let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let original = std::env::var(key).ok();
         std::env::remove_var(key);
         EnvGuard {
-            key: key.to_string(),
-            original,
+            originals: vec![(key.to_string(), original)],
             _lock: lock,
         }
     }
@@ -2,6 +2,8 @@
 //!
 //! Inbound: message from channel (e.g. Telegram chat) is routed to a session (get or create).
 //! Outbound: reply for a session can be delivered to the bound channel/conversation.
+//! A binding may also name the orchestrator that owns the session (after a handoff); without one,
+//! the conversation belongs to the default orchestrator.
 //!
 //! When a `data_dir` is provided, bindings are persisted to `bindings.json`
 //! in that directory. Write-through on every `bind()` and `remove_binding()`.
@@ -27,8 +29,18 @@ struct BindingRecord {
     channel_id: String,
     conversation_id: String,
     session_id: String,
+    /// Orchestrator that owns the session; absent = the default orchestrator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    orchestrator_id: Option<String>,
 }
 
+/// Bindings loaded from `bindings.json`: inbound map, outbound map, and session owners.
+type LoadedBindings = (
+    HashMap<ChannelConvKey, String>,
+    HashMap<String, ChannelConvKey>,
+    HashMap<String, String>,
+);
+
 /// In-memory store: (channel_id, conversation_id) <-> session_id (bidirectional).
 /// When `data_dir` is set, bindings are persisted to `bindings.json`.
 pub struct SessionBindingStore {
@@ -36,6 +48,8 @@ pub struct SessionBindingStore {
     to_session: Arc<RwLock<HashMap<ChannelConvKey, String>>>,
     /// session_id -> (channel_id, conversation_id) (outbound delivery)
     to_channel: Arc<RwLock<HashMap<String, ChannelConvKey>>>,
+    /// session_id -> orchestrator id, for bound sessions not owned by the default orchestrator
+    orchestrators: Arc<RwLock<HashMap<String, String>>>,
     /// Directory where `bindings.json` lives. `None` = in-memory only.
     data_dir: Option<PathBuf>,
 }
@@ -52,6 +66,7 @@ impl SessionBindingStore {
         Self {
             to_session: Arc::new(RwLock::new(HashMap::new())),
             to_channel: Arc::new(RwLock::new(HashMap::new())),
+            orchestrators: Arc::new(RwLock::new(HashMap::new())),
             data_dir: None,
         }
     }
@@ -59,22 +74,21 @@ impl SessionBindingStore {
     /// Create a persistent binding store that writes to `data_dir/bindings.json`.
     /// Loads existing bindings from disk if the file exists.
     pub fn with_data_dir(data_dir: PathBuf) -> Self {
-        let (to_session, to_channel) = Self::load_bindings_from_disk(&data_dir);
+        let (to_session, to_channel, orchestrators) = Self::load_bindings_from_disk(&data_dir);
         Self {
             to_session: Arc::new(RwLock::new(to_session)),
             to_channel: Arc::new(RwLock::new(to_channel)),
+            orchestrators: Arc::new(RwLock::new(orchestrators)),
             data_dir: Some(data_dir),
         }
     }
 
     /// Read `bindings.json` from disk and return populated maps.
     /// Missing file or corrupt file returns empty maps (corrupt file is logged).
-    fn load_bindings_from_disk(
-        dir: &PathBuf,
-    ) -> (HashMap<ChannelConvKey, String>, HashMap<String, ChannelConvKey>) {
+    fn load_bindings_from_disk(dir: &PathBuf) -> LoadedBindings {
         let path = dir.join("bindings.json");
         if !path.exists() {
-            return (HashMap::new(), HashMap::new());
+            return Default::default();
         }
         let content = match std::fs::read_to_string(&path) {
             Ok(c) => c,
@@ -84,7 +98,7 @@ impl SessionBindingStore {
                     path.display(),
                     e
                 );
-                return (HashMap::new(), HashMap::new());
+                return Default::default();
             }
         };
         let records: Vec<BindingRecord> = match serde_json::from_str(&content) {
@@ -95,20 +109,24 @@ impl SessionBindingStore {
                     path.display(),
                     e
                 );
-                return (HashMap::new(), HashMap::new());
+                return Default::default();
             }
         };
         let mut to_session = HashMap::new();
         let mut to_channel = HashMap::new();
+        let mut orchestrators = HashMap::new();
         for rec in records {
             let key = ChannelConvKey {
                 channel_id: rec.channel_id,
                 conversation_id: rec.conversation_id,
             };
+            if let Some(orchestrator_id) = rec.orchestrator_id {
+                orchestrators.insert(rec.session_id.clone(), orchestrator_id);
+            }
             to_session.insert(key.clone(), rec.session_id.clone());
             to_channel.insert(rec.session_id, key);
         }
-        (to_session, to_channel)
+        (to_session, to_channel, orchestrators)
     }
 
     /// Persist the current in-memory bindings to `bindings.json` (atomic: .tmp then rename).
@@ -117,15 +135,18 @@ impl SessionBindingStore {
             return;
         };
         let to_session = self.to_session.read().await;
+        let orchestrators = self.orchestrators.read().await;
         let records: Vec<BindingRecord> = to_session
             .iter()
             .map(|(key, session_id)| BindingRecord {
                 channel_id: key.channel_id.clone(),
                 conversation_id: key.conversation_id.clone(),
                 session_id: session_id.clone(),
+                orchestrator_id: orchestrators.get(session_id).cloned(),
             })
             .collect();
         drop(to_session);
+        drop(orchestrators);
 
         let path = dir.join("bindings.json");
         let tmp_path = dir.join("bindings.json.tmp");
@@ -161,6 +182,18 @@ impl SessionBindingStore {
         channel_id: impl Into<String>,
         conversation_id: impl Into<String>,
         session_id: impl Into<String>,
+    ) {
+        self.bind_for(channel_id, conversation_id, session_id, None).await;
+    }
+
+    /// [`SessionBindingStore::bind`] for a session owned by `orchestrator_id` (`None` = the
+    /// default orchestrator).
+    pub async fn bind_for(
+        &self,
+        channel_id: impl Into<String>,
+        conversation_id: impl Into<String>,
+        session_id: impl Into<String>,
+        orchestrator_id: Option<&str>,
     ) {
         let channel_id = channel_id.into();
         let conversation_id = conversation_id.into();
@@ -171,15 +204,22 @@ impl SessionBindingStore {
         };
         let mut to_session = self.to_session.write().await;
         let mut to_channel = self.to_channel.write().await;
+        let mut orchestrators = self.orchestrators.write().await;
         if let Some(old_key) = to_channel.get(&session_id).cloned() {
             to_session.remove(&old_key);
         }
         if let Some(old_session) = to_session.insert(key.clone(), session_id.clone()) {
             to_channel.remove(&old_session);
+            orchestrators.remove(&old_session);
         }
+        match orchestrator_id {
+            Some(id) => orchestrators.insert(session_id.clone(), id.to_string()),
+            None => orchestrators.remove(&session_id),
+        };
         to_channel.insert(session_id, key);
         drop(to_session);
         drop(to_channel);
+        drop(orchestrators);
         self.persist_to_disk().await;
     }
 
@@ -191,6 +231,7 @@ impl SessionBindingStore {
         if let Some(key) = to_channel.remove(session_id) {
             to_session.remove(&key);
         }
+        self.orchestrators.write().await.remove(session_id);
         drop(to_session);
         drop(to_channel);
         self.persist_to_disk().await;
@@ -214,6 +255,11 @@ impl SessionBindingStore {
             .map(|k| (k.channel_id.clone(), k.conversation_id.clone()))
     }
 
+    /// Orchestrator that owns a bound session; `None` = the default orchestrator.
+    pub async fn get_orchestrator_id(&self, session_id: &str) -> Option<String> {
+        self.orchestrators.read().await.get(session_id).cloned()
+    }
+
     /// Remove all bindings from memory and disk.
     /// Clears both in-memory maps and rewrites `bindings.json` as an empty array.
     pub async fn remove_all(&self) {
@@ -221,6 +267,7 @@ impl SessionBindingStore {
         let mut to_channel = self.to_channel.write().await;
         to_session.clear();
         to_channel.clear();
+        self.orchestrators.write().await.clear();
         drop(to_session);
         drop(to_channel);
         self.persist_to_disk().await;
@@ -322,6 +369,28 @@ mod tests {
         assert!(store.get_channel_binding("sess-old").await.is_none());
     }
 
+    #[tokio::test]
+    async fn binding_store_keeps_session_orchestrator() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionBindingStore::with_data_dir(dir.path().to_path_buf());
+        store.bind("telegram", "123", "sess-abc").await;
+        assert!(store.get_orchestrator_id("sess-abc").await.is_none());
+
+        store
+            .bind_for("telegram", "123", "sess-dev", Some("developer"))
+            .await;
+        assert_eq!(store.get_session_id("telegram", "123").await.unwrap(), "sess-dev");
+        let store2 = SessionBindingStore::with_data_dir(dir.path().to_path_buf());
+        assert_eq!(
+            store2.get_orchestrator_id("sess-dev").await.as_deref(),
+            Some("developer")
+        );
+
+        store.bind("telegram", "123", "sess-new").await;
+        assert!(store.get_orchestrator_id("sess-dev").await.is_none());
+        assert!(store.get_orchestrator_id("sess-new").await.is_none());
+    }
+
     #[tokio::test]
     async fn binding_store_remove_all_clears_maps() {
         let dir = TempDir::new().unwrap();
@@ -7,11 +7,15 @@
 //! under `<data_dir>/sess-<id>.json`. Write-through: every mutation writes to
 //! memory **and** disk (atomic write via `.tmp` + rename). Lazy loading: `get()`
 //! loads from disk if not in memory. `scan()` reads metadata without full history.
+//!
+//! Worker turns run for `delegate_task` are kept as [`DelegationTranscript`] files
+//! (`<delegationId>.json` in the worker's sessions directory) and linked from the parent
+//! session through [`Session::delegation_transcripts`].
 
 use log;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -75,6 +79,109 @@ pub struct Session {
     /// ISO 8601 timestamp updated on every message append and delegation record.
     #[serde(default)]
     pub updated_at: String,
+    /// Where each counted delegation landed in `messages`, so truncation can roll the counters back.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delegation_log: Vec<DelegationRecord>,
+    /// Message tails removed by truncation or regeneration, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub archived_branches: Vec<ArchivedBranch>,
+    /// Persisted worker transcripts of this session's delegations, in completion order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delegation_transcripts: Vec<DelegationTranscriptLink>,
+}
+
+/// One successful delegation: the index its tool result occupies in `Session::messages`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationRecord {
+    pub message_index: usize,
+    pub worker_id: String,
+}
+
+/// Messages cut from a session by `SessionStore::truncate`, kept so rollbacks never lose history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedBranch {
+    /// ISO 8601 timestamp when the branch was archived.
+    pub archived_at: String,
+    /// Index in the live history where the archived messages started.
+    pub from_index: usize,
+    pub messages: Vec<SessionMessage>,
+    /// Transcript links whose tool results were archived with this branch.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delegation_transcripts: Vec<DelegationTranscriptLink>,
+}
+
+/// Points from a session message to the worker transcript of the delegation that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationTranscriptLink {
+    pub delegation_id: String,
+    pub worker_id: String,
+    /// Index of the root `delegate_task` tool result in `Session::messages`.
+    pub message_index: usize,
+    /// Set for nested delegations: the delegation whose worker started this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_delegation_id: Option<String>,
+}
+
+/// Full message list of one worker turn run for `delegate_task`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationTranscript {
+    /// Delegation id (`dlg-<uuid>`); also the file stem.
+    pub id: String,
+    pub worker_id: String,
+    pub provider: String,
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_session_id: Option<SessionId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_delegation_id: Option<String>,
+    /// Index of the root `delegate_task` tool result in the parent session, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_index: Option<usize>,
+    /// ISO 8601 timestamp when the worker turn finished.
+    pub created_at: String,
+    /// Whether the worker turn was stopped by a stop signal.
+    #[serde(default)]
+    pub stopped: bool,
+    /// Why the delegation failed, when it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub messages: Vec<SessionMessage>,
+}
+
+/// Whether `id` is a well-formed delegation id (safe to use as a file stem).
+fn is_delegation_id(id: &str) -> bool {
+    id.strip_prefix("dlg-").is_some_and(|rest| {
+        !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// Write `transcript` to `<dir>/<id>.json` (atomic: .tmp then rename). Creates `dir` if needed.
+pub fn write_delegation_transcript(dir: &Path, transcript: &DelegationTranscript) -> Result<(), String> {
+    if !is_delegation_id(&transcript.id) {
+        return Err(format!("invalid delegation id: {}", transcript.id));
+    }
+    std::fs::create_dir_all(dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}.json", transcript.id));
+    let tmp_path = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(transcript).map_err(|e| e.to_string())?;
+    std::fs::write(&tmp_path, json).map_err(|e| format!("write {}: {}", tmp_path.display(), e))?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| format!("rename {}: {}", path.display(), e))
+}
+
+/// Load `<dir>/<id>.json`. Returns `None` for malformed ids and missing or corrupt files.
+pub fn read_delegation_transcript(dir: &Path, id: &str) -> Option<DelegationTranscript> {
+    if !is_delegation_id(id) {
+        return None;
+    }
+    let path = dir.join(format!("{}.json", id));
+    let content = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(t) => Some(t),
+        Err(e) => {
+            log::warn!("corrupt delegation transcript {}: {}", path.display(), e);
+            None
+        }
+    }
 }
 
 /// Lightweight summary of a session (no full message history).
@@ -97,6 +204,26 @@ pub struct SessionStore {
     data_dir: Option<PathBuf>,
     /// Set of session ids known to exist on disk (populated by `scan()`).
     disk_index: Arc<RwLock<HashSet<SessionId>>>,
+    /// Number of turns running per session (see [`SessionStore::begin_turn`]).
+    active_turns: Arc<std::sync::Mutex<HashMap<SessionId, usize>>>,
+}
+
+/// Marks a turn as running on a session until dropped (see [`SessionStore::begin_turn`]).
+pub struct ActiveTurn {
+    active_turns: Arc<std::sync::Mutex<HashMap<SessionId, usize>>>,
+    id: SessionId,
+}
+
+impl Drop for ActiveTurn {
+    fn drop(&mut self) {
+        let mut turns = self.active_turns.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(n) = turns.get_mut(&self.id) {
+            *n -= 1;
+            if *n == 0 {
+                turns.remove(&self.id);
+            }
+        }
+    }
 }
 
 impl Default for SessionStore {
@@ -112,6 +239,7 @@ impl SessionStore {
             inner: Arc::new(RwLock::new(HashMap::new())),
             data_dir: None,
             disk_index: Arc::new(RwLock::new(HashSet::new())),
+            active_turns: Arc::default(),
         }
     }
 
@@ -129,6 +257,7 @@ impl SessionStore {
             inner: Arc::new(RwLock::new(HashMap::new())),
             data_dir: Some(data_dir),
             disk_index: Arc::new(RwLock::new(HashSet::new())),
+            active_turns: Arc::default(),
         }
     }
 
@@ -288,6 +417,9 @@ impl SessionStore {
             messages: Vec::new(),
             delegation_count: 0,
             delegation_by_worker: HashMap::new(),
+            delegation_log: Vec::new(),
+            archived_branches: Vec::new(),
+            delegation_transcripts: Vec::new(),
             created_at: now.clone(),
             updated_at: now,
         };
@@ -339,6 +471,9 @@ impl SessionStore {
             messages: Vec::new(),
             delegation_count: 0,
             delegation_by_worker: HashMap::new(),
+            delegation_log: Vec::new(),
+            archived_branches: Vec::new(),
+            delegation_transcripts: Vec::new(),
             created_at: now.clone(),
             updated_at: now,
         };
@@ -398,6 +533,52 @@ impl SessionStore {
         from_disk
     }
 
+    /// Remove session `id` to move it to another store (see [`SessionStore::remove`]). Fails while
+    /// a turn runs on the session; the check and the removal happen under the store's write lock.
+    pub async fn remove_for_transfer(&self, id: &str) -> Result<Session, String> {
+        // Lazy-load so sessions that only exist on disk can be moved too.
+        self.get(id).await;
+        let mut g = self.inner.write().await;
+        if !g.contains_key(id) {
+            return Err("session not found".to_string());
+        }
+        if self.has_active_turn(id) {
+            return Err("a turn is running on this session; stop it before transferring".to_string());
+        }
+        let session = g.remove(id).ok_or_else(|| "session not found".to_string())?;
+        drop(g);
+        self.delete_from_disk(id);
+        self.disk_index.write().await.remove(id);
+        Ok(session)
+    }
+
+    /// Copy of session `id` to transfer to another store. Fails while a turn runs on the session,
+    /// so the copy never holds half of a turn.
+    pub async fn get_for_transfer(&self, id: &str) -> Result<Session, String> {
+        self.get(id).await;
+        let g = self.inner.read().await;
+        let session = g.get(id).ok_or_else(|| "session not found".to_string())?;
+        if self.has_active_turn(id) {
+            return Err("a turn is running on this session; stop it before transferring".to_string());
+        }
+        Ok(session.clone())
+    }
+
+    /// Add an existing session (e.g. one transferred from another orchestrator's store) under its
+    /// own id. Fails when this store already has a session with that id. Writes to disk if
+    /// `data_dir` is set.
+    pub async fn insert(&self, session: Session) -> Result<(), String> {
+        if self.get(&session.id).await.is_some() {
+            return Err(format!("session already exists: {}", session.id));
+        }
+        self.write_to_disk(&session);
+        if let Ok(mut index) = self.disk_index.try_write() {
+            index.insert(session.id.clone());
+        }
+        self.inner.write().await.insert(session.id.clone(), session);
+        Ok(())
+    }
+
     /// Append a message to the session; returns error if session not found.
     pub async fn append_message(
         &self,
@@ -437,11 +618,13 @@ impl SessionStore {
     }
 
     /// Increment successful delegation counters for policy (`maxDelegationsPerSession`, per-worker caps).
-    /// Updates `updated_at` and writes to disk.
+    /// `message_index` is where the delegation's tool result lands in `messages`; `None` means the
+    /// next message appended. Updates `updated_at` and writes to disk.
     pub async fn record_delegation(
         &self,
         id: &str,
         worker_id: &str,
+        message_index: Option<usize>,
     ) -> Result<(), String> {
         let mut g = self.inner.write().await;
         let session = g
@@ -452,6 +635,10 @@ impl SessionStore {
             .delegation_by_worker
             .entry(worker_id.to_string())
             .or_insert(0) += 1;
+        session.delegation_log.push(DelegationRecord {
+            message_index: message_index.unwrap_or(session.messages.len()),
+            worker_id: worker_id.to_string(),
+        });
         session.updated_at = chrono_now_iso8601();
         let session_clone = session.clone();
         drop(g);
@@ -459,6 +646,126 @@ impl SessionStore {
         Ok(())
     }
 
+    /// Link a persisted worker transcript to the session. `message_index: None` means the next
+    /// message appended (as in [`Self::record_delegation`]). Writes to disk.
+    pub async fn link_delegation_transcript(
+        &self,
+        id: &str,
+        delegation_id: &str,
+        worker_id: &str,
+        message_index: Option<usize>,
+        parent_delegation_id: Option<&str>,
+    ) -> Result<usize, String> {
+        let mut g = self.inner.write().await;
+        let session = g
+            .get_mut(id)
+            .ok_or_else(|| "session not found".to_string())?;
+        let message_index = message_index.unwrap_or(session.messages.len());
+        session.delegation_transcripts.push(DelegationTranscriptLink {
+            delegation_id: delegation_id.to_string(),
+            worker_id: worker_id.to_string(),
+            message_index,
+            parent_delegation_id: parent_delegation_id.map(str::to_string),
+        });
+        let session_clone = session.clone();
+        drop(g);
+        self.write_to_disk(&session_clone);
+        Ok(message_index)
+    }
+
+    /// Mark a turn as running on session `id` until the returned guard is dropped.
+    /// [`SessionStore::truncate`] refuses to cut a session while a turn runs on it.
+    pub fn begin_turn(&self, id: &str) -> ActiveTurn {
+        let mut turns = self.active_turns.lock().unwrap_or_else(|e| e.into_inner());
+        *turns.entry(id.to_string()).or_insert(0) += 1;
+        ActiveTurn {
+            active_turns: Arc::clone(&self.active_turns),
+            id: id.to_string(),
+        }
+    }
+
+    /// Whether a turn is running on session `id`.
+    pub fn has_active_turn(&self, id: &str) -> bool {
+        self.active_turns
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(id)
+    }
+
+    /// Keep the first `keep` messages and move the rest into `archived_branches`.
+    /// Delegation counters recorded for removed tool results are rolled back; their transcript
+    /// links move to the archived branch.
+    /// Fails while a turn runs on the session, and when `keep` falls inside a tool call exchange
+    /// (after an assistant message with `tool_calls`, or before one of their results).
+    /// Returns the number of messages removed; updates `updated_at` and writes to disk.
+    pub async fn truncate(&self, id: &str, keep: usize) -> Result<usize, String> {
+        // Lazy-load so sessions that only exist on disk can be truncated too.
+        self.get(id).await;
+        let mut g = self.inner.write().await;
+        let session = g
+            .get_mut(id)
+            .ok_or_else(|| "session not found".to_string())?;
+        if keep > session.messages.len() {
+            return Err(format!(
+                "message index out of range (session has {} messages)",
+                session.messages.len()
+            ));
+        }
+        if self.has_active_turn(id) {
+            return Err("a turn is running on this session; stop it before truncating".to_string());
+        }
+        if keep < session.messages.len() {
+            let splits_tool_calls = session.messages[keep].role == "tool"
+                || keep.checked_sub(1).is_some_and(|i| {
+                    session.messages[i]
+                        .tool_calls
+                        .as_ref()
+                        .is_some_and(|c| !c.is_empty())
+                });
+            if splits_tool_calls {
+                return Err(format!(
+                    "cannot truncate at message {}: it is inside a tool call exchange",
+                    keep
+                ));
+            }
+        }
+        let removed: Vec<SessionMessage> = session.messages.drain(keep..).collect();
+        if removed.is_empty() {
+            return Ok(0);
+        }
+        let (kept, rolled_back): (Vec<_>, Vec<_>) = std::mem::take(&mut session.delegation_log)
+            .into_iter()
+            .partition(|r| r.message_index < keep);
+        session.delegation_log = kept;
+        for record in rolled_back {
+            session.delegation_count = session.delegation_count.saturating_sub(1);
+            if let Some(n) = session.delegation_by_worker.get_mut(&record.worker_id) {
+                *n = n.saturating_sub(1);
+                if *n == 0 {
+                    session.delegation_by_worker.remove(&record.worker_id);
+                }
+            }
+        }
+        let (kept, archived_links): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut session.delegation_transcripts)
+                .into_iter()
+                .partition(|l| l.message_index < keep);
+        session.delegation_transcripts = kept;
+        let count = removed.len();
+        let now = chrono_now_iso8601();
+        session.archived_branches.push(ArchivedBranch {
+            archived_at: now.clone(),
+            from_index: keep,
+            messages: removed,
+            delegation_transcripts: archived_links,
+        });
+        session.updated_at = now;
+        let session_clone = session.clone();
+        drop(g);
+        self.write_to_disk(&session_clone);
+        Ok(count)
+    }
+
     /// Remove all sessions from memory and disk. Returns the number of sessions removed.
     /// Deletes all `sess-*.json` files from `data_dir` and clears the disk index.
     /// Counts both in-memory sessions and sessions that exist only on disk
@@ -501,7 +808,7 @@ impl SessionStore {
 
 /// Return the current time as an ISO 8601 string.
 /// Uses `std::time` to avoid adding a chrono dependency.
-fn chrono_now_iso8601() -> String {
+pub(crate) fn chrono_now_iso8601() -> String {
     let dur = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default();
@@ -582,6 +889,9 @@ mod tests {
             },
             created_at: "2025-01-01T00:00:00Z".to_string(),
             updated_at: "2025-01-01T00:01:00Z".to_string(),
+            delegation_log: Vec::new(),
+            archived_branches: Vec::new(),
+            delegation_transcripts: Vec::new(),
         };
         let json = serde_json::to_string(&session).expect("serialize");
         let back: Session = serde_json::from_str(&json).expect("deserialize");
@@ -658,6 +968,176 @@ mod tests {
         assert_eq!(disk_session.messages[0].content, "hello");
     }
 
+    #[tokio::test]
+    async fn session_store_truncate_archives_and_rolls_back_delegations() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::with_data_dir(dir.path().to_path_buf());
+
+        let id = store.create().await;
+        store.append_message(&id, "user", "first").await.unwrap();
+        store.append_message(&id, "assistant", "calling").await.unwrap();
+        store.record_delegation(&id, "search", None).await.unwrap();
+        store.append_message(&id, "tool", "found it").await.unwrap();
+        store.append_message(&id, "assistant", "done").await.unwrap();
+        store.append_message(&id, "user", "second").await.unwrap();
+        store.append_message(&id, "assistant", "calling again").await.unwrap();
+        store.record_delegation(&id, "search", None).await.unwrap();
+        store.append_message(&id, "tool", "found more").await.unwrap();
+
+        let removed = store.truncate(&id, 4).await.expect("truncate");
+        assert_eq!(removed, 3);
+        let session = store.get(&id).await.unwrap();
+        assert_eq!(session.messages.len(), 4);
+        assert_eq!(session.messages[3].content, "done");
+        assert_eq!(session.delegation_count, 1);
+        assert_eq!(session.delegation_by_worker.get("search"), Some(&1));
+        assert_eq!(session.archived_branches.len(), 1);
+        assert_eq!(session.archived_branches[0].from_index, 4);
+        assert_eq!(session.archived_branches[0].messages[0].content, "second");
+
+        store.truncate(&id, 1).await.expect("truncate");
+        let session = store.get(&id).await.unwrap();
+        assert_eq!(session.delegation_count, 0);
+        assert!(session.delegation_by_worker.is_empty());
+        assert_eq!(session.archived_branches.len(), 2);
+
+        // Archive persists with the session file.
+        let content = fs::read_to_string(dir.path().join(format!("{}.json", id))).unwrap();
+        let disk_session: Session = serde_json::from_str(&content).unwrap();
+        assert_eq!(disk_session.archived_branches.len(), 2);
+
+        assert!(store.truncate(&id, 2).await.is_err());
+        assert_eq!(store.truncate(&id, 1).await, Ok(0));
+    }
+
+    #[tokio::test]
+    async fn session_store_truncate_refuses_active_turns_and_split_tool_calls() {
+        let store = SessionStore::new();
+        let id = store.create().await;
+        let calls: Vec<crate::providers::ToolCall> = serde_json::from_value(serde_json::json!([
+            { "type": "function", "function": { "name": "list", "arguments": {} } },
+            { "type": "function", "function": { "name": "read", "arguments": {} } },
+        ]))
+        .unwrap();
+        store.append_message(&id, "user", "list then read").await.unwrap();
+        store
+            .append_message_full(&id, "assistant", "", Some(calls), None)
+            .await
+            .unwrap();
+        store
+            .append_message_full(&id, "tool", "a.txt", None, Some("list".to_string()))
+            .await
+            .unwrap();
+        store
+            .append_message_full(&id, "tool", "hello", None, Some("read".to_string()))
+            .await
+            .unwrap();
+        store.append_message(&id, "assistant", "done").await.unwrap();
+
+        // Between the tool calls and their results, and between two results.
+        for keep in [2, 3] {
+            let err = store.truncate(&id, keep).await.unwrap_err();
+            assert!(err.contains("inside a tool call exchange"), "{}", err);
+        }
+
+        let turn = store.begin_turn(&id);
+        let err = store.truncate(&id, 4).await.unwrap_err();
+        assert!(err.contains("a turn is running"), "{}", err);
+        drop(turn);
+        assert!(!store.has_active_turn(&id));
+
+        assert_eq!(store.truncate(&id, 4).await, Ok(1));
+        assert_eq!(store.truncate(&id, 1).await, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn session_store_transfer_refuses_active_turns() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::with_data_dir(dir.path().to_path_buf());
+        let id = store.create().await;
+        store.append_message(&id, "user", "hello").await.unwrap();
+
+        let turn = store.begin_turn(&id);
+        let err = store.get_for_transfer(&id).await.unwrap_err();
+        assert!(err.contains("a turn is running"), "{}", err);
+        let err = store.remove_for_transfer(&id).await.unwrap_err();
+        assert!(err.contains("a turn is running"), "{}", err);
+        assert!(store.get(&id).await.is_some());
+        drop(turn);
+
+        assert_eq!(store.get_for_transfer(&id).await.unwrap().messages.len(), 1);
+        let moved = store.remove_for_transfer(&id).await.unwrap();
+        assert_eq!(moved.id, id);
+        assert!(store.get(&id).await.is_none());
+        assert!(!dir.path().join(format!("{}.json", id)).exists());
+        assert_eq!(store.remove_for_transfer(&id).await.unwrap_err(), "session not found");
+    }
+
+    #[tokio::test]
+    async fn session_store_links_transcripts_and_archives_them_on_truncate() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::with_data_dir(dir.path().to_path_buf());
+
+        let id = store.create().await;
+        store.append_message(&id, "user", "first").await.unwrap();
+        store.append_message(&id, "assistant", "calling").await.unwrap();
+        let at = store
+            .link_delegation_transcript(&id, "dlg-a", "search", None, None)
+            .await
+            .unwrap();
+        assert_eq!(at, 2);
+        store
+            .link_delegation_transcript(&id, "dlg-b", "code", Some(at), Some("dlg-a"))
+            .await
+            .unwrap();
+        store.append_message(&id, "tool", "found it").await.unwrap();
+        store.append_message(&id, "user", "second").await.unwrap();
+        store.append_message(&id, "assistant", "calling again").await.unwrap();
+        store
+            .link_delegation_transcript(&id, "dlg-c", "search", None, None)
+            .await
+            .unwrap();
+        store.append_message(&id, "tool", "found more").await.unwrap();
+
+        store.truncate(&id, 4).await.unwrap();
+        let session = store.get(&id).await.unwrap();
+        let live: Vec<&str> = session.delegation_transcripts.iter().map(|l| l.delegation_id.as_str()).collect();
+        assert_eq!(live, ["dlg-a", "dlg-b"]);
+        assert_eq!(session.delegation_transcripts[1].parent_delegation_id.as_deref(), Some("dlg-a"));
+        let archived = &session.archived_branches[0].delegation_transcripts;
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].delegation_id, "dlg-c");
+        assert_eq!(archived[0].message_index, 5);
+    }
+
+    #[test]
+    fn delegation_transcript_round_trips_and_rejects_bad_ids() {
+        let dir = TempDir::new().unwrap();
+        let transcript = DelegationTranscript {
+            id: "dlg-1234-abcd".to_string(),
+            worker_id: "search".to_string(),
+            provider: "ollama".to_string(),
+            model: "llama3".to_string(),
+            parent_session_id: Some("sess-1".to_string()),
+            parent_delegation_id: None,
+            message_index: Some(2),
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+            stopped: false,
+            error: None,
+            messages: vec![SessionMessage::user("find it")],
+        };
+        write_delegation_transcript(dir.path(), &transcript).expect("write");
+        let back = read_delegation_transcript(dir.path(), "dlg-1234-abcd").expect("read");
+        assert_eq!(back.worker_id, "search");
+        assert_eq!(back.message_index, Some(2));
+        assert_eq!(back.messages[0].content, "find it");
+
+        assert!(read_delegation_transcript(dir.path(), "dlg-missing").is_none());
+        assert!(read_delegation_transcript(dir.path(), "../sess-1").is_none());
+        let bad = DelegationTranscript { id: "dlg-../x".to_string(), ..transcript };
+        assert!(write_delegation_transcript(dir.path(), &bad).is_err());
+    }
+
     #[tokio::test]
     async fn session_store_remove_deletes_file() {
         let dir = TempDir::new().unwrap();
@@ -672,6 +1152,27 @@ mod tests {
         assert!(store.get(&id).await.is_none());
     }
 
+    #[tokio::test]
+    async fn session_store_insert_moves_session_between_stores() {
+        let from_dir = TempDir::new().unwrap();
+        let to_dir = TempDir::new().unwrap();
+        let from = SessionStore::with_data_dir(from_dir.path().to_path_buf());
+        let to = SessionStore::with_data_dir(to_dir.path().to_path_buf());
+
+        let id = from.create().await;
+        from.append_message(&id, "user", "hello").await.unwrap();
+        let session = from.remove(&id).await.unwrap();
+        to.insert(session.clone()).await.unwrap();
+        assert!(to_dir.path().join(format!("{}.json", id)).exists());
+        assert!(from.get(&id).await.is_none());
+        assert_eq!(to.get(&id).await.unwrap().messages.len(), 1);
+
+        let reloaded = SessionStore::with_data_dir(to_dir.path().to_path_buf());
+        assert_eq!(reloaded.get(&id).await.unwrap().messages[0].content, "hello");
+        let err = to.insert(session).await.unwrap_err();
+        assert!(err.contains("already exists"), "{err}");
+    }
+
     #[tokio::test]
     async fn session_store_graceful_degradation() {
         let dir = TempDir::new().unwrap();
@@ -125,6 +125,35 @@ pub struct ExecutionSpec {
     /// with the standard blank-line separator. When absent, no hints are injected.
     #[serde(default, rename = "hintConditions")]
     pub hint_conditions: Option<Vec<HintCondition>>,
+    /// Optional: declares that this tool only reads state (no filesystem,
+    /// repository, or remote side effects). Under the `approve_writes`
+    /// approval policy, tools are gated unless every execution spec for the
+    /// tool sets `readOnly: true` and has no `writePath` argument.
+    #[serde(default)]
+    pub read_only: Option<bool>,
+    /// Optional: seconds before the command and its process group are killed and the tool
+    /// fails with "timed out after N s". Overrides the profile `exec.timeoutSecs`; `0` = no limit.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Optional: bytes of stdout and stderr kept; the rest is read and discarded and an
+    /// "output capped" line is appended. Unlike `maxOutputLines`, this bounds memory while the
+    /// command runs. Overrides the profile `exec.maxOutputBytes`; `0` = no limit.
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+    /// Optional: kernel resource limits (CPU seconds, address space, file size, open files,
+    /// processes) set on the command with `setrlimit` on Linux. Fields set here override the
+    /// profile `exec.limits` one by one.
+    #[serde(default)]
+    pub limits: Option<crate::exec::ResourceLimits>,
+    /// Optional: environment of the command beyond the baseline (`PATH`, `HOME`, `LANG`, ...):
+    /// `pass` names gateway variables to pass through, `set` gives fixed values. Combined with
+    /// the profile `exec.env`; the gateway's other variables (tokens, API keys) are never passed.
+    #[serde(default)]
+    pub env: Option<crate::exec::EnvPolicy>,
+    /// Optional: redaction rules for this tool's output, in addition to the profile
+    /// `redaction.rules`; applied under the same provider scope.
+    #[serde(default)]
+    pub redact: Option<Vec<crate::redaction::RedactionRule>>,
 }
 
 impl Default for ExecutionSpec {
@@ -143,10 +172,24 @@ impl Default for ExecutionSpec {
             max_output_lines: None,
             truncation_hint: None,
             hint_conditions: None,
+            read_only: None,
+            timeout_secs: None,
+            max_output_bytes: None,
+            limits: None,
+            env: None,
+            redact: None,
         }
     }
 }
 
+impl ExecutionSpec {
+    /// Whether the spec may modify state: it has a `writePath` argument or is not declared
+    /// `readOnly: true`.
+    pub fn may_write(&self) -> bool {
+        self.read_only != Some(true) || self.args.iter().any(|a| a.write_path == Some(true))
+    }
+}
+
 /// Condition that must be satisfied for an execution spec to be selected.
 ///
 /// The loader evaluates conditions during skill loading and filters execution
@@ -748,6 +791,17 @@ impl ToolDescriptor {
             .collect()
     }
 
+    /// Names of tools that may modify state: tools with at least one execution spec that has a
+    /// `writePath` argument or is not declared `readOnly: true`. Used by the `approve_writes`
+    /// approval policy.
+    pub fn write_tool_names(&self) -> std::collections::HashSet<String> {
+        self.execution
+            .iter()
+            .filter(|spec| spec.may_write())
+            .map(|spec| spec.tool.clone())
+            .collect()
+    }
+
     /// Build an exec::Allowlist from the descriptor's allowlist map.
     pub fn to_allowlist(&self) -> crate::exec::Allowlist {
         let mut a = crate::exec::Allowlist::new();
@@ -1208,6 +1262,38 @@ mod tests {
         assert_eq!(desc.execution[0].tool, "test_tool");
     }
 
+    // --- ToolDescriptor::write_tool_names tests ---
+
+    #[test]
+    fn write_tool_names_excludes_only_read_only_specs() {
+        let execution = vec![
+            ExecutionSpec {
+                tool: "notes_read".to_string(),
+                read_only: Some(true),
+                ..Default::default()
+            },
+            ExecutionSpec {
+                tool: "notes_write".to_string(),
+                read_only: Some(true),
+                args: vec![ArgMapping {
+                    param: Some("path".to_string()),
+                    write_path: Some(true),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ExecutionSpec {
+                tool: "git_commit".to_string(),
+                ..Default::default()
+            },
+        ];
+        let desc = ToolDescriptor::from_parts(Vec::new(), HashMap::new(), execution);
+        let writes = desc.write_tool_names();
+        assert!(!writes.contains("notes_read"));
+        assert!(writes.contains("notes_write"));
+        assert!(writes.contains("git_commit"));
+    }
+
     // --- Deserialization tests ---
 
     #[test]
@@ -259,6 +259,15 @@ fn load_skills_from_root(dir: &Path) -> Result<Vec<SkillEntry>> {
             },
         };
         let mut tool_descriptor = load_tool_descriptor(&content_dir);
+        if let Some(ref desc) = tool_descriptor {
+            let errors = super::validation::descriptor_errors(desc);
+            if !errors.is_empty() {
+                for error in &errors {
+                    log::warn!("skill {}: {} — skill has no descriptor", parsed.name, error);
+                }
+                tool_descriptor = None;
+            }
+        }
         // Filter execution specs based on condition.binGroup when OR-groups are present.
         if let Some(ref desc) = tool_descriptor {
             if matched_bin_group.is_some() {
@@ -747,6 +756,44 @@ mod tests {
         let _ = std::fs::remove_dir_all(&tmp);
     }
 
+    #[test]
+    fn misplaced_secret_placeholder_drops_descriptor() {
+        let tmp = std::env::temp_dir().join(format!(
+            "chai-test-misplaced-secret-{:?}",
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        std::fs::create_dir_all(&tmp).unwrap();
+
+        let skill_md = "---\ndescription: misplaced secret\n---\n\n# test\n";
+        let tools_json = r#"[{"name": "test_tool", "description": "test", "parameters": {"type": "object"}}]"#;
+        let allowlist_json = r#"{"echo": [""]}"#;
+        let execution_json = r#"[{"tool": "test_tool", "binary": "echo", "subcommand": "", "args": [{"param": "host", "kind": "flag", "flag": "{{secret:token}}"}]}]"#;
+
+        create_versioned_skill(
+            &tmp,
+            "misplaced_secret",
+            &[
+                ("SKILL.md", skill_md),
+                ("tools.json", tools_json),
+                ("allowlist.json", allowlist_json),
+                ("execution.json", execution_json),
+            ],
+        );
+
+        let skills = load_skills(&tmp).unwrap();
+        let entry = skills
+            .iter()
+            .find(|s| s.name == "misplaced_secret")
+            .expect("skill misplaced_secret");
+        assert!(
+            entry.tool_descriptor.is_none(),
+            "tool_descriptor should be None when a secret placeholder is in a flag name"
+        );
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
     #[test]
     fn load_tool_descriptor_no_tools_json_returns_none() {
         let tmp = std::env::temp_dir().join(format!(
@@ -1,19 +1,30 @@
 //! Startup validation of skill composition against agent configuration.
 //!
-//! Four checks run per agent:
+//! Five checks run per agent:
 //! - **Variant overlap** — warns when two enabled skills share a `variant_of` relationship
 //!   (e.g. `git` and `git-read` both enabled), creating redundant tool surfaces.
 //! - **Tier–model mismatch** — warns when a skill's `capability_tier` exceeds the likely capability
 //!   of the agent's configured model (e.g. `full`-tier skill with a 7B local model).
 //! - **unsafePath parameters** — warns when a skill tool has a parameter annotated with `unsafePath`,
 //!   which bypasses sandbox path validation. This makes escape hatches visible at startup.
+//! - **Environment pass-through** — warns when an execution spec passes gateway environment
+//!   variables beyond the baseline to its command (`env.pass`), since they may hold secrets.
 //! - **Schema–execution alignment** — warns when a tool's schema declares a parameter that has no
 //!   corresponding handler in the execution spec. This catches the reverse drift case from
 //!   schema-enforced validation (parameter in schema but not in execution).
+//!
+//! [`descriptor_errors`] runs once per skill at load time; the loader drops a tool descriptor that
+//! fails it, so the skill loads without tools:
+//! - **Secret placeholders** — `{{secret:name}}` is substituted only in a `literal` arg `value` and
+//!   in `env.set` values; anywhere else it would reach the command as written.
+//! - **Redaction rules** — every `redact` rule must name a built-in or carry a valid regex.
 
 use std::collections::HashMap;
 
+use super::descriptor::{ArgKind, ExecutionSpec, ResolveCommandSpec, ToolDescriptor};
 use super::SkillEntry;
+use crate::redaction::Redactor;
+use crate::secrets::has_placeholder;
 
 /// Validate skill composition for a single agent and log warnings.
 ///
@@ -27,12 +38,116 @@ pub fn validate_skill_composition(
 ) {
     check_variant_overlap(agent_label, enabled_entries);
     check_unsafe_path_params(agent_label, enabled_entries);
+    check_env_pass_through(agent_label, enabled_entries);
     check_schema_execution_alignment(agent_label, enabled_entries);
     if let Some(model) = default_model {
         check_tier_model_mismatch(agent_label, enabled_entries, model);
     }
 }
 
+/// Problems that make `descriptor` unusable, one message per problem (empty when it is valid).
+pub fn descriptor_errors(descriptor: &ToolDescriptor) -> Vec<String> {
+    let mut errors = Vec::new();
+    for spec in &descriptor.execution {
+        for field in misplaced_secret_placeholders(spec) {
+            errors.push(format!(
+                "tool '{}': {{{{secret:...}}}} in {} is not substituted (only literal arg values and env.set values are)",
+                spec.tool, field
+            ));
+        }
+        if let Some(Err(e)) = spec.redact.as_deref().map(Redactor::compile) {
+            errors.push(format!("tool '{}': {}", spec.tool, e));
+        }
+    }
+    errors
+}
+
+/// Fields of `spec` that contain a secret placeholder outside a `literal` arg `value` or an
+/// `env.set` value, named as in `execution.json`.
+fn misplaced_secret_placeholders(spec: &ExecutionSpec) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut check = |field: String, value: &str| {
+        if has_placeholder(value) {
+            fields.push(field);
+        }
+    };
+    check("binary".to_string(), &spec.binary);
+    check("subcommand".to_string(), &spec.subcommand);
+    for (i, part) in spec.binary_wrapper.iter().flatten().enumerate() {
+        check(format!("binaryWrapper[{}]", i), part);
+    }
+    for (i, arg) in spec.args.iter().enumerate() {
+        if arg.kind != ArgKind::Literal {
+            if let Some(value) = &arg.value {
+                check(format!("args[{}].value", i), value);
+            }
+        }
+        let strings = [
+            ("flag", &arg.flag),
+            ("flagIfTrue", &arg.flag_if_true),
+            ("flagIfFalse", &arg.flag_if_false),
+            ("denyPattern", &arg.deny_pattern),
+            ("subcommandOverride", &arg.subcommand_override),
+        ];
+        for (name, value) in strings {
+            if let Some(value) = value {
+                check(format!("args[{}].{}", i, name), value);
+            }
+        }
+        if let Some(serde_json::Value::String(value)) = &arg.absent_default {
+            check(format!("args[{}].absentDefault", i), value);
+        }
+        let resolvers = [
+            ("resolveCommand", &arg.resolve_command),
+            ("denyResolveCommand", &arg.deny_resolve_command),
+        ];
+        for (name, resolver) in resolvers {
+            if let Some(resolver) = resolver {
+                for (field, value) in resolve_command_strings(resolver) {
+                    check(format!("args[{}].{}.{}", i, name, field), value);
+                }
+            }
+        }
+    }
+    if let Some(post) = &spec.post_process {
+        let strings = [
+            ("script", &post.script),
+            ("binary", &post.binary),
+            ("subcommand", &post.subcommand),
+        ];
+        for (name, value) in strings {
+            if let Some(value) = value {
+                check(format!("postProcess.{}", name), value);
+            }
+        }
+        for (i, value) in post.args.iter().enumerate() {
+            check(format!("postProcess.args[{}]", i), value);
+        }
+    }
+    if let Some(side_read) = &spec.side_read {
+        check("sideRead.filename".to_string(), &side_read.filename);
+    }
+    fields
+}
+
+fn resolve_command_strings(spec: &ResolveCommandSpec) -> Vec<(String, &str)> {
+    let mut out = Vec::new();
+    let strings = [
+        ("script", &spec.script),
+        ("binary", &spec.binary),
+        ("subcommand", &spec.subcommand),
+    ];
+    for (name, value) in strings {
+        if let Some(value) = value {
+            out.push((name.to_string(), value.as_str()));
+        }
+    }
+    for (i, value) in spec.args.iter().enumerate() {
+        out.push((format!("args[{}]", i), value.as_str()));
+    }
+    out
+}
+
 /// Warn when two enabled skills share a variant relationship:
 /// - Both have the same `variant_of` parent
 /// - One is the parent and the other is its variant
@@ -105,6 +220,30 @@ fn check_unsafe_path_params(agent_label: &str, entries: &[SkillEntry]) {
     }
 }
 
+/// Warn when an execution spec passes gateway environment variables to its
+/// command. Commands otherwise see only the baseline environment, so this
+/// makes every variable that reaches a skill's binary visible at startup.
+fn check_env_pass_through(agent_label: &str, entries: &[SkillEntry]) {
+    for entry in entries {
+        let descriptor = match entry.tool_descriptor.as_ref() {
+            Some(d) => d,
+            None => continue,
+        };
+        for spec in &descriptor.execution {
+            let Some(env) = spec.env.as_ref().filter(|e| !e.pass.is_empty()) else {
+                continue;
+            };
+            log::warn!(
+                "{}: skill '{}' tool '{}' passes gateway environment variables to its command: {}",
+                agent_label,
+                entry.name,
+                spec.tool,
+                env.pass.join(", "),
+            );
+        }
+    }
+}
+
 /// Warn when a tool's schema declares a parameter that has no corresponding
 /// handler in the execution spec. When the schema declares a parameter, the
 /// agent can provide it — but if the execution spec ignores it, the agent's
@@ -292,6 +431,93 @@ mod tests {
         assert!(!skills_overlap(&a, &b));
     }
 
+    fn descriptor(execution: serde_json::Value) -> ToolDescriptor {
+        serde_json::from_value(serde_json::json!({ "execution": execution })).unwrap()
+    }
+
+    #[test]
+    fn secret_placeholders_allowed_in_literal_values_and_env_set() {
+        let d = descriptor(serde_json::json!([{
+            "tool": "deploy",
+            "binary": "deploy",
+            "subcommand": "",
+            "args": [{ "kind": "literal", "value": "--token={{secret:deploy_token}}" }],
+            "env": { "set": { "DEPLOY_TOKEN": "{{secret:deploy_token}}" } }
+        }]));
+        assert!(descriptor_errors(&d).is_empty());
+    }
+
+    #[test]
+    fn secret_placeholders_rejected_outside_supported_positions() {
+        let cases = [
+            (
+                serde_json::json!({ "param": "host", "kind": "flag", "flag": "{{secret:x}}" }),
+                "args[0].flag",
+            ),
+            (
+                serde_json::json!({ "param": "force", "kind": "flagifboolean", "flagIfTrue": "--key={{secret:x}}" }),
+                "args[0].flagIfTrue",
+            ),
+            (
+                serde_json::json!({ "param": "body", "kind": "stdin", "value": "{{secret:x}}" }),
+                "args[0].value",
+            ),
+            (
+                serde_json::json!({ "param": "body", "kind": "stdin", "optional": true, "absentDefault": "{{secret:x}}" }),
+                "args[0].absentDefault",
+            ),
+            (
+                serde_json::json!({ "param": "path", "kind": "positional", "resolveCommand": { "script": "resolve", "args": ["$param", "{{secret:x}}"] } }),
+                "args[0].resolveCommand.args[1]",
+            ),
+            (
+                serde_json::json!({ "param": "path", "kind": "positional", "denyPattern": "^x$", "denyResolveCommand": { "binary": "{{secret:x}}", "subcommand": "" } }),
+                "args[0].denyResolveCommand.binary",
+            ),
+        ];
+        for (arg, field) in cases {
+            let d = descriptor(serde_json::json!([{
+                "tool": "deploy",
+                "binary": "deploy",
+                "subcommand": "",
+                "args": [arg]
+            }]));
+            let errors = descriptor_errors(&d);
+            assert_eq!(errors.len(), 1, "{}: {:?}", field, errors);
+            assert!(
+                errors[0].starts_with(&format!("tool 'deploy': {{{{secret:...}}}} in {} is not substituted", field)),
+                "{}",
+                errors[0]
+            );
+        }
+
+        let d = descriptor(serde_json::json!([{
+            "tool": "deploy",
+            "binary": "deploy",
+            "subcommand": "{{secret:x}}",
+            "binaryWrapper": ["env", "TOKEN={{secret:x}}"],
+            "postProcess": { "script": "filter", "args": ["{{secret:x}}"] }
+        }]));
+        let errors = descriptor_errors(&d);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].contains(" in subcommand "), "{}", errors[0]);
+        assert!(errors[1].contains(" in binaryWrapper[1] "), "{}", errors[1]);
+        assert!(errors[2].contains(" in postProcess.args[0] "), "{}", errors[2]);
+    }
+
+    #[test]
+    fn invalid_redaction_rules_are_rejected() {
+        let d = descriptor(serde_json::json!([
+            { "tool": "log", "binary": "git", "subcommand": "log", "redact": ["email", { "name": "ticket", "pattern": "TCK-[0-9]+" }] },
+            { "tool": "show", "binary": "git", "subcommand": "show", "redact": ["phone"] },
+            { "tool": "blame", "binary": "git", "subcommand": "blame", "redact": [{ "name": "bad", "pattern": "(" }] }
+        ]));
+        let errors = descriptor_errors(&d);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].starts_with("tool 'show': unknown built-in redaction rule 'phone'"), "{}", errors[0]);
+        assert!(errors[1].starts_with("tool 'blame': redaction rule 'bad': invalid pattern"), "{}", errors[1]);
+    }
+
     fn make_entry(
         name: &str,
         capability_tier: Option<&str>,
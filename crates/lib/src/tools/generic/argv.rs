@@ -6,7 +6,8 @@
 
 use std::path::Path;
 
-use crate::exec::Allowlist;
+use crate::exec::{run_program, Allowlist, RunOptions};
+use crate::secrets::{has_placeholder, Secrets};
 use crate::skills::{ArgKind, ExecutionSpec};
 
 /// Substitute `$param_name` placeholders in resolve command args with values
@@ -57,6 +58,7 @@ pub(crate) fn resolve_value(
     arg: &crate::skills::ArgMapping,
     allowlist: &Allowlist,
     skill_dir: Option<&Path>,
+    opts: &RunOptions,
     tool_args: &serde_json::Value,
 ) -> Result<String, String> {
     let Some(ref cmd) = arg.resolve_command else {
@@ -65,7 +67,7 @@ pub(crate) fn resolve_value(
     let argv = substitute_resolve_args(&cmd.args, value, tool_args);
 
     if let (Some(dir), Some(ref script_name)) = (skill_dir, &cmd.script) {
-        let out = run_script(dir, script_name, &argv)?;
+        let out = run_script(dir, script_name, &argv, opts)?;
         let s = out.trim();
         return Ok(if s.is_empty() {
             value.to_string()
@@ -75,7 +77,7 @@ pub(crate) fn resolve_value(
     }
 
     if let (Some(ref binary), Some(ref subcommand)) = (&cmd.binary, &cmd.subcommand) {
-        match allowlist.run(binary, subcommand, &argv, None) {
+        match allowlist.run_with_options(binary, subcommand, &argv, opts).map(|(_, out)| out) {
             Ok(out) => {
                 let s = out.trim();
                 return Ok(if s.is_empty() {
@@ -90,10 +92,15 @@ pub(crate) fn resolve_value(
     Ok(value.to_string())
 }
 
-/// Run a script from the skill's `scripts/` directory via `sh`.
-/// Validates the script name for path traversal and checks that the
-/// resolved path stays within the scripts directory.
-pub(crate) fn run_script(skill_dir: &Path, script_name: &str, args: &[String]) -> Result<String, String> {
+/// Run a script from the skill's `scripts/` directory via `sh`, bounded and
+/// confined as `opts` describes. Validates the script name for path traversal
+/// and checks that the resolved path stays within the scripts directory.
+pub(crate) fn run_script(
+    skill_dir: &Path,
+    script_name: &str,
+    args: &[String],
+    opts: &RunOptions,
+) -> Result<String, String> {
     if script_name.contains("..") || script_name.contains('/') || script_name.contains('\\') {
         return Err("invalid script name".to_string());
     }
@@ -108,17 +115,20 @@ pub(crate) fn run_script(skill_dir: &Path, script_name: &str, args: &[String]) -
             return Err("script not found".to_string());
         }
     }
-    let output = std::process::Command::new("sh")
-        .arg(&script_path)
-        .args(args)
-        .output()
-        .map_err(|e| format!("exec failed: {}", e))?;
+    let mut argv = vec![script_path.to_string_lossy().into_owned()];
+    argv.extend_from_slice(args);
+    let output = run_program("sh", "", &argv, opts)?;
     let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-    if output.status.success() {
-        Ok(stdout)
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-        Err(format!("exit {}: {}", output.status, stderr))
+    match output.status {
+        Some(status) if status.success() => Ok(stdout),
+        Some(status) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            Err(format!("exit {}: {}", status, stderr))
+        }
+        None => Err(format!(
+            "timed out after {} s",
+            opts.limits.timeout.unwrap_or_default().as_secs()
+        )),
     }
 }
 
@@ -129,9 +139,10 @@ pub(crate) fn transform_param_value(
     arg: &crate::skills::ArgMapping,
     allowlist: &Allowlist,
     skill_dir: Option<&Path>,
+    opts: &RunOptions,
     tool_args: &serde_json::Value,
 ) -> Result<String, String> {
-    resolve_value(&s, arg, allowlist, skill_dir, tool_args)
+    resolve_value(&s, arg, allowlist, skill_dir, opts, tool_args)
 }
 
 /// Extract the value of the `Stdin`-kind argument from the tool call JSON,
@@ -141,6 +152,7 @@ pub(crate) fn extract_stdin_content(
     args: &serde_json::Value,
     allowlist: &Allowlist,
     skill_dir: Option<&Path>,
+    opts: &RunOptions,
 ) -> Result<Option<String>, String> {
     let obj = match args.as_object() {
         Some(o) => o,
@@ -186,7 +198,7 @@ pub(crate) fn extract_stdin_content(
                 ));
             }
         };
-        return Ok(Some(transform_param_value(value, arg, allowlist, skill_dir, args)?));
+        return Ok(Some(transform_param_value(value, arg, allowlist, skill_dir, opts, args)?));
     }
     Ok(None)
 }
@@ -199,6 +211,7 @@ pub(crate) fn compute_temp_files(
     args: &serde_json::Value,
     allowlist: &Allowlist,
     skill_dir: Option<&Path>,
+    opts: &RunOptions,
 ) -> Result<(Vec<String>, Vec<std::path::PathBuf>, Vec<(String, String)>), String> {
     let obj = match args.as_object() {
         Some(o) => o,
@@ -236,7 +249,7 @@ pub(crate) fn compute_temp_files(
                 ));
             }
         };
-        let resolved = transform_param_value(value, arg, allowlist, skill_dir, args)?;
+        let resolved = transform_param_value(value, arg, allowlist, skill_dir, opts, args)?;
 
         let temp_dir = std::env::temp_dir();
         let file_name = format!("chai_{}_{}", spec.tool, arg.param_name());
@@ -260,6 +273,7 @@ pub(crate) fn write_temp_files(
     args: &serde_json::Value,
     allowlist: &Allowlist,
     skill_dir: Option<&Path>,
+    opts: &RunOptions,
 ) -> Result<(Vec<String>, Vec<std::path::PathBuf>), String> {
     let obj = match args.as_object() {
         Some(o) => o,
@@ -296,7 +310,7 @@ pub(crate) fn write_temp_files(
                 ));
             }
         };
-        let resolved = transform_param_value(value, arg, allowlist, skill_dir, args)?;
+        let resolved = transform_param_value(value, arg, allowlist, skill_dir, opts, args)?;
 
         // Write the value to a temp file.
         let temp_dir = std::env::temp_dir();
@@ -338,6 +352,8 @@ pub(crate) fn build_argv(
     args: &serde_json::Value,
     allowlist: &Allowlist,
     skill_dir: Option<&Path>,
+    opts: &RunOptions,
+    secrets: Option<&Secrets>,
 ) -> Result<Vec<String>, String> {
     let obj = args
         .as_object()
@@ -379,7 +395,7 @@ pub(crate) fn build_argv(
                     argv.push("--".to_string());
                 }
                 skipped_optional_positional = false;
-                let resolved = transform_param_value(s, arg, allowlist, skill_dir, args)?;
+                let resolved = transform_param_value(s, arg, allowlist, skill_dir, opts, args)?;
                 if arg.split == Some(true) {
                     for part in resolved.split_whitespace() {
                         argv.push(part.to_string());
@@ -399,7 +415,7 @@ pub(crate) fn build_argv(
                         })?;
                         let flag = arg.flag.as_deref().unwrap_or(arg.param_name());
                         argv.push(format_flag(flag));
-                        argv.push(transform_param_value(s, arg, allowlist, skill_dir, args)?);
+                        argv.push(transform_param_value(s, arg, allowlist, skill_dir, opts, args)?);
                     }
                     _ if arg.absent_default.is_some() => {
                         let default = arg.absent_default.as_ref().unwrap();
@@ -411,11 +427,11 @@ pub(crate) fn build_argv(
                         })?;
                         let flag = arg.flag.as_deref().unwrap_or(arg.param_name());
                         argv.push(format_flag(flag));
-                        argv.push(transform_param_value(s, arg, allowlist, skill_dir, args)?);
+                        argv.push(transform_param_value(s, arg, allowlist, skill_dir, opts, args)?);
                     }
                     _ if arg.optional == Some(true) && arg.resolve_command.is_some() => {
                         let flag = arg.flag.as_deref().unwrap_or(arg.param_name());
-                        let resolved = transform_param_value(String::new(), arg, allowlist, skill_dir, args)?;
+                        let resolved = transform_param_value(String::new(), arg, allowlist, skill_dir, opts, args)?;
                         if !resolved.is_empty() {
                             argv.push(format_flag(flag));
                             argv.push(resolved);
@@ -463,7 +479,11 @@ pub(crate) fn build_argv(
             }
             ArgKind::Literal => {
                 if let Some(ref v) = arg.value {
-                    argv.push(v.clone());
+                    // Placeholders stay as written when previewing (no secrets).
+                    match secrets {
+                        Some(secrets) if has_placeholder(v) => argv.push(secrets.resolve(v)?),
+                        _ => argv.push(v.clone()),
+                    }
                 }
             }
         }
@@ -535,7 +555,7 @@ mod tests {
         };
 
         let args = serde_json::json!({ "content": "hello world" });
-        let result = extract_stdin_content(&spec, &args, &Allowlist::new(), None)
+        let result = extract_stdin_content(&spec, &args, &Allowlist::new(), None, &RunOptions::default())
             .expect("should not error");
         assert_eq!(result, Some("hello world".to_string()));
     }
@@ -555,7 +575,7 @@ mod tests {
         };
 
         let args = serde_json::json!({ "path": "/some/file" });
-        let result = extract_stdin_content(&spec, &args, &Allowlist::new(), None);
+        let result = extract_stdin_content(&spec, &args, &Allowlist::new(), None, &RunOptions::default());
         assert!(result.is_err(), "missing required stdin param should error");
         assert!(
             result.unwrap_err().contains("content"),
@@ -578,7 +598,7 @@ mod tests {
         };
 
         let args = serde_json::json!({ "content": null });
-        let result = extract_stdin_content(&spec, &args, &Allowlist::new(), None);
+        let result = extract_stdin_content(&spec, &args, &Allowlist::new(), None, &RunOptions::default());
         assert!(result.is_err(), "null required stdin param should error");
     }
 
@@ -598,7 +618,7 @@ mod tests {
         };
 
         let args = serde_json::json!({ "path": "/some/file" });
-        let result = extract_stdin_content(&spec, &args, &Allowlist::new(), None)
+        let result = extract_stdin_content(&spec, &args, &Allowlist::new(), None, &RunOptions::default())
             .expect("optional missing should not error");
         assert_eq!(result, None, "optional missing stdin should return None");
     }
@@ -643,14 +663,14 @@ mod tests {
         let allowlist = Allowlist::new();
 
         let args = serde_json::json!({ "content": "hello" });
-        let argv = build_argv(&spec, &args, &allowlist, Some(dir.as_path()))
+        let argv = build_argv(&spec, &args, &allowlist, Some(dir.as_path()), &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["--path", "/default/path"],
             "optional flag with resolveCommand should use resolver default when omitted");
 
         let args = serde_json::json!({ "date": "2026-05-28", "content": "hello" });
-        let argv = build_argv(&spec, &args, &allowlist, Some(dir.as_path()))
+        let argv = build_argv(&spec, &args, &allowlist, Some(dir.as_path()), &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["--path", "/resolved/2026-05-28"],
@@ -678,12 +698,12 @@ mod tests {
         let allowlist = Allowlist::new();
 
         let args = serde_json::json!({});
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
         assert!(argv.is_empty());
 
         let args = serde_json::json!({ "optional_flag": "value" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
         assert_eq!(argv, vec!["--opt", "value"]);
     }
@@ -706,7 +726,7 @@ mod tests {
         let allowlist = Allowlist::new();
 
         let args = serde_json::json!({});
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
         assert!(argv.is_empty());
     }
@@ -756,7 +776,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "count": "5" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["-n", "5"],
@@ -780,7 +800,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "path": "./chai" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["--path", "./chai"],
@@ -804,7 +824,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "output": "result.txt" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["--output", "result.txt"],
@@ -843,7 +863,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "count": "5", "oneline": true, "path": "/some/repo" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["-n", "5", "--oneline"],
@@ -868,7 +888,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "message": "Add search endpoint" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["-m", "Add search endpoint"],
@@ -894,7 +914,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({});
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["--continue"]);
@@ -917,7 +937,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({});
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert!(argv.is_empty(), "literal with no value should produce no argv entry");
@@ -942,7 +962,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "paths": "file1.rs file2.rs file3.rs" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["file1.rs", "file2.rs", "file3.rs"]);
@@ -965,7 +985,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "paths": "." });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["."]);
@@ -987,7 +1007,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "query": "hello world" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["hello world"],
@@ -1012,7 +1032,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({});
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["HEAD~1"],
@@ -1037,7 +1057,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "ref": "HEAD~3" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["HEAD~3"],
@@ -1061,7 +1081,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({});
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert!(argv.is_empty(),
@@ -1094,7 +1114,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "force": true, "branch_name": "feat/test" });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         // force should NOT produce an argv entry (subcommandOverride controls
@@ -1121,7 +1141,7 @@ mod tests {
 
         let allowlist = Allowlist::new();
         let args = serde_json::json!({ "verbose": true });
-        let argv = build_argv(&spec, &args, &allowlist, None)
+        let argv = build_argv(&spec, &args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv, vec!["--verbose"]);
@@ -7,7 +7,7 @@
 
 use std::path::Path;
 
-use crate::exec::Allowlist;
+use crate::exec::{Allowlist, RunOptions};
 use crate::skills::{ArgKind, ExecutionSpec};
 
 use super::argv::{json_value_to_string, run_script};
@@ -22,6 +22,7 @@ pub(crate) fn enforce_deny_patterns(
     args: &serde_json::Value,
     allowlist: &Allowlist,
     skill_dir: Option<&Path>,
+    opts: &RunOptions,
     working_dir: Option<&Path>,
 ) -> Result<(), String> {
     let obj = match args.as_object() {
@@ -60,7 +61,7 @@ pub(crate) fn enforce_deny_patterns(
         //    is omitted from git push, so resolve the current branch).
         let effective_value = if always_resolve {
             if let Some(ref deny_cmd) = arg.deny_resolve_command {
-                resolve_deny_value(deny_cmd, allowlist, skill_dir, working_dir)?
+                resolve_deny_value(deny_cmd, allowlist, skill_dir, opts, working_dir)?
             } else {
                 return Err(format!(
                     "denyAlwaysResolve is set on param '{}' but no denyResolveCommand is configured",
@@ -71,12 +72,12 @@ pub(crate) fn enforce_deny_patterns(
             if !val.is_empty() {
                 val.clone()
             } else if let Some(ref deny_cmd) = arg.deny_resolve_command {
-                resolve_deny_value(deny_cmd, allowlist, skill_dir, working_dir)?
+                resolve_deny_value(deny_cmd, allowlist, skill_dir, opts, working_dir)?
             } else {
                 continue // Empty value, no denyResolveCommand — skip.
             }
         } else if let Some(ref deny_cmd) = arg.deny_resolve_command {
-            resolve_deny_value(deny_cmd, allowlist, skill_dir, working_dir)?
+            resolve_deny_value(deny_cmd, allowlist, skill_dir, opts, working_dir)?
         } else {
             continue // Param absent, no denyResolveCommand — skip.
         };
@@ -106,6 +107,7 @@ fn resolve_deny_value(
     cmd: &crate::skills::ResolveCommandSpec,
     allowlist: &Allowlist,
     skill_dir: Option<&Path>,
+    opts: &RunOptions,
     working_dir: Option<&Path>,
 ) -> Result<String, String> {
     // Substitute $workingDir in args with the resolved working directory.
@@ -124,7 +126,7 @@ fn resolve_deny_value(
         .collect();
 
     if let (Some(dir), Some(ref script_name)) = (skill_dir, &cmd.script) {
-        if let Ok(out) = run_script(dir, script_name, &argv) {
+        if let Ok(out) = run_script(dir, script_name, &argv, opts) {
             let s = out.trim().to_string();
             return if s.is_empty() {
                 Err("denyResolveCommand returned empty value".to_string())
@@ -136,7 +138,11 @@ fn resolve_deny_value(
     }
 
     if let (Some(ref binary), Some(ref subcommand)) = (&cmd.binary, &cmd.subcommand) {
-        match allowlist.run(binary, subcommand, &argv, working_dir) {
+        let opts = RunOptions {
+            working_dir,
+            ..*opts
+        };
+        match allowlist.run_with_options(binary, subcommand, &argv, &opts).map(|(_, out)| out) {
             Ok(out) => {
                 let s = out.trim().to_string();
                 return if s.is_empty() {
@@ -7,8 +7,8 @@
 //! to the sandbox root, side-read augmentation (append a nearby file's
 //! contents to the tool result), parameter-based execution routing via
 //! `paramCondition` (multiple specs per tool name), and schema-enforced
-//! validation (the tool schema is the contract — undeclared parameters and
-//! type mismatches are rejected before execution).
+//! validation (the tool schema is the contract — undeclared parameters, type
+//! mismatches, and violated constraints are rejected before execution).
 
 mod argv;
 mod deny;
@@ -17,15 +17,19 @@ mod sandbox;
 mod validate;
 
 // Re-export for use in dry_run and execute.
-use validate::check_type;
+use validate::{escape_pointer, Patterns};
+pub(crate) use validate::SchemaValidator;
 
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 use serde::Serialize;
 
-use crate::agent::ToolExecutor;
-use crate::exec::{Allowlist, WriteSandbox};
+use crate::agent::{ToolCallContext, ToolExecutor};
+use crate::config::{ExecConfig, KernelSandbox, SandboxConfig};
+use crate::exec::{Allowlist, EnvPolicy, KernelPolicy, RunOptions, SandboxQuota, WriteSandbox};
+use crate::redaction::Redactor;
+use crate::secrets::Secrets;
 use crate::skills::{ArgKind, ToolDescriptor, ToolSpec};
 use crate::tools::post_process::run_post_process;
 
@@ -53,6 +57,8 @@ pub struct DryRunResult {
     pub argv: Vec<String>,
     /// Resolved working directory for the process, if any.
     pub working_dir: Option<String>,
+    /// Environment the process would get.
+    pub env: EnvPreview,
     /// Content that would be piped to stdin, if any.
     pub stdin_content: Option<String>,
     /// Temp files that would be written (path, content), if any.
@@ -67,6 +73,26 @@ pub struct DryRunResult {
     pub post_pipeline: PostPipelinePreview,
 }
 
+/// Environment of a previewed command, by variable name only: previews reach the model through
+/// `skills_dry_run`, so values stay out of them.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct EnvPreview {
+    /// Gateway variables passed through: the baseline and `env.pass`, when set in the gateway.
+    pub passed: Vec<String>,
+    /// Variables set to fixed values from `env.set` (spec over profile).
+    pub set: Vec<String>,
+}
+
+impl EnvPreview {
+    fn of(env: &EnvPolicy) -> Self {
+        Self {
+            passed: env.passed(),
+            set: env.set.keys().cloned().collect(),
+        }
+    }
+}
+
 /// A temp file that would be written during execution.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -229,6 +255,21 @@ struct ExecEntry {
     allowlist: Allowlist,
     spec: crate::skills::ExecutionSpec,
     skill_dir: Option<std::path::PathBuf>,
+    /// The spec's `redact` rules, compiled once. An invalid rule (the skill loader rejects
+    /// those) fails every call that would apply it.
+    redact: Result<Redactor, String>,
+}
+
+impl ExecEntry {
+    fn new(allowlist: Allowlist, spec: crate::skills::ExecutionSpec, skill_dir: Option<std::path::PathBuf>) -> Self {
+        let redact = Redactor::compile(spec.redact.as_deref().unwrap_or_default());
+        Self {
+            allowlist,
+            spec,
+            skill_dir,
+            redact,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -242,6 +283,20 @@ pub struct GenericToolExecutor {
     /// this session. Shared via Arc so clones of the executor share state.
     /// Grows monotonically; no eviction (sessions are few relative to memory).
     side_read_seen: Arc<Mutex<HashMap<String, HashSet<String>>>>,
+    /// Profile-wide timeout and output cap for commands whose spec sets none.
+    exec: ExecConfig,
+    /// Sandbox settings when spawned commands are confined in the kernel (`sandbox.kernel`);
+    /// `None` = path checks only.
+    kernel: Option<SandboxConfig>,
+    /// Sandbox disk quota checked before, and re-measured after, tools that may write.
+    quota: Option<SandboxQuota>,
+    /// Values for `{{secret:name}}` placeholders; also redacted from results and errors.
+    secrets: Arc<Secrets>,
+    /// Profile redaction rules, applied with the spec's `redact` rules to calls whose
+    /// [`ToolCallContext::redact_output`] is set.
+    redaction: Arc<Redactor>,
+    /// `pattern` regexes of the tool schemas, compiled once when the descriptors load.
+    patterns: Arc<Patterns>,
 }
 
 impl GenericToolExecutor {
@@ -256,15 +311,12 @@ impl GenericToolExecutor {
         let dir_map: HashMap<&String, &std::path::PathBuf> =
             skill_dirs.iter().map(|(n, p)| (n, p)).collect();
         let mut map: HashMap<String, (Vec<ExecEntry>, Option<ToolSpec>)> = HashMap::new();
+        let mut patterns = Patterns::default();
         for (skill_name, desc) in descriptors {
             let allowlist = desc.to_allowlist();
             let skill_dir = dir_map.get(skill_name).cloned().cloned();
             for spec in &desc.execution {
-                let entry = ExecEntry {
-                    allowlist: allowlist.clone(),
-                    spec: spec.clone(),
-                    skill_dir: skill_dir.clone(),
-                };
+                let entry = ExecEntry::new(allowlist.clone(), spec.clone(), skill_dir.clone());
                 map.entry(spec.tool.clone())
                     .or_insert_with(|| (Vec::new(), None))
                     .0
@@ -272,6 +324,10 @@ impl GenericToolExecutor {
             }
             // Index tool specs by name for schema validation.
             for tool_spec in &desc.tools {
+                // An invalid pattern is reported again by each call that reaches it.
+                if let Err(e) = patterns.add_schema(&tool_spec.parameters) {
+                    log::warn!("skill '{}' tool '{}': {}", skill_name, tool_spec.name, e);
+                }
                 if let Some((_, schema)) = map.get_mut(&tool_spec.name) {
                     *schema = Some(tool_spec.clone());
                 } else {
@@ -285,9 +341,47 @@ impl GenericToolExecutor {
             map,
             sandbox,
             side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::new(patterns),
         }
     }
 
+    /// Use the profile's `exec` bounds (timeout, output cap) for specs that do not set their own.
+    pub fn with_exec_config(mut self, exec: ExecConfig) -> Self {
+        self.exec = exec;
+        self
+    }
+
+    /// Confine spawned commands in the kernel per `sandbox.kernel`. The policy is built from the
+    /// sandbox's current roots for each command, with the command's skill directory readable.
+    pub fn with_kernel_sandbox(mut self, config: &SandboxConfig) -> Self {
+        self.kernel = (config.kernel != KernelSandbox::None).then(|| config.clone());
+        self
+    }
+
+    /// Resolve `{{secret:name}}` placeholders from `secrets` and redact their values from results.
+    pub fn with_secrets(mut self, secrets: Arc<Secrets>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Profile redaction rules. For calls that ask for redaction, they and the spec's `redact`
+    /// rules apply to the tool output after hints and before truncation.
+    pub fn with_redaction(mut self, redaction: Arc<Redactor>) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Refuse tools that may write while `quota` is exceeded, and re-measure it after they run.
+    pub fn with_quota(mut self, quota: Option<SandboxQuota>) -> Self {
+        self.quota = quota;
+        self
+    }
+
     /// Return true if this executor handles the given tool name.
     pub fn has_tool(&self, name: &str) -> bool {
         self.map.contains_key(name)
@@ -423,8 +517,9 @@ impl GenericToolExecutor {
     }
 
     /// Validate tool call arguments against the tool's parameter schema.
-    /// The schema is the contract: undeclared parameters and type mismatches
-    /// are rejected before execution.
+    /// The schema is the contract: undeclared parameters, type mismatches, and
+    /// violated constraints are rejected before execution. Constraint errors
+    /// name the offending value by JSON Pointer (`/files/2/path: must match ^[a-z]`).
     fn validate_schema(
         &self,
         name: &str,
@@ -472,19 +567,22 @@ impl GenericToolExecutor {
             }
         }
 
-        // Check for type mismatches on declared parameters.
+        // Check declared parameters against their schemas (type, constraints,
+        // nested items and properties).
         if let Some(props) = properties {
             for (key, value) in obj {
                 if value.is_null() {
-                    continue; // null is treated as absent for type checking.
+                    continue; // null is treated as absent for validation.
                 }
                 if let Some(param_schema) = props.get(key) {
-                    if let Some(type_err) = check_type(param_schema, value) {
-                        return Err(format!(
-                            "tool {}: parameter '{}' type mismatch: {}",
-                            name, key, type_err
-                        ));
-                    }
+                    let path = format!("/{}", escape_pointer(key));
+                    let validator = SchemaValidator {
+                        reject_undeclared: true,
+                        patterns: Some(&self.patterns),
+                    };
+                    validator
+                        .validate(param_schema, value, &path)
+                        .map_err(|e| format!("tool {}: {}", name, e))?;
                 }
             }
         }
@@ -509,8 +607,8 @@ impl GenericToolExecutor {
     ///
     /// Walks the execution pipeline up to (but not including) the actual command
     /// execution. Returns a structured preview showing sandbox validation, deny
-    /// pattern checks, argv construction, stdin content, temp files, and the
-    /// post-execution pipeline status.
+    /// pattern checks, argv construction, environment, stdin content, temp files,
+    /// and the post-execution pipeline status.
     ///
     /// Sandbox validation failures short-circuit the pipeline (nothing downstream
     /// can be computed without valid paths). Deny pattern failures do **not**
@@ -532,10 +630,13 @@ impl GenericToolExecutor {
 
         let entry = self.resolve_entry(name, args)?;
         let (allowlist, spec, skill_dir) = (&entry.allowlist, &entry.spec, &entry.skill_dir);
+        let env = EnvPreview::of(&self.exec.env_for(spec.env.as_ref()));
+        let kernel = self.kernel_policy_for(skill_dir.as_deref());
+        let scripts = self.script_options(spec, kernel.as_ref());
 
         // Step 1: Sandbox validation
         let (working_dir, canonical_paths) =
-            match sandbox::validate_write_paths(spec, args, allowlist, skill_dir.as_deref(), &self.sandbox) {
+            match sandbox::validate_write_paths(spec, args, allowlist, skill_dir.as_deref(), &scripts, &self.sandbox) {
                 Ok(result) => result,
                 Err(e) => {
                     return Ok(DryRunResult {
@@ -545,6 +646,7 @@ impl GenericToolExecutor {
                         binary_wrapper: spec.binary_wrapper.clone(),
                         argv: Vec::new(),
                         working_dir: None,
+                        env,
                         stdin_content: None,
                         temp_files: Vec::new(),
                         sandbox_validation: StepResult {
@@ -585,7 +687,7 @@ impl GenericToolExecutor {
         // Default CWD to sandbox root when no working directory was determined
         let working_dir = match (working_dir, &self.sandbox) {
             (Some(dir), _) => Some(dir),
-            (None, Some(sb)) => sb.roots().first().cloned(),
+            (None, Some(sb)) => sb.base().map(std::path::Path::to_path_buf),
             (None, None) => None,
         };
 
@@ -603,8 +705,8 @@ impl GenericToolExecutor {
             effective_args,
             allowlist,
             skill_dir.as_deref(),
-            working_dir.as_deref(),
-        ) {
+            &scripts,
+            working_dir.as_deref(),) {
             Ok(()) => StepResult {
                 status: "pass".to_string(),
                 error: None,
@@ -623,15 +725,15 @@ impl GenericToolExecutor {
         // are still valuable for the author.
 
         // Step 3: Build argv
-        let mut argv = argv::build_argv(spec, effective_args, allowlist, skill_dir.as_deref())?;
+        let mut argv = argv::build_argv(spec, effective_args, allowlist, skill_dir.as_deref(), &scripts, None)?;
 
         // Step 4: Extract stdin content
         let stdin_content =
-            argv::extract_stdin_content(spec, effective_args, allowlist, skill_dir.as_deref())?;
+            argv::extract_stdin_content(spec, effective_args, allowlist, skill_dir.as_deref(), &scripts)?;
 
         // Step 5: Compute temp files (without writing)
         let (temp_argv, _temp_paths, temp_file_details) =
-            argv::compute_temp_files(spec, effective_args, allowlist, skill_dir.as_deref())?;
+            argv::compute_temp_files(spec, effective_args, allowlist, skill_dir.as_deref(), &scripts)?;
         argv.extend(temp_argv);
 
         let temp_files: Vec<TempFilePreview> = temp_file_details
@@ -675,6 +777,7 @@ impl GenericToolExecutor {
                         allowlist,
                         skill_dir.as_deref(),
                         &pp_args,
+                        &scripts,
                     );
                 }
                 let post_processed = if has_post_process {
@@ -735,6 +838,7 @@ impl GenericToolExecutor {
             binary_wrapper: spec.binary_wrapper.clone(),
             argv,
             working_dir: working_dir.map(|d| d.to_string_lossy().into_owned()),
+            env,
             stdin_content,
             temp_files,
             sandbox_validation,
@@ -755,15 +859,67 @@ impl GenericToolExecutor {
 }
 
 impl ToolExecutor for GenericToolExecutor {
-    fn execute(&self, name: &str, args: &serde_json::Value, session_id: Option<&str>) -> Result<String, String> {
-        // Schema validation (Decision 2: the schema is the contract).
-        self.validate_schema(name, args)?;
+    fn execute(&self, name: &str, args: &serde_json::Value, ctx: ToolCallContext<'_>) -> Result<String, String> {
+        // Secret values never reach the model, history, or events.
+        self.run_tool(name, args, ctx)
+            .map(|result| self.secrets.redact(&result))
+            .map_err(|e| self.secrets.redact(&e))
+    }
 
+    fn working_dir(&self, name: &str, args: &serde_json::Value) -> Result<Option<std::path::PathBuf>, String> {
+        self.validate_schema(name, args)?;
         let entry = self.resolve_entry(name, args)?;
-        let (allowlist, spec, skill_dir) = (&entry.allowlist, &entry.spec, &entry.skill_dir);
+        self.entry_working_dir(entry, args).map(|(working_dir, _)| working_dir)
+    }
 
-        let (working_dir, canonical_paths) =
-            sandbox::validate_write_paths(spec, args, allowlist, skill_dir.as_deref(), &self.sandbox)?;
+    fn kernel_policy(&self) -> Option<KernelPolicy> {
+        self.kernel_policy_for(None)
+    }
+}
+
+impl GenericToolExecutor {
+    /// Kernel policy for the commands and scripts of the skill in `skill_dir` (made readable),
+    /// when `sandbox.kernel` is on; built from the sandbox's current roots.
+    fn kernel_policy_for(&self, skill_dir: Option<&std::path::Path>) -> Option<KernelPolicy> {
+        match (&self.kernel, &self.sandbox) {
+            (Some(config), Some(sb)) => config
+                .kernel_policy(sb)
+                .map(|p| p.with_readable(skill_dir.map(std::path::Path::to_path_buf))),
+            _ => None,
+        }
+    }
+
+    /// How the spec's own scripts and commands (`resolveCommand`, `denyResolveCommand`,
+    /// `postProcess`) run: bounded by the spec's limits, else the profile's `exec` limits, and
+    /// confined by `kernel` like the tool's command.
+    fn script_options<'a>(
+        &self,
+        spec: &crate::skills::ExecutionSpec,
+        kernel: Option<&'a KernelPolicy>,
+    ) -> RunOptions<'a> {
+        RunOptions {
+            limits: self.exec.limits_for(spec.timeout_secs, spec.max_output_bytes, spec.limits.as_ref()),
+            kernel,
+            ..Default::default()
+        }
+    }
+
+    /// Validate `args`' path parameters against the sandbox and return the command's working
+    /// directory with the canonical paths to substitute into the arguments.
+    fn entry_working_dir(
+        &self,
+        entry: &ExecEntry,
+        args: &serde_json::Value,
+    ) -> Result<(Option<std::path::PathBuf>, HashMap<String, String>), String> {
+        let kernel = self.kernel_policy_for(entry.skill_dir.as_deref());
+        let scripts = self.script_options(&entry.spec, kernel.as_ref());
+        let (working_dir, canonical_paths) = sandbox::validate_write_paths(
+            &entry.spec,
+            args,
+            &entry.allowlist,
+            entry.skill_dir.as_deref(),
+            &scripts,
+            &self.sandbox,)?;
 
         // Default CWD to sandbox root when no working directory was determined
         // from path-annotated parameters. This ensures that relative paths in
@@ -771,9 +927,29 @@ impl ToolExecutor for GenericToolExecutor {
         // relative to the gateway's launch directory.
         let working_dir = match (working_dir, &self.sandbox) {
             (Some(dir), _) => Some(dir),
-            (None, Some(sb)) => sb.roots().first().cloned(),
+            (None, Some(sb)) => sb.base().map(std::path::Path::to_path_buf),
             (None, None) => None,
         };
+        Ok((working_dir, canonical_paths))
+    }
+
+    /// Run a tool call: validate, build the command, run it, and apply the post-execution
+    /// pipeline. [`ToolExecutor::execute`] redacts secrets from what this returns.
+    fn run_tool(&self, name: &str, args: &serde_json::Value, ctx: ToolCallContext<'_>) -> Result<String, String> {
+        // Schema validation (Decision 2: the schema is the contract).
+        self.validate_schema(name, args)?;
+
+        let entry = self.resolve_entry(name, args)?;
+        let (allowlist, spec, skill_dir) = (&entry.allowlist, &entry.spec, &entry.skill_dir);
+
+        let quota = self.quota.as_ref().filter(|_| spec.may_write());
+        if let Some(quota) = quota {
+            quota.check()?;
+        }
+
+        let (working_dir, canonical_paths) = self.entry_working_dir(entry, args)?;
+        let kernel = self.kernel_policy_for(skill_dir.as_deref());
+        let scripts = self.script_options(spec, kernel.as_ref());
 
         let resolved_args;
         let effective_args = if canonical_paths.is_empty() {
@@ -785,49 +961,59 @@ impl ToolExecutor for GenericToolExecutor {
 
         sandbox::ensure_write_path_parents(spec, &canonical_paths)?;
 
-        deny::enforce_deny_patterns(spec, effective_args, allowlist, skill_dir.as_deref(), working_dir.as_deref())?;
+        deny::enforce_deny_patterns(spec, effective_args, allowlist, skill_dir.as_deref(), &scripts, working_dir.as_deref())?;
 
-        let mut argv = argv::build_argv(spec, effective_args, allowlist, skill_dir.as_deref())?;
+        let mut argv = argv::build_argv(spec, effective_args, allowlist, skill_dir.as_deref(), &scripts, Some(&self.secrets))?;
+        let env = self.secrets.resolve_env(&self.exec.env_for(spec.env.as_ref()))?;
 
-        let stdin_content = argv::extract_stdin_content(spec, effective_args, allowlist, skill_dir.as_deref())?;
+        let stdin_content = argv::extract_stdin_content(spec, effective_args, allowlist, skill_dir.as_deref(), &scripts)?;
 
-        let (temp_argv, temp_paths) = argv::write_temp_files(spec, effective_args, allowlist, skill_dir.as_deref())?;
+        let (temp_argv, temp_paths) = argv::write_temp_files(spec, effective_args, allowlist, skill_dir.as_deref(), &scripts)?;
         argv.extend(temp_argv);
 
         let effective_subcommand = resolve_subcommand(spec, effective_args);
         let success_codes = spec.success_exit_codes.as_deref().unwrap_or(&[]);
         let binary_wrapper = spec.binary_wrapper.as_deref();
-        let result = if let Some(ref content) = stdin_content {
-            allowlist.run_with_stdin_with_codes_and_exit(
-                &spec.binary,
-                effective_subcommand,
-                &argv,
-                working_dir.as_deref(),
-                content.as_bytes(),
-                success_codes,
+        let result = allowlist.run_with_options(
+            &spec.binary,
+            effective_subcommand,
+            &argv,
+            &RunOptions {
+                working_dir: working_dir.as_deref(),
+                stdin: stdin_content.as_deref().map(str::as_bytes),
+                success_exit_codes: success_codes,
                 binary_wrapper,
-            )
-        } else {
-            allowlist.run_with_codes_and_exit(
-                &spec.binary,
-                effective_subcommand,
-                &argv,
-                working_dir.as_deref(),
-                success_codes,
-                binary_wrapper,
-            )
-        };
+                limits: self.exec.limits_for(spec.timeout_secs, spec.max_output_bytes, spec.limits.as_ref()),
+                kernel: kernel.as_ref(),
+                env: Some(&env),
+            },
+        );
 
         // Clean up temp files regardless of execution success or failure.
         for path in &temp_paths {
             let _ = std::fs::remove_file(path);
         }
+        if let Some(quota) = quota {
+            quota.record_write();
+        }
 
-        let (exit_code, output) = result?;
+        // Profile and spec redaction rules, for calls whose output goes to a provider in scope.
+        let redactors = if ctx.redact_output {
+            Some((&self.redaction, entry.redact.as_ref().map_err(|e| format!("tool {}: {}", name, e))?))
+        } else {
+            None
+        };
+        let redact = |text: String| match redactors {
+            Some((profile, spec_rules)) => spec_rules.redact(&profile.redact(&text)),
+            None => text,
+        };
+
+        // A failing command's error carries its stdout and stderr.
+        let (exit_code, output) = result.map_err(redact)?;
 
         let result = if let Some(ref pp) = spec.post_process {
             let pp_args = augment_with_absent_defaults(spec, effective_args);
-            run_post_process(pp, exit_code, &output, allowlist, skill_dir.as_deref(), &pp_args)
+            run_post_process(pp, exit_code, &output, allowlist, skill_dir.as_deref(), &pp_args, &scripts)
         } else {
             output
         };
@@ -839,6 +1025,8 @@ impl ToolExecutor for GenericToolExecutor {
             result
         };
 
+        let result = redact(result);
+
         let result = if let Some(max_lines) = spec.max_output_lines {
             output::truncate_output(&result, max_lines, spec.truncation_hint.as_deref())
         } else {
@@ -846,7 +1034,7 @@ impl ToolExecutor for GenericToolExecutor {
         };
 
         if let Some(ref sr) = spec.side_read {
-            Ok(output::apply_side_read(sr, effective_args, &result, session_id, &self.side_read_seen))
+            Ok(output::apply_side_read(sr, effective_args, &result, ctx.session_id, &self.side_read_seen, redact))
         } else {
             Ok(result)
         }
@@ -1143,17 +1331,17 @@ mod tests {
     // --- resolve_entry partial match hint tests ---
 
     fn make_entry(tool: &str, subcommand: &str, param_condition: Option<crate::skills::ParamConditionSpec>) -> ExecEntry {
-        ExecEntry {
-            allowlist: Allowlist::default(),
-            spec: ExecutionSpec {
+        ExecEntry::new(
+            Allowlist::default(),
+            ExecutionSpec {
                 tool: tool.to_string(),
                 binary: "test".to_string(),
                 subcommand: subcommand.to_string(),
                 param_condition: param_condition,
                 ..Default::default()
             },
-            skill_dir: None,
-        }
+            None,
+        )
     }
 
     #[test]
@@ -1188,6 +1376,12 @@ mod tests {
             .collect(),
             sandbox: None,
             side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
         };
         // param_a provided but param_b missing
         let args = serde_json::json!({ "path": "foo.txt", "param_a": 5 });
@@ -1226,6 +1420,12 @@ mod tests {
             .collect(),
             sandbox: None,
             side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
         };
         // param_b provided but param_a missing
         let args = serde_json::json!({ "path": "foo.txt", "param_b": "old" });
@@ -1265,6 +1465,12 @@ mod tests {
             .collect(),
             sandbox: None,
             side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
         };
         // No mode params at all — no partial match
         let args = serde_json::json!({ "path": "foo.txt" });
@@ -1303,6 +1509,12 @@ mod tests {
             .collect(),
             sandbox: None,
             side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
         };
         // Both continue and abort provided — both conditions match
         let args = serde_json::json!({ "continue": true, "abort": true, "repo": "chai" });
@@ -1343,6 +1555,12 @@ mod tests {
             .collect(),
             sandbox: None,
             side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
         };
         // Neither flag_a nor flag_b provided — both absent-conditions match
         let args = serde_json::json!({ "path": "foo.txt" });
@@ -1383,6 +1601,12 @@ mod tests {
             .collect(),
             sandbox: None,
             side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
         };
         // flag_a provided, flag_b absent — both conditions match
         let args = serde_json::json!({ "flag_a": true, "path": "foo.txt" });
@@ -1392,4 +1616,314 @@ mod tests {
         assert!(err.contains("present: [flag_a]"), "should list 'flag_a' as present matching param: {}", err);
         assert!(err.contains("absent: [flag_b]"), "should list 'flag_b' as absent matching param: {}", err);
     }
+
+    fn schema_executor(parameters: serde_json::Value) -> GenericToolExecutor {
+        let spec = ToolSpec {
+            name: "files_write_many".to_string(),
+            description: None,
+            parameters,
+        };
+        let entries = vec![make_entry("files_write_many", "write", None)];
+        GenericToolExecutor {
+            map: vec![("files_write_many".to_string(), (entries, Some(spec)))]
+                .into_iter()
+                .collect(),
+            sandbox: None,
+            side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
+        }
+    }
+
+    #[test]
+    fn validate_schema_reports_constraint_violation_with_pointer() {
+        let executor = schema_executor(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "files": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": { "type": "string", "pattern": "^[a-z]" },
+                            "mode": { "type": "string", "enum": ["create", "overwrite"] }
+                        },
+                        "required": ["path"]
+                    }
+                },
+                "count": { "type": "integer", "minimum": 1 }
+            },
+            "required": ["files"]
+        }));
+
+        let ok = serde_json::json!({ "files": [{ "path": "a.md", "mode": "create" }], "count": null });
+        assert!(executor.validate_schema("files_write_many", &ok).is_ok());
+
+        let bad_path = serde_json::json!({ "files": [{ "path": "a" }, { "path": "b" }, { "path": "/etc" }] });
+        assert_eq!(
+            executor.validate_schema("files_write_many", &bad_path).unwrap_err(),
+            "tool files_write_many: /files/2/path: must match ^[a-z]"
+        );
+
+        let bad_enum = serde_json::json!({ "files": [{ "path": "a", "mode": "append" }] });
+        assert!(executor
+            .validate_schema("files_write_many", &bad_enum)
+            .unwrap_err()
+            .contains("/files/0/mode: must be one of"));
+
+        let bad_count = serde_json::json!({ "files": [], "count": 0 });
+        assert_eq!(
+            executor.validate_schema("files_write_many", &bad_count).unwrap_err(),
+            "tool files_write_many: /count: must be >= 1"
+        );
+
+        let bad_type = serde_json::json!({ "files": "a.md" });
+        assert_eq!(
+            executor.validate_schema("files_write_many", &bad_type).unwrap_err(),
+            "tool files_write_many: /files: expected array, got string"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn secrets_resolve_before_spawn_and_are_redacted_from_results() {
+        let mut allowlist = Allowlist::new();
+        allowlist.allow("sh", vec!["-c"]);
+        let spec = |script: &str| ExecutionSpec {
+            tool: "echo_secret".to_string(),
+            binary: "sh".to_string(),
+            subcommand: "-c".to_string(),
+            args: vec![ArgMapping {
+                kind: ArgKind::Literal,
+                value: Some(script.to_string()),
+                ..Default::default()
+            }],
+            env: Some(EnvPolicy {
+                pass: vec![],
+                set: [("API_KEY".to_string(), "{{secret:api_key}}".to_string())].into(),
+            }),
+            ..Default::default()
+        };
+        let executor = |script: &str| GenericToolExecutor {
+            map: [(
+                "echo_secret".to_string(),
+                (
+                    vec![ExecEntry::new(allowlist.clone(), spec(script), None)],
+                    None,
+                ),
+            )]
+            .into(),
+            sandbox: None,
+            side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::new(Secrets::new(
+                [("api_key".to_string(), "sk-test-0123456789".to_string())].into(),
+            )),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
+        };
+        let args = serde_json::json!({});
+
+        let exec = executor("echo arg={{secret:api_key}} env=$API_KEY");
+        let out = exec.execute("echo_secret", &args, ToolCallContext::default()).unwrap();
+        assert_eq!(out.trim(), "arg=[REDACTED:api_key] env=[REDACTED:api_key]");
+
+        // Errors (here a failing exit with the secret in stderr) are redacted too.
+        let failing = executor("echo $API_KEY >&2; exit 3");
+        let err = failing.execute("echo_secret", &args, ToolCallContext::default()).unwrap_err();
+        assert!(err.contains("[REDACTED:api_key]") && !err.contains("sk-test"), "{}", err);
+
+        // Previews keep the placeholder.
+        let preview = exec.dry_run("echo_secret", &args, None).unwrap();
+        assert_eq!(preview.argv, vec!["echo arg={{secret:api_key}} env=$API_KEY"]);
+        assert_eq!(preview.env.set, vec!["API_KEY"]);
+
+        let unknown = executor("echo {{secret:other}}");
+        let err = unknown.execute("echo_secret", &args, ToolCallContext::default()).unwrap_err();
+        assert_eq!(err, "unknown secret 'other' (not in secrets.json)");
+    }
+
+    #[test]
+    fn working_dir_matches_the_calls_validated_directory() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("repo")).unwrap();
+        let mut allowlist = Allowlist::new();
+        allowlist.allow("git", vec!["status"]);
+        let executor = GenericToolExecutor {
+            map: [(
+                "git_status".to_string(),
+                (
+                    vec![ExecEntry::new(
+                        allowlist,
+                        ExecutionSpec {
+                            tool: "git_status".to_string(),
+                            binary: "git".to_string(),
+                            subcommand: "status".to_string(),
+                            args: vec![ArgMapping {
+                                param: Some("path".to_string()),
+                                kind: ArgKind::WorkingDir,
+                                optional: Some(true),
+                                ..Default::default()
+                            }],
+                            ..Default::default()
+                        },
+                        None,
+                    )],
+                    None,
+                ),
+            )]
+            .into(),
+            sandbox: Some(WriteSandbox::new(&root)),
+            side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
+        };
+        let dir = |args: serde_json::Value| executor.working_dir("git_status", &args);
+        assert_eq!(dir(serde_json::json!({ "path": "repo" })), Ok(Some(root.join("repo"))));
+        assert_eq!(dir(serde_json::json!({})), Ok(Some(root.clone())));
+        assert!(dir(serde_json::json!({ "path": "/etc" })).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn redaction_applies_profile_and_spec_rules_before_truncation() {
+        use crate::redaction::RedactionRule;
+        let mut allowlist = Allowlist::new();
+        allowlist.allow("sh", vec!["-c"]);
+        let spec = |redact: Vec<RedactionRule>| ExecutionSpec {
+            tool: "log".to_string(),
+            binary: "sh".to_string(),
+            subcommand: "-c".to_string(),
+            args: vec![ArgMapping {
+                kind: ArgKind::Literal,
+                value: Some("echo 'TCK-42 by dev@example.com'; echo second".to_string()),
+                ..Default::default()
+            }],
+            max_output_lines: Some(1),
+            redact: Some(redact),
+            ..Default::default()
+        };
+        let executor = |redact: Vec<RedactionRule>| GenericToolExecutor {
+            map: [(
+                "log".to_string(),
+                (
+                    vec![ExecEntry::new(allowlist.clone(), spec(redact), None)],
+                    None,
+                ),
+            )]
+            .into(),
+            sandbox: None,
+            side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::default(),
+            patterns: Arc::default(),
+        };
+        let ticket = RedactionRule::Pattern {
+            name: "ticket".to_string(),
+            pattern: "TCK-[0-9]+".to_string(),
+            replacement: None,
+        };
+        let profile = Arc::new(Redactor::compile(&[RedactionRule::Builtin("email".to_string())]).unwrap());
+        let args = serde_json::json!({});
+        let redacted = ToolCallContext {
+            session_id: None,
+            redact_output: true,
+        };
+
+        let exec = executor(vec![ticket]).with_redaction(profile);
+        let out = exec.execute("log", &args, redacted).unwrap();
+        assert!(out.starts_with("[REDACTED:ticket] by [REDACTED:email]\n"), "{}", out);
+        assert!(!out.contains("second"));
+
+        // A call of a turn whose provider is out of scope gets neither profile nor spec rules.
+        let out = exec.execute("log", &args, ToolCallContext::default()).unwrap();
+        assert!(out.starts_with("TCK-42 by dev@example.com"), "{}", out);
+
+        let bad = RedactionRule::Builtin("phone".to_string());
+        let err = executor(vec![bad]).execute("log", &args, redacted).unwrap_err();
+        assert!(err.starts_with("tool log: unknown built-in redaction rule"), "{}", err);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn redaction_covers_command_errors_and_side_reads() {
+        use crate::redaction::RedactionRule;
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("NOTES.md"), "owner: dev@example.com\n").unwrap();
+        let mut allowlist = Allowlist::new();
+        allowlist.allow("sh", vec!["-c"]);
+        let sh = |tool: &str, script: &str| ExecutionSpec {
+            tool: tool.to_string(),
+            binary: "sh".to_string(),
+            subcommand: "-c".to_string(),
+            args: vec![
+                ArgMapping {
+                    kind: ArgKind::Literal,
+                    value: Some(script.to_string()),
+                    ..Default::default()
+                },
+                ArgMapping {
+                    param: Some("path".to_string()),
+                    optional: Some(true),
+                    unsafe_path: Some(true),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let side_read = ExecutionSpec {
+            side_read: Some(crate::skills::SideReadSpec {
+                path_param: "path".to_string(),
+                filename: "NOTES.md".to_string(),
+                label: None,
+                once_per_session: None,
+            }),
+            ..sh("list", "echo listing")
+        };
+        let exec = GenericToolExecutor {
+            map: [
+                ("fail", sh("fail", "echo 'denied for dev@example.com' >&2; exit 3")),
+                ("list", side_read),
+            ]
+            .into_iter()
+            .map(|(name, spec)| (name.to_string(), (vec![ExecEntry::new(allowlist.clone(), spec, None)], None)))
+            .collect(),
+            sandbox: None,
+            side_read_seen: Arc::new(Mutex::new(HashMap::new())),
+            exec: ExecConfig::default(),
+            kernel: None,
+            quota: None,
+            secrets: Arc::default(),
+            redaction: Arc::new(Redactor::compile(&[RedactionRule::Builtin("email".to_string())]).unwrap()),
+            patterns: Arc::default(),
+        };
+        let redacted = ToolCallContext {
+            session_id: None,
+            redact_output: true,
+        };
+
+        let err = exec.execute("fail", &serde_json::json!({}), redacted).unwrap_err();
+        assert!(err.contains("denied for [REDACTED:email]"), "{}", err);
+        let err = exec.execute("fail", &serde_json::json!({}), ToolCallContext::default()).unwrap_err();
+        assert!(err.contains("dev@example.com"), "{}", err);
+
+        let args = serde_json::json!({ "path": dir.path().to_string_lossy() });
+        let out = exec.execute("list", &args, redacted).unwrap();
+        assert!(out.contains("owner: [REDACTED:email]"), "{}", out);
+        assert!(!out.contains("dev@example.com"), "{}", out);
+    }
 }
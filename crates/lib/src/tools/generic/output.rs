@@ -10,13 +10,14 @@ use crate::skills::{HintCondition, SideReadSpec};
 ///
 /// When `once_per_session` is true and a session ID is provided, the append
 /// is skipped for any (session, path) pair that was already surfaced in the
-/// current session.
+/// current session. The file's contents pass through `redact` before they are appended.
 pub(crate) fn apply_side_read(
     sr: &SideReadSpec,
     args: &serde_json::Value,
     current_output: &str,
     session_id: Option<&str>,
     seen: &Arc<Mutex<HashMap<String, HashSet<String>>>>,
+    redact: impl Fn(String) -> String,
 ) -> String {
     if sr.filename.contains("..") || sr.filename.contains('/') || sr.filename.contains('\\') {
         log::warn!("sideRead: invalid filename in spec: {}", sr.filename);
@@ -61,6 +62,7 @@ pub(crate) fn apply_side_read(
     if content.trim().is_empty() {
         return current_output.to_string();
     }
+    let content = redact(content);
 
     let label = sr.label.as_deref().unwrap_or(&sr.filename);
     format!(
@@ -240,7 +242,7 @@ mod tests {
         let args = args_with_path(dir.to_str().unwrap());
         let seen = make_seen();
 
-        let result = apply_side_read(&sr, &args, "file1.txt\nfile2.rs", None, &seen);
+        let result = apply_side_read(&sr, &args, "file1.txt\nfile2.rs", None, &seen, std::convert::identity);
         assert!(result.contains("file1.txt"), "original output preserved");
         assert!(result.contains("--- AGENTS.md (BOF) ---"), "separator present");
         assert!(result.contains("--- AGENTS.md (EOF) ---"), "separator present");
@@ -259,7 +261,7 @@ mod tests {
         let args = args_with_path(dir.to_str().unwrap());
         let seen = make_seen();
 
-        let result = apply_side_read(&sr, &args, "listing output", None, &seen);
+        let result = apply_side_read(&sr, &args, "listing output", None, &seen, std::convert::identity);
         assert_eq!(result, "listing output");
 
         let _ = fs::remove_dir_all(&dir);
@@ -276,7 +278,7 @@ mod tests {
         let args = args_with_path("/tmp");
         let seen = make_seen();
 
-        let result = apply_side_read(&sr, &args, "safe output", None, &seen);
+        let result = apply_side_read(&sr, &args, "safe output", None, &seen, std::convert::identity);
         assert_eq!(result, "safe output");
     }
 
@@ -291,7 +293,7 @@ mod tests {
         let args = args_with_path("/tmp");
         let seen = make_seen();
 
-        let result = apply_side_read(&sr, &args, "safe output", None, &seen);
+        let result = apply_side_read(&sr, &args, "safe output", None, &seen, std::convert::identity);
         assert_eq!(result, "safe output");
     }
 
@@ -306,13 +308,13 @@ mod tests {
         let args = args_with_path(dir.to_str().unwrap());
         let seen = make_seen();
 
-        let first = apply_side_read(&sr, &args, "ls output", Some("session-1"), &seen);
+        let first = apply_side_read(&sr, &args, "ls output", Some("session-1"), &seen, std::convert::identity);
         assert!(first.contains("Project rules"), "first call appends");
 
-        let second = apply_side_read(&sr, &args, "ls output", Some("session-1"), &seen);
+        let second = apply_side_read(&sr, &args, "ls output", Some("session-1"), &seen, std::convert::identity);
         assert_eq!(second, "ls output", "second call in same session is skipped");
 
-        let other_session = apply_side_read(&sr, &args, "ls output", Some("session-2"), &seen);
+        let other_session = apply_side_read(&sr, &args, "ls output", Some("session-2"), &seen, std::convert::identity);
         assert!(other_session.contains("Project rules"), "different session appends");
 
         let _ = fs::remove_dir_all(&dir);
@@ -329,10 +331,10 @@ mod tests {
         let args = args_with_path(dir.to_str().unwrap());
         let seen = make_seen();
 
-        let first = apply_side_read(&sr, &args, "output", None, &seen);
+        let first = apply_side_read(&sr, &args, "output", None, &seen, std::convert::identity);
         assert!(first.contains("Always"), "appends without session");
 
-        let second = apply_side_read(&sr, &args, "output", None, &seen);
+        let second = apply_side_read(&sr, &args, "output", None, &seen, std::convert::identity);
         assert!(second.contains("Always"), "appends again without session");
 
         let _ = fs::remove_dir_all(&dir);
@@ -354,7 +356,7 @@ mod tests {
         let args = args_with_path(dir.to_str().unwrap());
         let seen = make_seen();
 
-        let result = apply_side_read(&sr, &args, "listing", None, &seen);
+        let result = apply_side_read(&sr, &args, "listing", None, &seen, std::convert::identity);
         assert!(result.contains("--- Instructions (BOF) ---"), "custom label used");
         assert!(result.contains("--- Instructions (EOF) ---"), "custom label used");
         assert!(!result.contains("--- AGENTS.md (BOF) ---"), "default label not used");
@@ -374,7 +376,7 @@ mod tests {
         let args = args_with_path(dir.to_str().unwrap());
         let seen = make_seen();
 
-        let result = apply_side_read(&sr, &args, "listing", None, &seen);
+        let result = apply_side_read(&sr, &args, "listing", None, &seen, std::convert::identity);
         assert_eq!(result, "listing", "whitespace-only file treated as empty");
 
         let _ = fs::remove_dir_all(&dir);
@@ -386,7 +388,7 @@ mod tests {
         let args = serde_json::json!({ "other": "/tmp" });
         let seen = make_seen();
 
-        let result = apply_side_read(&sr, &args, "output", None, &seen);
+        let result = apply_side_read(&sr, &args, "output", None, &seen, std::convert::identity);
         assert_eq!(result, "output");
     }
 
@@ -11,7 +11,7 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use crate::exec::{Allowlist, WriteSandbox};
+use crate::exec::{Allowlist, RunOptions, WriteSandbox};
 use crate::skills::{ArgKind, ExecutionSpec};
 
 use super::argv::{json_value_to_string, transform_param_value};
@@ -97,6 +97,7 @@ pub(crate) fn validate_write_paths(
     args: &serde_json::Value,
     allowlist: &Allowlist,
     skill_dir: Option<&Path>,
+    opts: &RunOptions,
     sandbox: &Option<WriteSandbox>,
 ) -> Result<(Option<std::path::PathBuf>, HashMap<String, String>), String> {
     let obj = match args.as_object() {
@@ -116,7 +117,7 @@ pub(crate) fn validate_write_paths(
     for arg in &spec.args {
         let is_write = arg.write_path == Some(true);
         // workingDir args implicitly act as readPath for sandbox validation
-        // and working directory resolution.
+        // and working directory resolution (writable unless the spec is readOnly).
         let is_read = arg.read_path == Some(true) || arg.kind == ArgKind::WorkingDir;
         let is_unsafe = arg.unsafe_path == Some(true);
 
@@ -199,7 +200,7 @@ pub(crate) fn validate_write_paths(
             }
         };
 
-        let resolved = transform_param_value(raw_value, arg, allowlist, skill_dir, args)?;
+        let resolved = transform_param_value(raw_value, arg, allowlist, skill_dir, opts, args)?;
 
         // An empty resolved value means different things depending on context:
         // - For readPath args, an empty path is semantically "the current
@@ -219,13 +220,17 @@ pub(crate) fn validate_write_paths(
 
         has_sandboxed_path = true;
 
-        let canonical = sandbox.validate(&resolved).map_err(|e| {
-            if is_read {
-                e.replace("write path outside sandbox", "read path outside sandbox")
-            } else {
-                e
-            }
-        })?;
+        // Read-only roots (`ro.` grants, read-only subset entries) satisfy reads but never
+        // writes. A spec that may write runs in its workingDir, so that must be writable too.
+        let canonical = if is_write {
+            sandbox.validate(&resolved)?
+        } else if arg.kind == ArgKind::WorkingDir && spec.may_write() {
+            sandbox.validate(&resolved).map_err(|e| {
+                format!("tool {} may write in its {} directory: {}", spec.tool, arg.param_name(), e)
+            })?
+        } else {
+            sandbox.validate_read(&resolved)?
+        };
 
         // For workingDir args, the canonical path IS the working directory.
         if arg.kind == ArgKind::WorkingDir {
@@ -237,10 +242,10 @@ pub(crate) fn validate_write_paths(
             canonical.to_string_lossy().into_owned(),
         );
 
+        // Only a writable root becomes the working directory: a tool reading from a read-only
+        // grant still runs where its implicit relative writes are allowed.
         if matched_root.is_none() {
-            if let Some(root) = sandbox.roots().iter().find(|r| canonical.starts_with(*r)) {
-                matched_root = Some(root.clone());
-            }
+            matched_root = sandbox.writable_root_for(&canonical);
         }
     }
 
@@ -254,8 +259,8 @@ pub(crate) fn validate_write_paths(
             return Ok((Some(root), canonical_paths));
         }
         if let Some(ref sb) = sandbox {
-            if let Some(root) = sb.roots().first() {
-                return Ok((Some(root.clone()), canonical_paths));
+            if let Some(base) = sb.base() {
+                return Ok((Some(base.to_path_buf()), canonical_paths));
             }
         }
     }
@@ -509,7 +514,7 @@ mod tests {
         let args = serde_json::json!({ "target": "/etc/passwd" });
         let allowlist = Allowlist::new();
 
-        let result = validate_write_paths(&spec, &args, &allowlist, None, &sandbox);
+        let result = validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &sandbox);
         assert!(result.is_err(), "absolute path in unannotated positional should be rejected");
         let err = result.unwrap_err();
         assert!(err.contains("absolute path"), "error should mention absolute path: {}", err);
@@ -544,7 +549,7 @@ mod tests {
         let args = serde_json::json!({ "name": "../../etc/passwd" });
         let allowlist = Allowlist::new();
 
-        let result = validate_write_paths(&spec, &args, &allowlist, None, &sandbox);
+        let result = validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &sandbox);
         assert!(result.is_err(), "traversal in unannotated flag should be rejected");
         let err = result.unwrap_err();
         assert!(err.contains(".."), "error should mention traversal: {}", err);
@@ -577,7 +582,7 @@ mod tests {
         let args = serde_json::json!({ "skill_name": "my-skill" });
         let allowlist = Allowlist::new();
 
-        let result = validate_write_paths(&spec, &args, &allowlist, None, &sandbox);
+        let result = validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &sandbox);
         assert!(result.is_ok(), "simple name in unannotated positional should pass: {:?}", result);
 
         let _ = fs::remove_dir_all(&base);
@@ -609,7 +614,7 @@ mod tests {
         let args = serde_json::json!({ "target": "/etc/passwd" });
         let allowlist = Allowlist::new();
 
-        let result = validate_write_paths(&spec, &args, &allowlist, None, &sandbox);
+        let result = validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &sandbox);
         assert!(result.is_ok(), "unsafePath should skip the heuristic check: {:?}", result);
 
         let _ = fs::remove_dir_all(&base);
@@ -645,7 +650,7 @@ mod tests {
         let args = serde_json::json!({ "path": external_canonical.to_string_lossy().as_ref() });
         let allowlist = Allowlist::new();
 
-        let result = validate_write_paths(&spec, &args, &allowlist, None, &sandbox);
+        let result = validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &sandbox);
         // This should fail because the external dir is not a sandbox writable root
         // (no symlink from sandbox). The point is that readPath doesn't trigger
         // the heuristic — it triggers sandbox validation instead.
@@ -680,7 +685,7 @@ mod tests {
         let args = serde_json::json!({ "all": true });
         let allowlist = Allowlist::new();
 
-        let result = validate_write_paths(&spec, &args, &allowlist, None, &sandbox);
+        let result = validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &sandbox);
         assert!(result.is_ok(), "flagIfBoolean should not be checked");
 
 
@@ -717,7 +722,7 @@ mod tests {
         let allowlist = Allowlist::new();
 
         let (working_dir, canonical_paths) =
-            validate_write_paths(&spec, &args, &allowlist, None, &sandbox)
+            validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &sandbox)
                 .expect("empty readPath should resolve to sandbox root");
 
         let sandbox_canonical = fs::canonicalize(&sandbox_dir).expect("canonicalize sandbox");
@@ -737,6 +742,115 @@ mod tests {
         let _ = fs::remove_dir_all(&base);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn validate_write_paths_read_only_grant_keeps_writable_working_dir() {
+        let base = test_dir("vwp-ro-grant");
+        let _ = fs::remove_dir_all(&base);
+        let sandbox_dir = base.join("sandbox");
+        let reference = base.join("reference");
+        fs::create_dir_all(&sandbox_dir).expect("create sandbox");
+        fs::create_dir_all(&reference).expect("create reference");
+        fs::write(reference.join("a.md"), "ref").expect("write");
+        std::os::unix::fs::symlink(&reference, sandbox_dir.join("ro.reference")).expect("symlink");
+
+        let sandbox = Some(WriteSandbox::new(&sandbox_dir));
+        let spec = |read: bool| ExecutionSpec {
+            tool: "files_copy".to_string(),
+            binary: "cat".to_string(),
+            subcommand: "".to_string(),
+            args: vec![ArgMapping {
+                param: Some("path".to_string()),
+                kind: ArgKind::Positional,
+                read_path: Some(read),
+                write_path: Some(!read),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let args = serde_json::json!({ "path": "ro.reference/a.md" });
+        let allowlist = Allowlist::new();
+
+        let (working_dir, _) = validate_write_paths(&spec(true), &args, &allowlist, None, &RunOptions::default(), &sandbox)
+            .expect("reads from a read-only grant are allowed");
+        let sandbox_canonical = fs::canonicalize(&sandbox_dir).expect("canonicalize sandbox");
+        assert_eq!(working_dir.as_deref(), Some(sandbox_canonical.as_path()));
+
+        let err = validate_write_paths(&spec(false), &args, &allowlist, None, &RunOptions::default(), &sandbox).unwrap_err();
+        assert!(err.contains("read-only"), "{err}");
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    /// `git_add`-like spec: runs in its `repo` workingDir; `readOnly` like `git_status` when `read_only`.
+    fn git_spec(read_only: bool) -> ExecutionSpec {
+        ExecutionSpec {
+            tool: if read_only { "git_status" } else { "git_add" }.to_string(),
+            binary: "git".to_string(),
+            subcommand: if read_only { "status" } else { "add" }.to_string(),
+            args: vec![ArgMapping {
+                param: Some("repo".to_string()),
+                kind: ArgKind::WorkingDir,
+                optional: Some(true),
+                ..Default::default()
+            }],
+            read_only: read_only.then_some(true),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn validate_write_paths_write_tool_refuses_read_only_working_dir() {
+        let base = test_dir("vwp-ro-workdir");
+        let _ = fs::remove_dir_all(&base);
+        let sandbox_dir = base.join("sandbox");
+        let repo = base.join("repo");
+        fs::create_dir_all(&sandbox_dir).expect("create sandbox");
+        fs::create_dir_all(&repo).expect("create repo");
+        std::os::unix::fs::symlink(&repo, sandbox_dir.join("ro.repo")).expect("symlink");
+
+        let sandbox = Some(WriteSandbox::new(&sandbox_dir));
+        let args = serde_json::json!({ "repo": "ro.repo" });
+        let allowlist = Allowlist::new();
+
+        let err = validate_write_paths(&git_spec(false), &args, &allowlist, None, &RunOptions::default(), &sandbox)
+            .unwrap_err();
+        assert!(err.contains("git_add may write in its repo directory"), "{err}");
+        assert!(err.contains("read-only"), "{err}");
+
+        let (working_dir, _) =
+            validate_write_paths(&git_spec(true), &args, &allowlist, None, &RunOptions::default(), &sandbox)
+                .expect("a readOnly tool may run in a read-only grant");
+        assert_eq!(working_dir, Some(fs::canonicalize(&repo).expect("canonicalize repo")));
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn validate_write_paths_write_tool_refuses_read_only_subset_entry() {
+        let base = test_dir("vwp-subset-ro-workdir");
+        let _ = fs::remove_dir_all(&base);
+        let sandbox_dir = base.join("sandbox");
+        fs::create_dir_all(sandbox_dir.join("app")).expect("create app");
+        fs::create_dir_all(sandbox_dir.join("docs")).expect("create docs");
+
+        let sandbox = Some(
+            WriteSandbox::subset(&sandbox_dir, &["app".to_string()], &["docs".to_string()], None).expect("subset"),
+        );
+        let allowlist = Allowlist::new();
+        let run = |spec: &ExecutionSpec, repo: &str| {
+            validate_write_paths(spec, &serde_json::json!({ "repo": repo }), &allowlist, None, &RunOptions::default(), &sandbox)
+        };
+
+        let err = run(&git_spec(false), "docs").unwrap_err();
+        assert!(err.contains("read-only"), "{err}");
+        assert!(run(&git_spec(false), "app").is_ok());
+        assert!(run(&git_spec(true), "docs").is_ok());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
     #[test]
     fn validate_write_paths_empty_write_path_is_skipped() {
         let base = test_dir("vwp-empty-writepath");
@@ -767,7 +881,7 @@ mod tests {
         let allowlist = Allowlist::new();
 
         let (working_dir, canonical_paths) =
-            validate_write_paths(&spec, &args, &allowlist, None, &sandbox)
+            validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &sandbox)
                 .expect("empty writePath should be skipped, not error");
 
         assert!(working_dir.is_none(), "empty writePath should not set working_dir");
@@ -841,7 +955,7 @@ mod tests {
         let allowlist = Allowlist::new();
 
         let (working_dir, canonical_paths) =
-            validate_write_paths(&spec, &args, &allowlist, None, &sandbox)
+            validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &sandbox)
                 .expect("validation should succeed");
 
         let external_canonical = fs::canonicalize(&external).expect("canonicalize external");
@@ -892,11 +1006,11 @@ mod tests {
         let allowlist = Allowlist::new();
 
         let (_, canonical_paths) =
-            validate_write_paths(&spec, &args, &allowlist, None, &Some(sb))
+            validate_write_paths(&spec, &args, &allowlist, None, &RunOptions::default(), &Some(sb))
                 .expect("validation should succeed");
 
         let resolved_args = substitute_canonical_paths(&args, &canonical_paths);
-        let argv = build_argv(&spec, &resolved_args, &allowlist, None)
+        let argv = build_argv(&spec, &resolved_args, &allowlist, None, &RunOptions::default(), None)
             .expect("build_argv should succeed");
 
         assert_eq!(argv.len(), 1);
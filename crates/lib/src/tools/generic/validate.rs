@@ -1,21 +1,52 @@
 //! Schema validation for tool call arguments.
 //!
 //! Validates that tool call parameters conform to the tool's JSON schema
-//! (from `tools.json`). The schema is the contract: undeclared parameters
-//! and type mismatches are rejected before execution.
+//! (from `tools.json`). The schema is the contract: undeclared parameters,
+//! type mismatches, and violated constraints (`enum`, `pattern`, ranges,
+//! lengths, array `items`, nested objects, `oneOf`/`anyOf`/`allOf`) are
+//! rejected before execution, with the JSON Pointer of the offending value.
+//!
+//! The same [`SchemaValidator`] checks worker `outputSchema` results, with undeclared properties
+//! allowed as in standard JSON Schema.
+
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
 
-/// Check a JSON value against its schema's `type` constraint.
-/// Returns `None` if the value matches, or `Some(description)` if it doesn't.
+/// Check a JSON value against its schema's `type` constraint (a type name or
+/// an array of them). Returns `None` if the value matches, or
+/// `Some(description)` if it doesn't.
 /// Null values are treated as absent — type checking is skipped.
-pub(crate) fn check_type(
-    param_schema: &serde_json::Value,
-    value: &serde_json::Value,
-) -> Option<String> {
+pub(crate) fn check_type(param_schema: &Value, value: &Value) -> Option<String> {
     // null is treated as absent for type checking.
     if value.is_null() {
         return None;
     }
-    let type_decl = param_schema.get("type")?.as_str()?;
+    type_error(param_schema.get("type")?, value)
+}
+
+/// Check a value against a `type` declaration: a type name or an array of them.
+fn type_error(decl: &Value, value: &Value) -> Option<String> {
+    match decl {
+        Value::String(type_decl) => type_mismatch(type_decl, value),
+        Value::Array(decls) => {
+            let names: Vec<&str> = decls.iter().filter_map(|d| d.as_str()).collect();
+            if names.is_empty() || names.iter().any(|t| type_mismatch(t, value).is_none()) {
+                None
+            } else {
+                Some(format!(
+                    "expected {}, got {}",
+                    names.join(" or "),
+                    json_type_name(value)
+                ))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Check a value against a single type name.
+fn type_mismatch(type_decl: &str, value: &Value) -> Option<String> {
     match type_decl {
         "string" => {
             if !value.is_string() {
@@ -23,7 +54,7 @@ pub(crate) fn check_type(
             }
         }
         "integer" => {
-            if !value.is_i64() {
+            if !value.is_i64() && !value.is_u64() {
                 // Accept numbers that are integers (e.g. 5.0 as i64).
                 if value.is_f64() {
                     if let Some(f) = value.as_f64() {
@@ -58,21 +89,395 @@ pub(crate) fn check_type(
                 return Some(format!("expected object, got {}", json_type_name(value)));
             }
         }
+        "null" if !value.is_null() => {
+            return Some(format!("expected null, got {}", json_type_name(value)));
+        }
         // Unknown type declaration — skip type checking (don't reject).
         _ => {}
     }
     None
 }
 
+/// Compiled `pattern` regexes of tool schemas by source, built when the descriptors load so
+/// validation does not compile them on every call.
+#[derive(Debug, Default)]
+pub(crate) struct Patterns(HashMap<String, Regex>);
+
+impl Patterns {
+    /// Compile every `pattern` in `schema`, nested schemas included. Fails on an invalid one.
+    pub(crate) fn add_schema(&mut self, schema: &Value) -> Result<(), String> {
+        match schema {
+            Value::Object(obj) => {
+                if let Some(Value::String(pattern)) = obj.get("pattern") {
+                    if !self.0.contains_key(pattern) {
+                        let re = Regex::new(pattern)
+                            .map_err(|e| format!("invalid pattern '{}': {}", pattern, e))?;
+                        self.0.insert(pattern.clone(), re);
+                    }
+                }
+                for (key, value) in obj {
+                    // Literal values, not schemas.
+                    if !matches!(key.as_str(), "enum" | "const" | "default" | "examples") {
+                        self.add_schema(value)?;
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.add_schema(item)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// JSON Schema validation shared by tool arguments and worker `outputSchema` results.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct SchemaValidator<'a> {
+    /// Reject object properties that a schema with `properties` does not declare (tool
+    /// arguments). Off = standard JSON Schema, where undeclared properties are allowed.
+    pub(crate) reject_undeclared: bool,
+    /// Precompiled patterns; a pattern not found here is compiled when it is checked.
+    pub(crate) patterns: Option<&'a Patterns>,
+}
+
+impl SchemaValidator<'_> {
+    /// Validate a value against a parameter schema, recursing into array items
+    /// and object properties. `path` is the JSON Pointer of `value` within the
+    /// tool arguments (e.g. `/files/2/path`); the error for the first violation
+    /// is `<path>: <message>`.
+    ///
+    /// Supported keywords: `type`, `enum`, `const`, `minLength`, `maxLength`,
+    /// `pattern`, `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`,
+    /// `multipleOf`, `items`, `minItems`, `maxItems`, `uniqueItems`,
+    /// `properties`, `required`, `additionalProperties`, `allOf`, `anyOf`,
+    /// `oneOf`, and `not`. Other keywords (`format`, `$ref`, ...) are ignored.
+    /// With `reject_undeclared`, an object schema that declares `properties`
+    /// rejects undeclared properties unless `additionalProperties` allows them.
+    pub(crate) fn validate(
+        &self,
+        param_schema: &Value,
+        value: &Value,
+        path: &str,
+    ) -> Result<(), String> {
+        let Some(schema) = param_schema.as_object() else {
+            // `true` or a malformed schema: nothing to check.
+            if param_schema == &Value::Bool(false) {
+                return Err(fail(path, "no value is allowed here"));
+            }
+            return Ok(());
+        };
+
+        // Type first: the other keywords assume it holds. Unlike in `check_type`,
+        // null is a value like any other and must be allowed by `type`.
+        let type_err = match value {
+            Value::Null => schema.get("type").and_then(|decl| type_error(decl, value)),
+            _ => check_type(param_schema, value),
+        };
+        if let Some(err) = type_err {
+            return Err(fail(path, &err));
+        }
+
+        if let Some(options) = schema.get("enum").and_then(|e| e.as_array()) {
+            if !options.iter().any(|o| json_eq(o, value)) {
+                let list: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+                return Err(fail(path, &format!("must be one of [{}]", list.join(", "))));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if !json_eq(expected, value) {
+                return Err(fail(path, &format!("must be {}", expected)));
+            }
+        }
+
+        match value {
+            Value::String(s) => self.check_string(schema, s, path)?,
+            Value::Number(_) => check_number(schema, value.as_f64().unwrap_or_default(), path)?,
+            Value::Array(items) => self.check_array(schema, items, path)?,
+            Value::Object(obj) => self.check_object(schema, obj, path)?,
+            _ => {}
+        }
+
+        self.check_combinators(schema, value, path)
+    }
+
+    fn check_string(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        s: &str,
+        path: &str,
+    ) -> Result<(), String> {
+        let len = s.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(|v| v.as_u64()) {
+            if len < min {
+                return Err(fail(path, &format!("must be at least {} characters", min)));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(|v| v.as_u64()) {
+            if len > max {
+                return Err(fail(path, &format!("must be at most {} characters", max)));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(|v| v.as_str()) {
+            let compiled;
+            let re = match self.patterns.and_then(|p| p.0.get(pattern)) {
+                Some(re) => re,
+                None => {
+                    compiled = regex::Regex::new(pattern).map_err(|e| {
+                        fail(
+                            path,
+                            &format!("invalid pattern '{}' in schema: {}", pattern, e),
+                        )
+                    })?;
+                    &compiled
+                }
+            };
+            if !re.is_match(s) {
+                return Err(fail(path, &format!("must match {}", pattern)));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_array(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        items: &[Value],
+        path: &str,
+    ) -> Result<(), String> {
+        let len = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(|v| v.as_u64()) {
+            if len < min {
+                return Err(fail(path, &format!("must have at least {} items", min)));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(|v| v.as_u64()) {
+            if len > max {
+                return Err(fail(path, &format!("must have at most {} items", max)));
+            }
+        }
+        if schema.get("uniqueItems").and_then(|v| v.as_bool()) == Some(true) {
+            for (i, item) in items.iter().enumerate() {
+                if let Some(j) = items[..i].iter().position(|prev| json_eq(prev, item)) {
+                    return Err(fail(
+                        path,
+                        &format!("items must be unique (item {} repeats item {})", i, j),
+                    ));
+                }
+            }
+        }
+        match schema.get("items") {
+            // Tuple form: one schema per position; extra items are unchecked.
+            Some(Value::Array(positional)) => {
+                for (i, (item_schema, item)) in positional.iter().zip(items).enumerate() {
+                    self.validate(item_schema, item, &format!("{}/{}", path, i))?;
+                }
+            }
+            Some(item_schema) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.validate(item_schema, item, &format!("{}/{}", path, i))?;
+                }
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn check_object(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        obj: &serde_json::Map<String, Value>,
+        path: &str,
+    ) -> Result<(), String> {
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        let additional = schema.get("additionalProperties");
+        for (key, value) in obj {
+            let child = format!("{}/{}", path, escape_pointer(key));
+            match properties.and_then(|props| props.get(key)) {
+                Some(prop_schema) => self.validate(prop_schema, value, &child)?,
+                None => match additional {
+                    Some(Value::Bool(true)) => {}
+                    Some(extra @ Value::Object(_)) => self.validate(extra, value, &child)?,
+                    Some(Value::Bool(false)) => {
+                        return Err(fail(
+                            path,
+                            &format!("undeclared property '{}' (not in schema)", key),
+                        ));
+                    }
+                    _ if properties.is_some() && self.reject_undeclared => {
+                        return Err(fail(
+                            path,
+                            &format!("undeclared property '{}' (not in schema)", key),
+                        ));
+                    }
+                    _ => {}
+                },
+            }
+        }
+        if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+            for req in required.iter().filter_map(|r| r.as_str()) {
+                if !obj.contains_key(req) {
+                    return Err(fail(path, &format!("missing required property '{}'", req)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_combinators(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        value: &Value,
+        path: &str,
+    ) -> Result<(), String> {
+        if let Some(all) = schema.get("allOf").and_then(|v| v.as_array()) {
+            for sub in all {
+                self.validate(sub, value, path)?;
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(|v| v.as_array()) {
+            let errors: Vec<String> = any
+                .iter()
+                .filter_map(|sub| self.validate(sub, value, path).err())
+                .collect();
+            if errors.len() == any.len() && !any.is_empty() {
+                return Err(fail(
+                    path,
+                    &format!(
+                        "must match at least one schema in anyOf ({})",
+                        options_summary(&errors)
+                    ),
+                ));
+            }
+        }
+        if let Some(one) = schema.get("oneOf").and_then(|v| v.as_array()) {
+            let results: Vec<Result<(), String>> = one
+                .iter()
+                .map(|sub| self.validate(sub, value, path))
+                .collect();
+            let matched: Vec<usize> = results
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.is_ok())
+                .map(|(i, _)| i + 1)
+                .collect();
+            if matched.is_empty() && !one.is_empty() {
+                let errors: Vec<String> = results.into_iter().filter_map(|r| r.err()).collect();
+                return Err(fail(
+                    path,
+                    &format!(
+                        "must match exactly one schema in oneOf ({})",
+                        options_summary(&errors)
+                    ),
+                ));
+            }
+            if matched.len() > 1 {
+                let list: Vec<String> = matched.iter().map(|i| i.to_string()).collect();
+                return Err(fail(
+                    path,
+                    &format!(
+                        "must match exactly one schema in oneOf, but matches options {}",
+                        list.join(" and ")
+                    ),
+                ));
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.validate(not, value, path).is_ok() {
+                return Err(fail(path, "must not match the schema in not"));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_number(schema: &serde_json::Map<String, Value>, n: f64, path: &str) -> Result<(), String> {
+    let bound = |key: &str| schema.get(key).and_then(|v| v.as_f64());
+    // Draft 4 spells exclusive bounds as booleans next to minimum/maximum.
+    let exclusive = |key: &str| schema.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+    if let Some(min) = bound("minimum") {
+        if exclusive("exclusiveMinimum") && n <= min {
+            return Err(fail(path, &format!("must be > {}", min)));
+        }
+        if n < min {
+            return Err(fail(path, &format!("must be >= {}", min)));
+        }
+    }
+    if let Some(max) = bound("maximum") {
+        if exclusive("exclusiveMaximum") && n >= max {
+            return Err(fail(path, &format!("must be < {}", max)));
+        }
+        if n > max {
+            return Err(fail(path, &format!("must be <= {}", max)));
+        }
+    }
+    if let Some(min) = bound("exclusiveMinimum") {
+        if n <= min {
+            return Err(fail(path, &format!("must be > {}", min)));
+        }
+    }
+    if let Some(max) = bound("exclusiveMaximum") {
+        if n >= max {
+            return Err(fail(path, &format!("must be < {}", max)));
+        }
+    }
+    if let Some(step) = bound("multipleOf").filter(|s| *s > 0.0) {
+        let q = n / step;
+        if (q - q.round()).abs() > 1e-9 {
+            return Err(fail(path, &format!("must be a multiple of {}", step)));
+        }
+    }
+    Ok(())
+}
+
+/// `option 1: /a: ...; option 2: ...` from the per-branch errors of a combinator.
+fn options_summary(errors: &[String]) -> String {
+    errors
+        .iter()
+        .enumerate()
+        .map(|(i, e)| format!("option {}: {}", i + 1, e))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Format a violation at `path`; the argument object itself is `/`.
+fn fail(path: &str, message: &str) -> String {
+    format!("{}: {}", if path.is_empty() { "/" } else { path }, message)
+}
+
+/// Escape a property name for use as a JSON Pointer segment (RFC 6901).
+pub(crate) fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// JSON equality where numbers compare by value (`1` equals `1.0`).
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Value::Array(xs), Value::Array(ys)) => {
+            xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| json_eq(x, y))
+        }
+        (Value::Object(xs), Value::Object(ys)) => {
+            xs.len() == ys.len()
+                && xs
+                    .iter()
+                    .all(|(k, x)| ys.get(k).is_some_and(|y| json_eq(x, y)))
+        }
+        _ => a == b,
+    }
+}
+
 /// Human-readable type name for a JSON value.
-fn json_type_name(value: &serde_json::Value) -> &'static str {
+fn json_type_name(value: &Value) -> &'static str {
     match value {
-        serde_json::Value::Null => "null",
-        serde_json::Value::Bool(_) => "boolean",
-        serde_json::Value::Number(_) => "number",
-        serde_json::Value::String(_) => "string",
-        serde_json::Value::Array(_) => "array",
-        serde_json::Value::Object(_) => "object",
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
     }
 }
 
@@ -80,6 +485,14 @@ fn json_type_name(value: &serde_json::Value) -> &'static str {
 mod tests {
     use super::*;
 
+    fn validate_value(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+        SchemaValidator {
+            reject_undeclared: true,
+            patterns: None,
+        }
+        .validate(schema, value, path)
+    }
+
     #[test]
     fn check_type_string_match() {
         let schema = serde_json::json!({"type": "string"});
@@ -137,4 +550,215 @@ mod tests {
         // No "type" field — skip type checking.
         assert!(check_type(&schema, &value).is_none());
     }
+
+    #[test]
+    fn check_type_union_accepts_any_listed_type() {
+        let schema = serde_json::json!({"type": ["string", "integer"]});
+        assert!(check_type(&schema, &serde_json::json!(3)).is_none());
+        let err = check_type(&schema, &serde_json::json!(true)).unwrap();
+        assert_eq!(err, "expected string or integer, got boolean");
+    }
+
+    #[test]
+    fn validate_value_reports_nested_pointer() {
+        let schema = serde_json::json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": { "path": { "type": "string", "pattern": "^[a-z]" } },
+                "required": ["path"]
+            }
+        });
+        let value =
+            serde_json::json!([{ "path": "a.md" }, { "path": "b.md" }, { "path": "Caps.md" }]);
+        assert_eq!(
+            validate_value(&schema, &value, "/files").unwrap_err(),
+            "/files/2/path: must match ^[a-z]"
+        );
+        let missing = serde_json::json!([{}]);
+        assert_eq!(
+            validate_value(&schema, &missing, "/files").unwrap_err(),
+            "/files/0: missing required property 'path'"
+        );
+    }
+
+    #[test]
+    fn validate_value_enum_and_const() {
+        let schema = serde_json::json!({"type": "string", "enum": ["asc", "desc"]});
+        assert!(validate_value(&schema, &serde_json::json!("asc"), "/order").is_ok());
+        assert_eq!(
+            validate_value(&schema, &serde_json::json!("up"), "/order").unwrap_err(),
+            "/order: must be one of [\"asc\", \"desc\"]"
+        );
+        // Numbers compare by value.
+        let schema = serde_json::json!({"enum": [1, 2]});
+        assert!(validate_value(&schema, &serde_json::json!(2.0), "/n").is_ok());
+        let schema = serde_json::json!({"const": "v1"});
+        assert!(validate_value(&schema, &serde_json::json!("v2"), "/v").is_err());
+    }
+
+    #[test]
+    fn validate_value_string_length() {
+        let schema = serde_json::json!({"type": "string", "minLength": 2, "maxLength": 3});
+        assert!(validate_value(&schema, &serde_json::json!("äö"), "/s").is_ok());
+        assert!(validate_value(&schema, &serde_json::json!("a"), "/s")
+            .unwrap_err()
+            .contains("at least 2 characters"));
+        assert!(validate_value(&schema, &serde_json::json!("abcd"), "/s")
+            .unwrap_err()
+            .contains("at most 3 characters"));
+    }
+
+    #[test]
+    fn validate_value_invalid_pattern_is_reported() {
+        let schema = serde_json::json!({"type": "string", "pattern": "("});
+        let err = validate_value(&schema, &serde_json::json!("x"), "/s").unwrap_err();
+        assert!(
+            err.starts_with("/s: invalid pattern '(' in schema"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn patterns_are_compiled_once_from_nested_schemas() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "files": {
+                    "type": "array",
+                    "items": { "type": "string", "pattern": "^[a-z]+\\.md$" }
+                },
+                "mode": { "enum": [{ "pattern": "(" }] }
+            }
+        });
+        let mut patterns = Patterns::default();
+        patterns.add_schema(&schema).unwrap();
+        assert_eq!(patterns.0.len(), 1);
+        let validator = SchemaValidator {
+            reject_undeclared: true,
+            patterns: Some(&patterns),
+        };
+        assert_eq!(
+            validator
+                .validate(&schema, &serde_json::json!({"files": ["a.md", "B.md"]}), "")
+                .unwrap_err(),
+            "/files/1: must match ^[a-z]+\\.md$"
+        );
+
+        let err = Patterns::default()
+            .add_schema(&serde_json::json!({"pattern": "("}))
+            .unwrap_err();
+        assert!(err.starts_with("invalid pattern '('"), "{}", err);
+    }
+
+    #[test]
+    fn undeclared_properties_are_allowed_unless_rejected() {
+        let schema = serde_json::json!({"type": "object", "properties": {"a": {}}});
+        let value = serde_json::json!({"a": 1, "b": 2});
+        assert!(SchemaValidator::default().validate(&schema, &value, "").is_ok());
+        assert_eq!(
+            validate_value(&schema, &value, "").unwrap_err(),
+            "/: undeclared property 'b' (not in schema)"
+        );
+    }
+
+    #[test]
+    fn validate_value_numeric_bounds() {
+        let schema = serde_json::json!({"type": "integer", "minimum": 1, "maximum": 100});
+        assert!(validate_value(&schema, &serde_json::json!(1), "/count").is_ok());
+        assert_eq!(
+            validate_value(&schema, &serde_json::json!(0), "/count").unwrap_err(),
+            "/count: must be >= 1"
+        );
+        assert_eq!(
+            validate_value(&schema, &serde_json::json!(101), "/count").unwrap_err(),
+            "/count: must be <= 100"
+        );
+        let schema = serde_json::json!({"exclusiveMinimum": 0, "multipleOf": 0.5});
+        assert!(validate_value(&schema, &serde_json::json!(0), "/x").is_err());
+        assert!(validate_value(&schema, &serde_json::json!(1.5), "/x").is_ok());
+        assert!(validate_value(&schema, &serde_json::json!(1.2), "/x")
+            .unwrap_err()
+            .contains("multiple of 0.5"));
+        // Draft 4 boolean form.
+        let schema = serde_json::json!({"minimum": 0, "exclusiveMinimum": true});
+        assert_eq!(
+            validate_value(&schema, &serde_json::json!(0), "/x").unwrap_err(),
+            "/x: must be > 0"
+        );
+    }
+
+    #[test]
+    fn validate_value_array_bounds_and_uniqueness() {
+        let schema =
+            serde_json::json!({"type": "array", "minItems": 1, "maxItems": 2, "uniqueItems": true});
+        assert!(validate_value(&schema, &serde_json::json!([]), "/a").is_err());
+        assert!(validate_value(&schema, &serde_json::json!([1, 2, 3]), "/a").is_err());
+        assert_eq!(
+            validate_value(&schema, &serde_json::json!(["x", "x"]), "/a").unwrap_err(),
+            "/a: items must be unique (item 1 repeats item 0)"
+        );
+    }
+
+    #[test]
+    fn validate_value_nested_objects_reject_undeclared_properties() {
+        let schema = serde_json::json!({"type": "object", "properties": {"a": {"type": "string"}}});
+        assert_eq!(
+            validate_value(&schema, &serde_json::json!({"a": "x", "b": 1}), "/opts").unwrap_err(),
+            "/opts: undeclared property 'b' (not in schema)"
+        );
+        let open = serde_json::json!({"type": "object", "properties": {}, "additionalProperties": {"type": "integer"}});
+        assert!(validate_value(&open, &serde_json::json!({"b": 1}), "/opts").is_ok());
+        assert_eq!(
+            validate_value(&open, &serde_json::json!({"b/c": "x"}), "/opts").unwrap_err(),
+            "/opts/b~1c: expected integer, got string"
+        );
+        // No declared properties: any property is allowed.
+        let free = serde_json::json!({"type": "object"});
+        assert!(validate_value(&free, &serde_json::json!({"b": 1}), "/opts").is_ok());
+    }
+
+    #[test]
+    fn validate_value_nested_null_must_be_allowed_by_type() {
+        let schema = serde_json::json!({"type": "array", "items": {"type": "string"}});
+        assert_eq!(
+            validate_value(&schema, &serde_json::json!(["a", null]), "/tags").unwrap_err(),
+            "/tags/1: expected string, got null"
+        );
+        let nullable = serde_json::json!({"type": "array", "items": {"type": ["string", "null"]}});
+        assert!(validate_value(&nullable, &serde_json::json!(["a", null]), "/tags").is_ok());
+    }
+
+    #[test]
+    fn validate_value_one_of() {
+        let schema = serde_json::json!({"oneOf": [
+            {"type": "string", "pattern": "^v"},
+            {"type": "integer", "minimum": 0}
+        ]});
+        assert!(validate_value(&schema, &serde_json::json!("v1"), "/ref").is_ok());
+        assert!(validate_value(&schema, &serde_json::json!(3), "/ref").is_ok());
+        assert_eq!(
+            validate_value(&schema, &serde_json::json!(-1), "/ref").unwrap_err(),
+            "/ref: must match exactly one schema in oneOf (option 1: /ref: expected string, got number; option 2: /ref: must be >= 0)"
+        );
+        let overlapping = serde_json::json!({"oneOf": [{"type": "integer"}, {"minimum": 0}]});
+        assert!(validate_value(&overlapping, &serde_json::json!(1), "/n")
+            .unwrap_err()
+            .contains("matches options 1 and 2"));
+    }
+
+    #[test]
+    fn validate_value_any_of_all_of_not() {
+        let any = serde_json::json!({"anyOf": [{"type": "string"}, {"type": "boolean"}]});
+        assert!(validate_value(&any, &serde_json::json!(true), "/x").is_ok());
+        assert!(validate_value(&any, &serde_json::json!(1), "/x")
+            .unwrap_err()
+            .contains("must match at least one schema in anyOf"));
+        let all = serde_json::json!({"allOf": [{"type": "string"}, {"maxLength": 1}]});
+        assert!(validate_value(&all, &serde_json::json!("ab"), "/x").is_err());
+        let not = serde_json::json!({"not": {"enum": ["main"]}});
+        assert!(validate_value(&not, &serde_json::json!("dev"), "/x").is_ok());
+        assert!(validate_value(&not, &serde_json::json!("main"), "/x").is_err());
+    }
 }
@@ -0,0 +1,673 @@
+//! Profile-level tool hooks: operator scripts run before and after matching tool calls (see
+//! [`crate::config::HooksConfig`]). [`HookedExecutor`] wraps an agent's tool executor so hooks
+//! apply to orchestrators and workers alike.
+//!
+//! **Pre hooks** receive the call's arguments as JSON on stdin. A non-zero exit vetoes the call and
+//! the hook's stdout (or stderr) is returned to the model as the tool error. On success, a JSON
+//! object on stdout replaces the arguments; empty stdout leaves them unchanged. A pre hook that
+//! cannot run vetoes the call (fail closed).
+//!
+//! **Post hooks** receive the tool result on stdin after successful execution. On success,
+//! non-empty stdout is appended to the result (or replaces it with `mode: "replace"`); failures
+//! leave the result unchanged.
+//!
+//! Hooks run through the same machinery as a skill's `postProcess`, bounded by the profile's
+//! `exec` timeout and output cap (a pre hook that times out vetoes the call), with `CHAI_HOOK`
+//! (`pre` / `post`), `CHAI_TOOL_NAME`, and `CHAI_SESSION_ID` (when known) in the environment, and in the
+//! working directory the wrapped tool resolves for the call (see [`ToolExecutor::working_dir`]);
+//! a call whose paths fail sandbox validation is rejected before any hook runs. Calls that no
+//! hook matches go straight to the wrapped executor. With `sandbox.kernel`, hooks are confined by
+//! the wrapped tool's policy (see [`ToolExecutor::kernel_policy`]) with the hook scripts directory
+//! readable.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::agent::{ToolCallContext, ToolExecutor};
+use crate::config::{ExecConfig, HookConfig, HookResultMode, HooksConfig};
+use crate::exec::{Allowlist, EnvPolicy, ExecLimits, KernelPolicy, RunOptions};
+use crate::redaction::Redactor;
+use crate::skills::PostProcessSpec;
+
+use super::post_process::run_script;
+
+/// One configured hook, resolved for execution.
+struct Hook {
+    /// Tool name patterns (`*` and `?` globs).
+    tools: Vec<String>,
+    spec: PostProcessSpec,
+    mode: HookResultMode,
+    /// Script name or `binary subcommand`, for logs and veto messages.
+    label: String,
+}
+
+impl Hook {
+    fn matches(&self, tool_name: &str) -> bool {
+        self.tools.iter().any(|p| glob_match(p, tool_name))
+    }
+}
+
+/// Pre and post hooks for a profile, shared by every agent's executor.
+pub struct ToolHooks {
+    pre: Vec<Hook>,
+    post: Vec<Hook>,
+    allowlist: Allowlist,
+    scripts_dir: PathBuf,
+    /// Timeout, output cap, and rlimits for every hook run.
+    limits: ExecLimits,
+    /// Profile redaction rules, applied to post hook output of calls that ask for redaction.
+    redaction: Arc<Redactor>,
+}
+
+impl ToolHooks {
+    /// Resolve `config` against the profile's hook scripts directory.
+    /// Errors name the first invalid hook: no `tools`, neither or both of `script` / `binary`,
+    /// a missing script, or a binary/subcommand not in `hooks.allowlist`.
+    pub fn from_config(config: &HooksConfig, scripts_dir: PathBuf) -> Result<Self, String> {
+        let mut allowlist = Allowlist::new();
+        for (binary, subcommands) in &config.allowlist {
+            allowlist.allow_subcommands(binary.clone(), subcommands.clone());
+        }
+        let resolve = |kind: &str, hooks: &[HookConfig]| -> Result<Vec<Hook>, String> {
+            hooks
+                .iter()
+                .enumerate()
+                .map(|(i, h)| resolve_hook(h, &allowlist, &scripts_dir).map_err(|e| format!("hooks.{}[{}]: {}", kind, i, e)))
+                .collect()
+        };
+        let pre = resolve("pre", &config.pre)?;
+        let post = resolve("post", &config.post)?;
+        Ok(Self {
+            pre,
+            post,
+            allowlist,
+            scripts_dir,
+            limits: ExecConfig::default().limits_for(None, None, None),
+            redaction: Arc::default(),
+        })
+    }
+
+    /// Bound hook runs by the profile's `exec` timeout, output cap, and rlimits.
+    pub fn with_exec_config(mut self, exec: &ExecConfig) -> Self {
+        self.limits = exec.limits_for(None, None, None);
+        self
+    }
+
+    /// Profile redaction rules: a post hook's output replaces or extends an already redacted
+    /// result, so it is redacted too when the call asks for it.
+    pub fn with_redaction(mut self, redaction: Arc<Redactor>) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+
+    /// Whether a pre hook matches `tool_name`.
+    pub fn has_pre(&self, tool_name: &str) -> bool {
+        self.pre.iter().any(|h| h.matches(tool_name))
+    }
+
+    /// Whether a post hook matches `tool_name`.
+    pub fn has_post(&self, tool_name: &str) -> bool {
+        self.post.iter().any(|h| h.matches(tool_name))
+    }
+
+    fn env(phase: &str, tool_name: &str, session_id: Option<&str>) -> EnvPolicy {
+        let mut vars = vec![
+            ("CHAI_HOOK".to_string(), phase.to_string()),
+            ("CHAI_TOOL_NAME".to_string(), tool_name.to_string()),
+        ];
+        if let Some(sid) = session_id {
+            vars.push(("CHAI_SESSION_ID".to_string(), sid.to_string()));
+        }
+        EnvPolicy::default().with_set(vars)
+    }
+
+    /// Kernel policy for hook runs: the wrapped executor's, with the hook scripts readable.
+    fn kernel_policy(&self, kernel: Option<&KernelPolicy>) -> Option<KernelPolicy> {
+        kernel.map(|p| p.with_readable([self.scripts_dir.clone()]))
+    }
+
+    /// Run matching pre hooks in order; each sees the arguments as rewritten by the previous one.
+    /// Returns the final arguments, or `Err(message)` when a hook vetoes the call. Hooks run in
+    /// `cwd` (`None` = the gateway's working directory), confined by `kernel` when set.
+    pub fn run_pre(
+        &self,
+        tool_name: &str,
+        args: &serde_json::Value,
+        session_id: Option<&str>,
+        cwd: Option<&Path>,
+        kernel: Option<&KernelPolicy>,
+    ) -> Result<serde_json::Value, String> {
+        let env = Self::env("pre", tool_name, session_id);
+        let kernel = self.kernel_policy(kernel);
+        let opts = RunOptions {
+            working_dir: cwd,
+            env: Some(&env),
+            limits: self.limits,
+            kernel: kernel.as_ref(),
+            ..Default::default()
+        };
+        let mut args = args.clone();
+        for hook in self.pre.iter().filter(|h| h.matches(tool_name)) {
+            let input = serde_json::to_string(&args).unwrap_or_default();
+            let out = run_script(
+                &hook.spec,
+                &input,
+                &self.allowlist,
+                Some(&self.scripts_dir),
+                &args,
+                &opts,
+            )
+            .ok_or_else(|| format!("pre hook {} could not run", hook.label))?;
+            if !out.success {
+                let message = [out.stdout.trim(), out.stderr.trim()]
+                    .into_iter()
+                    .find(|m| !m.is_empty())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("vetoed by pre hook {}", hook.label));
+                log::info!("hooks: pre hook {} vetoed {}", hook.label, tool_name);
+                return Err(message);
+            }
+            let stdout = out.stdout.trim();
+            if stdout.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<serde_json::Value>(stdout) {
+                Ok(v) if v.is_object() => {
+                    log::debug!("hooks: pre hook {} rewrote arguments for {}", hook.label, tool_name);
+                    args = v;
+                }
+                _ => log::warn!(
+                    "hooks: pre hook {} printed non-object output for {}; arguments unchanged",
+                    hook.label,
+                    tool_name
+                ),
+            }
+        }
+        Ok(args)
+    }
+
+    /// Run matching post hooks in order over a successful tool result, in `cwd`, confined by
+    /// `kernel` when set. Hook output is redacted when `ctx` asks for redaction.
+    pub fn run_post(
+        &self,
+        tool_name: &str,
+        args: &serde_json::Value,
+        result: String,
+        ctx: ToolCallContext<'_>,
+        cwd: Option<&Path>,
+        kernel: Option<&KernelPolicy>,
+    ) -> String {
+        let env = Self::env("post", tool_name, ctx.session_id);
+        let kernel = self.kernel_policy(kernel);
+        let opts = RunOptions {
+            working_dir: cwd,
+            env: Some(&env),
+            limits: self.limits,
+            kernel: kernel.as_ref(),
+            ..Default::default()
+        };
+        let mut result = result;
+        for hook in self.post.iter().filter(|h| h.matches(tool_name)) {
+            let out = run_script(
+                &hook.spec,
+                &result,
+                &self.allowlist,
+                Some(&self.scripts_dir),
+                args,
+                &opts,
+            );
+            match out {
+                Some(out) if out.success => {
+                    if out.stdout.is_empty() {
+                        continue;
+                    }
+                    let stdout = if ctx.redact_output { self.redaction.redact(&out.stdout) } else { out.stdout };
+                    result = match hook.mode {
+                        HookResultMode::Replace => stdout,
+                        HookResultMode::Append if result.is_empty() => stdout,
+                        HookResultMode::Append => {
+                            let sep = if result.ends_with('\n') { "" } else { "\n" };
+                            format!("{}{}{}", result, sep, stdout)
+                        }
+                    };
+                }
+                _ => log::warn!("hooks: post hook {} failed for {}; result unchanged", hook.label, tool_name),
+            }
+        }
+        result
+    }
+}
+
+fn resolve_hook(hook: &HookConfig, allowlist: &Allowlist, scripts_dir: &Path) -> Result<Hook, String> {
+    if hook.tools.is_empty() {
+        return Err("tools must list at least one tool name or glob".to_string());
+    }
+    let label = match (&hook.script, &hook.binary) {
+        (Some(script), None) => {
+            if script.contains("..") || script.contains('/') || script.contains('\\') {
+                return Err(format!("script must be a file name under {}", scripts_dir.display()));
+            }
+            let path = scripts_dir.join(script);
+            if !path.is_file() && !path.with_extension("sh").is_file() {
+                return Err(format!("script {} not found in {}", script, scripts_dir.display()));
+            }
+            script.clone()
+        }
+        (None, Some(binary)) => {
+            let subcommand = hook
+                .subcommand
+                .as_deref()
+                .ok_or_else(|| "subcommand is required when binary is set".to_string())?;
+            if !allowlist.is_allowed(binary, subcommand) {
+                return Err(format!("{} {} is not in hooks.allowlist", binary, subcommand));
+            }
+            format!("{} {}", binary, subcommand)
+        }
+        _ => return Err("set exactly one of script or binary".to_string()),
+    };
+    Ok(Hook {
+        tools: hook.tools.clone(),
+        spec: PostProcessSpec {
+            script: hook.script.clone(),
+            binary: hook.binary.clone(),
+            subcommand: hook.subcommand.clone(),
+            args: hook.args.clone(),
+            empty_is_result: None,
+        },
+        mode: hook.mode,
+        label,
+    })
+}
+
+/// Match `name` against a glob where `*` matches any run of characters and `?` one character.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ni = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+/// Tool executor that runs profile hooks around an inner executor.
+pub struct HookedExecutor {
+    inner: Arc<dyn ToolExecutor>,
+    hooks: Arc<ToolHooks>,
+}
+
+impl HookedExecutor {
+    pub fn new(inner: Arc<dyn ToolExecutor>, hooks: Arc<ToolHooks>) -> Self {
+        Self { inner, hooks }
+    }
+}
+
+impl ToolExecutor for HookedExecutor {
+    fn execute(&self, name: &str, args: &serde_json::Value, ctx: ToolCallContext<'_>) -> Result<String, String> {
+        let (pre, post) = (self.hooks.has_pre(name), self.hooks.has_post(name));
+        if !pre && !post {
+            return self.inner.execute(name, args, ctx);
+        }
+        let session_id = ctx.session_id;
+        let kernel = self.inner.kernel_policy();
+        let mut cwd = None;
+        let args = if pre {
+            let dir = self.inner.working_dir(name, args)?;
+            let rewritten = self
+                .hooks
+                .run_pre(name, args, session_id, dir.as_deref(), kernel.as_ref())
+                .map_err(|msg| format!("blocked by hook: {}", msg))?;
+            if rewritten == *args {
+                cwd = Some(dir);
+            }
+            rewritten
+        } else {
+            args.clone()
+        };
+        // Post hooks follow the call as run, whose paths a pre hook may have rewritten.
+        let cwd = match cwd {
+            Some(dir) => dir,
+            None if post => self.inner.working_dir(name, &args)?,
+            None => None,
+        };
+        let result = self.inner.execute(name, &args, ctx)?;
+        if !post {
+            return Ok(result);
+        }
+        Ok(self.hooks.run_post(name, &args, result, ctx, cwd.as_deref(), kernel.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn hook(tools: &[&str], script: &str) -> HookConfig {
+        HookConfig {
+            tools: tools.iter().map(|t| t.to_string()).collect(),
+            script: Some(script.to_string()),
+            ..Default::default()
+        }
+    }
+
+    struct EchoExecutor;
+
+    impl ToolExecutor for EchoExecutor {
+        fn execute(&self, name: &str, args: &serde_json::Value, _ctx: ToolCallContext<'_>) -> Result<String, String> {
+            Ok(format!("{} {}", name, args))
+        }
+    }
+
+    /// Resolves the working directory from a `dir` argument, like a skill tool's `workingDir` arg;
+    /// a `dir` outside `root` fails validation.
+    struct DirExecutor {
+        root: PathBuf,
+    }
+
+    impl ToolExecutor for DirExecutor {
+        fn execute(&self, name: &str, _args: &serde_json::Value, _ctx: ToolCallContext<'_>) -> Result<String, String> {
+            Ok(name.to_string())
+        }
+
+        fn working_dir(&self, _name: &str, args: &serde_json::Value) -> Result<Option<PathBuf>, String> {
+            let dir = self.root.join(args["dir"].as_str().unwrap_or(""));
+            if dir.starts_with(&self.root) && !dir.to_string_lossy().contains("..") {
+                Ok(Some(dir))
+            } else {
+                Err("path escapes sandbox".to_string())
+            }
+        }
+    }
+
+    #[test]
+    fn glob_match_supports_star_and_question_mark() {
+        assert!(glob_match("git_*", "git_push"));
+        assert!(glob_match("*", "files_write"));
+        assert!(glob_match("files_wri?e", "files_write"));
+        assert!(glob_match("*_write", "files_write"));
+        assert!(!glob_match("git_*", "files_write"));
+        assert!(!glob_match("git_push", "git_push_tags"));
+    }
+
+    #[test]
+    fn from_config_rejects_invalid_hooks() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let missing = HooksConfig {
+            pre: vec![hook(&["git_*"], "nope.sh")],
+            ..Default::default()
+        };
+        let err = ToolHooks::from_config(&missing, dir.clone()).err().expect("missing script");
+        assert!(err.starts_with("hooks.pre[0]:"), "{}", err);
+
+        let not_allowlisted = HooksConfig {
+            post: vec![HookConfig {
+                tools: vec!["git_push".to_string()],
+                binary: Some("notify-send".to_string()),
+                subcommand: Some("".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let err = ToolHooks::from_config(&not_allowlisted, dir.clone()).err().expect("not allowlisted");
+        assert!(err.contains("hooks.allowlist"), "{}", err);
+    }
+
+    #[test]
+    fn pre_hook_vetoes_with_message() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        fs::write(dir.join("protect.sh"), "#!/bin/sh\necho \"commits to main are blocked\"\nexit 1\n").unwrap();
+        let config = HooksConfig {
+            pre: vec![hook(&["git_commit"], "protect.sh")],
+            ..Default::default()
+        };
+        let hooks = Arc::new(ToolHooks::from_config(&config, dir.clone()).unwrap());
+        let exec = HookedExecutor::new(Arc::new(EchoExecutor), hooks);
+        let err = exec.execute("git_commit", &serde_json::json!({}), ToolCallContext::default()).unwrap_err();
+        assert_eq!(err, "blocked by hook: commits to main are blocked");
+        // Unmatched tools run untouched.
+        assert!(exec.execute("git_status", &serde_json::json!({}), ToolCallContext::default()).is_ok());
+    }
+
+    #[test]
+    fn pre_hook_rewrites_arguments() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        fs::write(dir.join("force-msg.sh"), "#!/bin/sh\necho '{\"message\":\"rewritten\"}'\n").unwrap();
+        let config = HooksConfig {
+            pre: vec![hook(&["git_*"], "force-msg")],
+            ..Default::default()
+        };
+        let hooks = Arc::new(ToolHooks::from_config(&config, dir.clone()).unwrap());
+        let exec = HookedExecutor::new(Arc::new(EchoExecutor), hooks);
+        let out = exec.execute("git_commit", &serde_json::json!({"message": "orig"}), ToolCallContext::default()).unwrap();
+        assert_eq!(out, r#"git_commit {"message":"rewritten"}"#);
+    }
+
+    #[test]
+    fn post_hooks_append_and_replace() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        fs::write(dir.join("note.sh"), "#!/bin/sh\necho \"formatted $1\"\n").unwrap();
+        fs::write(dir.join("redact.sh"), "#!/bin/sh\nprintf 'redacted'\n").unwrap();
+        let mut append = hook(&["files_write"], "note.sh");
+        append.args = vec!["$path".to_string()];
+        let mut replace = hook(&["secrets_*"], "redact.sh");
+        replace.mode = HookResultMode::Replace;
+        let config = HooksConfig {
+            post: vec![append, replace],
+            ..Default::default()
+        };
+        let hooks = Arc::new(ToolHooks::from_config(&config, dir.clone()).unwrap());
+        let exec = HookedExecutor::new(Arc::new(EchoExecutor), hooks);
+
+        let out = exec.execute("files_write", &serde_json::json!({"path": "a.rs"}), ToolCallContext::default()).unwrap();
+        assert_eq!(out, "files_write {\"path\":\"a.rs\"}\nformatted a.rs\n");
+        let out = exec.execute("secrets_read", &serde_json::json!({}), ToolCallContext::default()).unwrap();
+        assert_eq!(out, "redacted");
+    }
+
+    #[test]
+    fn post_hook_output_is_redacted_for_redacting_calls() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        fs::write(dir.join("owner.sh"), "#!/bin/sh\necho 'owner: dev@example.com'\n").unwrap();
+        let config = HooksConfig {
+            post: vec![hook(&["*"], "owner.sh")],
+            ..Default::default()
+        };
+        let rules = [crate::redaction::RedactionRule::Builtin("email".to_string())];
+        let hooks = ToolHooks::from_config(&config, dir)
+            .unwrap()
+            .with_redaction(Arc::new(Redactor::compile(&rules).unwrap()));
+        let exec = HookedExecutor::new(Arc::new(EchoExecutor), Arc::new(hooks));
+        let redacted = ToolCallContext {
+            session_id: None,
+            redact_output: true,
+        };
+
+        let out = exec.execute("files_read", &serde_json::json!({}), redacted).unwrap();
+        assert_eq!(out, "files_read {}\nowner: [REDACTED:email]\n");
+        let out = exec.execute("files_read", &serde_json::json!({}), ToolCallContext::default()).unwrap();
+        assert_eq!(out, "files_read {}\nowner: dev@example.com\n");
+    }
+
+    #[test]
+    fn hooks_are_bounded_by_the_exec_timeout() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        fs::write(dir.join("hang.sh"), "#!/bin/sh\nsleep 30\n").unwrap();
+        let config = HooksConfig {
+            pre: vec![hook(&["git_*"], "hang.sh")],
+            post: vec![hook(&["files_*"], "hang.sh")],
+            ..Default::default()
+        };
+        let exec = ExecConfig {
+            timeout_secs: Some(1),
+            ..Default::default()
+        };
+        let hooks = Arc::new(ToolHooks::from_config(&config, dir).unwrap().with_exec_config(&exec));
+        let exec = HookedExecutor::new(Arc::new(EchoExecutor), hooks);
+
+        let started = std::time::Instant::now();
+        let err = exec.execute("git_push", &serde_json::json!({}), ToolCallContext::default()).unwrap_err();
+        assert_eq!(err, "blocked by hook: timed out after 1 s");
+        let out = exec.execute("files_read", &serde_json::json!({}), ToolCallContext::default()).unwrap();
+        assert_eq!(out, "files_read {}");
+        assert!(started.elapsed() < std::time::Duration::from_secs(10), "took {:?}", started.elapsed());
+    }
+
+    #[test]
+    fn replace_hook_filters_large_results_without_deadlock() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        fs::write(dir.join("upper.sh"), "#!/bin/sh\ntr a A\n").unwrap();
+        let mut replace = hook(&["*"], "upper.sh");
+        replace.mode = HookResultMode::Replace;
+        let config = HooksConfig {
+            post: vec![replace],
+            ..Default::default()
+        };
+        let hooks = ToolHooks::from_config(&config, dir).unwrap();
+        let result = "a".repeat(512 * 1024);
+        let out = hooks.run_post("files_read", &serde_json::json!({}), result, ToolCallContext::default(), None, None);
+        assert_eq!(out, "A".repeat(512 * 1024));
+    }
+
+    /// Reports a kernel policy that only lets commands write under `root`.
+    struct ConfinedExecutor {
+        root: PathBuf,
+    }
+
+    impl ToolExecutor for ConfinedExecutor {
+        fn execute(&self, name: &str, _args: &serde_json::Value, _ctx: ToolCallContext<'_>) -> Result<String, String> {
+            Ok(name.to_string())
+        }
+
+        fn kernel_policy(&self) -> Option<KernelPolicy> {
+            Some(KernelPolicy {
+                writable: vec![self.root.clone()],
+                readable: KernelPolicy::SYSTEM_READ_PATHS.iter().map(PathBuf::from).collect(),
+                deny_tcp_connect: false,
+            })
+        }
+    }
+
+    #[test]
+    fn hooks_are_confined_by_the_tools_kernel_policy() {
+        if crate::exec::landlock_abi().is_none() {
+            return;
+        }
+        let tmp = tempfile::TempDir::new().unwrap();
+        let scripts = tmp.path().join("hooks");
+        let root = tmp.path().join("sandbox");
+        let outside = tmp.path().join("outside");
+        for dir in [&scripts, &root, &outside] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        let script = format!("#!/bin/sh\necho x > {}/escaped\necho x > {}/kept\n", outside.display(), root.display());
+        fs::write(scripts.join("write.sh"), script).unwrap();
+        let config = HooksConfig {
+            post: vec![hook(&["*"], "write.sh")],
+            ..Default::default()
+        };
+        let hooks = Arc::new(ToolHooks::from_config(&config, scripts).unwrap());
+        let exec = HookedExecutor::new(Arc::new(ConfinedExecutor { root: root.clone() }), hooks);
+
+        exec.execute("files_write", &serde_json::json!({}), ToolCallContext::default()).unwrap();
+        assert!(!outside.join("escaped").exists());
+        assert!(root.join("kept").exists());
+    }
+
+    #[test]
+    fn hooks_run_in_the_calls_working_directory() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let scripts = tmp.path().join("hooks");
+        let root = tmp.path().join("sandbox");
+        fs::create_dir_all(&scripts).unwrap();
+        fs::create_dir_all(root.join("repo")).unwrap();
+        fs::write(scripts.join("where.sh"), "#!/bin/sh\npwd\n").unwrap();
+        let config = HooksConfig {
+            post: vec![hook(&["*"], "where.sh")],
+            ..Default::default()
+        };
+        let hooks = Arc::new(ToolHooks::from_config(&config, scripts).unwrap());
+        let exec = HookedExecutor::new(Arc::new(DirExecutor { root: root.clone() }), hooks);
+
+        let out = exec.execute("git_status", &serde_json::json!({"dir": "repo"}), ToolCallContext::default()).unwrap();
+        let cwd = out.lines().nth(1).expect("hook output");
+        assert_eq!(
+            fs::canonicalize(cwd).unwrap(),
+            fs::canonicalize(root.join("repo")).unwrap()
+        );
+
+        let err = exec.execute("git_status", &serde_json::json!({"dir": "../.."}), ToolCallContext::default()).unwrap_err();
+        assert_eq!(err, "path escapes sandbox");
+    }
+
+    /// Counts `working_dir` lookups, which repeat the wrapped tool's validation and scripts.
+    struct CountingExecutor {
+        lookups: std::sync::atomic::AtomicUsize,
+    }
+
+    impl ToolExecutor for CountingExecutor {
+        fn execute(&self, name: &str, _args: &serde_json::Value, _ctx: ToolCallContext<'_>) -> Result<String, String> {
+            Ok(name.to_string())
+        }
+
+        fn working_dir(&self, _name: &str, _args: &serde_json::Value) -> Result<Option<PathBuf>, String> {
+            self.lookups.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn working_dir_is_resolved_only_for_hooked_calls() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        fs::write(dir.join("ok.sh"), "#!/bin/sh
+").unwrap();
+        fs::write(dir.join("rewrite.sh"), "#!/bin/sh
+echo '{\"message\":\"rewritten\"}'\n").unwrap();
+        let config = HooksConfig {
+            pre: vec![hook(&["git_commit", "git_push"], "ok.sh"), hook(&["git_push"], "rewrite.sh")],
+            post: vec![hook(&["git_*"], "ok.sh")],
+            ..Default::default()
+        };
+        let hooks = Arc::new(ToolHooks::from_config(&config, dir).unwrap());
+        let inner = Arc::new(CountingExecutor {
+            lookups: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let exec = HookedExecutor::new(inner.clone(), hooks);
+        let lookups = |tool: &str| {
+            let before = inner.lookups.load(std::sync::atomic::Ordering::SeqCst);
+            exec.execute(tool, &serde_json::json!({"message": "m"}), ToolCallContext::default()).unwrap();
+            inner.lookups.load(std::sync::atomic::Ordering::SeqCst) - before
+        };
+
+        assert_eq!(lookups("files_read"), 0);
+        assert_eq!(lookups("git_status"), 1);
+        assert_eq!(lookups("git_commit"), 1);
+        // The rewritten arguments are resolved again for the post hooks.
+        assert_eq!(lookups("git_push"), 2);
+    }
+}
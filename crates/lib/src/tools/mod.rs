@@ -1,7 +1,11 @@
-//! Tool layer: generic executor driven by skills' tools.json (allowlist + execution mapping).
+//! Tool layer: generic executor driven by skills' tools.json (allowlist + execution mapping),
+//! plus profile-level hooks around tool calls.
 
 mod generic;
+mod hooks;
 mod post_process;
 
 pub use crate::providers::ToolDefinition;
-pub use generic::{DryRunResult, GenericToolExecutor, PostPipelinePreview, StepResult, TempFilePreview};
+pub use hooks::{HookedExecutor, ToolHooks};
+pub(crate) use generic::SchemaValidator;
+pub use generic::{DryRunResult, EnvPreview, GenericToolExecutor, PostPipelinePreview, StepResult, TempFilePreview};
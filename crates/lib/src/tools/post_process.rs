@@ -4,29 +4,9 @@
 
 use std::path::Path;
 
-use crate::exec::{resolve_binary, Allowlist};
+use crate::exec::{run_program, Allowlist, RunOptions};
 use crate::skills::PostProcessSpec;
 
-/// Write stdin bytes to the child's pipe, dropping the pipe before returning
-/// so the child sees EOF. Returns an error if the pipe cannot be acquired
-/// (should not happen when `Stdio::piped()` is set).
-fn pipe_stdin(
-    child: &mut std::process::Child,
-    input: &[u8],
-) -> Result<(), String> {
-    use std::io::Write;
-    {
-        let mut pipe = child.stdin.take().ok_or_else(|| {
-            "failed to acquire stdin pipe: Stdio::piped() was set but pipe is unavailable"
-                .to_string()
-        })?;
-        pipe.write_all(input)
-            .map_err(|e| format!("failed to write stdin: {}", e))?;
-    }
-    // Pipe is dropped here — child sees EOF on stdin.
-    Ok(())
-}
-
 /// Substitute `$param_name` placeholders in post-process args with values from
 /// the tool call JSON. Placeholders use the format `$param_name` (e.g.
 /// `$scope`). If the parameter is absent or null in the tool call args,
@@ -59,108 +39,123 @@ fn substitute_pp_args(pp_args: &[String], tool_args: &serde_json::Value) -> Vec<
         .collect()
 }
 
-/// Run a post-process script or command, piping `input` to its stdin.
-/// Returns the script's stdout on success, or the original input on failure.
-/// `exit_code` is the exit code of the main command, passed to the
-/// post-process script as the `CHAI_EXIT_CODE` environment variable so that
-/// scripts can make decisions based on whether the command succeeded (0)
-/// or returned a non-zero code that was in `successExitCodes`.
-/// `tool_args` provides parameter values for `$param_name` substitution in
-/// `pp.args` (e.g. `$scope` is replaced with the `scope` parameter value).
-pub fn run_post_process(
-    pp: &PostProcessSpec,
-    exit_code: i32,
+/// Raw outcome of a script or allowlisted command started by [`run_script`].
+pub(crate) struct ScriptOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Run `spec` (a script under `scripts_dir` or an allowlisted command), piping
+/// `input` to its stdin. `$param_name` placeholders in `spec.args` are filled
+/// from `tool_args`. `opts` gives the working directory, environment, and
+/// limits; a run the timeout kills fails with `timed out after N s` as its
+/// stderr. Returns `None` when the spec cannot run (script missing or escaping
+/// `scripts_dir`, command not allowlisted, spawn failure).
+pub(crate) fn run_script(
+    spec: &PostProcessSpec,
     input: &str,
     allowlist: &Allowlist,
-    skill_dir: Option<&Path>,
+    scripts_dir: Option<&Path>,
     tool_args: &serde_json::Value,
-) -> String {
-    use std::process::Stdio;
-
-    let resolved_args = substitute_pp_args(&pp.args, tool_args);
+    opts: &RunOptions,
+) -> Option<ScriptOutput> {
+    let resolved_args = substitute_pp_args(&spec.args, tool_args);
 
-    // Script path: run via sh with stdin piped.
-    if let (Some(dir), Some(ref script_name)) = (skill_dir, &pp.script) {
+    let (program, subcommand, args) = if let (Some(scripts_dir), Some(ref script_name)) = (scripts_dir, &spec.script) {
+        // Script path: run via sh.
         if script_name.contains("..") || script_name.contains('/') || script_name.contains('\\') {
-            return input.to_string();
+            return None;
         }
-        let scripts_dir = Path::new(dir).join("scripts");
         let mut script_path = scripts_dir.join(script_name);
-        if !script_path.starts_with(&scripts_dir) {
-            return input.to_string();
+        if !script_path.starts_with(scripts_dir) {
+            return None;
         }
         if !script_path.is_file() {
             script_path = script_path.with_extension("sh");
-            if !script_path.starts_with(&scripts_dir) || !script_path.is_file() {
-                return input.to_string();
+            if !script_path.starts_with(scripts_dir) || !script_path.is_file() {
+                return None;
             }
         }
+        let mut args = vec![script_path.to_string_lossy().into_owned()];
+        args.extend(resolved_args);
+        ("sh", "", args)
+    } else if let (Some(ref binary), Some(ref subcommand)) = (&spec.binary, &spec.subcommand) {
+        // Allowlisted command path.
+        if !allowlist.is_allowed(binary, subcommand) {
+            return None;
+        }
+        (binary.as_str(), subcommand.as_str(), resolved_args)
+    } else {
+        return None;
+    };
 
-        let child = std::process::Command::new("sh")
-            .env("CHAI_EXIT_CODE", exit_code.to_string())
-            .arg(&script_path)
-            .args(&resolved_args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn();
-
-        match child {
-            Ok(mut child) => {
-                if let Err(e) = pipe_stdin(&mut child, input.as_bytes()) {
-                    log::warn!("run_post_process: {}", e);
-                }
-                match child.wait_with_output() {
-                    Ok(output) if output.status.success() => {
-                        let s = String::from_utf8_lossy(&output.stdout).into_owned();
-                        if s.is_empty() && !pp.empty_is_result.unwrap_or(false) {
-                            input.to_string()
-                        } else {
-                            s
-                        }
-                    }
-                    _ => input.to_string(),
-                }
-            }
-            Err(_) => input.to_string(),
+    let opts = RunOptions {
+        stdin: Some(input.as_bytes()),
+        ..*opts
+    };
+    let output = match run_program(program, subcommand, &args, &opts) {
+        Ok(output) => output,
+        Err(e) => {
+            log::warn!("run_script: {}", e);
+            return None;
         }
-    }
-    // Allowlisted command path: run via allowlist with stdin piped.
-    else if let (Some(ref binary), Some(ref subcommand)) = (&pp.binary, &pp.subcommand) {
-        if !allowlist.is_allowed(binary, subcommand) {
-            return input.to_string();
+    };
+    let mut stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if let Some(ref notice) = output.capped {
+        if !stdout.is_empty() && !stdout.ends_with('\n') {
+            stdout.push('\n');
         }
+        stdout.push_str(notice);
+    }
+    let stderr = match output.status {
+        Some(_) => String::from_utf8_lossy(&output.stderr).into_owned(),
+        None => format!("timed out after {} s", opts.limits.timeout.unwrap_or_default().as_secs()),
+    };
+    Some(ScriptOutput {
+        success: output.success(),
+        stdout,
+        stderr,
+    })
+}
 
-        let resolved = resolve_binary(binary);
-        let mut cmd = std::process::Command::new(&resolved);
-        cmd.env("CHAI_EXIT_CODE", exit_code.to_string())
-            .args(subcommand.split_whitespace())
-            .args(&resolved_args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        match cmd.spawn() {
-            Ok(mut child) => {
-                if let Err(e) = pipe_stdin(&mut child, input.as_bytes()) {
-                    log::warn!("run_post_process: {}", e);
-                }
-                match child.wait_with_output() {
-                    Ok(output) if output.status.success() => {
-                        let s = String::from_utf8_lossy(&output.stdout).into_owned();
-                        if s.is_empty() && !pp.empty_is_result.unwrap_or(false) {
-                            input.to_string()
-                        } else {
-                            s
-                        }
-                    }
-                    _ => input.to_string(),
-                }
+/// Run a post-process script or command, piping `input` to its stdin.
+/// Returns the script's stdout on success, or the original input on failure.
+/// `exit_code` is the exit code of the main command, passed to the
+/// post-process script as the `CHAI_EXIT_CODE` environment variable so that
+/// scripts can make decisions based on whether the command succeeded (0)
+/// or returned a non-zero code that was in `successExitCodes`.
+/// `tool_args` provides parameter values for `$param_name` substitution in
+/// `pp.args` (e.g. `$scope` is replaced with the `scope` parameter value).
+/// `opts` bounds the run like the tool's own command (timeout, output cap).
+pub fn run_post_process(
+    pp: &PostProcessSpec,
+    exit_code: i32,
+    input: &str,
+    allowlist: &Allowlist,
+    skill_dir: Option<&Path>,
+    tool_args: &serde_json::Value,
+    opts: &RunOptions,
+) -> String {
+    let scripts_dir = skill_dir.map(|dir| dir.join("scripts"));
+    let env = opts
+        .env
+        .cloned()
+        .unwrap_or_default()
+        .with_set([("CHAI_EXIT_CODE".to_string(), exit_code.to_string())]);
+    let opts = RunOptions {
+        env: Some(&env),
+        ..*opts
+    };
+    match run_script(pp, input, allowlist, scripts_dir.as_deref(), tool_args, &opts) {
+        Some(out) if out.success => {
+            if out.stdout.is_empty() && !pp.empty_is_result.unwrap_or(false) {
+                input.to_string()
+            } else {
+                out.stdout
             }
-            Err(_) => input.to_string(),
         }
-    } else {
-        input.to_string()
+        _ => input.to_string(),
     }
 }
 
@@ -208,7 +203,7 @@ mod tests {
             empty_is_result: None,
         };
 
-        let result = run_post_process(&pp, 0, "hello world", &Allowlist::new(), Some(dir.as_path()), &empty_args());
+        let result = run_post_process(&pp, 0, "hello world", &Allowlist::new(), Some(dir.as_path()), &empty_args(), &RunOptions::default());
         assert_eq!(result, "HELLO WORLD");
         cleanup(&dir);
     }
@@ -231,7 +226,7 @@ mod tests {
             "original output",
             &Allowlist::new(),
             Some(dir.as_path()),
-            &empty_args(),
+            &empty_args(),            &RunOptions::default(),
         );
         assert_eq!(result, "original output");
         cleanup(&dir);
@@ -255,7 +250,7 @@ mod tests {
             "original output",
             &Allowlist::new(),
             Some(dir.as_path()),
-            &empty_args(),
+            &empty_args(),            &RunOptions::default(),
         );
         assert_eq!(result, "original output");
         cleanup(&dir);
@@ -279,7 +274,7 @@ mod tests {
             "original output",
             &Allowlist::new(),
             Some(dir.as_path()),
-            &empty_args(),
+            &empty_args(),            &RunOptions::default(),
         );
         assert_eq!(result, "");
         cleanup(&dir);
@@ -298,7 +293,7 @@ mod tests {
         };
 
         let input = "line1\nline2\nline3\nline4\n";
-        let result = run_post_process(&pp, 0, input, &Allowlist::new(), Some(dir.as_path()), &empty_args());
+        let result = run_post_process(&pp, 0, input, &Allowlist::new(), Some(dir.as_path()), &empty_args(), &RunOptions::default());
         assert_eq!(result, "line1\nline2\n");
         cleanup(&dir);
     }
@@ -320,7 +315,7 @@ mod tests {
         };
 
         let tool_args = serde_json::json!({ "scope": "my-notes" });
-        let result = run_post_process(&pp, 0, "input", &Allowlist::new(), Some(dir.as_path()), &tool_args);
+        let result = run_post_process(&pp, 0, "input", &Allowlist::new(), Some(dir.as_path()), &tool_args, &RunOptions::default());
         assert_eq!(result.trim(), "arg=my-notes");
         cleanup(&dir);
     }
@@ -342,7 +337,7 @@ mod tests {
         };
 
         let tool_args = serde_json::json!({});
-        let result = run_post_process(&pp, 0, "input", &Allowlist::new(), Some(dir.as_path()), &tool_args);
+        let result = run_post_process(&pp, 0, "input", &Allowlist::new(), Some(dir.as_path()), &tool_args, &RunOptions::default());
         assert_eq!(result.trim(), "empty");
         cleanup(&dir);
     }
@@ -359,7 +354,7 @@ mod tests {
             empty_is_result: None,
         };
 
-        let result = run_post_process(&pp, 0, "safe", &Allowlist::new(), Some(dir.as_path()), &empty_args());
+        let result = run_post_process(&pp, 0, "safe", &Allowlist::new(), Some(dir.as_path()), &empty_args(), &RunOptions::default());
         assert_eq!(result, "safe");
         cleanup(&dir);
     }
@@ -378,7 +373,7 @@ mod tests {
             empty_is_result: None,
         };
 
-        let result = run_post_process(&pp, 0, "original", &Allowlist::new(), Some(dir.as_path()), &empty_args());
+        let result = run_post_process(&pp, 0, "original", &Allowlist::new(), Some(dir.as_path()), &empty_args(), &RunOptions::default());
         assert_eq!(result, "original");
         cleanup(&dir);
     }
@@ -393,7 +388,7 @@ mod tests {
             empty_is_result: None,
         };
 
-        let result = run_post_process(&pp, 0, "original", &Allowlist::new(), None, &empty_args());
+        let result = run_post_process(&pp, 0, "original", &Allowlist::new(), None, &empty_args(), &RunOptions::default());
         assert_eq!(result, "original");
     }
 
@@ -413,7 +408,7 @@ mod tests {
             empty_is_result: None,
         };
 
-        let result = run_post_process(&pp, 1, "input", &Allowlist::new(), Some(dir.as_path()), &empty_args());
+        let result = run_post_process(&pp, 1, "input", &Allowlist::new(), Some(dir.as_path()), &empty_args(), &RunOptions::default());
         assert_eq!(result.trim(), "exit=1");
         cleanup(&dir);
     }
@@ -443,8 +438,61 @@ mod tests {
         // Simulate what augment_with_absent_defaults would produce: "ref" is
         // injected with its absentDefault value.
         let tool_args = serde_json::json!({ "ref": "HEAD~1" });
-        let result = run_post_process(&pp, 0, "input", &Allowlist::new(), Some(dir.as_path()), &tool_args);
+        let result = run_post_process(&pp, 0, "input", &Allowlist::new(), Some(dir.as_path()), &tool_args, &RunOptions::default());
         assert_eq!(result.trim(), "ref=HEAD~1");
         cleanup(&dir);
     }
+
+    #[test]
+    fn post_process_timeout_returns_original() {
+        let dir = setup_skill_with_script("pp-timeout", "hang", "#!/bin/sh\nsleep 30\necho late");
+        let pp = PostProcessSpec {
+            script: Some("hang".to_string()),
+            binary: None,
+            subcommand: None,
+            args: vec![],
+            empty_is_result: None,
+        };
+        let opts = RunOptions {
+            limits: crate::exec::ExecLimits {
+                timeout: Some(std::time::Duration::from_secs(1)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let started = std::time::Instant::now();
+        let result = run_post_process(&pp, 0, "original", &Allowlist::new(), Some(dir.as_path()), &empty_args(), &opts);
+        assert_eq!(result, "original");
+        assert!(started.elapsed() < std::time::Duration::from_secs(10), "took {:?}", started.elapsed());
+        cleanup(&dir);
+    }
+
+    #[test]
+    fn post_process_script_is_confined_by_the_kernel_policy() {
+        if crate::exec::landlock_abi().is_none() {
+            return;
+        }
+        let dir = setup_skill_with_script("pp-landlock", "escape", "#!/bin/sh\necho x > \"$1/escaped\"\ncat");
+        let outside = tempfile::tempdir().expect("tempdir");
+        let pp = PostProcessSpec {
+            script: Some("escape".to_string()),
+            binary: None,
+            subcommand: None,
+            args: vec![outside.path().to_string_lossy().into_owned()],
+            empty_is_result: None,
+        };
+        let policy = crate::exec::KernelPolicy {
+            readable: crate::exec::KernelPolicy::SYSTEM_READ_PATHS.iter().map(PathBuf::from).collect(),
+            ..Default::default()
+        }
+        .with_readable([dir.clone()]);
+        let opts = RunOptions {
+            kernel: Some(&policy),
+            ..Default::default()
+        };
+        let result = run_post_process(&pp, 0, "input", &Allowlist::new(), Some(dir.as_path()), &empty_args(), &opts);
+        assert_eq!(result, "input");
+        assert!(!outside.path().join("escaped").exists());
+        cleanup(&dir);
+    }
 }